@@ -0,0 +1,94 @@
+//! Batch quotes of many amounts through one pool, one call per amount against one call per batch.
+//!
+//! Run with `cargo bench --bench batch_quotes`.
+
+use alloy::primitives::{aliases::U24, Address, U160, U256};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use evm_arb_bot::models::pool::v3::{Tick, V3PoolType};
+use evm_arb_bot::models::pool::{MockPool, PoolInterface, UniswapV3Pool};
+
+const BATCH_SIZES: [usize; 2] = [100, 1000];
+
+/// A V3 pool with liquidity spread over ticks, so large amounts cross several of them
+fn v3_pool() -> UniswapV3Pool {
+    let mut pool = UniswapV3Pool::new(
+        Address::repeat_byte(0xa),
+        Address::repeat_byte(1),
+        Address::repeat_byte(2),
+        U24::from(3000),
+        60,
+        U160::from(1u128 << 96),
+        0,
+        1_000_000_000_000_000_000,
+        Address::ZERO,
+        V3PoolType::UniswapV3,
+    );
+    for step in 1..=20 {
+        for (index, liquidity_net) in [
+            (-60 * step, 50_000_000_000_000_000i128),
+            (60 * step, -50_000_000_000_000_000),
+        ] {
+            pool.ticks.insert(
+                index,
+                Tick {
+                    index,
+                    liquidity_net,
+                    liquidity_gross: liquidity_net.unsigned_abs(),
+                },
+            );
+        }
+    }
+    pool
+}
+
+fn v2_pool() -> MockPool {
+    MockPool::new_v2(
+        Address::repeat_byte(0xb),
+        Address::repeat_byte(1),
+        Address::repeat_byte(2),
+        U256::from(1_000_000_000_000_000_000u128),
+        U256::from(1_000_000_000_000_000_000u128),
+    )
+}
+
+/// Amounts evenly spread up to about a tenth of the pool's liquidity
+fn amounts(size: usize) -> Vec<U256> {
+    (1..=size as u64)
+        .map(|index| U256::from(100_000_000_000_000_000u128 / size as u128) * U256::from(index))
+        .collect()
+}
+
+fn batch_quotes(c: &mut Criterion) {
+    let token_in = Address::repeat_byte(1);
+    let pools: [(&str, Box<dyn PoolInterface>); 2] =
+        [("v3", Box::new(v3_pool())), ("v2", Box::new(v2_pool()))];
+
+    for (name, pool) in &pools {
+        let mut group = c.benchmark_group(format!("batch_quotes_{}", name));
+        for size in BATCH_SIZES {
+            let amounts = amounts(size);
+            group.throughput(Throughput::Elements(size as u64));
+
+            group.bench_with_input(
+                BenchmarkId::new("per_amount", size),
+                &amounts,
+                |b, amounts| {
+                    b.iter(|| {
+                        amounts
+                            .iter()
+                            .map(|amount| pool.calculate_output(&token_in, *amount).unwrap())
+                            .collect::<Vec<_>>()
+                    })
+                },
+            );
+
+            group.bench_with_input(BenchmarkId::new("batch", size), &amounts, |b, amounts| {
+                b.iter(|| pool.calculate_outputs(&token_in, amounts).unwrap())
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, batch_quotes);
+criterion_main!(benches);
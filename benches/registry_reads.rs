@@ -0,0 +1,113 @@
+//! Quote throughput against a pool registry while an updater applies events to it.
+//!
+//! Run with `cargo bench --bench registry_reads`.
+
+use alloy::primitives::{Address, U256};
+use alloy::rpc::types::Log;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use evm_arb_bot::models::pool::{MockPool, PoolRegistry};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const POOL_COUNT: u8 = 200;
+const EVENTS_PER_BATCH: u64 = 16;
+
+fn pool_address(index: u8) -> Address {
+    Address::left_padding_from(&[1, index])
+}
+
+fn registry(runtime: &tokio::runtime::Runtime) -> Arc<PoolRegistry> {
+    let registry = Arc::new(PoolRegistry::new(1));
+    runtime.block_on(async {
+        for index in 0..POOL_COUNT {
+            registry
+                .add_pool(Box::new(MockPool::new_v2(
+                    pool_address(index),
+                    Address::repeat_byte(1),
+                    Address::repeat_byte(2),
+                    U256::from(1_000_000_000_000u64),
+                    U256::from(2_000_000_000_000u64),
+                )))
+                .await;
+        }
+    });
+    registry
+}
+
+/// Apply batches of events to the registry until `stop` is set
+fn spawn_updater(registry: Arc<PoolRegistry>, stop: Arc<AtomicBool>) -> thread::JoinHandle<u64> {
+    thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let mut block = 0;
+            while !stop.load(Ordering::Relaxed) {
+                block += 1;
+                let events: Vec<Log> = (0..EVENTS_PER_BATCH)
+                    .map(|offset| {
+                        let mut event = Log::default();
+                        event.inner.address =
+                            pool_address(((block + offset) % POOL_COUNT as u64) as u8);
+                        event.block_number = Some(block);
+                        event
+                    })
+                    .collect();
+                registry.apply_logs(&events).await;
+                tokio::task::yield_now().await;
+            }
+            block
+        })
+    })
+}
+
+fn quote_throughput(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let registry = registry(&runtime);
+    let stop = Arc::new(AtomicBool::new(false));
+    let updater = spawn_updater(registry.clone(), stop.clone());
+
+    let token_in = Address::repeat_byte(1);
+    let path = [pool_address(7), pool_address(42)];
+    let amount_in = U256::from(1_000_000u64);
+
+    let mut group = c.benchmark_group("quote_during_updates");
+    group.throughput(Throughput::Elements(1));
+
+    // Every pool of the route read from one published snapshot, without locks
+    group.bench_function("snapshot", |b| {
+        b.iter(|| {
+            let route = registry.snapshot().route(&path, token_in).unwrap();
+            route.quote_exact_in(amount_in).unwrap()
+        })
+    });
+
+    // Every pool of the route read through its live state lock
+    group.bench_function("live_locks", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let mut amount = amount_in;
+                let mut token = token_in;
+                for address in &path {
+                    let pool = registry.get_pool(address).await.unwrap();
+                    let pool = pool.read().await;
+                    let (token0, token1) = pool.tokens();
+                    amount = pool.calculate_output(&token, amount).unwrap();
+                    token = if token == token0 { token1 } else { token0 };
+                }
+                amount
+            })
+        })
+    });
+
+    group.finish();
+    stop.store(true, Ordering::Relaxed);
+    let blocks = updater.join().unwrap();
+    println!("updater applied {} batches during the benchmark", blocks);
+}
+
+criterion_group!(benches, quote_throughput);
+criterion_main!(benches);
@@ -1,223 +1,369 @@
-use alloy::eips::BlockNumberOrTag;
-use alloy::primitives::Address;
-use alloy::providers::{Provider, ProviderBuilder, MULTICALL3_ADDRESS};
-use alloy::rpc::client::RpcClient;
-use alloy::transports::http::Http;
-use alloy::transports::layers::FallbackLayer;
-use anyhow::Result;
-use evm_arb_bot::blockchain::pool_fetcher::fetch_and_display_pool_info;
-
-use clap::Parser;
-use env_logger::Env;
-use evm_arb_bot::api::create_router;
-use evm_arb_bot::blockchain::{
-    EventQueue, PoolUpdaterLatestBlock, PoolUpdaterLatestBlockWs, WebsocketListener,
-};
+use alloy::primitives::{Address, U256};
+use alloy::providers::MULTICALL3_ADDRESS;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use evm_arb_bot::api::server::{apply_server_config, serve};
+use evm_arb_bot::api::{auth::ApiKeyRegistry, create_admin_router, create_router};
+use evm_arb_bot::core::chain_manager::ChainManager;
+use evm_arb_bot::core::offline::{self, DEFAULT_MAX_PATH_LENGTH};
+use evm_arb_bot::core::proccessor::QuoteType;
 use evm_arb_bot::core::{proccessor::Proccessor, Database};
-
-use evm_arb_bot::models::pool::multichain_registry::MultichainPoolRegistry;
-use evm_arb_bot::models::pool::PoolRegistry;
-use evm_arb_bot::models::token::{MultichainTokenRegistry, TokenRegistry};
-use evm_arb_bot::utils::config::AppConfig;
-use log::{error, info, LevelFilter};
+use evm_arb_bot::models::pool::erc4626::ERC4626Pool;
+use evm_arb_bot::models::pool::{PoolType, QuoteCache};
+use evm_arb_bot::utils::config::{AppConfig, ConfigFile, TlsConfig, DEFAULT_CONFIG_PATH};
+use evm_arb_bot::utils::metrics::Metrics;
+use evm_arb_bot::utils::telemetry::{self, LogFormat, TelemetryConfig, DEFAULT_SERVICE_NAME};
+use log::{error, info, warn, LevelFilter};
 use std::net::SocketAddr;
-use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tower::ServiceBuilder;
-use url::Url;
-
-// Example pool addresses
-
-async fn initialize_chain(
-    chain_config: evm_arb_bot::utils::config::ChainConfigs,
-    multichain_pool_registry: Arc<MultichainPoolRegistry>,
-    multichain_token_registry: Arc<MultichainTokenRegistry>,
-    db: Option<Database>,
-    should_load_snapshot_pool: bool,
-) -> Result<(), anyhow::Error> {
-    info!("Initializing chain...");
-
-    // 1. Setup RPC provider
-    let rpc_len = chain_config.rpc_urls.len();
-    let fallback_layer =
-        FallbackLayer::default().with_active_transport_count(NonZeroUsize::new(rpc_len).unwrap());
-
-    let transports = chain_config
-        .rpc_urls
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Config file, repeat to layer files with later ones overriding earlier keys
+    #[arg(long = "config", global = true, default_value = DEFAULT_CONFIG_PATH)]
+    config_paths: Vec<PathBuf>,
+
+    /// Log level, info when serving and warn for the other commands
+    #[arg(long, global = true)]
+    log_level: Option<String>,
+
+    /// Log line format, text or json
+    #[arg(long, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// OTLP/HTTP collector to export spans to, e.g. http://localhost:4318
+    #[arg(long)]
+    otlp_endpoint: Option<String>,
+
+    /// Address of the public quote API, overrides server.bind_address
+    #[arg(long)]
+    bind: Option<SocketAddr>,
+
+    /// Address of the admin API, overrides server.admin_bind_address
+    #[arg(long)]
+    admin_bind: Option<SocketAddr>,
+
+    /// Origin allowed to call the API from a browser, repeat for several, `*` for any
+    #[arg(long = "cors-origin")]
+    cors_origins: Vec<String>,
+
+    /// Seconds before a request times out, 0 disables the timeout
+    #[arg(long)]
+    request_timeout: Option<u64>,
+
+    /// Maximum request body size in bytes
+    #[arg(long)]
+    body_limit: Option<usize>,
+
+    /// Disable gzip compression of responses
+    #[arg(long)]
+    no_compression: bool,
+
+    /// PEM certificate chain, serves both listeners over TLS together with --tls-key
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key of --tls-cert
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Index the configured chains and serve the APIs, the default command
+    Serve,
+    /// Validate the config file without connecting to any chain
+    CheckConfig,
+    /// Print the state of a pool at a block
+    FetchPool {
+        /// Pool address
+        address: Address,
+        /// RPC endpoint of the pool's chain
+        #[arg(long)]
+        rpc_url: String,
+        /// Block to read the state at, the latest one if omitted
+        #[arg(long)]
+        block: Option<u64>,
+        /// UniswapV2, UniswapV3 or VerioIP, V2 and V3 pools are detected if omitted
+        #[arg(long, value_parser = parse_pool_type)]
+        pool_type: Option<PoolType>,
+        /// Multicall3 contract of the chain
+        #[arg(long, default_value_t = MULTICALL3_ADDRESS)]
+        multicall: Address,
+    },
+    /// Quote a route against the pools of a snapshot database, without RPC
+    Quote {
+        /// Database name, database.db_path of the config if omitted
+        #[arg(long)]
+        db: Option<String>,
+        /// Chain id of the pools
+        #[arg(long)]
+        network: u64,
+        /// Pools to swap through, comma separated in swap order
+        #[arg(long, value_delimiter = ',', required = true)]
+        path: Vec<Address>,
+        #[arg(long)]
+        token_in: Address,
+        #[arg(long)]
+        token_out: Address,
+        /// Raw amount of token_in, or of token_out with --exact-out
+        #[arg(long)]
+        amount: U256,
+        /// Quote the input needed for an exact output
+        #[arg(long)]
+        exact_out: bool,
+    },
+    /// Inspect or clean up the snapshot database
+    Db {
+        /// Database name, database.db_path of the config if omitted
+        #[arg(long)]
+        db: Option<String>,
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+    /// Write the cycles through the pools of a snapshot database to a text file
+    ExportCycles {
+        /// Database name, database.db_path of the config if omitted
+        #[arg(long)]
+        db: Option<String>,
+        /// Chain id of the pools
+        #[arg(long)]
+        network: u64,
+        /// Token cycles start and end at, repeat for several. Defaults to the wrapped native
+        /// tokens and stablecoins of the config.
+        #[arg(long = "profit-token")]
+        profit_tokens: Vec<Address>,
+        /// Most pools in a cycle
+        #[arg(long, default_value_t = DEFAULT_MAX_PATH_LENGTH)]
+        max_path_length: usize,
+        #[arg(long, default_value = "cycles.txt")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DbCommand {
+    /// Print the entries and size of every tree
+    Stats,
+    /// Print the entries of a tree as JSON lines
+    Dump {
+        /// Tree name, as listed by `db stats`
+        tree: String,
+    },
+    /// Remove the pools no longer in their chain's config and candles beyond
+    /// candles.max_candles. Each chain's RPC is asked for its chain id.
+    Prune {
+        /// Only print what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+fn parse_pool_type(value: &str) -> Result<PoolType, String> {
+    match value {
+        "UniswapV2" => Ok(PoolType::UniswapV2),
+        "UniswapV3" => Ok(PoolType::UniswapV3),
+        "VerioIP" => Ok(PoolType::ERC4626(ERC4626Pool::VerioIP)),
+        _ => Err("expected one of UniswapV2, UniswapV3, VerioIP".to_string()),
+    }
+}
+
+impl Args {
+    /// Server settings given on the command line take precedence over the config file
+    fn apply_server_overrides(&self, config: &mut AppConfig) {
+        let server = &mut config.server;
+        if let Some(bind) = self.bind {
+            server.bind_address = bind;
+        }
+        if let Some(admin_bind) = self.admin_bind {
+            server.admin_bind_address = admin_bind;
+        }
+        if !self.cors_origins.is_empty() {
+            server.cors_origins = self.cors_origins.clone();
+        }
+        if let Some(request_timeout) = self.request_timeout {
+            server.request_timeout = request_timeout;
+        }
+        if let Some(body_limit) = self.body_limit {
+            server.body_limit = body_limit;
+        }
+        if self.no_compression {
+            server.compression = false;
+        }
+        if let (Some(cert_path), Some(key_path)) = (&self.tls_cert, &self.tls_key) {
+            server.tls = Some(TlsConfig {
+                cert_path: cert_path.clone(),
+                key_path: key_path.clone(),
+            });
+        }
+    }
+}
+
+/// Prints every issue of the config files, failing when there is one
+fn check_config(paths: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+    let config = ConfigFile::load(paths)?;
+    let issues = config.validate();
+    if !issues.is_empty() {
+        for issue in &issues {
+            eprintln!("{}", issue);
+        }
+        eprintln!("{} invalid config fields", issues.len());
+        std::process::exit(1);
+    }
+    let config = AppConfig::from_file(config)?;
+    let pools = config
+        .chain_configs
         .iter()
-        .map(|url| Http::new(Url::parse(url).unwrap()))
-        .collect::<Vec<_>>();
-
-    let transport = ServiceBuilder::new()
-        .layer(fallback_layer)
-        .service(transports);
-    let client = RpcClient::builder().transport(transport, false);
-    let provider = ProviderBuilder::new().connect_client(client.clone());
-    let provider = Arc::new(provider);
-    let chain_id = provider.get_chain_id().await.unwrap();
-
-    info!("Connected to chain with ID: {}", chain_id);
-
-    // 2. Create registries for this chain
-    let pool_registry = Arc::new(PoolRegistry::new(chain_id));
-    let token_registry = Arc::new(RwLock::new(TokenRegistry::new(chain_id)));
-
-    // 3. Add to multichain registries
-    multichain_pool_registry
-        .add_pool_registry(chain_id, pool_registry.clone())
-        .await;
-    multichain_token_registry
-        .add_token_registry(chain_id, token_registry.clone())
-        .await;
-
-    // 4. Load pools from database if available and if load_snapshot is enabled
-    if should_load_snapshot_pool {
-        if let Some(ref db) = db {
-            info!("Loading pools from database for chain {}...", chain_id);
-            if let Err(e) = pool_registry.load_from_db(db).await {
-                error!(
-                    "Error loading pools from database for chain {}: {}",
-                    chain_id, e
-                );
-            }
-            if let Err(e) = token_registry.write().await.load_from_db(db).await {
-                error!(
-                    "Error loading tokens from database for chain {}: {}",
-                    chain_id, e
-                );
+        .map(|chain| chain.pools.len())
+        .sum::<usize>();
+    println!(
+        "Config is valid: {} chains, {} pools",
+        config.chain_configs.len(),
+        pools
+    );
+    Ok(())
+}
+
+/// Opens the database given on the command line, or the one of the config
+fn open_database(db: Option<String>, config_paths: &[PathBuf]) -> Result<Database> {
+    let db_path = match db {
+        Some(db_path) => db_path,
+        None => AppConfig::load(config_paths)?
+            .database
+            .db_path
+            .ok_or_else(|| anyhow!("No database given with --db or database.db_path"))?,
+    };
+    Database::new(db_path)
+}
+
+/// Runs a command that works on the snapshot database or a single RPC call, without starting
+/// the indexer
+async fn run_offline(command: Command, config_paths: &[PathBuf]) -> Result<()> {
+    match command {
+        Command::Serve | Command::CheckConfig => unreachable!("not an offline command"),
+        Command::FetchPool {
+            address,
+            rpc_url,
+            block,
+            pool_type,
+            multicall,
+        } => {
+            let (pool, block) =
+                offline::fetch_pool_at(&rpc_url, address, block, pool_type, multicall).await?;
+            println!("Block: {}", block);
+            println!("{}", pool.log_summary());
+            if let Some(state) = pool.state() {
+                println!("{}", serde_json::to_string_pretty(&state)?);
             }
         }
-    } else {
-        info!(
-            "Snapshot loading disabled for chain {}, starting with empty pool registry",
-            chain_id
-        );
-        // 5. Initialize block number
-        let last_processed_block = pool_registry.get_last_processed_block().await;
-        let start_block = if should_load_snapshot_pool && last_processed_block > 0 {
-            // Only use last_processed_block if loading snapshots is enabled
-            last_processed_block
-        } else {
-            provider.get_block_number().await?
-        };
-        info!("Starting block for chain {}: {}", chain_id, start_block);
-
-        // 6. Fetch additional pool information if needed
-        info!("Fetching pool information from chain {}...", chain_id);
-        let custom_multicall_address =
-            if let Some(addr) = chain_config.custom_multicall_address.as_ref() {
-                addr.parse::<Address>().unwrap()
+        Command::Quote {
+            db,
+            network,
+            path,
+            token_in,
+            token_out,
+            amount,
+            exact_out,
+        } => {
+            let db = open_database(db, config_paths)?;
+            let quote_type = if exact_out {
+                QuoteType::ExactOut
             } else {
-                MULTICALL3_ADDRESS
+                QuoteType::ExactIn
             };
-        fetch_and_display_pool_info(
-            &provider,
-            &chain_config
-                .pools
-                .iter()
-                .map(|p| p.address.clone())
-                .collect(),
-            BlockNumberOrTag::Number(start_block),
-            &token_registry,
-            &pool_registry,
-            chain_config.wait_time_for_startup,
-            custom_multicall_address,
-        )
-        .await?;
-
-        // 7. Save pool registry to database
-        if let Some(db) = db {
-            info!(
-                "Saving pool registry to database for chain {} at block: {}",
-                chain_id,
-                pool_registry.get_last_processed_block().await
-            );
-            pool_registry.save_to_db(&db).await?;
-            token_registry.write().await.save_to_db(&db).await?;
+            let quote = offline::quote_from_snapshot(
+                &db, network, &path, token_in, token_out, amount, quote_type,
+            )
+            .await?;
+            println!("{}", serde_json::to_string_pretty(&quote)?);
         }
-    }
-
-    // 8. Start pool updater
-    if chain_config.use_websocket {
-        info!(
-            "Starting pool updater with websocket for chain {}",
-            chain_id
-        );
-        let event_queue = EventQueue::new(1000, 1000);
-
-        for url in chain_config.websocket_urls {
-            let event_sender = event_queue.get_sender().clone();
-            let pool_registry_clone = pool_registry.clone();
-            let chain_id_clone = chain_id;
-            tokio::spawn(async move {
-                let ws = WebsocketListener::new(
-                    url,
-                    pool_registry_clone.get_all_addresses().await,
-                    event_sender,
-                    pool_registry_clone.get_topics().await.clone(),
-                );
-                if let Err(e) = ws.start().await {
-                    error!(
-                        "Websocket listener error for chain {}: {}",
-                        chain_id_clone, e
+        Command::Db { db, command } => {
+            let db = open_database(db, config_paths)?;
+            match command {
+                DbCommand::Stats => {
+                    for tree in offline::db_stats(&db)? {
+                        println!(
+                            "{:<32} {:>10} entries {:>14} bytes",
+                            tree.name, tree.entries, tree.bytes
+                        );
+                    }
+                    println!("Size on disk: {} bytes", db.size_on_disk()?);
+                }
+                DbCommand::Dump { tree } => {
+                    let mut out = std::io::stdout().lock();
+                    offline::dump_tree(&db, &tree, &mut out)?;
+                }
+                DbCommand::Prune { dry_run } => {
+                    let config = AppConfig::load(config_paths)?;
+                    let configured_pools = offline::configured_pools(&config.chain_configs).await?;
+                    let report = offline::prune_db(
+                        &db,
+                        &configured_pools,
+                        config.candles.max_candles,
+                        dry_run,
+                    )?;
+                    for (tree, pool) in &report.pools_removed {
+                        println!("Pool {} in {}", pool, tree);
+                    }
+                    println!(
+                        "{} {} pools, {} path index entries and {} candles",
+                        if dry_run { "Would remove" } else { "Removed" },
+                        report.pools_removed.len(),
+                        report.path_entries_removed,
+                        report.candles_removed
                     );
                 }
-            });
-        }
-
-        let mut pool_updater = PoolUpdaterLatestBlockWs::new(
-            Arc::clone(&provider),
-            event_queue,
-            pool_registry.clone(),
-            chain_config.max_blocks_per_batch,
-        )
-        .await;
-
-        let chain_id_clone = chain_id;
-        tokio::spawn(async move {
-            if let Err(e) = pool_updater.start().await {
-                error!("Pool updater error for chain {}: {}", chain_id_clone, e);
             }
-        });
-    } else {
-        info!(
-            "Starting pool updater with latest block for chain {}",
-            chain_id
-        );
-        let mut pool_updater = PoolUpdaterLatestBlock::new(
-            Arc::clone(&provider),
-            pool_registry.clone(),
-            pool_registry.get_last_processed_block().await,
-            chain_config.max_blocks_per_batch,
-        )
-        .await;
-
-        let chain_id_clone = chain_id;
-        tokio::spawn(async move {
-            if let Err(e) = pool_updater.start().await {
-                error!("Pool updater error for chain {}: {}", chain_id_clone, e);
+        }
+        Command::ExportCycles {
+            db,
+            network,
+            mut profit_tokens,
+            max_path_length,
+            output,
+        } => {
+            if profit_tokens.is_empty() {
+                let config = AppConfig::load(config_paths)?;
+                for chain in &config.chain_configs {
+                    for token in chain.wrap_native.iter().chain(&chain.stablecoins) {
+                        profit_tokens.push(token.parse()?);
+                    }
+                }
             }
-        });
+            let db = open_database(db, config_paths)?;
+            let cycles =
+                offline::export_cycles(&db, network, &profit_tokens, max_path_length, &output)
+                    .await?;
+            println!("Exported {} cycles to {}", cycles, output);
+        }
     }
-
-    info!("Chain {} initialized successfully!", chain_id);
     Ok(())
 }
 
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
-struct Args {
-    #[arg(long, default_value = "info")]
-    log_level: String,
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // 1. Parse command line arguments and setup logging
-    let args = Args::parse();
-    let log_level = match args.log_level.to_lowercase().as_str() {
+    // 1. Parse command line arguments and setup logging. Variables in .env are available
+    // to RUST_LOG, config interpolation and overrides
+    dotenv::dotenv().ok();
+    let mut args = Args::parse();
+    let command = args.command.take().unwrap_or(Command::Serve);
+    let default_log_level = match command {
+        Command::Serve => "info",
+        _ => "warn",
+    };
+    let log_level = match args
+        .log_level
+        .as_deref()
+        .unwrap_or(default_log_level)
+        .to_lowercase()
+        .as_str()
+    {
         "error" => LevelFilter::Error,
         "warn" => LevelFilter::Warn,
         "info" => LevelFilter::Info,
@@ -225,85 +371,141 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "trace" => LevelFilter::Trace,
         _ => LevelFilter::Info,
     };
-    env_logger::Builder::from_env(Env::default().default_filter_or(log_level.to_string())).init();
+    let telemetry = telemetry::init(&TelemetryConfig {
+        log_level: log_level.to_string().to_lowercase(),
+        log_format: args.log_format,
+        otlp_endpoint: args.otlp_endpoint.clone(),
+        service_name: DEFAULT_SERVICE_NAME.to_string(),
+    })?;
+
+    match command {
+        Command::Serve => {}
+        Command::CheckConfig => return check_config(&args.config_paths),
+        command => {
+            let result = run_offline(command, &args.config_paths).await;
+            telemetry.shutdown();
+            if let Err(e) = result {
+                eprintln!("Error: {:#}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    }
 
     // 2. Load configuration
-    let config = AppConfig::load()?;
+    let mut config = AppConfig::load(&args.config_paths)?;
+    args.apply_server_overrides(&mut config);
 
     // 3. Initialize databases
+    let metrics = Arc::new(Metrics::new());
+    let mut chain_manager = ChainManager::new(
+        config.candles.clone(),
+        config.pricing.clone(),
+        metrics.clone(),
+    );
     // Initialize local database if configured
-    let db = if let Some(db_path) = &config.database.db_path {
+    if let Some(db_path) = &config.database.db_path {
         info!("Initializing local database with name: {}", db_path);
-        Some(Database::new(db_path)?)
-    } else {
-        None
-    };
-
-    let multichain_pool_registry = Arc::new(MultichainPoolRegistry::new());
-    let multichain_token_registry = Arc::new(MultichainTokenRegistry::new());
-
-    // Initialize all chains concurrently for faster startup
-    let mut chain_handles = Vec::new();
-
-    for (_chain_index, chain_config) in config.chain_configs.into_iter().enumerate() {
-        let first_rpc = chain_config.rpc_urls.first().unwrap().clone();
-        let multichain_pool_registry = multichain_pool_registry.clone();
-        let multichain_token_registry = multichain_token_registry.clone();
-        let db = db.clone();
-        let should_load_snapshot_pool = config.database.load_snapshot_pool.unwrap_or(false);
-
-        let handle = tokio::spawn(async move {
-            let result = initialize_chain(
-                chain_config,
-                multichain_pool_registry,
-                multichain_token_registry,
-                db,
-                should_load_snapshot_pool,
-            )
-            .await;
-            (first_rpc, result)
-        });
-
-        chain_handles.push(handle);
+        chain_manager.set_database(
+            Database::new(db_path)?,
+            config.database.load_snapshot_pool.unwrap_or(false),
+        );
     }
+    chain_manager.set_config_paths(args.config_paths.clone());
+    let chain_manager = Arc::new(chain_manager);
 
-    // Wait for all chains to initialize
+    // Initialize all chains concurrently for faster startup
     info!("Waiting for all chains to initialize...");
-    for handle in chain_handles {
-        match handle.await? {
-            (first_rpc, Ok(())) => {
-                info!("Chain {} initialized successfully", first_rpc);
-            }
-            (first_rpc, Err(e)) => {
-                error!("Chain {} initialization failed: {}", first_rpc, e);
-                return Err(e.into());
-            }
-        }
+    if let Err(e) = chain_manager.start_chains(config.chain_configs).await {
+        error!("Chain initialization failed: {:#}", e);
+        return Err(e.into());
     }
     info!("All chains initialized successfully!");
 
     // Create processor with multichain registries
-    let processor = Arc::new(Proccessor::new(
-        multichain_pool_registry.clone(),
-        multichain_token_registry.clone(),
-    ));
-
-    // Start API server
-    let app = create_router(processor);
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3001));
-    info!("Starting API server on {}", addr);
-
+    let mut processor = Proccessor::new(
+        chain_manager.pool_registries(),
+        chain_manager.token_registries(),
+        chain_manager.price_registries(),
+    );
+    processor.set_router_registry(chain_manager.router_registry());
+    processor.set_gas_registry(chain_manager.gas_registry());
+    processor.set_quote_cache(Arc::new(QuoteCache::new(config.quotes.cache_size)));
+    let processor = Arc::new(processor);
+
+    // Start the public and admin API servers
+    let api_keys = Arc::new(ApiKeyRegistry::new(&config.api.keys));
+    if !api_keys.is_enabled() {
+        warn!("No API keys configured, the API is open to anyone who can reach it");
+    }
+    let server = config.server.clone();
+    let app = apply_server_config(
+        create_router(processor, api_keys.clone(), metrics.clone()),
+        &server,
+    )?;
+    let admin_app = apply_server_config(
+        create_admin_router(api_keys, metrics, chain_manager.clone()),
+        &server,
+    )?;
+    info!(
+        "Starting API server on {} and admin server on {}",
+        server.bind_address, server.admin_bind_address
+    );
+
+    let (addr, tls) = (server.bind_address, server.tls.clone());
     let _server_handle = tokio::spawn(async move {
-        let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-        axum::serve(listener, app).await.unwrap();
+        if let Err(e) = serve(app, addr, tls.as_ref()).await {
+            error!("API server error: {:#}", e);
+        }
+    });
+    let (admin_addr, tls) = (server.admin_bind_address, server.tls);
+    let _admin_server_handle = tokio::spawn(async move {
+        if let Err(e) = serve(admin_app, admin_addr, tls.as_ref()).await {
+            error!("Admin server error: {:#}", e);
+        }
     });
 
     // Keep main thread alive with periodic database snapshot before exit
     let running = Arc::new(AtomicBool::new(true));
+    tokio::spawn({
+        let running = running.clone();
+        async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                info!("Shutting down");
+                running.store(false, std::sync::atomic::Ordering::SeqCst);
+            }
+        }
+    });
+
+    // Chains and pools are reloaded from the config files on SIGHUP
+    #[cfg(unix)]
+    tokio::spawn({
+        let chain_manager = chain_manager.clone();
+        async move {
+            use tokio::signal::unix::{signal, SignalKind};
+            let mut hangup = match signal(SignalKind::hangup()) {
+                Ok(hangup) => hangup,
+                Err(e) => {
+                    error!("Failed to listen for SIGHUP: {}", e);
+                    return;
+                }
+            };
+            while hangup.recv().await.is_some() {
+                info!("Received SIGHUP");
+                match chain_manager.reload().await {
+                    Ok(report) => info!("Config reloaded: {:?}", report),
+                    Err(e) => error!("Config reload failed, chains left unchanged: {:#}", e),
+                }
+            }
+        }
+    });
 
     while running.load(std::sync::atomic::Ordering::SeqCst) {
         tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
     }
+    chain_manager.shutdown().await;
 
+    // Export the spans still buffered
+    telemetry.shutdown();
     Ok(())
 }
@@ -0,0 +1,470 @@
+use axum::{
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{Json, Response},
+    Extension,
+};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{
+    api::errors::{ApiError, ApiErrorCode},
+    api::models::{ApiKeyUsageInfo, ApiKeysResponse},
+    utils::config::ApiKeyConfig,
+};
+
+/// Requests per minute allowed for a key that does not set its own rate
+pub const DEFAULT_REQUESTS_PER_MINUTE: u32 = 600;
+
+/// Amounts allowed in one batch quote for a key that does not set its own limit
+pub const DEFAULT_MAX_BATCH_AMOUNTS: usize = 100;
+
+/// Pools allowed in a quoted route for a key that does not set its own limit
+pub const DEFAULT_MAX_HOPS: usize = 4;
+
+/// Header carrying the API key, `Authorization: Bearer <key>` is accepted as well
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Routes served without a key, so health checks and the API reference keep working
+fn is_public(path: &str) -> bool {
+    path == "/health"
+        || path.starts_with("/health/")
+        || path == "/openapi.json"
+        || path == "/docs"
+        || path.starts_with("/docs/")
+}
+
+/// Request size limits of the calling key, checked by the handlers that take batches or routes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    pub max_batch_amounts: usize,
+    pub max_hops: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_batch_amounts: DEFAULT_MAX_BATCH_AMOUNTS,
+            max_hops: DEFAULT_MAX_HOPS,
+        }
+    }
+}
+
+impl RequestLimits {
+    pub fn check_amounts(&self, amounts: usize) -> Result<(), ApiError> {
+        if amounts > self.max_batch_amounts {
+            return Err(ApiError::LimitExceeded(format!(
+                "{} amounts requested, this key allows {} per batch",
+                amounts, self.max_batch_amounts
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn check_hops(&self, hops: usize) -> Result<(), ApiError> {
+        if hops > self.max_hops {
+            return Err(ApiError::LimitExceeded(format!(
+                "{} pools requested, this key allows routes of {} pools",
+                hops, self.max_hops
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The key a request was authenticated with, added to the request extensions
+#[derive(Debug, Clone)]
+pub struct ApiCaller {
+    pub name: String,
+    pub admin: bool,
+    pub limits: RequestLimits,
+}
+
+impl ApiCaller {
+    /// Caller of a server without configured keys
+    fn anonymous() -> Self {
+        Self {
+            name: "anonymous".to_string(),
+            admin: false,
+            limits: RequestLimits::default(),
+        }
+    }
+}
+
+/// Token bucket refilled continuously at the key's rate, holding at most a minute of requests
+#[derive(Debug)]
+struct RateLimiter {
+    capacity: f64,
+    per_second: f64,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_minute: u32, now: Instant) -> Self {
+        Self {
+            capacity: requests_per_minute as f64,
+            per_second: requests_per_minute as f64 / 60.0,
+            bucket: Mutex::new((requests_per_minute as f64, now)),
+        }
+    }
+
+    /// Take a token, or return how long until one is available
+    fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let (tokens, last) = *bucket;
+        let tokens = (tokens + now.saturating_duration_since(last).as_secs_f64() * self.per_second)
+            .min(self.capacity);
+        if tokens >= 1.0 {
+            *bucket = (tokens - 1.0, now);
+            Ok(())
+        } else {
+            *bucket = (tokens, now);
+            Err(Duration::from_secs_f64((1.0 - tokens) / self.per_second))
+        }
+    }
+}
+
+/// Usage counters of a key since startup
+#[derive(Debug, Default)]
+struct ApiKeyUsage {
+    requests: AtomicU64,
+    rate_limited: AtomicU64,
+    limit_exceeded: AtomicU64,
+    errors: AtomicU64,
+    last_used: AtomicU64,
+}
+
+#[derive(Debug)]
+struct ApiKeyEntry {
+    caller: ApiCaller,
+    /// None when the key is not rate limited
+    rate_limiter: Option<RateLimiter>,
+    usage: ApiKeyUsage,
+}
+
+/// Configured API keys with their limits and usage. Authentication is off when no key is set.
+#[derive(Debug, Default)]
+pub struct ApiKeyRegistry {
+    keys: HashMap<String, ApiKeyEntry>,
+}
+
+impl ApiKeyRegistry {
+    pub fn new(configs: &[ApiKeyConfig]) -> Self {
+        let now = Instant::now();
+        let keys = configs
+            .iter()
+            .map(|config| {
+                let entry = ApiKeyEntry {
+                    caller: ApiCaller {
+                        name: config.name.clone(),
+                        admin: config.admin,
+                        limits: RequestLimits {
+                            max_batch_amounts: config.max_batch_amounts,
+                            max_hops: config.max_hops,
+                        },
+                    },
+                    rate_limiter: (config.requests_per_minute > 0)
+                        .then(|| RateLimiter::new(config.requests_per_minute, now)),
+                    usage: ApiKeyUsage::default(),
+                };
+                (config.key.clone(), entry)
+            })
+            .collect();
+        Self { keys }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Usage of every key, sorted by name
+    pub fn usage(&self) -> Vec<ApiKeyUsageInfo> {
+        let mut usage: Vec<ApiKeyUsageInfo> = self
+            .keys
+            .values()
+            .map(|entry| ApiKeyUsageInfo {
+                name: entry.caller.name.clone(),
+                admin: entry.caller.admin,
+                requests_per_minute: entry
+                    .rate_limiter
+                    .as_ref()
+                    .map(|limiter| limiter.capacity as u32),
+                max_batch_amounts: entry.caller.limits.max_batch_amounts,
+                max_hops: entry.caller.limits.max_hops,
+                requests: entry.usage.requests.load(Ordering::Relaxed),
+                rate_limited: entry.usage.rate_limited.load(Ordering::Relaxed),
+                limit_exceeded: entry.usage.limit_exceeded.load(Ordering::Relaxed),
+                errors: entry.usage.errors.load(Ordering::Relaxed),
+                last_used: match entry.usage.last_used.load(Ordering::Relaxed) {
+                    0 => None,
+                    timestamp => Some(timestamp),
+                },
+            })
+            .collect();
+        usage.sort_by(|a, b| a.name.cmp(&b.name));
+        usage
+    }
+}
+
+/// Key sent in `X-API-Key` or as an `Authorization` bearer token
+fn request_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(axum::http::header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+}
+
+/// Middleware authenticating requests with an API key, enforcing the key's rate limit and
+/// counting its usage. Handlers read the key's limits from the `ApiCaller` extension.
+pub async fn authenticate(
+    State(api_keys): State<Arc<ApiKeyRegistry>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if is_public(request.uri().path()) {
+        return Ok(next.run(request).await);
+    }
+    if !api_keys.is_enabled() {
+        request.extensions_mut().insert(ApiCaller::anonymous());
+        return Ok(next.run(request).await);
+    }
+
+    let key = request_key(request.headers()).ok_or_else(|| {
+        ApiError::Unauthorized("An API key is required in the X-API-Key header".to_string())
+    })?;
+    let entry = api_keys
+        .keys
+        .get(key)
+        .ok_or_else(|| ApiError::Unauthorized("Unknown API key".to_string()))?;
+
+    let usage = &entry.usage;
+    usage.requests.fetch_add(1, Ordering::Relaxed);
+    usage
+        .last_used
+        .store(chrono::Utc::now().timestamp() as u64, Ordering::Relaxed);
+    if let Some(rate_limiter) = &entry.rate_limiter {
+        if let Err(retry_after) = rate_limiter.try_acquire(Instant::now()) {
+            usage.rate_limited.fetch_add(1, Ordering::Relaxed);
+            usage.errors.fetch_add(1, Ordering::Relaxed);
+            warn!("API key {} is rate limited", entry.caller.name);
+            return Err(ApiError::RateLimited {
+                retry_after_secs: retry_after.as_secs().max(1),
+            });
+        }
+    }
+
+    request.extensions_mut().insert(entry.caller.clone());
+    let response = next.run(request).await;
+    if !response.status().is_success() && !response.status().is_informational() {
+        usage.errors.fetch_add(1, Ordering::Relaxed);
+    }
+    if response
+        .extensions()
+        .get::<ApiErrorCode>()
+        .is_some_and(|code| code.0 == "LIMIT_EXCEEDED")
+    {
+        usage.limit_exceeded.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/keys",
+    tag = "admin",
+    summary = "Limits and usage counters of every API key, for admin keys only",
+    responses((status = 200, body = ApiKeysResponse))
+)]
+pub async fn get_api_keys(
+    Extension(api_keys): Extension<Arc<ApiKeyRegistry>>,
+    Extension(caller): Extension<ApiCaller>,
+) -> Result<Json<ApiKeysResponse>, ApiError> {
+    if !caller.admin {
+        return Err(ApiError::Forbidden(
+            "An admin API key is required".to_string(),
+        ));
+    }
+    let keys = api_keys.usage();
+    info!("GET /admin/keys requested by {}", caller.name);
+    Ok(Json(ApiKeysResponse {
+        total_keys: keys.len(),
+        keys,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_admin_router, create_router};
+    use crate::core::chain_manager::ChainManager;
+    use crate::core::proccessor::Proccessor;
+    use crate::models::pool::multichain_registry::MultichainPoolRegistry;
+    use crate::models::price::MultichainPriceRegistry;
+    use crate::models::token::MultichainTokenRegistry;
+    use crate::utils::metrics::Metrics;
+
+    #[test]
+    fn test_rate_limiter_refills_over_time() {
+        let start = Instant::now();
+        let limiter = RateLimiter::new(60, start);
+
+        // A full minute of requests is available as a burst
+        for _ in 0..60 {
+            assert!(limiter.try_acquire(start).is_ok());
+        }
+        let retry_after = limiter.try_acquire(start).unwrap_err();
+        assert_eq!(retry_after.as_secs(), 1);
+
+        // One request per second comes back
+        assert!(limiter.try_acquire(start + Duration::from_secs(1)).is_ok());
+        assert!(limiter.try_acquire(start + Duration::from_secs(1)).is_err());
+        // The bucket never holds more than a minute of requests
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..60 {
+            assert!(limiter.try_acquire(later).is_ok());
+        }
+        assert!(limiter.try_acquire(later).is_err());
+    }
+
+    #[test]
+    fn test_request_limits() {
+        let limits = RequestLimits {
+            max_batch_amounts: 2,
+            max_hops: 1,
+        };
+        assert!(limits.check_amounts(2).is_ok());
+        assert_eq!(
+            limits.check_amounts(3).unwrap_err().code(),
+            "LIMIT_EXCEEDED"
+        );
+        assert!(limits.check_hops(1).is_ok());
+        assert_eq!(limits.check_hops(2).unwrap_err().code(), "LIMIT_EXCEEDED");
+    }
+
+    #[tokio::test]
+    async fn test_requests_need_a_key_within_its_limits() {
+        let key = |key: &str, name: &str, admin: bool, requests_per_minute: u32| ApiKeyConfig {
+            key: key.to_string(),
+            name: name.to_string(),
+            admin,
+            requests_per_minute,
+            max_batch_amounts: 2,
+            max_hops: 2,
+        };
+        let api_keys = Arc::new(ApiKeyRegistry::new(&[
+            key("user-secret", "user", false, 2),
+            key("admin-secret", "admin", true, 0),
+        ]));
+        let processor = Arc::new(Proccessor::new(
+            Arc::new(MultichainPoolRegistry::new()),
+            Arc::new(MultichainTokenRegistry::new()),
+            Arc::new(MultichainPriceRegistry::new()),
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let admin_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let admin_addr = admin_listener.local_addr().unwrap();
+        let metrics = Arc::new(Metrics::new());
+        let chain_manager = Arc::new(ChainManager::new(
+            Default::default(),
+            Default::default(),
+            metrics.clone(),
+        ));
+        let admin_router = create_admin_router(api_keys.clone(), metrics.clone(), chain_manager);
+        tokio::spawn(async move {
+            axum::serve(listener, create_router(processor, api_keys, metrics))
+                .await
+                .unwrap()
+        });
+        tokio::spawn(async move { axum::serve(admin_listener, admin_router).await.unwrap() });
+        let client = reqwest::Client::new();
+        let get = |path: &str, key: Option<&str>| {
+            // Admin routes are served by the admin listener
+            let addr = if path.starts_with("/admin") || path == "/metrics" {
+                admin_addr
+            } else {
+                addr
+            };
+            let request = client.get(format!("http://{}{}", addr, path));
+            match key {
+                Some(key) => request.header(API_KEY_HEADER, key),
+                None => request,
+            }
+            .send()
+        };
+
+        // Health checks need no key, everything else does
+        assert_eq!(get("/health", None).await.unwrap().status(), 200);
+        let response = get("/networks", None).await.unwrap();
+        assert_eq!(response.status(), 401);
+        assert_eq!(get("/networks", Some("wrong")).await.unwrap().status(), 401);
+
+        // Two requests a minute, the third waits for a refill
+        assert_eq!(
+            get("/networks", Some("user-secret"))
+                .await
+                .unwrap()
+                .status(),
+            200
+        );
+        let response = client
+            .post(format!("http://{}/quote/batch/amount-in/path/raw", addr))
+            .bearer_auth("user-secret")
+            .json(&serde_json::json!({
+                "network_id": 1,
+                "token_in": "0x0000000000000000000000000000000000000001",
+                "token_out": "0x0000000000000000000000000000000000000002",
+                "amounts": ["1", "2", "3"],
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "LIMIT_EXCEEDED");
+        let response = get("/networks", Some("user-secret")).await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert!(response.headers().contains_key("retry-after"));
+
+        // Usage is only visible to admin keys
+        assert_eq!(
+            get("/admin/keys", Some("user-secret"))
+                .await
+                .unwrap()
+                .status(),
+            429
+        );
+        let response = get("/admin/keys", Some("admin-secret")).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let usage: ApiKeysResponse = response.json().await.unwrap();
+        assert_eq!(usage.total_keys, 2);
+        let user = &usage.keys[1];
+        assert_eq!(user.name, "user");
+        assert_eq!(user.requests, 4);
+        assert_eq!(user.rate_limited, 2);
+        assert_eq!(user.limit_exceeded, 1);
+        assert_eq!(user.errors, 3);
+        assert!(user.last_used.is_some());
+
+        // Both listeners record request latencies by route
+        let response = get("/metrics", Some("admin-secret")).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let metrics = response.text().await.unwrap();
+        assert!(metrics.contains(
+            "evm_indexer_api_request_duration_seconds_count{method=\"GET\",route=\"/networks\",status=\"200\"} 1"
+        ));
+        assert!(metrics.contains(
+            "evm_indexer_api_request_duration_seconds_count{method=\"GET\",route=\"/admin/keys\",status=\"200\"} 1"
+        ));
+    }
+}
@@ -0,0 +1,216 @@
+use axum::{
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRequest, FromRequestParts,
+    },
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use thiserror::Error;
+
+use crate::{api::models::ErrorResponse, utils::errors::ArbitrageError};
+
+/// Errors returned by the API. Every error is sent with its status and a JSON body holding a
+/// machine-readable code and a message.
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("Request exceeds the API key's limits: {0}")]
+    LimitExceeded(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Request body too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Rate limit exceeded, retry in {retry_after_secs}s")]
+    RateLimited { retry_after_secs: u64 },
+
+    #[error("No route found from {token_in} to {token_out}")]
+    RouteNotFound { token_in: String, token_out: String },
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error(transparent)]
+    Domain(#[from] ArbitrageError),
+
+    #[error("Quote failed: {0}")]
+    QuoteFailed(String),
+
+    #[error("Request did not complete within {0}s")]
+    Timeout(u64),
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl ApiError {
+    /// Machine-readable code sent in the response body
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidRequest(_) => "INVALID_REQUEST",
+            Self::InvalidAddress(_) => "INVALID_ADDRESS",
+            Self::InvalidAmount(_) => "INVALID_AMOUNT",
+            Self::LimitExceeded(_) => "LIMIT_EXCEEDED",
+            Self::Unauthorized(_) => "UNAUTHORIZED",
+            Self::Forbidden(_) => "FORBIDDEN",
+            Self::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            Self::RateLimited { .. } => "RATE_LIMITED",
+            Self::RouteNotFound { .. } => "ROUTE_NOT_FOUND",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::Domain(error) => match error {
+                ArbitrageError::NetworkNotFound(_) => "NETWORK_NOT_FOUND",
+                ArbitrageError::PoolNotFound(_) => "POOL_NOT_FOUND",
+                ArbitrageError::TokenNotFound(_) => "TOKEN_NOT_FOUND",
+                ArbitrageError::TokenNotInPool { .. } => "TOKEN_NOT_IN_POOL",
+                ArbitrageError::InsufficientLiquidity(_) => "INSUFFICIENT_LIQUIDITY",
+                ArbitrageError::InvalidAmount(_) => "INVALID_AMOUNT",
+                ArbitrageError::PoolError(_) | ArbitrageError::SimulationError(_) => "QUOTE_FAILED",
+                ArbitrageError::BlockchainError(_)
+                | ArbitrageError::ConfigError(_)
+                | ArbitrageError::IoError(_)
+                | ArbitrageError::ProviderError(_) => "INTERNAL_ERROR",
+            },
+            Self::QuoteFailed(_) => "QUOTE_FAILED",
+            Self::Timeout(_) => "TIMEOUT",
+            Self::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    /// Status sent with the error, derived from its code so a code always has the same status
+    pub fn status(&self) -> StatusCode {
+        match self.code() {
+            "INVALID_REQUEST" | "INVALID_ADDRESS" | "INVALID_AMOUNT" | "TOKEN_NOT_IN_POOL"
+            | "LIMIT_EXCEEDED" => StatusCode::BAD_REQUEST,
+            "UNAUTHORIZED" => StatusCode::UNAUTHORIZED,
+            "FORBIDDEN" => StatusCode::FORBIDDEN,
+            "PAYLOAD_TOO_LARGE" => StatusCode::PAYLOAD_TOO_LARGE,
+            "RATE_LIMITED" => StatusCode::TOO_MANY_REQUESTS,
+            "NOT_FOUND" | "NETWORK_NOT_FOUND" | "POOL_NOT_FOUND" | "TOKEN_NOT_FOUND"
+            | "ROUTE_NOT_FOUND" => StatusCode::NOT_FOUND,
+            "INSUFFICIENT_LIQUIDITY" | "QUOTE_FAILED" => StatusCode::UNPROCESSABLE_ENTITY,
+            "TIMEOUT" => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Invalid address in a request field
+    pub fn invalid_address(field: &str, value: &str) -> Self {
+        Self::InvalidAddress(format!("{} {:?} is not an address", field, value))
+    }
+}
+
+/// Domain errors travel through `anyhow`, anything else failed while quoting
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<ArbitrageError>() {
+            Ok(error) => Self::Domain(error),
+            Err(error) => Self::QuoteFailed(error.to_string()),
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        // Bodies over the server's body limit are rejected while they are read
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            return Self::PayloadTooLarge(rejection.body_text());
+        }
+        Self::InvalidRequest(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::InvalidRequest(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::InvalidRequest(rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse {
+            success: false,
+            code: self.code().to_string(),
+            error: self.to_string(),
+        };
+        let mut response = (self.status(), Json(body)).into_response();
+        if let Self::RateLimited { retry_after_secs } = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+        }
+        // Lets middleware tell errors apart without parsing the body
+        response.extensions_mut().insert(ApiErrorCode(self.code()));
+        response
+    }
+}
+
+/// Code of an error response, in the response extensions
+#[derive(Debug, Clone, Copy)]
+pub struct ApiErrorCode(pub &'static str);
+
+/// JSON request body that rejects malformed input with an `ApiError`
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+pub struct ApiJson<T>(pub T);
+
+/// Path parameters that reject malformed input with an `ApiError`
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+pub struct ApiPath<T>(pub T);
+
+/// Query parameters that reject malformed input with an `ApiError`
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+pub struct ApiQuery<T>(pub T);
+
+/// Response for requests to unknown routes
+pub async fn route_not_found() -> ApiError {
+    ApiError::NotFound("Route not found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::Address;
+
+    #[test]
+    fn test_domain_errors_keep_their_code_through_anyhow() {
+        let error: ApiError =
+            anyhow::Error::from(ArbitrageError::PoolNotFound(Address::ZERO)).into();
+        assert_eq!(error.code(), "POOL_NOT_FOUND");
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+
+        let error: ApiError = anyhow::Error::from(ArbitrageError::TokenNotInPool {
+            token: Address::ZERO,
+            pool: Address::ZERO,
+        })
+        .into();
+        assert_eq!(error.code(), "TOKEN_NOT_IN_POOL");
+        assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+
+        let error: ApiError = anyhow::anyhow!("MulDiv overflow").into();
+        assert_eq!(error.code(), "QUOTE_FAILED");
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -1,21 +1,32 @@
 use alloy::primitives::{utils::parse_units, Address, U256};
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-};
-use log::info;
+use axum::{extract::State, http::StatusCode, response::Json, Extension};
+use log::warn;
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::task::JoinSet;
 
 use crate::{
+    api::auth::ApiCaller,
+    api::errors::{ApiError, ApiJson, ApiPath, ApiQuery},
     api::models::{
-        BatchQuoteRequest, BatchQuoteRequestWithPool, BatchQuoteResponse, HealthResponse,
-        NetworksResponse, PoolsResponse, QuoteRequestWithPool, QuoteResponse, TokenInfo,
-        TokensResponse,
+        BatchQuoteRequest, BatchQuoteRequestWithPool, BatchQuoteResponse, CalldataRequest,
+        CandleInfo, CandleQuery, CandlesResponse, ChainSyncInfo, DepthPointInfo, DepthRequest,
+        DepthResponse, HealthResponse, HopQuoteInfo, LiquidityBucketInfo, NetworksResponse,
+        PoolDetailResponse, PoolLiquidityInfo, PoolStateInfo, PoolTokenInfo, PoolsQuery,
+        PoolsResponse, PriceImpactInfo, PriceInfo, PricesResponse, QuoteCacheStatsResponse,
+        QuoteDetailsInfo, QuoteRequestWithPool, QuoteResponse, ReadinessResponse,
+        RouteQuoteRequest, RouteQuoteResponse, SwapCalldataInfo, SyncErrorInfo, TickInfo,
+        TokenInfo, TokensResponse, WebsocketInfo,
     },
+    api::trace,
     core::proccessor::QuoteType,
+    models::candle::{Candle, CandleKey, DEFAULT_MAX_CANDLES},
+    models::gas::RouteCost,
+    models::pool::{
+        depth::{depth_point, input_for_price_impact, liquidity_distribution},
+        PoolRegistry, PoolSnapshot, PoolType, RouteQuote, RouteSnapshot, UniswapV3Pool,
+        MAX_HEAD_AGE_SECS,
+    },
+    models::router::{SwapParams, DEFAULT_DEADLINE_SECS},
+    utils::errors::ArbitrageError,
 };
 use crate::{
     api::models::{BatchQuoteRequestWithPools, BatchQuoteResponseWithSteps},
@@ -23,598 +34,692 @@ use crate::{
     Token,
 };
 
-// Helper function to parse token address from optional string
-fn parse_token_address(token_str: Option<String>) -> Result<Address, StatusCode> {
-    token_str
-        .ok_or(StatusCode::BAD_REQUEST)?
+/// Pools in the longest path searched by the best route quotes
+const MAX_PATH_HOPS: usize = 3;
+
+// Helper function to parse an address from a request field
+fn parse_address(field: &str, value: &str) -> Result<Address, ApiError> {
+    value
         .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)
+        .map_err(|_| ApiError::invalid_address(field, value))
 }
 
-// Helper function to validate token input and return appropriate error
+fn parse_optional_address(
+    field: &str,
+    value: Option<&String>,
+) -> Result<Option<Address>, ApiError> {
+    value.map(|value| parse_address(field, value)).transpose()
+}
+
+fn parse_required_address(field: &str, value: Option<&String>) -> Result<Address, ApiError> {
+    parse_optional_address(field, value)?
+        .ok_or_else(|| ApiError::InvalidRequest(format!("{} is required", field)))
+}
+
+// Helper function to parse a raw decimal amount
+fn parse_amount(value: &str) -> Result<U256, ApiError> {
+    value
+        .parse::<U256>()
+        .map_err(|_| ApiError::InvalidAmount(format!("{:?} is not a raw amount", value)))
+}
+
+fn parse_amounts(values: &[String]) -> Result<Vec<U256>, ApiError> {
+    values.iter().map(|value| parse_amount(value)).collect()
+}
+
+// Helper function to validate that exactly one of token_in and token_out is given
 fn validate_token_input(
     token_in: &Option<String>,
     token_out: &Option<String>,
-) -> Result<(), QuoteResponse> {
+) -> Result<(), ApiError> {
     match (token_in, token_out) {
-        (Some(_), Some(_)) => Err(QuoteResponse::error(
+        (Some(_), Some(_)) => Err(ApiError::InvalidRequest(
             "Only one of token_in or token_out should be provided".to_string(),
         )),
-        (None, None) => Err(QuoteResponse::error(
+        (None, None) => Err(ApiError::InvalidRequest(
             "Either token_in or token_out is required".to_string(),
         )),
         _ => Ok(()),
     }
 }
 
-// Helper function to validate batch token input and return appropriate error
+// Helper function to validate that a batch request gives at least one token
 fn validate_batch_token_input(
     token_in: &Option<String>,
     token_out: &Option<String>,
-) -> Result<(), BatchQuoteResponse> {
+) -> Result<(), ApiError> {
     match (token_in, token_out) {
-        (None, None) => Err(BatchQuoteResponse::error(
+        (None, None) => Err(ApiError::InvalidRequest(
             "Either token_in or token_out is required".to_string(),
         )),
         _ => Ok(()),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    summary = "Service health",
+    responses((status = 200, body = HealthResponse))
+)]
 pub async fn health_check() -> Json<HealthResponse> {
-    let start = Instant::now();
-    let result = Json(HealthResponse {
+    Json(HealthResponse {
         status: "ok".to_string(),
         message: "EVM Arbitrage Bot API is running".to_string(),
-    });
-    info!("GET /health completed in {:?}", start.elapsed());
-    result
+    })
 }
 
-pub async fn quote_amount_in_raw(
-    State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<QuoteRequestWithPool>,
-) -> Result<Json<QuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    let pool_address = request
-        .pool
-        .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    // Validate token input
-    if let Err(error_response) = validate_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
-    }
-
-    let amount_out = request
-        .amount
-        .parse::<U256>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    let result = if let Some(token_in_str) = request.token_in {
-        let token_in = parse_token_address(Some(token_in_str))?;
-        processor
-            .quote_amount_in_token_in_raw(request.network_id, pool_address, token_in, amount_out)
-            .await
-    } else {
-        let token_out_str = request.token_out.unwrap(); // Safe because we validated above
-        let token_out = parse_token_address(Some(token_out_str))?;
-        processor
-            .quote_amount_in_token_out_raw(request.network_id, pool_address, token_out, amount_out)
-            .await
-    };
-
-    let response = match result {
-        Ok(amount) => Ok(Json(QuoteResponse::success(amount))),
-        Err(e) => Ok(Json(QuoteResponse::error(e.to_string()))),
-    };
-    info!(
-        "POST /quote/amount-in/raw completed in {:?}",
-        start.elapsed()
-    );
-    response
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    summary = "Liveness, ok while the server answers requests",
+    responses((status = 200, body = HealthResponse))
+)]
+pub async fn health_live() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        message: "EVM Arbitrage Bot API is running".to_string(),
+    })
 }
 
-pub async fn quote_amount_in_token(
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    summary = "Readiness, fails while any chain lags its head by more than its max lag",
+    responses(
+        (status = 200, description = "Every chain is in sync", body = ReadinessResponse),
+        (status = 503, description = "A chain is behind or its head is unknown", body = ReadinessResponse)
+    )
+)]
+pub async fn health_ready(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<QuoteRequestWithPool>,
-) -> Result<Json<QuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    let pool_address = request
-        .pool
-        .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    // Validate token input
-    if let Err(error_response) = validate_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let mut network_ids = processor.pool_registry().get_all_network_ids().await;
+    network_ids.sort();
+    let mut chains = Vec::with_capacity(network_ids.len());
+    for network_id in network_ids {
+        if let Some(pool_registry) = processor
+            .pool_registry()
+            .get_pool_registry(network_id)
+            .await
+        {
+            chains.push(chain_sync_info(network_id, &pool_registry).await);
+        }
     }
 
-    let result = if let Some(token_in_str) = request.token_in {
-        let token_in = parse_token_address(Some(token_in_str))?;
-        processor
-            .quote_amount_in_token_in(request.network_id, pool_address, token_in, request.amount)
-            .await
+    // Nothing can be quoted without a chain
+    let ready = !chains.is_empty() && chains.iter().all(|chain| chain.ready);
+    let (status, label) = if ready {
+        (StatusCode::OK, "ready")
     } else {
-        let token_out_str = request.token_out.unwrap(); // Safe because we validated above
-        let token_out = parse_token_address(Some(token_out_str))?;
-        processor
-            .quote_amount_in_token_out(request.network_id, pool_address, token_out, request.amount)
-            .await
-    };
-
-    let response = match result {
-        Ok(amount) => Ok(Json(QuoteResponse::success(amount))),
-        Err(e) => Ok(Json(QuoteResponse::error(e.to_string()))),
+        (StatusCode::SERVICE_UNAVAILABLE, "not_ready")
     };
-    info!(
-        "POST /quote/amount-in/token completed in {:?}",
-        start.elapsed()
-    );
-    response
+    (
+        status,
+        Json(ReadinessResponse {
+            status: label.to_string(),
+            chains,
+        }),
+    )
 }
 
-pub async fn quote_amount_out_raw(
-    State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<QuoteRequestWithPool>,
-) -> Result<Json<QuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    let pool_address = request
-        .pool
-        .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    // Validate token input
-    if let Err(error_response) = validate_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
+// Helper function to report a chain's sync progress against its head
+async fn chain_sync_info(network_id: u64, pool_registry: &PoolRegistry) -> ChainSyncInfo {
+    let sync_status = pool_registry.sync_status();
+    let last_processed_block = pool_registry.get_last_processed_block().await;
+    let head_block = sync_status.head_block();
+    let head_age_secs = sync_status.head_age();
+    let lag = head_block.map(|head| head.saturating_sub(last_processed_block));
+    let max_lag = pool_registry.max_sync_lag();
+    // A head that stopped refreshing leaves the lag frozen, so it cannot vouch for the chain
+    let head_fresh = head_age_secs.is_some_and(|age| age <= MAX_HEAD_AGE_SECS);
+    ChainSyncInfo {
+        network_id,
+        ready: head_fresh && lag.is_some_and(|lag| lag <= max_lag),
+        last_processed_block,
+        head_block,
+        head_age_secs,
+        lag,
+        max_lag,
+        websockets: sync_status
+            .websockets()
+            .into_iter()
+            .map(|(endpoint, connected)| WebsocketInfo {
+                endpoint,
+                connected,
+            })
+            .collect(),
+        last_error: sync_status.last_error().map(|error| SyncErrorInfo {
+            message: error.message,
+            timestamp: error.timestamp,
+        }),
     }
+}
 
-    let amount_in = request
-        .amount
-        .parse::<U256>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
+// Helper function to quote a route with its prices, slippage limits and fees per hop
+async fn route_quote_details(
+    processor: &Proccessor,
+    network_id: u64,
+    block_number: u64,
+    route: &RouteSnapshot,
+    amounts: &[U256],
+    exact_input: bool,
+    slippage_bps: Option<u64>,
+) -> Result<(RouteQuote, QuoteDetailsInfo), ApiError> {
+    let quote = RouteQuote::from_hop_amounts(route, amounts, exact_input)?;
+    let token_in = processor
+        .token_registry()
+        .get_token(network_id, route.token_in())
+        .await
+        .ok_or(ArbitrageError::TokenNotFound(route.token_in()))?;
+    let token_out = processor
+        .token_registry()
+        .get_token(network_id, route.token_out())
+        .await
+        .ok_or(ArbitrageError::TokenNotFound(route.token_out()))?;
+    // Raw output per raw input to token units
+    let scale = 10f64.powi(token_in.decimals as i32 - token_out.decimals as i32);
+
+    let details = QuoteDetailsInfo {
+        block_number,
+        amount_in: quote.amount_in.to_string(),
+        amount_out: quote.amount_out.to_string(),
+        slippage_bps,
+        amount_out_min: slippage_bps.map(|bps| quote.amount_out_min(bps).to_string()),
+        amount_in_max: slippage_bps.map(|bps| quote.amount_in_max(bps).to_string()),
+        mid_price: quote.mid_price * scale,
+        effective_price: quote.effective_price * scale,
+        price_impact: quote.price_impact,
+        hops: quote
+            .hops
+            .iter()
+            .zip(route.hops())
+            .map(|(hop, route_hop)| HopQuoteInfo {
+                pool: format!("{:?}", hop.pool),
+                token_in: format!("{:?}", hop.token_in),
+                token_out: format!("{:?}", hop.token_out),
+                amount_in: hop.amount_in.to_string(),
+                amount_out: hop.amount_out.to_string(),
+                fee: route_hop.pool.fee(),
+                fee_amount: hop.fee_amount.to_string(),
+            })
+            .collect(),
+    };
+    Ok((quote, details))
+}
 
-    let result = if let Some(token_in_str) = request.token_in {
-        let token_in = parse_token_address(Some(token_in_str))?;
-        processor
-            .quote_amount_out_token_in_raw(request.network_id, pool_address, token_in, amount_in)
-            .await
+// Helper function to quote a single pool request from one snapshot: the amount on the other
+// side of the swap and the snapshot's block, with the quote details when they can be built
+async fn quote_pool_request(
+    processor: &Proccessor,
+    request: &QuoteRequestWithPool,
+    exact_input: bool,
+    raw_amount: bool,
+) -> Result<(U256, u64, Option<QuoteDetailsInfo>), ApiError> {
+    let pool_address = parse_address("pool", &request.pool)?;
+    let token_in = parse_optional_address("token_in", request.token_in.as_ref())?;
+    let token_out = parse_optional_address("token_out", request.token_out.as_ref())?;
+    let snapshot = pool_snapshot(processor, request.network_id).await?;
+    let (pool, token_in, _) =
+        Proccessor::snapshot_pool_swap(&snapshot, pool_address, token_in, token_out)?;
+    let route = RouteSnapshot::new(vec![pool], token_in)?;
+
+    let amount = if raw_amount {
+        parse_amount(&request.amount)?
     } else {
-        let token_out_str = request.token_out.unwrap(); // Safe because we validated above
-        let token_out = parse_token_address(Some(token_out_str))?;
+        // Token amounts are given for the fixed side of the swap
+        let token = if exact_input {
+            route.token_in()
+        } else {
+            route.token_out()
+        };
         processor
-            .quote_amount_out_token_out_raw(request.network_id, pool_address, token_out, amount_in)
+            .token_registry()
+            .get_token(request.network_id, token)
             .await
+            .ok_or(ArbitrageError::TokenNotFound(token))?
+            .to_raw_amount(&request.amount)
+            .map_err(|e| ApiError::InvalidAmount(e.to_string()))?
     };
 
-    let response = match result {
-        Ok(amount) => Ok(Json(QuoteResponse::success(amount))),
-        Err(e) => Ok(Json(QuoteResponse::error(e.to_string()))),
+    let amounts = processor.route_hop_amounts(
+        request.network_id,
+        &snapshot,
+        &route,
+        amount,
+        exact_input,
+        request.bypass_cache.unwrap_or(false),
+    )?;
+    let result = if exact_input {
+        amounts[amounts.len() - 1]
+    } else {
+        amounts[0]
     };
-    info!(
-        "POST /quote/amount-out/raw completed in {:?}",
-        start.elapsed()
-    );
-    response
+
+    // Details are extra, a quote is still returned without them
+    let details = match route_quote_details(
+        processor,
+        request.network_id,
+        snapshot.block_number(),
+        &route,
+        &amounts,
+        exact_input,
+        request.slippage_bps,
+    )
+    .await
+    {
+        Ok((_, details)) => Some(details),
+        Err(e) => {
+            warn!(
+                "Quote details for pool {} unavailable: {:?}",
+                pool_address, e
+            );
+            None
+        }
+    };
+    Ok((result, snapshot.block_number(), details))
 }
 
-pub async fn quote_amount_out_token(
+#[utoipa::path(
+    post,
+    path = "/quote/amount-in/raw",
+    tag = "quotes",
+    summary = "Input needed for a raw output amount through one pool",
+    request_body = QuoteRequestWithPool,
+    responses((status = 200, body = QuoteResponse))
+)]
+pub async fn quote_amount_in_raw(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<QuoteRequestWithPool>,
-) -> Result<Json<QuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    let pool_address = request
-        .pool
-        .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    ApiJson(request): ApiJson<QuoteRequestWithPool>,
+) -> Result<Json<QuoteResponse>, ApiError> {
+    let pool_address = parse_address("pool", &request.pool)?;
+    trace::record_pool(request.network_id, pool_address);
+    validate_token_input(&request.token_in, &request.token_out)?;
+
+    let (amount, block_number, details) =
+        quote_pool_request(&processor, &request, false, true).await?;
+
+    Ok(Json(
+        QuoteResponse::success(amount, block_number).with_details(details),
+    ))
+}
 
-    // Validate token input
-    if let Err(error_response) = validate_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
-    }
+#[utoipa::path(
+    post,
+    path = "/quote/amount-in/token",
+    tag = "quotes",
+    summary = "Input needed for an output amount in token units through one pool",
+    request_body = QuoteRequestWithPool,
+    responses((status = 200, body = QuoteResponse))
+)]
+pub async fn quote_amount_in_token(
+    State(processor): State<Arc<Proccessor>>,
+    ApiJson(request): ApiJson<QuoteRequestWithPool>,
+) -> Result<Json<QuoteResponse>, ApiError> {
+    let pool_address = parse_address("pool", &request.pool)?;
+    trace::record_pool(request.network_id, pool_address);
+    validate_token_input(&request.token_in, &request.token_out)?;
+
+    let (amount, block_number, details) =
+        quote_pool_request(&processor, &request, false, false).await?;
+
+    Ok(Json(
+        QuoteResponse::success(amount, block_number).with_details(details),
+    ))
+}
 
-    let result = if let Some(token_in_str) = request.token_in {
-        let token_in = parse_token_address(Some(token_in_str))?;
-        processor
-            .quote_amount_out_token_in(request.network_id, pool_address, token_in, request.amount)
-            .await
-    } else {
-        let token_out_str = request.token_out.unwrap(); // Safe because we validated above
-        let token_out = parse_token_address(Some(token_out_str))?;
-        processor
-            .quote_amount_out_token_out(request.network_id, pool_address, token_out, request.amount)
-            .await
-    };
+#[utoipa::path(
+    post,
+    path = "/quote/amount-out/raw",
+    tag = "quotes",
+    summary = "Output for a raw input amount through one pool",
+    request_body = QuoteRequestWithPool,
+    responses((status = 200, body = QuoteResponse))
+)]
+pub async fn quote_amount_out_raw(
+    State(processor): State<Arc<Proccessor>>,
+    ApiJson(request): ApiJson<QuoteRequestWithPool>,
+) -> Result<Json<QuoteResponse>, ApiError> {
+    let pool_address = parse_address("pool", &request.pool)?;
+    trace::record_pool(request.network_id, pool_address);
+    validate_token_input(&request.token_in, &request.token_out)?;
+
+    let (amount, block_number, details) =
+        quote_pool_request(&processor, &request, true, true).await?;
+
+    Ok(Json(
+        QuoteResponse::success(amount, block_number).with_details(details),
+    ))
+}
 
-    let response = match result {
-        Ok(amount) => Ok(Json(QuoteResponse::success(amount))),
-        Err(e) => Ok(Json(QuoteResponse::error(e.to_string()))),
-    };
-    info!(
-        "POST /quote/amount-out/token completed in {:?}",
-        start.elapsed()
-    );
-    response
+#[utoipa::path(
+    post,
+    path = "/quote/amount-out/token",
+    tag = "quotes",
+    summary = "Output for an input amount in token units through one pool",
+    request_body = QuoteRequestWithPool,
+    responses((status = 200, body = QuoteResponse))
+)]
+pub async fn quote_amount_out_token(
+    State(processor): State<Arc<Proccessor>>,
+    ApiJson(request): ApiJson<QuoteRequestWithPool>,
+) -> Result<Json<QuoteResponse>, ApiError> {
+    let pool_address = parse_address("pool", &request.pool)?;
+    trace::record_pool(request.network_id, pool_address);
+    validate_token_input(&request.token_in, &request.token_out)?;
+
+    let (amount, block_number, details) =
+        quote_pool_request(&processor, &request, true, false).await?;
+
+    Ok(Json(
+        QuoteResponse::success(amount, block_number).with_details(details),
+    ))
 }
 
 // Batch quote handlers
+#[utoipa::path(
+    post,
+    path = "/quote/batch/amount-in/raw",
+    tag = "batch quotes",
+    summary = "Inputs needed for raw output amounts through one pool",
+    request_body = BatchQuoteRequestWithPool,
+    responses((status = 200, body = BatchQuoteResponse))
+)]
 pub async fn batch_quote_amount_in_raw_with_pool(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<BatchQuoteRequestWithPool>,
-) -> Result<Json<BatchQuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    let pool_address = request
-        .pool
-        .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    // Validate token input
-    if let Err(error_response) = validate_batch_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
-    }
-
-    // Parse all amounts as decimal
-    let amounts: Result<Vec<U256>, StatusCode> = request
-        .amounts
-        .iter()
-        .map(|amount_str| {
-            amount_str
-                .parse::<U256>()
-                .map_err(|_| StatusCode::BAD_REQUEST)
-        })
-        .collect();
-
-    let amounts = amounts?;
-
-    let results = if let Some(token_in_str) = request.token_in {
-        let token_in = parse_token_address(Some(token_in_str))?;
-        let mut results = Vec::new();
-        for amount in amounts {
-            match processor
-                .quote_amount_in_token_in_raw(request.network_id, pool_address, token_in, amount)
-                .await
-            {
-                Ok(result) => results.push(result),
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
-            }
-        }
-        results
-    } else {
-        let token_out_str = request.token_out.unwrap(); // Safe because we validated above
-        let token_out = parse_token_address(Some(token_out_str))?;
-        let mut results = Vec::new();
-        for amount in amounts {
-            match processor
-                .quote_amount_in_token_out_raw(request.network_id, pool_address, token_out, amount)
-                .await
-            {
-                Ok(result) => results.push(result),
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
-            }
-        }
-        results
-    };
-
-    let response = Ok(Json(BatchQuoteResponse::success(results)));
-    info!(
-        "POST /quote/batch/amount-in/raw completed in {:?}",
-        start.elapsed()
-    );
-    response
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<BatchQuoteRequestWithPool>,
+) -> Result<Json<BatchQuoteResponse>, ApiError> {
+    caller.limits.check_amounts(request.amounts.len())?;
+    let pool_address = parse_address("pool", &request.pool)?;
+    trace::record_pool(request.network_id, pool_address);
+    validate_batch_token_input(&request.token_in, &request.token_out)?;
+    let amounts = parse_amounts(&request.amounts)?;
+
+    // Every amount is quoted in one call against the same pool state
+    let token_in = parse_optional_address("token_in", request.token_in.as_ref())?;
+    let token_out = parse_optional_address("token_out", request.token_out.as_ref())?;
+    let (results, block_number) = processor
+        .quote_pool_amounts_raw(
+            request.network_id,
+            pool_address,
+            token_in,
+            token_out,
+            &amounts,
+            false,
+        )
+        .await?;
+
+    Ok(Json(BatchQuoteResponse::success(results, block_number)))
 }
 
 // Batch quote handlers
+#[utoipa::path(
+    post,
+    path = "/quote/batch/amount-in/path/raw",
+    tag = "batch quotes",
+    summary = "Smallest inputs needed for raw output amounts over the best path",
+    request_body = BatchQuoteRequest,
+    responses((status = 200, body = BatchQuoteResponse))
+)]
 pub async fn batch_quote_amount_in_raw(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<BatchQuoteRequest>,
-) -> Result<Json<BatchQuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    // Validate token input
-    if let Err(error_response) = validate_batch_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
-    }
-
-    // Parse all amounts as decimal
-    let amounts: Result<Vec<U256>, StatusCode> = request
-        .amounts
-        .iter()
-        .map(|amount_str| {
-            amount_str
-                .parse::<U256>()
-                .map_err(|_| StatusCode::BAD_REQUEST)
-        })
-        .collect();
-
-    let amounts = amounts?;
-
-    let token_in = parse_token_address(request.token_in)?;
-    let token_out = parse_token_address(request.token_out)?;
-    let paths = processor
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<BatchQuoteRequest>,
+) -> Result<Json<BatchQuoteResponse>, ApiError> {
+    caller.limits.check_amounts(request.amounts.len())?;
+    validate_batch_token_input(&request.token_in, &request.token_out)?;
+    let amounts = parse_amounts(&request.amounts)?;
+
+    let token_in = parse_required_address("token_in", request.token_in.as_ref())?;
+    let token_out = parse_required_address("token_out", request.token_out.as_ref())?;
+    let pool_registry = processor
         .pool_registry()
         .get_pool_registry(request.network_id)
         .await
-        .ok_or(StatusCode::NOT_FOUND)?
-        .get_all_path_from_token_to_token(token_in, token_out, 3)
+        .ok_or(ArbitrageError::NetworkNotFound(request.network_id))?;
+    // Rank every amount against the same pool states
+    let snapshot = pool_registry.snapshot();
+    let routes = snapshot_paths(
+        &pool_registry,
+        &snapshot,
+        token_in,
+        token_out,
+        MAX_PATH_HOPS.min(caller.limits.max_hops),
+    )
+    .await?;
+    // Routes are ranked net of gas when its price in the quoted token is known
+    let gas_price = processor
+        .gas_price_in_token(request.network_id, token_in)
         .await;
 
-    let mut results = Vec::new();
-    for amount in amounts {
-        let mut best_result = U256::MAX;
-        for path in &paths {
-            match processor
-                .quote_amount_token_with_path_raw(
-                    request.network_id,
-                    &path,
-                    amount,
-                    &QuoteType::ExactOut,
-                    token_in,
-                    token_out,
-                )
-                .await
-            {
-                Ok(result) => {
-                    let input_amount = result
-                        .input
-                        .amount
-                        .parse::<U256>()
-                        .map_err(|_| StatusCode::BAD_REQUEST)?;
-                    if input_amount < best_result {
-                        best_result = input_amount;
-                    }
-                }
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
+    // Each route quotes all amounts at once
+    let mut best: Vec<Option<RouteCost>> = vec![None; amounts.len()];
+    for route in &routes {
+        let route_amounts = processor.route_hop_amounts_many(
+            request.network_id,
+            &snapshot,
+            route,
+            &amounts,
+            false,
+            request.bypass_cache.unwrap_or(false),
+        )?;
+        for (best, hop_amounts) in best.iter_mut().zip(route_amounts) {
+            let cost = RouteCost::from_hop_amounts(route, &hop_amounts, false, gas_price);
+            if best.as_ref().is_none_or(|best| cost.is_better_than(best)) {
+                *best = Some(cost);
             }
         }
-        results.push(best_result);
     }
+    let results = best
+        .into_iter()
+        .map(|best| best.map_or(U256::MAX, |best| best.amount))
+        .collect();
 
-    let response = Ok(Json(BatchQuoteResponse::success(results)));
-    info!(
-        "POST /quote/batch/amount-in/path/raw completed in {:?}",
-        start.elapsed()
-    );
-    response
+    Ok(Json(BatchQuoteResponse::success(
+        results,
+        snapshot.block_number(),
+    )))
 }
 
+#[utoipa::path(
+    post,
+    path = "/quote/batch/amount-in/token",
+    tag = "batch quotes",
+    summary = "Inputs needed for output amounts in token units through one pool",
+    request_body = BatchQuoteRequestWithPool,
+    responses((status = 200, body = BatchQuoteResponse))
+)]
 pub async fn batch_quote_amount_in_token_with_pool(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<BatchQuoteRequestWithPool>,
-) -> Result<Json<BatchQuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    let pool_address = request
-        .pool
-        .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    // Validate token input
-    if let Err(error_response) = validate_batch_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
-    }
-
-    let results = if let Some(token_in_str) = request.token_in {
-        let token_in = parse_token_address(Some(token_in_str))?;
-        let mut results = Vec::new();
-        for amount in request.amounts {
-            match processor
-                .quote_amount_in_token_in(request.network_id, pool_address, token_in, amount)
-                .await
-            {
-                Ok(result) => results.push(result),
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
-            }
-        }
-        results
-    } else {
-        let token_out_str = request.token_out.unwrap(); // Safe because we validated above
-        let token_out = parse_token_address(Some(token_out_str))?;
-        let mut results = Vec::new();
-        for amount in request.amounts {
-            match processor
-                .quote_amount_in_token_out(request.network_id, pool_address, token_out, amount)
-                .await
-            {
-                Ok(result) => results.push(result),
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
-            }
-        }
-        results
-    };
-
-    let response = Ok(Json(BatchQuoteResponse::success(results)));
-    info!(
-        "POST /quote/batch/amount-in/token completed in {:?}",
-        start.elapsed()
-    );
-    response
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<BatchQuoteRequestWithPool>,
+) -> Result<Json<BatchQuoteResponse>, ApiError> {
+    caller.limits.check_amounts(request.amounts.len())?;
+    let pool_address = parse_address("pool", &request.pool)?;
+    trace::record_pool(request.network_id, pool_address);
+    validate_batch_token_input(&request.token_in, &request.token_out)?;
+
+    // Every amount is quoted in one call against the same pool state
+    let token_in = parse_optional_address("token_in", request.token_in.as_ref())?;
+    let token_out = parse_optional_address("token_out", request.token_out.as_ref())?;
+    let (results, block_number) = processor
+        .quote_pool_amounts(
+            request.network_id,
+            pool_address,
+            token_in,
+            token_out,
+            &request.amounts,
+            false,
+        )
+        .await?;
+
+    Ok(Json(BatchQuoteResponse::success(results, block_number)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/quote/batch/amount-out/raw",
+    tag = "batch quotes",
+    summary = "Outputs for raw input amounts through one pool",
+    request_body = BatchQuoteRequestWithPool,
+    responses((status = 200, body = BatchQuoteResponse))
+)]
 pub async fn batch_quote_amount_out_raw_with_pool(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<BatchQuoteRequestWithPool>,
-) -> Result<Json<BatchQuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    let pool_address = request
-        .pool
-        .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    // Validate token input
-    if let Err(error_response) = validate_batch_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
-    }
-
-    // Parse all amounts as decimal
-    let amounts: Result<Vec<U256>, StatusCode> = request
-        .amounts
-        .iter()
-        .map(|amount_str| {
-            amount_str
-                .parse::<U256>()
-                .map_err(|_| StatusCode::BAD_REQUEST)
-        })
-        .collect();
-
-    let amounts = amounts?;
-
-    let results = if let Some(token_in_str) = request.token_in {
-        let token_in = parse_token_address(Some(token_in_str))?;
-        let mut results = Vec::new();
-        for amount in amounts {
-            match processor
-                .quote_amount_out_token_in_raw(request.network_id, pool_address, token_in, amount)
-                .await
-            {
-                Ok(result) => results.push(result),
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
-            }
-        }
-        results
-    } else {
-        let token_out_str = request.token_out.unwrap(); // Safe because we validated above
-        let token_out = parse_token_address(Some(token_out_str))?;
-        let mut results = Vec::new();
-        for amount in amounts {
-            match processor
-                .quote_amount_out_token_out_raw(request.network_id, pool_address, token_out, amount)
-                .await
-            {
-                Ok(result) => results.push(result),
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
-            }
-        }
-        results
-    };
-
-    let response = Ok(Json(BatchQuoteResponse::success(results)));
-    info!(
-        "POST /quote/batch/amount-out/raw completed in {:?}",
-        start.elapsed()
-    );
-    response
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<BatchQuoteRequestWithPool>,
+) -> Result<Json<BatchQuoteResponse>, ApiError> {
+    caller.limits.check_amounts(request.amounts.len())?;
+    let pool_address = parse_address("pool", &request.pool)?;
+    trace::record_pool(request.network_id, pool_address);
+    validate_batch_token_input(&request.token_in, &request.token_out)?;
+    let amounts = parse_amounts(&request.amounts)?;
+
+    // Every amount is quoted in one call against the same pool state
+    let token_in = parse_optional_address("token_in", request.token_in.as_ref())?;
+    let token_out = parse_optional_address("token_out", request.token_out.as_ref())?;
+    let (results, block_number) = processor
+        .quote_pool_amounts_raw(
+            request.network_id,
+            pool_address,
+            token_in,
+            token_out,
+            &amounts,
+            true,
+        )
+        .await?;
+
+    Ok(Json(BatchQuoteResponse::success(results, block_number)))
 }
 
 // Batch quote handlers
+#[utoipa::path(
+    post,
+    path = "/quote/batch/amount-out/path/raw",
+    tag = "batch quotes",
+    summary = "Largest outputs for raw input amounts over the best path",
+    request_body = BatchQuoteRequest,
+    responses((status = 200, body = BatchQuoteResponse))
+)]
 pub async fn batch_quote_amount_out_raw(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<BatchQuoteRequest>,
-) -> Result<Json<BatchQuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    // Validate token input
-    if let Err(error_response) = validate_batch_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
-    }
-
-    // Parse all amounts as decimal
-    let amounts: Result<Vec<U256>, StatusCode> = request
-        .amounts
-        .iter()
-        .map(|amount_str| {
-            amount_str
-                .parse::<U256>()
-                .map_err(|_| StatusCode::BAD_REQUEST)
-        })
-        .collect();
-
-    let amounts = amounts?;
-
-    let token_in = parse_token_address(request.token_in)?;
-    let token_out = parse_token_address(request.token_out)?;
-    let paths = processor
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<BatchQuoteRequest>,
+) -> Result<Json<BatchQuoteResponse>, ApiError> {
+    caller.limits.check_amounts(request.amounts.len())?;
+    validate_batch_token_input(&request.token_in, &request.token_out)?;
+    let amounts = parse_amounts(&request.amounts)?;
+
+    let token_in = parse_required_address("token_in", request.token_in.as_ref())?;
+    let token_out = parse_required_address("token_out", request.token_out.as_ref())?;
+    let pool_registry = processor
         .pool_registry()
         .get_pool_registry(request.network_id)
         .await
-        .ok_or(StatusCode::NOT_FOUND)?
-        .get_all_path_from_token_to_token(token_in, token_out, 3)
+        .ok_or(ArbitrageError::NetworkNotFound(request.network_id))?;
+    // Rank every amount against the same pool states
+    let snapshot = pool_registry.snapshot();
+    let routes = snapshot_paths(
+        &pool_registry,
+        &snapshot,
+        token_in,
+        token_out,
+        MAX_PATH_HOPS.min(caller.limits.max_hops),
+    )
+    .await?;
+    // Routes are ranked net of gas when its price in the quoted token is known
+    let gas_price = processor
+        .gas_price_in_token(request.network_id, token_out)
         .await;
 
-    let mut results = Vec::new();
-    for amount in amounts {
-        let mut best_result = U256::ZERO;
-        for path in &paths {
-            match processor
-                .quote_amount_token_with_path_raw(
-                    request.network_id,
-                    &path,
-                    amount,
-                    &QuoteType::ExactIn,
-                    token_in,
-                    token_out,
-                )
-                .await
-            {
-                Ok(result) => {
-                    let output_amount = result
-                        .output
-                        .amount
-                        .parse::<U256>()
-                        .map_err(|_| StatusCode::BAD_REQUEST)?;
-                    if output_amount > best_result {
-                        best_result = output_amount;
-                    }
-                }
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
+    // Each route quotes all amounts at once
+    let mut best: Vec<Option<RouteCost>> = vec![None; amounts.len()];
+    for route in &routes {
+        let route_amounts = processor.route_hop_amounts_many(
+            request.network_id,
+            &snapshot,
+            route,
+            &amounts,
+            true,
+            request.bypass_cache.unwrap_or(false),
+        )?;
+        for (best, hop_amounts) in best.iter_mut().zip(route_amounts) {
+            let cost = RouteCost::from_hop_amounts(route, &hop_amounts, true, gas_price);
+            if best.as_ref().is_none_or(|best| cost.is_better_than(best)) {
+                *best = Some(cost);
             }
         }
-        results.push(best_result);
     }
+    let results = best
+        .into_iter()
+        .map(|best| best.map_or(U256::ZERO, |best| best.amount))
+        .collect();
 
-    let response = Ok(Json(BatchQuoteResponse::success(results)));
-    info!(
-        "POST /quote/batch/amount-out/path/raw completed in {:?}",
-        start.elapsed()
-    );
-    response
+    Ok(Json(BatchQuoteResponse::success(
+        results,
+        snapshot.block_number(),
+    )))
 }
 
+#[utoipa::path(
+    post,
+    path = "/quote/batch/amount-out/pools/raw",
+    tag = "batch quotes",
+    summary = "Outputs for input amounts in token units through a chain of pools, with every step",
+    request_body = BatchQuoteRequestWithPools,
+    responses((status = 200, body = BatchQuoteResponseWithSteps))
+)]
 pub async fn batch_quote_amount_out_token_with_pools(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<BatchQuoteRequestWithPools>,
-) -> Result<Json<BatchQuoteResponseWithSteps>, StatusCode> {
-    let start = Instant::now();
-    // Parse all amounts as decimal
-    let first_pool = request.pools.first().unwrap();
-    let token_in = parse_token_address(Some(first_pool.token_in.clone()))?;
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<BatchQuoteRequestWithPools>,
+) -> Result<Json<BatchQuoteResponseWithSteps>, ApiError> {
+    caller.limits.check_amounts(request.amounts.len())?;
+    caller.limits.check_hops(request.pools.len())?;
+    // Parse all amounts in the first pool's input token
+    let first_pool = request
+        .pools
+        .first()
+        .ok_or_else(|| ApiError::InvalidRequest("pools must not be empty".to_string()))?;
+    let token_in = parse_address("token_in", &first_pool.token_in)?;
     let token_in_decimals = processor
         .token_registry()
         .get_token(first_pool.network_id, token_in)
         .await
-        .ok_or(StatusCode::NOT_FOUND)?
+        .ok_or(ArbitrageError::TokenNotFound(token_in))?
         .decimals;
-    let amounts: Result<Vec<U256>, StatusCode> = request
+    let mut amounts = request
         .amounts
         .iter()
         .map(|amount_str| {
             parse_units(amount_str, token_in_decimals)
                 .map(Into::into)
-                .map_err(|_| StatusCode::BAD_REQUEST)
+                .map_err(|e| ApiError::InvalidAmount(format!("{:?}: {}", amount_str, e)))
         })
-        .collect();
+        .collect::<Result<Vec<U256>, ApiError>>()?;
 
-    let mut amounts = amounts?;
-    let mut last_token = None;
+    let mut last_token: Option<Token> = None;
     let mut steps = Vec::new();
     let mut step_tokens = Vec::new();
     let mut step_decimals = Vec::new();
+    let mut step_blocks = Vec::new();
     for pool in request.pools {
-        let pool_address = pool
-            .pool_address
-            .parse::<Address>()
-            .map_err(|_| StatusCode::BAD_REQUEST)?;
-        let pool_token_in = parse_token_address(Some(pool.token_in))?;
+        let pool_address = parse_address("pool_address", &pool.pool_address)?;
+        let pool_token_in = parse_address("token_in", &pool.token_in)?;
         let pool_token_in_data = processor
             .token_registry()
             .get_token(pool.network_id, pool_token_in)
             .await
-            .ok_or(StatusCode::NOT_FOUND)?;
-        if last_token.is_some() {
-            let last_token_obj: Token = last_token.unwrap();
-            if last_token_obj.decimals != pool_token_in_data.decimals {
+            .ok_or(ArbitrageError::TokenNotFound(pool_token_in))?;
+        if let Some(last_token) = &last_token {
+            if last_token.decimals != pool_token_in_data.decimals {
                 // Convert last token to pool token in decimals
-                let this_token_mult: U256 = parse_units("1", pool_token_in_data.decimals)
-                    .map(Into::into)
-                    .unwrap();
-                let last_token_mult: U256 = parse_units("1", last_token_obj.decimals)
-                    .map(Into::into)
-                    .unwrap();
+                let this_token_mult = U256::from(10).pow(U256::from(pool_token_in_data.decimals));
+                let last_token_mult = U256::from(10).pow(U256::from(last_token.decimals));
                 amounts = amounts
                     .iter()
                     .map(|amount| *amount * this_token_mult / last_token_mult)
@@ -622,185 +727,273 @@ pub async fn batch_quote_amount_out_token_with_pools(
             }
         }
 
-        // Process all quotes for this pool in parallel
-        let mut step_amounts = vec![U256::ZERO; amounts.len()];
-        let mut join_set = JoinSet::new();
-
-        for (index, amount) in amounts.iter().enumerate() {
-            let processor_clone = Arc::clone(&processor);
-            let amount = *amount;
-            join_set.spawn(async move {
-                let result = processor_clone
-                    .quote_amount_out_token_in_raw(
-                        pool.network_id,
-                        pool_address,
-                        pool_token_in,
-                        amount,
-                    )
-                    .await;
-                (index, result)
-            });
-        }
-
-        while let Some(result) = join_set.join_next().await {
-            match result {
-                Ok((index, Ok(quote_result))) => {
-                    step_amounts[index] = quote_result;
-                }
-                Ok((_, Err(e))) => {
-                    return Ok(Json(BatchQuoteResponseWithSteps::error(e.to_string())))
-                }
-                Err(e) => return Ok(Json(BatchQuoteResponseWithSteps::error(e.to_string()))),
-            }
-        }
-        amounts = step_amounts;
+        // Quote every amount for this pool in one call against the same pool state
+        let block_number;
+        (amounts, block_number) = processor
+            .quote_pool_amounts_raw(
+                pool.network_id,
+                pool_address,
+                Some(pool_token_in),
+                None,
+                &amounts,
+                true,
+            )
+            .await?;
         let (token0, token1) = processor
             .pool_registry()
             .get_pool_registry(pool.network_id)
             .await
-            .ok_or(StatusCode::NOT_FOUND)?
+            .ok_or(ArbitrageError::NetworkNotFound(pool.network_id))?
+            .snapshot()
             .get_pool(&pool_address)
-            .await
-            .ok_or(StatusCode::NOT_FOUND)?
-            .read()
-            .await
+            .ok_or(ArbitrageError::PoolNotFound(pool_address))?
             .tokens();
         let token_out = if pool_token_in == token0 {
             token1
         } else {
             token0
         };
-        last_token = processor
+        let token_out_data = processor
             .token_registry()
             .get_token(pool.network_id, token_out)
             .await
-            .ok_or(StatusCode::NOT_FOUND)
-            .ok();
+            .ok_or(ArbitrageError::TokenNotFound(token_out))?;
 
-        step_tokens.push(last_token.as_ref().unwrap().symbol.clone());
-        step_decimals.push(last_token.as_ref().unwrap().decimals.clone());
+        step_tokens.push(token_out_data.symbol.clone());
+        step_decimals.push(token_out_data.decimals);
         steps.push(amounts.clone());
+        step_blocks.push(block_number);
+        last_token = Some(token_out_data);
     }
 
-    let response = Ok(Json(BatchQuoteResponseWithSteps::success(
+    Ok(Json(BatchQuoteResponseWithSteps::success(
         amounts,
         steps,
         step_tokens,
         step_decimals,
-    )));
-    info!(
-        "POST /quote/batch/amount-out/pools/raw completed in {:?}",
-        start.elapsed()
-    );
-    response
+        step_blocks,
+    )))
 }
 
+#[utoipa::path(
+    post,
+    path = "/quote/batch/amount-out/token",
+    tag = "batch quotes",
+    summary = "Outputs for input amounts in token units through one pool",
+    request_body = BatchQuoteRequestWithPool,
+    responses((status = 200, body = BatchQuoteResponse))
+)]
 pub async fn batch_quote_amount_out_token_with_pool(
     State(processor): State<Arc<Proccessor>>,
-    Json(request): Json<BatchQuoteRequestWithPool>,
-) -> Result<Json<BatchQuoteResponse>, StatusCode> {
-    let start = Instant::now();
-    let pool_address = request
-        .pool
-        .parse::<Address>()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    // Validate token input
-    if let Err(error_response) = validate_batch_token_input(&request.token_in, &request.token_out) {
-        return Ok(Json(error_response));
-    }
-
-    let results = if let Some(token_in_str) = request.token_in {
-        let token_in = parse_token_address(Some(token_in_str))?;
-        let mut results = Vec::new();
-        for amount in request.amounts {
-            match processor
-                .quote_amount_out_token_in(request.network_id, pool_address, token_in, amount)
-                .await
-            {
-                Ok(result) => results.push(result),
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
-            }
-        }
-        results
-    } else {
-        let token_out_str = request.token_out.unwrap(); // Safe because we validated above
-        let token_out = parse_token_address(Some(token_out_str))?;
-        let mut results = Vec::new();
-        for amount in request.amounts {
-            match processor
-                .quote_amount_out_token_out(request.network_id, pool_address, token_out, amount)
-                .await
-            {
-                Ok(result) => results.push(result),
-                Err(e) => return Ok(Json(BatchQuoteResponse::error(e.to_string()))),
-            }
-        }
-        results
-    };
-
-    let response = Ok(Json(BatchQuoteResponse::success(results)));
-    info!(
-        "POST /quote/batch/amount-out/token completed in {:?}",
-        start.elapsed()
-    );
-    response
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<BatchQuoteRequestWithPool>,
+) -> Result<Json<BatchQuoteResponse>, ApiError> {
+    caller.limits.check_amounts(request.amounts.len())?;
+    let pool_address = parse_address("pool", &request.pool)?;
+    trace::record_pool(request.network_id, pool_address);
+    validate_batch_token_input(&request.token_in, &request.token_out)?;
+
+    // Every amount is quoted in one call against the same pool state
+    let token_in = parse_optional_address("token_in", request.token_in.as_ref())?;
+    let token_out = parse_optional_address("token_out", request.token_out.as_ref())?;
+    let (results, block_number) = processor
+        .quote_pool_amounts(
+            request.network_id,
+            pool_address,
+            token_in,
+            token_out,
+            &request.amounts,
+            true,
+        )
+        .await?;
+
+    Ok(Json(BatchQuoteResponse::success(results, block_number)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/networks",
+    tag = "networks",
+    summary = "Networks with a pool registry",
+    responses((status = 200, body = NetworksResponse))
+)]
 pub async fn get_networks(
     State(processor): State<Arc<Proccessor>>,
-) -> Result<Json<NetworksResponse>, StatusCode> {
-    let start = Instant::now();
+) -> Result<Json<NetworksResponse>, ApiError> {
     let networks = processor.pool_registry().get_all_network_ids().await;
 
-    let response = Ok(Json(NetworksResponse {
+    Ok(Json(NetworksResponse {
         networks: networks.clone(),
         total_networks: networks.len(),
-    }));
-    info!("GET /networks completed in {:?}", start.elapsed());
-    response
+    }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/networks/{network_id}/pools",
+    tag = "networks",
+    summary = "Pool addresses of a network, filtered and paginated",
+    params(("network_id" = u64, Path, description = "Chain id of the network"), PoolsQuery),
+    responses((status = 200, body = PoolsResponse))
+)]
 pub async fn get_pools(
     State(processor): State<Arc<Proccessor>>,
-    Path(network_id): Path<u64>,
-) -> Result<Json<PoolsResponse>, StatusCode> {
-    let start = Instant::now();
+    ApiPath(network_id): ApiPath<u64>,
+    ApiQuery(query): ApiQuery<PoolsQuery>,
+) -> Result<Json<PoolsResponse>, ApiError> {
     let pool_registry = processor
         .pool_registry()
         .get_pool_registry(network_id)
         .await
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .ok_or(ArbitrageError::NetworkNotFound(network_id))?;
+
+    let mut pool_addresses = match &query.token {
+        Some(token) => {
+            let token = parse_address("token", token)?;
+            pool_registry.get_addresses_by_token(&token).await
+        }
+        None => pool_registry.get_all_addresses().await.to_vec(),
+    };
 
-    let pool_addresses = pool_registry.get_all_addresses().await;
+    if let Some(pool_type) = &query.pool_type {
+        if !PoolType::NAMES
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(pool_type))
+        {
+            return Err(ApiError::InvalidRequest(format!(
+                "Unknown pool type {:?}, expected one of {}",
+                pool_type,
+                PoolType::NAMES.join(", ")
+            )));
+        }
+        let snapshot = pool_registry.snapshot();
+        let mut matching = Vec::with_capacity(pool_addresses.len());
+        for address in pool_addresses {
+            if let Some(pool) = snapshot.get_pool(&address) {
+                if pool.pool_type().name().eq_ignore_ascii_case(pool_type) {
+                    matching.push(address);
+                }
+            }
+        }
+        pool_addresses = matching;
+    }
+
+    pool_addresses.sort();
+    let total_pools = pool_addresses.len();
     let pool_strings: Vec<String> = pool_addresses
         .iter()
+        .skip(query.offset.unwrap_or(0))
+        .take(query.limit.unwrap_or(usize::MAX))
         .map(|addr| format!("{:?}", addr))
         .collect();
 
-    let response = Ok(Json(PoolsResponse {
+    Ok(Json(PoolsResponse {
         network_id,
-        pools: pool_strings.clone(),
-        total_pools: pool_strings.len(),
-    }));
-    info!(
-        "GET /networks/{}/pools completed in {:?}",
+        pools: pool_strings,
+        total_pools,
+    }))
+}
+
+// Helper function to describe a pool token, with symbol and decimals when known
+async fn pool_token_info(processor: &Proccessor, network_id: u64, token: Address) -> PoolTokenInfo {
+    let token_data = processor
+        .token_registry()
+        .get_token(network_id, token)
+        .await;
+    PoolTokenInfo {
+        address: format!("{:?}", token),
+        symbol: token_data.as_ref().map(|token| token.symbol.clone()),
+        decimals: token_data.map(|token| token.decimals),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/networks/{network_id}/pools/{pool}",
+    tag = "networks",
+    summary = "Pool tokens, fee and current state",
+    params(("network_id" = u64, Path, description = "Chain id of the network"), ("pool" = String, Path, description = "Pool address")),
+    responses((status = 200, body = PoolDetailResponse))
+)]
+pub async fn get_pool(
+    State(processor): State<Arc<Proccessor>>,
+    ApiPath((network_id, pool)): ApiPath<(u64, String)>,
+) -> Result<Json<PoolDetailResponse>, ApiError> {
+    let pool_address = parse_address("pool", &pool)?;
+    trace::record_pool(network_id, pool_address);
+    let pool_registry = processor
+        .pool_registry()
+        .get_pool_registry(network_id)
+        .await
+        .ok_or(ArbitrageError::NetworkNotFound(network_id))?;
+    let pool_state = pool_registry
+        .snapshot()
+        .get_pool(&pool_address)
+        .ok_or(ArbitrageError::PoolNotFound(pool_address))?;
+
+    let (pool_type, tokens, fee, state, tick_spacing, ticks) = {
+        let pool = &pool_state;
+        let v3_pool = pool.downcast_ref::<UniswapV3Pool>();
+        (
+            pool.pool_type(),
+            pool.tokens(),
+            pool.fee(),
+            pool.state(),
+            v3_pool.map(|v3_pool| v3_pool.tick_spacing),
+            v3_pool.map(|v3_pool| {
+                v3_pool
+                    .ticks
+                    .values()
+                    .map(|tick| TickInfo {
+                        index: tick.index,
+                        liquidity_net: tick.liquidity_net.to_string(),
+                        liquidity_gross: tick.liquidity_gross.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+            }),
+        )
+    };
+
+    Ok(Json(PoolDetailResponse {
         network_id,
-        start.elapsed()
-    );
-    response
+        address: format!("{:?}", pool_address),
+        pool_type: pool_type.name().to_string(),
+        token0: pool_token_info(&processor, network_id, tokens.0).await,
+        token1: pool_token_info(&processor, network_id, tokens.1).await,
+        fee,
+        state: state.as_ref().map(PoolStateInfo::from),
+        tick_spacing,
+        ticks,
+        last_updated_block: pool_registry.get_last_updated_block(&pool_address).await,
+    }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/networks/{network_id}/tokens",
+    tag = "networks",
+    summary = "Tokens of a network with their USD prices",
+    params(("network_id" = u64, Path, description = "Chain id of the network")),
+    responses((status = 200, body = TokensResponse))
+)]
 pub async fn get_tokens(
     State(processor): State<Arc<Proccessor>>,
-    Path(network_id): Path<u64>,
-) -> Result<Json<TokensResponse>, StatusCode> {
-    let start = Instant::now();
+    ApiPath(network_id): ApiPath<u64>,
+) -> Result<Json<TokensResponse>, ApiError> {
     let token_registry = processor
         .token_registry()
         .get_token_registry(network_id)
         .await
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .ok_or(ArbitrageError::NetworkNotFound(network_id))?;
+
+    let prices = match processor
+        .price_registry()
+        .get_price_registry(network_id)
+        .await
+    {
+        Some(price_registry) => price_registry.get_all_prices().await,
+        None => Default::default(),
+    };
 
     let registry_guard = token_registry.read().await;
     let tokens = registry_guard.get_all_tokens();
@@ -811,18 +1004,739 @@ pub async fn get_tokens(
             symbol: token.symbol.clone(),
             name: token.name.clone(),
             decimals: token.decimals,
+            price_usd: prices.get(&token.address).map(|price| price.price_usd),
         })
         .collect();
 
-    let response = Ok(Json(TokensResponse {
+    Ok(Json(TokensResponse {
         network_id,
         tokens: token_infos.clone(),
         total_tokens: token_infos.len(),
-    }));
-    info!(
-        "GET /networks/{}/tokens completed in {:?}",
+    }))
+}
+
+// Helper function to scale raw candles by token decimals and build the response
+async fn build_candles_response(
+    processor: &Proccessor,
+    network_id: u64,
+    key: CandleKey,
+    base_token: Address,
+    quote_token: Address,
+    query: CandleQuery,
+) -> Result<CandlesResponse, ApiError> {
+    let candle_registry = processor
+        .pool_registry()
+        .get_pool_registry(network_id)
+        .await
+        .ok_or(ArbitrageError::NetworkNotFound(network_id))?
+        .candle_registry()
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Candles are not enabled for network {}",
+                network_id
+            ))
+        })?;
+
+    let interval = query
+        .interval
+        .unwrap_or_else(|| candle_registry.intervals()[0]);
+    if !candle_registry.intervals().contains(&interval) {
+        return Err(ApiError::InvalidRequest(format!(
+            "Unsupported candle interval {}, expected one of {:?}",
+            interval,
+            candle_registry.intervals()
+        )));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_MAX_CANDLES).max(1);
+
+    let candles = candle_registry
+        .get_candles(key, interval, query.from, query.to, limit)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let base = processor
+        .token_registry()
+        .get_token(network_id, base_token)
+        .await
+        .ok_or(ArbitrageError::TokenNotFound(base_token))?;
+    let quote = processor
+        .token_registry()
+        .get_token(network_id, quote_token)
+        .await
+        .ok_or(ArbitrageError::TokenNotFound(quote_token))?;
+
+    // Candles are stored as base in quote, so flip the pair when asked for the inverse
+    let (base, quote, candles): (Token, Token, Vec<Candle>) = if query.invert.unwrap_or(false) {
+        (quote, base, candles.iter().map(Candle::inverted).collect())
+    } else {
+        (base, quote, candles)
+    };
+    let scale = 10f64.powi(base.decimals as i32 - quote.decimals as i32);
+
+    let candles: Vec<CandleInfo> = candles
+        .into_iter()
+        .map(|candle| CandleInfo {
+            start: candle.start,
+            open: candle.open * scale,
+            high: candle.high * scale,
+            low: candle.low * scale,
+            close: candle.close * scale,
+            volume_base: base.to_human_amount(candle.volume0).unwrap_or_default(),
+            volume_quote: quote.to_human_amount(candle.volume1).unwrap_or_default(),
+            trades: candle.trades,
+            first_block: candle.first_block,
+            last_block: candle.last_block,
+        })
+        .collect();
+
+    let series = match key {
+        CandleKey::Pool(pool) => format!("{:?}", pool),
+        CandleKey::Pair(token0, token1) => format!("{:?}-{:?}", token0, token1),
+    };
+    Ok(CandlesResponse {
+        network_id,
+        series,
+        base_token: format!("{:?}", base.address),
+        quote_token: format!("{:?}", quote.address),
+        interval,
+        total_candles: candles.len(),
+        candles,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/networks/{network_id}/pools/{pool}/candles",
+    tag = "candles",
+    summary = "Price candles of a pool",
+    params(("network_id" = u64, Path, description = "Chain id of the network"), ("pool" = String, Path, description = "Pool address"), CandleQuery),
+    responses((status = 200, body = CandlesResponse))
+)]
+pub async fn get_pool_candles(
+    State(processor): State<Arc<Proccessor>>,
+    ApiPath((network_id, pool)): ApiPath<(u64, String)>,
+    ApiQuery(query): ApiQuery<CandleQuery>,
+) -> Result<Json<CandlesResponse>, ApiError> {
+    let pool_address = parse_address("pool", &pool)?;
+    trace::record_pool(network_id, pool_address);
+    let pool_registry = processor
+        .pool_registry()
+        .get_pool_registry(network_id)
+        .await
+        .ok_or(ArbitrageError::NetworkNotFound(network_id))?;
+    let tokens = pool_registry
+        .snapshot()
+        .get_pool(&pool_address)
+        .ok_or(ArbitrageError::PoolNotFound(pool_address))?
+        .tokens();
+
+    let response = build_candles_response(
+        &processor,
         network_id,
-        start.elapsed()
-    );
-    response
+        CandleKey::Pool(pool_address),
+        tokens.0,
+        tokens.1,
+        query,
+    )
+    .await?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/networks/{network_id}/pairs/{token_a}/{token_b}/candles",
+    tag = "candles",
+    summary = "Price candles of a token pair across its pools",
+    params(("network_id" = u64, Path, description = "Chain id of the network"), ("token_a" = String, Path, description = "Token address"), ("token_b" = String, Path, description = "Token address"), CandleQuery),
+    responses((status = 200, body = CandlesResponse))
+)]
+pub async fn get_pair_candles(
+    State(processor): State<Arc<Proccessor>>,
+    ApiPath((network_id, token_a, token_b)): ApiPath<(u64, String, String)>,
+    ApiQuery(query): ApiQuery<CandleQuery>,
+) -> Result<Json<CandlesResponse>, ApiError> {
+    let token_a_address = parse_address("token_a", &token_a)?;
+    let token_b_address = parse_address("token_b", &token_b)?;
+    let key = CandleKey::pair(token_a_address, token_b_address);
+    let CandleKey::Pair(token0, token1) = key else {
+        return Err(ApiError::InvalidRequest(
+            "A pair needs two tokens".to_string(),
+        ));
+    };
+
+    let response =
+        build_candles_response(&processor, network_id, key, token0, token1, query).await?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/networks/{network_id}/prices",
+    tag = "networks",
+    summary = "On-chain USD prices of a network",
+    params(("network_id" = u64, Path, description = "Chain id of the network")),
+    responses((status = 200, body = PricesResponse))
+)]
+pub async fn get_prices(
+    State(processor): State<Arc<Proccessor>>,
+    ApiPath(network_id): ApiPath<u64>,
+) -> Result<Json<PricesResponse>, ApiError> {
+    let price_registry = processor
+        .price_registry()
+        .get_price_registry(network_id)
+        .await
+        .ok_or(ArbitrageError::NetworkNotFound(network_id))?;
+    let token_registry = processor
+        .token_registry()
+        .get_token_registry(network_id)
+        .await
+        .ok_or(ArbitrageError::NetworkNotFound(network_id))?;
+
+    let prices = price_registry.get_all_prices().await;
+    let registry_guard = token_registry.read().await;
+    let mut price_infos: Vec<PriceInfo> = prices
+        .values()
+        .map(|price| PriceInfo {
+            address: format!("{:?}", price.address),
+            symbol: registry_guard
+                .get_token(price.address)
+                .map(|token| token.symbol.clone())
+                .unwrap_or_default(),
+            price_usd: price.price_usd,
+            liquidity_usd: price.liquidity_usd,
+            hops: price.hops,
+            pools: price.pools,
+        })
+        .collect();
+    price_infos.sort_by(|a, b| a.address.cmp(&b.address));
+
+    Ok(Json(PricesResponse {
+        network_id,
+        updated_at: price_registry.updated_at().await,
+        total_prices: price_infos.len(),
+        prices: price_infos,
+    }))
+}
+
+/// Impact targets used when a depth request does not give any
+const DEFAULT_PRICE_IMPACTS: [f64; 3] = [0.005, 0.01, 0.02];
+
+/// Tick spacings each side of the current tick returned by default
+const DEFAULT_TICK_RANGE: u32 = 20;
+
+// Helper function to get the latest pool snapshot of a network
+async fn pool_snapshot(
+    processor: &Proccessor,
+    network_id: u64,
+) -> Result<Arc<PoolSnapshot>, ApiError> {
+    Ok(processor
+        .pool_registry()
+        .get_pool_registry(network_id)
+        .await
+        .ok_or(ArbitrageError::NetworkNotFound(network_id))?
+        .snapshot())
+}
+
+// Helper function to build a route from a snapshot's pools
+fn snapshot_route(
+    snapshot: &PoolSnapshot,
+    pools: &[Address],
+    token_in: Address,
+) -> Result<RouteSnapshot, ApiError> {
+    Ok(snapshot.route(pools, token_in)?)
+}
+
+/// Routes of every path between two tokens, skipping paths through pools the snapshot does not
+/// have yet: paths come from the live token graph, which pools being added reach first
+async fn snapshot_paths(
+    pool_registry: &PoolRegistry,
+    snapshot: &PoolSnapshot,
+    token_in: Address,
+    token_out: Address,
+    max_hops: usize,
+) -> Result<Vec<RouteSnapshot>, ApiError> {
+    let routes = pool_registry
+        .get_all_path_from_token_to_token(token_in, token_out, max_hops)
+        .await
+        .iter()
+        .filter(|path| path.iter().all(|pool| snapshot.pools().contains_key(pool)))
+        .map(|path| snapshot_route(snapshot, path, token_in))
+        .collect::<Result<Vec<_>, _>>()?;
+    if routes.is_empty() {
+        return Err(ApiError::RouteNotFound {
+            token_in: format!("{:?}", token_in),
+            token_out: format!("{:?}", token_out),
+        });
+    }
+    Ok(routes)
+}
+
+#[utoipa::path(
+    post,
+    path = "/quote/depth",
+    tag = "quotes",
+    summary = "Depth curve, price impact sizes and tick liquidity of a route",
+    request_body = DepthRequest,
+    responses((status = 200, body = DepthResponse))
+)]
+pub async fn get_depth(
+    State(processor): State<Arc<Proccessor>>,
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<DepthRequest>,
+) -> Result<Json<DepthResponse>, ApiError> {
+    caller.limits.check_amounts(request.amounts.len())?;
+    caller.limits.check_hops(request.pools.len())?;
+    let token_in = parse_address("token_in", &request.token_in)?;
+    let amounts = parse_amounts(&request.amounts)?;
+    let targets = request
+        .price_impacts
+        .clone()
+        .unwrap_or_else(|| DEFAULT_PRICE_IMPACTS.to_vec());
+    if let Some(target) = targets.iter().find(|target| !(0.0..1.0).contains(*target)) {
+        return Err(ApiError::InvalidRequest(format!(
+            "Price impact {} is not between 0 and 1",
+            target
+        )));
+    }
+    let tick_range = request.tick_range.unwrap_or(DEFAULT_TICK_RANGE);
+    let pools = request
+        .pools
+        .iter()
+        .map(|pool| parse_address("pools", pool))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let snapshot = pool_snapshot(&processor, request.network_id).await?;
+    let route = snapshot_route(&snapshot, &pools, token_in)?;
+    let token_in_data = processor
+        .token_registry()
+        .get_token(request.network_id, route.token_in())
+        .await
+        .ok_or(ArbitrageError::TokenNotFound(route.token_in()))?;
+    let token_out_data = processor
+        .token_registry()
+        .get_token(request.network_id, route.token_out())
+        .await
+        .ok_or(ArbitrageError::TokenNotFound(route.token_out()))?;
+    // Raw output per raw input to token units
+    let scale = 10f64.powi(token_in_data.decimals as i32 - token_out_data.decimals as i32);
+
+    // Searching for impact targets runs many swaps, keep it off the async workers
+    let (mid_price, points, price_impacts, liquidity) = tokio::task::spawn_blocking(move || {
+        let mid_price = route.mid_price()?;
+        let points: Vec<DepthPointInfo> = amounts
+            .into_iter()
+            .map(|amount_in| {
+                let point = depth_point(&route, mid_price, amount_in);
+                DepthPointInfo {
+                    amount_in: point.amount_in.to_string(),
+                    amount_out: point.amount_out.map(|amount| amount.to_string()),
+                    effective_price: point.effective_price.map(|price| price * scale),
+                    marginal_price: point.marginal_price.map(|price| price * scale),
+                    price_impact: point.price_impact,
+                }
+            })
+            .collect();
+        let price_impacts: Vec<PriceImpactInfo> = targets
+            .into_iter()
+            .map(|target| {
+                let amount_in = input_for_price_impact(&route, mid_price, target);
+                let amount_out = amount_in.and_then(|amount| route.quote_exact_in(amount).ok());
+                PriceImpactInfo {
+                    price_impact: target,
+                    amount_in: amount_in.map(|amount| amount.to_string()),
+                    amount_out: amount_out.map(|amount| amount.to_string()),
+                }
+            })
+            .collect();
+        let liquidity: Vec<PoolLiquidityInfo> = route
+            .hops()
+            .iter()
+            .filter_map(|hop| hop.pool.downcast_ref::<UniswapV3Pool>())
+            .map(|pool| PoolLiquidityInfo {
+                pool: format!("{:?}", pool.address),
+                tick: pool.tick,
+                tick_spacing: pool.tick_spacing,
+                buckets: liquidity_distribution(pool, tick_range)
+                    .into_iter()
+                    .map(|bucket| LiquidityBucketInfo {
+                        tick_lower: bucket.tick_lower,
+                        tick_upper: bucket.tick_upper,
+                        liquidity: bucket.liquidity.to_string(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok::<_, ApiError>((mid_price * scale, points, price_impacts, liquidity))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))??;
+
+    Ok(Json(DepthResponse {
+        network_id: request.network_id,
+        block_number: snapshot.block_number(),
+        token_in: format!("{:?}", token_in_data.address),
+        token_out: format!("{:?}", token_out_data.address),
+        mid_price,
+        points,
+        price_impacts,
+        liquidity,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/quote/cache",
+    tag = "quotes",
+    summary = "Quote cache size and hit rate",
+    responses((status = 200, body = QuoteCacheStatsResponse))
+)]
+pub async fn get_quote_cache_stats(
+    State(processor): State<Arc<Proccessor>>,
+) -> Json<QuoteCacheStatsResponse> {
+    let stats = processor.quote_cache().stats();
+    Json(QuoteCacheStatsResponse {
+        capacity: stats.capacity,
+        entries: stats.entries,
+        hits: stats.hits,
+        misses: stats.misses,
+        bypassed: stats.bypassed,
+        hit_rate: stats.hit_rate(),
+    })
+}
+
+// Helper function to encode a quoted route for a configured router
+async fn build_calldata(
+    processor: &Proccessor,
+    network_id: u64,
+    route: &RouteSnapshot,
+    request: &CalldataRequest,
+    quote: &RouteQuote,
+    slippage_bps: u64,
+) -> Result<SwapCalldataInfo, ApiError> {
+    let recipient = parse_address("recipient", &request.recipient)?;
+    let router_address = parse_optional_address("router", request.router.as_ref())?;
+    let router = processor
+        .router_registry()
+        .get_router(network_id, router_address)
+        .await
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("No router configured on network {}", network_id))
+        })?;
+
+    let deadline = request
+        .deadline
+        .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64 + DEFAULT_DEADLINE_SECS);
+    let params = SwapParams {
+        recipient,
+        exact_input: quote.exact_input,
+        amount: if quote.exact_input {
+            quote.amount_in
+        } else {
+            quote.amount_out
+        },
+        amount_limit: quote.amount_limit(slippage_bps),
+        deadline,
+    };
+    let calldata = router.encode_swap(route, &params)?;
+
+    Ok(SwapCalldataInfo {
+        router: format!("{:?}", calldata.to),
+        router_type: router.router_type,
+        data: calldata.data.to_string(),
+        value: calldata.value.to_string(),
+        amount_limit: params.amount_limit.to_string(),
+        deadline,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/quote/route",
+    tag = "quotes",
+    summary = "Quote a route with its prices, fees per hop and optional router calldata",
+    request_body = RouteQuoteRequest,
+    responses((status = 200, body = RouteQuoteResponse))
+)]
+pub async fn quote_route(
+    State(processor): State<Arc<Proccessor>>,
+    Extension(caller): Extension<ApiCaller>,
+    ApiJson(request): ApiJson<RouteQuoteRequest>,
+) -> Result<Json<RouteQuoteResponse>, ApiError> {
+    caller.limits.check_hops(request.pools.len())?;
+    let token_in = parse_address("token_in", &request.token_in)?;
+    let token_out = parse_address("token_out", &request.token_out)?;
+    let amount = parse_amount(&request.amount)?;
+    let pools = request
+        .pools
+        .iter()
+        .map(|pool| parse_address("pools", pool))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The quote, its details and the calldata all come from one snapshot
+    let snapshot = pool_snapshot(&processor, request.network_id).await?;
+    let route = snapshot_route(&snapshot, &pools, token_in)?;
+    if route.token_out() != token_out {
+        return Err(ApiError::InvalidRequest(format!(
+            "Path does not end with expected token. Expected: {:?}, Got: {:?}",
+            token_out,
+            route.token_out()
+        )));
+    }
+    let exact_input = request.quote_type == QuoteType::ExactIn;
+    let amounts = processor.route_hop_amounts(
+        request.network_id,
+        &snapshot,
+        &route,
+        amount,
+        exact_input,
+        request.bypass_cache.unwrap_or(false),
+    )?;
+    let quote = processor
+        .quote_data(
+            request.network_id,
+            snapshot.block_number(),
+            &route,
+            &amounts,
+        )
+        .await?;
+
+    let (route_quote, details) = route_quote_details(
+        &processor,
+        request.network_id,
+        snapshot.block_number(),
+        &route,
+        &amounts,
+        exact_input,
+        request.slippage_bps,
+    )
+    .await?;
+
+    let calldata = match &request.calldata {
+        Some(calldata_request) => {
+            let slippage_bps = calldata_request
+                .slippage_bps
+                .or(request.slippage_bps)
+                .ok_or_else(|| {
+                    ApiError::InvalidRequest(
+                        "A slippage tolerance is required for calldata".to_string(),
+                    )
+                })?;
+            Some(
+                build_calldata(
+                    &processor,
+                    request.network_id,
+                    &route,
+                    calldata_request,
+                    &route_quote,
+                    slippage_bps,
+                )
+                .await?,
+            )
+        }
+        None => None,
+    };
+
+    Ok(Json(RouteQuoteResponse::success(quote, details, calldata)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::{multichain_registry::MultichainPoolRegistry, MockPool};
+    use crate::models::price::MultichainPriceRegistry;
+    use crate::models::token::{MultichainTokenRegistry, Token, TokenRegistry};
+    use alloy::rpc::types::Log;
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn test_pool_quote_without_token_metadata() {
+        let (pool, token0, token1) = (
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+        );
+        let pool_registry = Arc::new(PoolRegistry::new(1));
+        pool_registry
+            .add_pool(Box::new(MockPool::new_v2(
+                pool,
+                token0,
+                token1,
+                U256::from(1_000_000u64),
+                U256::from(2_000_000u64),
+            )))
+            .await;
+        pool_registry.set_last_processed_block(7).await;
+        let pool_registries = Arc::new(MultichainPoolRegistry::new());
+        pool_registries.add_pool_registry(1, pool_registry).await;
+        let token_registries = Arc::new(MultichainTokenRegistry::new());
+        token_registries
+            .add_token_registry(1, Arc::new(RwLock::new(TokenRegistry::new(1))))
+            .await;
+        let processor = Arc::new(Proccessor::new(
+            pool_registries,
+            token_registries.clone(),
+            Arc::new(MultichainPriceRegistry::new()),
+        ));
+        let quote = || async {
+            let request = QuoteRequestWithPool {
+                network_id: 1,
+                pool: format!("{:?}", pool),
+                token_in: Some(format!("{:?}", token0)),
+                token_out: None,
+                amount: "1000".to_string(),
+                slippage_bps: None,
+                bypass_cache: None,
+            };
+            quote_amount_out_raw(State(processor.clone()), ApiJson(request))
+                .await
+                .unwrap()
+                .0
+        };
+
+        // Unknown tokens only cost the details, not the block of the quote
+        let response = quote().await;
+        assert!(response.success);
+        assert!(response.details.is_none());
+        assert_eq!(response.block_number, Some(7));
+        let amount_out = response.result.unwrap();
+
+        for (token, symbol) in [(token0, "T0"), (token1, "T1")] {
+            let token = Token::new(token, 1, symbol.to_string(), symbol.to_string(), 18);
+            token_registries.add_token(1, token).await.unwrap();
+        }
+        let response = quote().await;
+        let details = response.details.unwrap();
+        assert_eq!(response.result.unwrap(), amount_out);
+        assert_eq!(
+            details.amount_out.parse::<U256>().unwrap(),
+            amount_out.parse::<U256>().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pool_last_updated_block() {
+        let (fetched, updated) = (Address::repeat_byte(0xa), Address::repeat_byte(0xb));
+        let pool_registry = Arc::new(PoolRegistry::new(1));
+        for pool in [fetched, updated] {
+            pool_registry
+                .add_pool_at(
+                    Box::new(MockPool::new_v2(
+                        pool,
+                        Address::repeat_byte(1),
+                        Address::repeat_byte(2),
+                        U256::from(1_000_000u64),
+                        U256::from(2_000_000u64),
+                    )),
+                    100,
+                )
+                .await;
+        }
+        let event = Log {
+            inner: alloy::primitives::Log {
+                address: updated,
+                ..Default::default()
+            },
+            block_number: Some(105),
+            ..Default::default()
+        };
+        assert!(pool_registry.apply_log(&event).await.unwrap());
+
+        let pool_registries = Arc::new(MultichainPoolRegistry::new());
+        pool_registries.add_pool_registry(1, pool_registry).await;
+        let processor = Arc::new(Proccessor::new(
+            pool_registries,
+            Arc::new(MultichainTokenRegistry::new()),
+            Arc::new(MultichainPriceRegistry::new()),
+        ));
+        let last_updated_block = |pool: Address| {
+            let processor = processor.clone();
+            async move {
+                get_pool(State(processor), ApiPath((1, format!("{:?}", pool))))
+                    .await
+                    .unwrap()
+                    .last_updated_block
+            }
+        };
+
+        // Untouched since it was fetched
+        assert_eq!(last_updated_block(fetched).await, Some(100));
+        assert_eq!(last_updated_block(updated).await, Some(105));
+    }
+
+    #[tokio::test]
+    async fn test_chain_sync_info() {
+        let mut pool_registry = PoolRegistry::new(1);
+        pool_registry.set_max_sync_lag(5);
+        let sync_status = pool_registry.sync_status();
+
+        // Not ready until the head is known
+        let info = chain_sync_info(1, &pool_registry).await;
+        assert!(!info.ready);
+        assert_eq!(info.lag, None);
+
+        pool_registry.set_last_processed_block(100).await;
+        pool_registry.record_chain_head(105);
+        sync_status.set_websocket_connected("eth.example.com", true);
+        let info = chain_sync_info(1, &pool_registry).await;
+        assert!(info.ready);
+        assert_eq!(info.head_block, Some(105));
+        assert_eq!(info.lag, Some(5));
+        assert!(info.websockets[0].connected);
+
+        // One block more than the max lag
+        pool_registry.record_chain_head(106);
+        sync_status.record_error("Error fetching events: timeout");
+        let info = chain_sync_info(1, &pool_registry).await;
+        assert!(!info.ready);
+        assert_eq!(info.lag, Some(6));
+        assert_eq!(
+            info.last_error.unwrap().message,
+            "Error fetching events: timeout"
+        );
+
+        // Caught up, but the head has not been fetched for too long
+        pool_registry.set_last_processed_block(106).await;
+        assert!(chain_sync_info(1, &pool_registry).await.ready);
+        let stale = chrono::Utc::now().timestamp() as u64 - MAX_HEAD_AGE_SECS - 1;
+        let mut pool_registry = PoolRegistry::new(1);
+        pool_registry.set_max_sync_lag(5);
+        pool_registry.set_last_processed_block(106).await;
+        pool_registry.sync_status().set_head_block_at(106, stale);
+        let info = chain_sync_info(1, &pool_registry).await;
+        assert!(!info.ready);
+        assert_eq!(info.lag, Some(0));
+        assert!(info.head_age_secs.unwrap() > MAX_HEAD_AGE_SECS);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_paths_skip_unpublished_pools() {
+        let (published, unpublished) = (Address::repeat_byte(0xa), Address::repeat_byte(0xb));
+        let (token0, token1) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let pool = |address| {
+            Box::new(MockPool::new_v2(
+                address,
+                token0,
+                token1,
+                U256::from(1_000_000u64),
+                U256::from(2_000_000u64),
+            ))
+        };
+        let pool_registry = PoolRegistry::new(1);
+        pool_registry.add_pool(pool(published)).await;
+        // Taken before the second pool joins the token graph
+        let snapshot = pool_registry.snapshot();
+        pool_registry.add_pool(pool(unpublished)).await;
+
+        let routes = snapshot_paths(&pool_registry, &snapshot, token0, token1, 1)
+            .await
+            .unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].hops()[0].pool.address(), published);
+
+        let empty = PoolRegistry::new(1).snapshot();
+        assert!(matches!(
+            snapshot_paths(&pool_registry, &empty, token0, token1, 1).await,
+            Err(ApiError::RouteNotFound { .. })
+        ));
+    }
 }
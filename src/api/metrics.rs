@@ -0,0 +1,49 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::utils::metrics::Metrics;
+
+/// Records the latency of every request by route template, so path parameters do not
+/// create a series per pool or network
+pub async fn record_request(
+    State(metrics): State<Arc<Metrics>>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let route = matched_path
+        .as_ref()
+        .map(|path| path.as_str())
+        .unwrap_or("unmatched");
+    metrics.record_api_request(
+        method.as_str(),
+        route,
+        response.status().as_u16(),
+        start.elapsed(),
+    );
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "admin",
+    summary = "Indexer and API metrics in the Prometheus text format",
+    responses((status = 200, body = String, content_type = "text/plain"))
+)]
+pub async fn get_metrics(Extension(metrics): Extension<Arc<Metrics>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+}
@@ -1,61 +1,161 @@
 use axum::{
-    routing::{get, post},
-    Router,
+    middleware,
+    routing::{get, post, MethodRouter},
+    Extension, Router,
 };
 use std::sync::Arc;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
+use crate::api::auth::ApiKeyRegistry;
+use crate::core::chain_manager::ChainManager;
 use crate::core::proccessor::Proccessor;
+use crate::utils::metrics::Metrics;
 
+pub mod auth;
+pub mod errors;
 pub mod handlers;
+pub mod metrics;
 pub mod models;
+pub mod openapi;
+pub mod pool_stream;
+pub mod quote_stream;
+pub mod reload;
+pub mod server;
+pub mod trace;
 
-pub fn create_router(processor: Arc<Proccessor>) -> Router {
-    Router::new()
-        .route("/health", get(handlers::health_check))
-        .route("/networks", get(handlers::get_networks))
-        .route("/networks/:network_id/pools", get(handlers::get_pools))
-        .route("/networks/:network_id/tokens", get(handlers::get_tokens))
-        .route("/quote/amount-in/raw", post(handlers::quote_amount_in_raw))
-        .route(
+/// Routes of the public quote API, each documented in `openapi::ApiDoc`
+pub(crate) fn public_routes() -> Vec<(&'static str, MethodRouter<Arc<Proccessor>>)> {
+    vec![
+        ("/health", get(handlers::health_check)),
+        ("/health/live", get(handlers::health_live)),
+        ("/health/ready", get(handlers::health_ready)),
+        ("/networks", get(handlers::get_networks)),
+        ("/networks/:network_id/pools", get(handlers::get_pools)),
+        ("/networks/:network_id/pools/:pool", get(handlers::get_pool)),
+        ("/networks/:network_id/tokens", get(handlers::get_tokens)),
+        ("/networks/:network_id/prices", get(handlers::get_prices)),
+        (
+            "/networks/:network_id/pools/:pool/candles",
+            get(handlers::get_pool_candles),
+        ),
+        (
+            "/networks/:network_id/pairs/:token_a/:token_b/candles",
+            get(handlers::get_pair_candles),
+        ),
+        ("/quote/amount-in/raw", post(handlers::quote_amount_in_raw)),
+        (
             "/quote/amount-in/token",
             post(handlers::quote_amount_in_token),
-        )
-        .route(
+        ),
+        (
             "/quote/amount-out/raw",
             post(handlers::quote_amount_out_raw),
-        )
-        .route(
+        ),
+        (
             "/quote/amount-out/token",
             post(handlers::quote_amount_out_token),
-        )
+        ),
         // Batch quote endpoints
-        .route(
+        (
             "/quote/batch/amount-in/raw",
             post(handlers::batch_quote_amount_in_raw_with_pool),
-        )
-        .route(
+        ),
+        (
             "/quote/batch/amount-in/token",
             post(handlers::batch_quote_amount_in_token_with_pool),
-        )
-        .route(
+        ),
+        (
             "/quote/batch/amount-out/raw",
             post(handlers::batch_quote_amount_out_raw_with_pool),
-        )
-        .route(
+        ),
+        (
             "/quote/batch/amount-out/token",
             post(handlers::batch_quote_amount_out_token_with_pool),
-        )
-        .route(
+        ),
+        (
             "/quote/batch/amount-in/path/raw",
             post(handlers::batch_quote_amount_in_raw),
-        )
-        .route(
+        ),
+        (
             "/quote/batch/amount-out/path/raw",
             post(handlers::batch_quote_amount_out_raw),
-        )
-        .route(
+        ),
+        (
             "/quote/batch/amount-out/pools/raw",
             post(handlers::batch_quote_amount_out_token_with_pools),
-        )
+        ),
+        ("/quote/route", post(handlers::quote_route)),
+        ("/quote/depth", post(handlers::get_depth)),
+        ("/quote/cache", get(handlers::get_quote_cache_stats)),
+        // Streaming quotes
+        ("/ws/quotes", get(quote_stream::quote_stream)),
+        ("/events/pools", get(pool_stream::pool_update_stream)),
+    ]
+}
+
+/// Public quote API
+pub fn create_router(
+    processor: Arc<Proccessor>,
+    api_keys: Arc<ApiKeyRegistry>,
+    metrics: Arc<Metrics>,
+) -> Router {
+    public_routes()
+        .into_iter()
+        .fold(Router::new(), |router, (path, route)| {
+            router.route(path, route)
+        })
+        // API reference, generated from the handlers above
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", openapi::ApiDoc::openapi()))
+        // Latency of every matched route
+        .route_layer(middleware::from_fn_with_state(
+            metrics,
+            metrics::record_request,
+        ))
+        .fallback(errors::route_not_found)
+        // Every request is authenticated and rate limited by its API key
+        .layer(middleware::from_fn_with_state(
+            api_keys.clone(),
+            auth::authenticate,
+        ))
+        .layer(Extension(api_keys))
+        // Outermost, so authentication failures are traced too
+        .layer(middleware::from_fn(trace::trace_request))
         .with_state(processor)
 }
+
+/// Routes of the administration API, each documented in `openapi::ApiDoc`
+pub(crate) fn admin_routes() -> Vec<(&'static str, MethodRouter)> {
+    vec![
+        // Admin keys only
+        ("/admin/keys", get(auth::get_api_keys)),
+        ("/admin/reload", post(reload::reload_config)),
+        ("/metrics", get(metrics::get_metrics)),
+    ]
+}
+
+/// Administration API, served on its own listener
+pub fn create_admin_router(
+    api_keys: Arc<ApiKeyRegistry>,
+    metrics: Arc<Metrics>,
+    chain_manager: Arc<ChainManager>,
+) -> Router {
+    admin_routes()
+        .into_iter()
+        .fold(Router::new(), |router, (path, route)| {
+            router.route(path, route)
+        })
+        .route_layer(middleware::from_fn_with_state(
+            metrics.clone(),
+            metrics::record_request,
+        ))
+        .fallback(errors::route_not_found)
+        .layer(middleware::from_fn_with_state(
+            api_keys.clone(),
+            auth::authenticate,
+        ))
+        .layer(Extension(api_keys))
+        .layer(Extension(metrics))
+        .layer(Extension(chain_manager))
+        .layer(middleware::from_fn(trace::trace_request))
+}
@@ -1,16 +1,23 @@
 use alloy::primitives::U256;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::core::proccessor::{QuoteData, QuoteType};
+use crate::models::pool::{PoolState, PoolUpdate};
+use crate::models::router::RouterType;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QuoteRequestWithPool {
     pub network_id: u64,
-    pub pool: String,              // Address as string
-    pub token_in: Option<String>,  // Address as string
-    pub token_out: Option<String>, // Address as string
-    pub amount: String,            // Amount as string (for token amounts) or hex (for raw amounts)
+    pub pool: String,               // Address as string
+    pub token_in: Option<String>,   // Address as string
+    pub token_out: Option<String>,  // Address as string
+    pub amount: String,             // Amount as string (for token amounts) or hex (for raw amounts)
+    pub slippage_bps: Option<u64>,  // Tolerance for amount_out_min / amount_in_max, 50 = 0.5%
+    pub bypass_cache: Option<bool>, // Recompute the quote details instead of using the quote cache
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchQuoteRequestWithPool {
     pub network_id: u64,
     pub pool: String,              // Address as string
@@ -19,22 +26,23 @@ pub struct BatchQuoteRequestWithPool {
     pub amounts: Vec<String>, // Array of amounts as strings (for token amounts) or hex (for raw amounts)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchQuoteRequest {
     pub network_id: u64,
-    pub token_in: Option<String>,  // Address as string
-    pub token_out: Option<String>, // Address as string
+    pub token_in: Option<String>,   // Address as string
+    pub token_out: Option<String>,  // Address as string
     pub amounts: Vec<String>, // Array of amounts as strings (for token amounts) or hex (for raw amounts)
+    pub bypass_cache: Option<bool>, // Recompute every route instead of using the quote cache
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PoolRequest {
     pub token_in: String,
     pub pool_address: String,
     pub network_id: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchQuoteRequestWithPools {
     pub token_in: String,          // Address as string
     pub token_out: Option<String>, // Address as string
@@ -42,77 +50,265 @@ pub struct BatchQuoteRequestWithPools {
     pub pools: Vec<PoolRequest>, // Array of pool addresses as strings
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CalldataRequest {
+    pub recipient: String,         // Address receiving the output tokens
+    pub slippage_bps: Option<u64>, // Tolerance for the swap limit, the quote's if omitted
+    pub router: Option<String>,    // Configured router address, the chain's first router if omitted
+    pub deadline: Option<u64>,     // Unix timestamp, defaults to 20 minutes from now
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RouteQuoteRequest {
+    pub network_id: u64,
+    pub pools: Vec<String>, // Route pool addresses in swap order
+    pub token_in: String,   // Address as string
+    pub token_out: String,  // Address as string
+    pub amount: String,     // Raw amount: input for exact_in, output for exact_out
+    pub quote_type: QuoteType,
+    pub slippage_bps: Option<u64>, // Tolerance for amount_out_min / amount_in_max, 50 = 0.5%
+    pub calldata: Option<CalldataRequest>, // Encode the swap for a configured router
+    pub bypass_cache: Option<bool>, // Recompute the quote instead of using the quote cache
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SwapCalldataInfo {
+    pub router: String,
+    pub router_type: RouterType,
+    pub data: String,         // Hex encoded calldata
+    pub value: String,        // Native value to send, in wei
+    pub amount_limit: String, // Minimum output for exact_in, maximum input for exact_out
+    pub deadline: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RouteQuoteResponse {
+    pub success: bool,
+    pub quote: Option<QuoteData>,
+    pub details: Option<QuoteDetailsInfo>,
+    pub calldata: Option<SwapCalldataInfo>,
+    pub error: Option<String>,
+}
+
+impl RouteQuoteResponse {
+    pub fn success(
+        quote: QuoteData,
+        details: QuoteDetailsInfo,
+        calldata: Option<SwapCalldataInfo>,
+    ) -> Self {
+        Self {
+            success: true,
+            quote: Some(quote),
+            details: Some(details),
+            calldata,
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HopQuoteInfo {
+    pub pool: String,
+    pub token_in: String,
+    pub token_out: String,
+    pub amount_in: String,
+    pub amount_out: String,
+    pub fee: f64,           // Pool fee as a fraction
+    pub fee_amount: String, // Fee paid, in raw token_in units
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct QuoteDetailsInfo {
+    pub block_number: u64, // Block of the pool states the quote was computed from
+    pub amount_in: String,
+    pub amount_out: String,
+    pub slippage_bps: Option<u64>,
+    pub amount_out_min: Option<String>, // Only with a slippage tolerance
+    pub amount_in_max: Option<String>,  // Only with a slippage tolerance
+    pub mid_price: f64,                 // Output per input in token units, net of fees
+    pub effective_price: f64,           // Output per input in token units
+    pub price_impact: f64,              // Shortfall against the mid price, as a fraction
+    pub hops: Vec<HopQuoteInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QuoteResponse {
     pub success: bool,
-    pub result: Option<String>, // U256 as hex string
+    pub result: Option<String>,    // U256 as hex string
+    pub block_number: Option<u64>, // Block of the pool states the quote was computed from
+    pub details: Option<Box<QuoteDetailsInfo>>,
     pub error: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchQuoteResponse {
     pub success: bool,
     pub results: Option<Vec<String>>, // Array of U256 as hex strings
+    pub block_number: u64,            // Block of the pool states the quotes were computed from
     pub error: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchQuoteResponseWithSteps {
     pub success: bool,
     pub results: Option<Vec<String>>, // Array of arrays of U256 as hex strings
     pub steps: Option<Vec<Vec<String>>>, // Array of arrays of U256 as hex strings
     pub step_tokens: Option<Vec<String>>, // Array of arrays of U256 as hex strings
     pub step_decimals: Option<Vec<u8>>, // Array of arrays of u8
+    pub step_blocks: Option<Vec<u64>>, // Block of the pool state each step was quoted from
     pub error: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub success: bool, // Always false
+    pub code: String,  // Machine-readable error code, e.g. POOL_NOT_FOUND
+    pub error: String, // Human-readable message
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: String, // "ready", or "not_ready" when any chain is not ready
+    pub chains: Vec<ChainSyncInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ChainSyncInfo {
+    pub network_id: u64,
+    pub ready: bool,                // Head fetched recently and lag within max_lag
+    pub last_processed_block: u64,  // Block of the latest pool states
+    pub head_block: Option<u64>,    // None until the head was fetched
+    pub head_age_secs: Option<u64>, // Seconds since the head was last fetched
+    pub lag: Option<u64>,           // Blocks behind the head
+    pub max_lag: u64,
+    pub websockets: Vec<WebsocketInfo>, // Empty when the chain is polled
+    pub last_error: Option<SyncErrorInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WebsocketInfo {
+    pub endpoint: String, // Host of the websocket URL
+    pub connected: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SyncErrorInfo {
+    pub message: String,
+    pub timestamp: u64, // Unix seconds
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct NetworksResponse {
     pub networks: Vec<u64>,
     pub total_networks: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PoolsQuery {
+    pub pool_type: Option<String>, // UniswapV2, UniswapV3 or ERC4626
+    pub token: Option<String>,     // Only pools containing this token address
+    pub offset: Option<usize>,     // Number of matching pools to skip
+    pub limit: Option<usize>,      // Maximum number of pools returned, all if omitted
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PoolsResponse {
     pub network_id: u64,
-    pub pools: Vec<String>, // Pool addresses as strings
-    pub total_pools: usize,
+    pub pools: Vec<String>, // Pool addresses as strings, sorted
+    pub total_pools: usize, // Number of pools matching the filters, before pagination
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PoolTokenInfo {
+    pub address: String,
+    pub symbol: Option<String>, // None if the token is not in the registry
+    pub decimals: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TickInfo {
+    pub index: i32,
+    pub liquidity_net: String, // Signed raw liquidity as decimal string
+    pub liquidity_gross: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoolDetailResponse {
+    pub network_id: u64,
+    pub address: String,
+    pub pool_type: String,
+    pub token0: PoolTokenInfo,
+    pub token1: PoolTokenInfo,
+    pub fee: f64,                        // Fee as a fraction (e.g., 0.003 for 0.3%)
+    pub state: Option<PoolStateInfo>,    // Reserves, price/tick/liquidity or vault reserves
+    pub tick_spacing: Option<i32>,       // V3 only
+    pub ticks: Option<Vec<TickInfo>>,    // V3 initialized ticks, ascending
+    pub last_updated_block: Option<u64>, // Block of the last applied event, else the fetch or load block
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TokensResponse {
     pub network_id: u64,
     pub tokens: Vec<TokenInfo>,
     pub total_tokens: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TokenInfo {
     pub address: String,
     pub symbol: String,
     pub name: String,
     pub decimals: u8,
+    pub price_usd: Option<f64>, // On-chain USD price, if reachable from a stablecoin
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PricesResponse {
+    pub network_id: u64,
+    pub updated_at: u64, // Unix timestamp of the last price recomputation
+    pub prices: Vec<PriceInfo>,
+    pub total_prices: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PriceInfo {
+    pub address: String,
+    pub symbol: String,
+    pub price_usd: f64,
+    pub liquidity_usd: f64, // Liquidity of the pools the price was derived from
+    pub hops: usize,        // Pool hops from the nearest stablecoin
+    pub pools: usize,       // Number of pools averaged
 }
 
 impl QuoteResponse {
-    pub fn success(result: U256) -> Self {
+    pub fn success(result: U256, block_number: u64) -> Self {
         Self {
             success: true,
             result: Some(result.to_string()),
+            block_number: Some(block_number),
+            details: None,
             error: None,
         }
     }
 
+    pub fn with_details(mut self, details: Option<QuoteDetailsInfo>) -> Self {
+        self.details = details.map(Box::new);
+        self
+    }
+
     pub fn error(error: String) -> Self {
         Self {
             success: false,
             result: None,
+            block_number: None,
+            details: None,
             error: Some(error),
         }
     }
@@ -124,6 +320,7 @@ impl BatchQuoteResponseWithSteps {
         steps: Vec<Vec<U256>>,
         step_tokens: Vec<String>,
         step_decimals: Vec<u8>,
+        step_blocks: Vec<u64>,
     ) -> Self {
         Self {
             success: true,
@@ -136,36 +333,278 @@ impl BatchQuoteResponseWithSteps {
             ),
             step_tokens: Some(step_tokens),
             step_decimals: Some(step_decimals),
+            step_blocks: Some(step_blocks),
             error: None,
         }
     }
-
-    pub fn error(error: String) -> Self {
-        Self {
-            success: false,
-            results: None,
-            steps: None,
-            step_tokens: None,
-            step_decimals: None,
-            error: Some(error),
-        }
-    }
 }
 
 impl BatchQuoteResponse {
-    pub fn success(results: Vec<U256>) -> Self {
+    pub fn success(results: Vec<U256>, block_number: u64) -> Self {
         Self {
             success: true,
             results: Some(results.into_iter().map(|r| r.to_string()).collect()),
+            block_number,
             error: None,
         }
     }
+}
 
-    pub fn error(error: String) -> Self {
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DepthRequest {
+    pub network_id: u64,
+    pub pools: Vec<String>, // Route pool addresses in swap order, one for a single pool
+    pub token_in: String,   // Address as string
+    pub amounts: Vec<String>, // Raw input sizes as decimal strings
+    pub price_impacts: Option<Vec<f64>>, // Impact targets as fractions, defaults to 0.5%, 1% and 2%
+    pub tick_range: Option<u32>, // Tick spacings each side of the current V3 tick, default 20
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DepthPointInfo {
+    pub amount_in: String,            // Raw amount as decimal string
+    pub amount_out: Option<String>,   // None if the route cannot fill the input
+    pub effective_price: Option<f64>, // Average output per input, in token units
+    pub marginal_price: Option<f64>,  // Output per input for the next unit traded
+    pub price_impact: Option<f64>,    // Fraction below the mid price
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PriceImpactInfo {
+    pub price_impact: f64,
+    pub amount_in: Option<String>, // Largest raw input within the impact, None if unreachable
+    pub amount_out: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LiquidityBucketInfo {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: String, // Active raw liquidity as decimal string
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PoolLiquidityInfo {
+    pub pool: String,
+    pub tick: i32,
+    pub tick_spacing: i32,
+    pub buckets: Vec<LiquidityBucketInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DepthResponse {
+    pub network_id: u64,
+    pub block_number: u64, // Block of the pool states the curve was computed from
+    pub token_in: String,
+    pub token_out: String,
+    pub mid_price: f64, // Output per input for a negligible trade, net of fees, in token units
+    pub points: Vec<DepthPointInfo>,
+    pub price_impacts: Vec<PriceImpactInfo>,
+    pub liquidity: Vec<PoolLiquidityInfo>, // Tick liquidity of the V3 pools in the route
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct QuoteCacheStatsResponse {
+    pub capacity: usize, // Maximum cached quotes, 0 when caching is disabled
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub bypassed: u64, // Quotes computed without the cache
+    pub hit_rate: f64, // Hits over hits and misses
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyUsageInfo {
+    pub name: String,
+    pub admin: bool,
+    pub requests_per_minute: Option<u32>, // None when the key is not rate limited
+    pub max_batch_amounts: usize,
+    pub max_hops: usize,
+    pub requests: u64,          // Authenticated requests since startup
+    pub rate_limited: u64,      // Requests rejected by the rate limit
+    pub limit_exceeded: u64,    // Requests rejected for too many amounts or hops
+    pub errors: u64,            // Requests answered with an error status, rejections included
+    pub last_used: Option<u64>, // Unix timestamp of the last request
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeysResponse {
+    pub keys: Vec<ApiKeyUsageInfo>, // Sorted by name, secrets are never returned
+    pub total_keys: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ChainReloadInfo {
+    pub network_id: u64,
+    pub pools_added: Vec<String>,
+    pub pools_removed: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReloadResponse {
+    pub started: Vec<u64>, // Chains started, including the ones restarted for changed settings
+    pub stopped: Vec<u64>,
+    pub updated: Vec<ChainReloadInfo>, // Running chains whose pools changed
+    pub unchanged: Vec<u64>,
+    pub errors: Vec<String>, // Chains that failed to start and pools that failed to be added
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CandleQuery {
+    pub interval: Option<u64>, // Candle interval in seconds
+    pub from: Option<u64>,     // Start timestamp (inclusive)
+    pub to: Option<u64>,       // End timestamp (inclusive)
+    pub limit: Option<usize>,  // Maximum number of candles returned (latest first cut)
+    pub invert: Option<bool>,  // Price token1 in token0 instead of token0 in token1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CandleInfo {
+    pub start: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume_base: String,  // Base token volume in token units
+    pub volume_quote: String, // Quote token volume in token units
+    pub trades: u64,
+    pub first_block: u64,
+    pub last_block: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CandlesResponse {
+    pub network_id: u64,
+    pub series: String,      // Pool address or "token0-token1" pair
+    pub base_token: String,  // Token being priced
+    pub quote_token: String, // Token the price is denominated in
+    pub interval: u64,
+    pub candles: Vec<CandleInfo>,
+    pub total_candles: usize,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuoteStreamRequest {
+    Subscribe(QuoteSubscription),
+    Unsubscribe { id: String },
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct QuoteSubscription {
+    pub id: String, // Client chosen subscription id, echoed on every message
+    pub network_id: u64,
+    pub pools: Vec<String>, // Route pool addresses in swap order
+    pub token_in: String,   // Address as string
+    pub token_out: String,  // Address as string
+    pub amount: String,     // Raw amount as decimal string
+    pub quote_type: QuoteType,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuoteStreamMessage {
+    Subscribed {
+        id: String,
+    },
+    Unsubscribed {
+        id: String,
+    },
+    Quote {
+        id: String,
+        block_number: u64, // Block of the pool update that triggered the quote
+        quote: QuoteResponse,
+    },
+    Error {
+        id: Option<String>,
+        message: String,
+    },
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PoolStreamQuery {
+    pub chains: Option<String>, // Comma separated network ids, all networks if omitted
+    pub pools: Option<String>,  // Comma separated pool addresses
+    pub pool_types: Option<String>, // Comma separated pool types (UniswapV2, UniswapV3, ERC4626)
+    pub cursor: Option<String>, // Event id to resume after, overrides Last-Event-ID
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PoolStateInfo {
+    Reserves {
+        reserve0: String, // Raw amount as decimal string
+        reserve1: String,
+    },
+    ConcentratedLiquidity {
+        sqrt_price_x96: String,
+        tick: i32,
+        liquidity: String,
+    },
+    Vault {
+        vault_reserve: String, // Vault share supply
+        asset_reserve: String, // Underlying assets held
+    },
+}
+
+impl From<&PoolState> for PoolStateInfo {
+    fn from(state: &PoolState) -> Self {
+        match state {
+            PoolState::Reserves { reserve0, reserve1 } => Self::Reserves {
+                reserve0: reserve0.to_string(),
+                reserve1: reserve1.to_string(),
+            },
+            PoolState::ConcentratedLiquidity {
+                sqrt_price_x96,
+                tick,
+                liquidity,
+            } => Self::ConcentratedLiquidity {
+                sqrt_price_x96: sqrt_price_x96.to_string(),
+                tick: *tick,
+                liquidity: liquidity.to_string(),
+            },
+            PoolState::Vault {
+                vault_reserve,
+                asset_reserve,
+            } => Self::Vault {
+                vault_reserve: vault_reserve.to_string(),
+                asset_reserve: asset_reserve.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PoolUpdateEvent {
+    pub sequence: u64, // Position in the chain's update log
+    pub network_id: u64,
+    pub pool: String,
+    pub pool_type: String,
+    pub block_number: u64,
+    pub transaction_hash: Option<String>,
+    pub log_index: Option<u64>,
+    pub state: Option<PoolStateInfo>, // State after the event was applied
+}
+
+impl From<&PoolUpdate> for PoolUpdateEvent {
+    fn from(update: &PoolUpdate) -> Self {
         Self {
-            success: false,
-            results: None,
-            error: Some(error),
+            sequence: update.sequence,
+            network_id: update.network_id,
+            pool: format!("{:?}", update.pool),
+            pool_type: update.pool_type.name().to_string(),
+            block_number: update.block_number,
+            transaction_hash: update.transaction_hash.map(|hash| format!("{:?}", hash)),
+            log_index: update.log_index,
+            state: update.state.as_ref().map(PoolStateInfo::from),
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PoolStreamGap {
+    pub network_id: u64,
+    pub after_sequence: u64, // Some events after this sequence are no longer available
+}
@@ -0,0 +1,224 @@
+use utoipa::{
+    openapi::{
+        path::Operation,
+        response::ResponseBuilder,
+        security::{ApiKey, ApiKeyValue, SecurityRequirement, SecurityScheme},
+        Content, OpenApi as OpenApiDocument, Ref, RefOr,
+    },
+    Modify, OpenApi,
+};
+
+use crate::api::{
+    auth, handlers, metrics, models::ErrorResponse, models::PoolStreamGap,
+    models::QuoteStreamMessage, models::QuoteStreamRequest, pool_stream, quote_stream, reload,
+};
+
+/// OpenAPI document of the routes in `create_router` and `create_admin_router`, served at
+/// `/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "EVM Arbitrage Bot API",
+        description = "Pool state, prices and swap quotes of the indexed EVM networks"
+    ),
+    paths(
+        handlers::health_check,
+        handlers::health_live,
+        handlers::health_ready,
+        handlers::get_networks,
+        handlers::get_pools,
+        handlers::get_pool,
+        handlers::get_tokens,
+        handlers::get_prices,
+        handlers::get_pool_candles,
+        handlers::get_pair_candles,
+        handlers::quote_amount_in_raw,
+        handlers::quote_amount_in_token,
+        handlers::quote_amount_out_raw,
+        handlers::quote_amount_out_token,
+        handlers::batch_quote_amount_in_raw_with_pool,
+        handlers::batch_quote_amount_in_token_with_pool,
+        handlers::batch_quote_amount_out_raw_with_pool,
+        handlers::batch_quote_amount_out_token_with_pool,
+        handlers::batch_quote_amount_in_raw,
+        handlers::batch_quote_amount_out_raw,
+        handlers::batch_quote_amount_out_token_with_pools,
+        handlers::quote_route,
+        handlers::get_depth,
+        handlers::get_quote_cache_stats,
+        quote_stream::quote_stream,
+        pool_stream::pool_update_stream,
+        auth::get_api_keys,
+        metrics::get_metrics,
+        reload::reload_config,
+    ),
+    // Stream payloads are not request or response bodies, so they are listed here
+    components(schemas(ErrorResponse, QuoteStreamRequest, QuoteStreamMessage, PoolStreamGap)),
+    modifiers(&ErrorResponses, &ApiKeyAuth)
+)]
+pub struct ApiDoc;
+
+/// Documents the `ErrorResponse` body as the default response of every operation
+struct ErrorResponses;
+
+impl Modify for ErrorResponses {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        let response = ResponseBuilder::new()
+            .description("Error with its machine-readable code, see the error codes in API.md")
+            .content(
+                "application/json",
+                Content::new(Some(RefOr::Ref(Ref::from_schema_name("ErrorResponse")))),
+            )
+            .build();
+        for path in openapi.paths.paths.values_mut() {
+            let operations: [&mut Option<Operation>; 2] = [&mut path.get, &mut path.post];
+            for operation in operations.into_iter().flatten() {
+                operation
+                    .responses
+                    .responses
+                    .insert("default".to_string(), response.clone().into());
+            }
+        }
+    }
+}
+
+/// Documents the API key every request except the health checks needs when keys are configured
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+        openapi.security = Some(vec![SecurityRequirement::new::<_, [&str; 0], &str>(
+            "api_key",
+            [],
+        )]);
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if path == "/health" || path.starts_with("/health/") {
+                if let Some(operation) = item.get.as_mut() {
+                    // An empty requirement lets the operation be called without a key
+                    operation.security = Some(vec![SecurityRequirement::default()]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{admin_routes, public_routes};
+    use crate::core::proccessor::Proccessor;
+    use crate::models::pool::multichain_registry::MultichainPoolRegistry;
+    use crate::models::price::MultichainPriceRegistry;
+    use crate::models::token::MultichainTokenRegistry;
+    use axum::body::Body;
+    use axum::http::{header::ALLOW, Method, Request, StatusCode};
+    use axum::Router;
+    use std::sync::Arc;
+    use tower::Service;
+
+    /// Every route of the public and admin routers with the methods it serves, with axum
+    /// `:param` segments written as OpenAPI `{param}`
+    async fn router_routes() -> Vec<(String, String)> {
+        let processor = Arc::new(Proccessor::new(
+            Arc::new(MultichainPoolRegistry::new()),
+            Arc::new(MultichainTokenRegistry::new()),
+            Arc::new(MultichainPriceRegistry::new()),
+        ));
+        let public = public_routes().into_iter().map(|(path, route)| {
+            let router = Router::new().route(path, route);
+            (path, router.with_state(Arc::clone(&processor)))
+        });
+        let admin = admin_routes()
+            .into_iter()
+            .map(|(path, route)| (path, Router::new().route(path, route)));
+
+        let mut routes = Vec::new();
+        for (path, mut router) in public.chain(admin) {
+            let uri = path
+                .split('/')
+                .map(|segment| {
+                    if segment.starts_with(':') {
+                        "1"
+                    } else {
+                        segment
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            // No route takes DELETE, so the router answers with the methods it allows
+            let request = Request::builder()
+                .method(Method::DELETE)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            let response = router.call(request).await.unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{}",
+                path
+            );
+            let allowed = response.headers()[ALLOW].to_str().unwrap().to_lowercase();
+
+            let path = path
+                .split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            for method in allowed.split(',').map(str::trim) {
+                if method != "head" {
+                    routes.push((path.clone(), method.to_string()));
+                }
+            }
+        }
+        routes
+    }
+
+    #[tokio::test]
+    async fn test_every_route_is_documented() {
+        let document = ApiDoc::openapi();
+        let routes = router_routes().await;
+        assert!(routes.len() > 20);
+
+        for (path, method) in &routes {
+            let item = document
+                .paths
+                .paths
+                .get(path)
+                .unwrap_or_else(|| panic!("{} is not in the OpenAPI document", path));
+            let operation = match method.as_str() {
+                "get" => item.get.as_ref(),
+                "post" => item.post.as_ref(),
+                _ => panic!("{} {} uses an unexpected method", method, path),
+            };
+            let operation = operation
+                .unwrap_or_else(|| panic!("{} {} is not in the OpenAPI document", method, path));
+            // Actions that take no input
+            if method == "post" && path != "/admin/reload" {
+                assert!(
+                    operation.request_body.is_some(),
+                    "POST {} has no request body schema",
+                    path
+                );
+            }
+            assert!(operation.responses.responses.contains_key("default"));
+        }
+
+        // Nothing is documented that the router does not serve
+        let documented: usize = document
+            .paths
+            .paths
+            .values()
+            .map(|item| item.get.is_some() as usize + item.post.is_some() as usize)
+            .sum();
+        assert_eq!(documented, routes.len());
+    }
+}
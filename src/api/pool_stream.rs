@@ -0,0 +1,411 @@
+use alloy::primitives::Address;
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::stream::{self, Stream};
+use log::{debug, info};
+use std::collections::{BTreeMap, HashSet};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{info_span, Instrument};
+
+use crate::{
+    api::errors::{ApiError, ApiQuery},
+    api::models::{PoolStreamGap, PoolStreamQuery, PoolUpdateEvent},
+    core::proccessor::Proccessor,
+    models::pool::{PoolType, PoolUpdate, PoolUpdateLog},
+    utils::errors::ArbitrageError,
+};
+
+/// Events buffered per connection before forwarders wait on the client
+const EVENT_BUFFER: usize = 256;
+
+#[derive(Debug, Default)]
+struct PoolUpdateFilter {
+    pools: Option<HashSet<Address>>,
+    /// Lowercase pool type names
+    pool_types: Option<HashSet<String>>,
+}
+
+impl PoolUpdateFilter {
+    fn matches(&self, update: &PoolUpdate) -> bool {
+        self.pools
+            .as_ref()
+            .is_none_or(|pools| pools.contains(&update.pool))
+            && self.pool_types.as_ref().is_none_or(|pool_types| {
+                pool_types.contains(&update.pool_type.name().to_lowercase())
+            })
+    }
+}
+
+#[derive(Debug)]
+enum StreamItem {
+    Update(PoolUpdate),
+    Gap(PoolStreamGap),
+}
+
+/// Stream cursor: the last delivered sequence per chain, written as `1:120,56:33`
+fn parse_cursor(cursor: &str) -> Option<BTreeMap<u64, u64>> {
+    cursor
+        .split(',')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (network_id, sequence) = entry.split_once(':')?;
+            Some((
+                network_id.trim().parse().ok()?,
+                sequence.trim().parse().ok()?,
+            ))
+        })
+        .collect()
+}
+
+fn format_cursor(cursor: &BTreeMap<u64, u64>) -> String {
+    cursor
+        .iter()
+        .map(|(network_id, sequence)| format!("{}:{}", network_id, sequence))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_list<T>(
+    value: &Option<String>,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Option<Option<Vec<T>>> {
+    match value {
+        None => Some(None),
+        Some(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse)
+            .collect::<Option<Vec<_>>>()
+            .map(Some),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/events/pools",
+    tag = "streams",
+    summary = "Server-sent events of pool state updates",
+    description = "Sends a `pool_update` event with a `PoolUpdateEvent` for every applied pool event, \
+                   and a `gap` event with a `PoolStreamGap` when updates to resume from are no \
+                   longer in history. Event ids are cursors to resume from.",
+    params(
+        PoolStreamQuery,
+        ("Last-Event-ID" = Option<String>, Header, description = "Cursor to resume after"),
+    ),
+    responses((status = 200, content_type = "text/event-stream", body = PoolUpdateEvent))
+)]
+pub async fn pool_update_stream(
+    State(processor): State<Arc<Proccessor>>,
+    ApiQuery(query): ApiQuery<PoolStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let chains = parse_list(&query.chains, |chain| chain.parse::<u64>().ok())
+        .ok_or_else(|| ApiError::InvalidRequest("chains must be chain ids".to_string()))?;
+    let pools = parse_list(&query.pools, |pool| pool.parse::<Address>().ok())
+        .ok_or_else(|| ApiError::InvalidAddress("pools must be addresses".to_string()))?;
+    let pool_types = parse_list(&query.pool_types, |pool_type| {
+        PoolType::NAMES
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(pool_type))
+            .then(|| pool_type.to_lowercase())
+    })
+    .ok_or_else(|| {
+        ApiError::InvalidRequest(format!(
+            "pool_types must be among {}",
+            PoolType::NAMES.join(", ")
+        ))
+    })?;
+    let filter = Arc::new(PoolUpdateFilter {
+        pools: pools.map(|pools| pools.into_iter().collect()),
+        pool_types: pool_types.map(|pool_types| pool_types.into_iter().collect()),
+    });
+
+    let cursor = match query.cursor.as_deref().or_else(|| {
+        headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+    }) {
+        Some(cursor) => parse_cursor(cursor).ok_or_else(|| {
+            ApiError::InvalidRequest(format!("Invalid stream cursor {:?}", cursor))
+        })?,
+        None => BTreeMap::new(),
+    };
+
+    let network_ids = match chains {
+        Some(chains) => chains,
+        None => processor.pool_registry().get_all_network_ids().await,
+    };
+    let (tx, rx) = mpsc::channel(EVENT_BUFFER);
+    for network_id in &network_ids {
+        let pool_registry = processor
+            .pool_registry()
+            .get_pool_registry(*network_id)
+            .await
+            .ok_or(ArbitrageError::NetworkNotFound(*network_id))?;
+        tokio::spawn(
+            forward_updates(
+                *network_id,
+                pool_registry.update_log(),
+                cursor.get(network_id).copied(),
+                filter.clone(),
+                tx.clone(),
+            )
+            .instrument(info_span!("pool_stream", chain_id = *network_id)),
+        );
+    }
+    info!(
+        "Pool update stream opened for networks {:?} with cursor {:?}",
+        network_ids, cursor
+    );
+
+    let events = stream::unfold((rx, cursor), |(mut rx, mut cursor)| async move {
+        let event = match rx.recv().await? {
+            StreamItem::Update(update) => {
+                cursor.insert(update.network_id, update.sequence);
+                Event::default()
+                    .event("pool_update")
+                    .json_data(PoolUpdateEvent::from(&update))
+                    .ok()?
+            }
+            StreamItem::Gap(gap) => Event::default().event("gap").json_data(gap).ok()?,
+        };
+        let event = if cursor.is_empty() {
+            event
+        } else {
+            event.id(format_cursor(&cursor))
+        };
+        Some((Ok(event), (rx, cursor)))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Forward one chain's updates after `resume_after` to the connection, in sequence order.
+/// Missed sequences, from a resume or from lagging the broadcast, are backfilled from history.
+async fn forward_updates(
+    network_id: u64,
+    update_log: Arc<PoolUpdateLog>,
+    resume_after: Option<u64>,
+    filter: Arc<PoolUpdateFilter>,
+    tx: mpsc::Sender<StreamItem>,
+) {
+    let mut last = resume_after.unwrap_or_else(|| update_log.latest_sequence());
+    let mut updates = update_log.subscribe();
+    if resume_after.is_some() && !catch_up(network_id, &update_log, &mut last, &filter, &tx).await {
+        return;
+    }
+
+    loop {
+        let received = tokio::select! {
+            _ = tx.closed() => break,
+            received = updates.recv() => received,
+        };
+        match received {
+            Ok(update) => {
+                if update.sequence > last + 1
+                    && !catch_up(network_id, &update_log, &mut last, &filter, &tx).await
+                {
+                    break;
+                }
+                if update.sequence <= last {
+                    continue;
+                }
+                last = update.sequence;
+                if filter.matches(&update) && tx.send(StreamItem::Update(update)).await.is_err() {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("Pool update stream lagged by {} updates", skipped);
+                if !catch_up(network_id, &update_log, &mut last, &filter, &tx).await {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Send everything after `last` still in history, reporting a gap if some of it is gone.
+/// Returns false once the client has disconnected.
+async fn catch_up(
+    network_id: u64,
+    update_log: &PoolUpdateLog,
+    last: &mut u64,
+    filter: &PoolUpdateFilter,
+    tx: &mpsc::Sender<StreamItem>,
+) -> bool {
+    let (missed, gap) = update_log.since(*last);
+    if let Some(resume_after) = gap {
+        let gap = PoolStreamGap {
+            network_id,
+            after_sequence: *last,
+        };
+        if tx.send(StreamItem::Gap(gap)).await.is_err() {
+            return false;
+        }
+        // The cursor may be ahead of a log recreated by a restart or reload
+        *last = resume_after;
+    }
+    for update in missed {
+        *last = update.sequence;
+        if filter.matches(&update) && tx.send(StreamItem::Update(update)).await.is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::models::pool::{
+        multichain_registry::MultichainPoolRegistry, MockPool, PoolRegistry,
+    };
+    use crate::models::price::MultichainPriceRegistry;
+    use crate::models::token::MultichainTokenRegistry;
+    use alloy::primitives::U256;
+    use alloy::rpc::types::Log;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = parse_cursor("1:120,56:33").unwrap();
+        assert_eq!(cursor[&1], 120);
+        assert_eq!(cursor[&56], 33);
+        assert_eq!(format_cursor(&cursor), "1:120,56:33");
+        assert!(parse_cursor("1-120").is_none());
+        assert!(parse_cursor("").unwrap().is_empty());
+    }
+
+    async fn next_item(rx: &mut mpsc::Receiver<StreamItem>) -> StreamItem {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resume_with_cursor_ahead_of_log() {
+        // The log was recreated, so the client's cursor is ahead of it
+        let update_log = Arc::new(PoolUpdateLog::new(16, 16));
+        let publish = |block: u64| {
+            let event = Log {
+                block_number: Some(block),
+                ..Default::default()
+            };
+            update_log.publish(PoolUpdate::from_log(1, &event, PoolType::UniswapV2, None))
+        };
+        publish(1);
+        publish(2);
+
+        let (tx, mut rx) = mpsc::channel(EVENT_BUFFER);
+        tokio::spawn(forward_updates(
+            1,
+            update_log.clone(),
+            Some(50),
+            Arc::new(PoolUpdateFilter::default()),
+            tx,
+        ));
+
+        match next_item(&mut rx).await {
+            StreamItem::Gap(gap) => assert_eq!(gap.after_sequence, 50),
+            item => panic!("expected a gap, got {:?}", item),
+        }
+        let mut sequences = Vec::new();
+        for _ in 0..2 {
+            if let StreamItem::Update(update) = next_item(&mut rx).await {
+                sequences.push(update.sequence);
+            }
+        }
+        // Updates published after the resume are still delivered
+        publish(3);
+        if let StreamItem::Update(update) = next_item(&mut rx).await {
+            sequences.push(update.sequence);
+        }
+        assert_eq!(sequences, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_resumes_from_cursor_with_filter() {
+        let pool_a = Address::repeat_byte(0xa);
+        let pool_b = Address::repeat_byte(0xb);
+        let pool_registry = Arc::new(PoolRegistry::new(1));
+        for pool in [pool_a, pool_b] {
+            pool_registry
+                .add_pool(Box::new(MockPool::new_v2(
+                    pool,
+                    Address::repeat_byte(1),
+                    Address::repeat_byte(2),
+                    U256::from(1_000_000u64),
+                    U256::from(2_000_000u64),
+                )))
+                .await;
+        }
+        let apply = |pool: Address, block: u64| {
+            let pool_registry = pool_registry.clone();
+            async move {
+                let mut event = Log::default();
+                event.inner.address = pool;
+                event.block_number = Some(block);
+                pool_registry.apply_log(&event).await.unwrap();
+            }
+        };
+        apply(pool_a, 1).await;
+        apply(pool_b, 2).await;
+        apply(pool_a, 3).await;
+
+        let multichain_pool_registry = Arc::new(MultichainPoolRegistry::new());
+        multichain_pool_registry
+            .add_pool_registry(1, pool_registry.clone())
+            .await;
+        let processor = Arc::new(Proccessor::new(
+            multichain_pool_registry,
+            Arc::new(MultichainTokenRegistry::new()),
+            Arc::new(MultichainPriceRegistry::new()),
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                create_router(processor, Default::default(), Default::default()),
+            )
+            .await
+            .unwrap()
+        });
+
+        let mut response = reqwest::get(format!(
+            "http://{}/events/pools?chains=1&pools={:?}&cursor=1:1",
+            addr, pool_a
+        ))
+        .await
+        .unwrap();
+        assert!(response.status().is_success());
+
+        apply(pool_a, 4).await;
+        let mut body = String::new();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !body.contains("\"sequence\":4") {
+                let chunk = response.chunk().await.unwrap().unwrap();
+                body.push_str(&String::from_utf8_lossy(&chunk));
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(body.contains("\"sequence\":3"));
+        assert!(body.contains("\"block_number\":4"));
+        assert!(body.contains("id: 1:4"));
+        // Pool B was filtered out and sequence 1 was before the cursor
+        assert!(!body.contains("\"sequence\":2"));
+        assert!(!body.contains("\"sequence\":1,"));
+    }
+}
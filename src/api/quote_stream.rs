@@ -0,0 +1,504 @@
+use alloy::primitives::{Address, U256};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    Extension,
+};
+use log::{debug, info, warn};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{info_span, Instrument};
+
+use crate::{
+    api::auth::{ApiCaller, RequestLimits},
+    api::models::{QuoteResponse, QuoteStreamMessage, QuoteStreamRequest, QuoteSubscription},
+    core::proccessor::{Proccessor, QuoteType},
+    models::pool::PoolUpdate,
+};
+
+/// Maximum number of live subscriptions on a single connection
+pub const MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = 32;
+
+/// Pool updates buffered per connection before the chain broadcast starts lagging
+const UPDATE_BUFFER: usize = 256;
+
+/// A client that does not accept a message within this time is disconnected
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct ActiveSubscription {
+    network_id: u64,
+    pools: Vec<Address>,
+    token_in: Address,
+    token_out: Address,
+    amount: U256,
+    quote_type: QuoteType,
+}
+
+enum StreamEvent {
+    Update(PoolUpdate),
+    /// The connection fell behind the chain's update broadcast; requote everything on it
+    Lagged {
+        network_id: u64,
+    },
+}
+
+#[utoipa::path(
+    get,
+    path = "/ws/quotes",
+    tag = "streams",
+    summary = "WebSocket of route quotes pushed on every pool update",
+    description = "Clients send `QuoteStreamRequest` messages to subscribe to routes and receive \
+                   `QuoteStreamMessage` messages, a quote for every block that changes a pool on \
+                   a subscribed route.",
+    responses((status = 101, description = "Switching to the WebSocket protocol"))
+)]
+pub async fn quote_stream(
+    ws: WebSocketUpgrade,
+    State(processor): State<Arc<Proccessor>>,
+    Extension(caller): Extension<ApiCaller>,
+) -> Response {
+    ws.on_upgrade(move |socket| handle_quote_stream(socket, processor, caller.limits))
+}
+
+async fn handle_quote_stream(
+    mut socket: WebSocket,
+    processor: Arc<Proccessor>,
+    limits: RequestLimits,
+) {
+    info!("Quote stream connection opened");
+    let (event_tx, mut event_rx) = mpsc::channel(UPDATE_BUFFER);
+    // One update forwarder per chain with at least one subscription on it
+    let mut forwarders: HashMap<u64, JoinHandle<()>> = HashMap::new();
+    let mut subscriptions: HashMap<String, ActiveSubscription> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let replies = match serde_json::from_str::<QuoteStreamRequest>(&text) {
+                    Ok(QuoteStreamRequest::Subscribe(request)) => {
+                        let id = request.id.clone();
+                        match subscribe(&processor, request, &subscriptions, &limits).await {
+                            Ok(subscription) => {
+                                let network_id = subscription.network_id;
+                                if let Entry::Vacant(entry) = forwarders.entry(network_id) {
+                                    if let Some(updates) = processor
+                                        .pool_registry()
+                                        .get_pool_registry(network_id)
+                                        .await
+                                        .map(|registry| registry.subscribe_updates())
+                                    {
+                                        let forwarder = tokio::spawn(
+                                            forward_updates(
+                                                network_id,
+                                                updates,
+                                                event_tx.clone(),
+                                            )
+                                            .instrument(info_span!(
+                                                "quote_stream",
+                                                chain_id = network_id
+                                            )),
+                                        );
+                                        entry.insert(forwarder);
+                                    }
+                                }
+                                let (block_number, quote) = quote(&processor, &subscription).await;
+                                // Resubscribing an id may move it off its previous chain
+                                subscriptions.insert(id.clone(), subscription);
+                                stop_idle_forwarders(&mut forwarders, &subscriptions);
+                                vec![
+                                    QuoteStreamMessage::Subscribed { id: id.clone() },
+                                    QuoteStreamMessage::Quote { id, block_number, quote },
+                                ]
+                            }
+                            Err(message) => vec![QuoteStreamMessage::Error { id: Some(id), message }],
+                        }
+                    }
+                    Ok(QuoteStreamRequest::Unsubscribe { id }) => {
+                        if subscriptions.remove(&id).is_some() {
+                            stop_idle_forwarders(&mut forwarders, &subscriptions);
+                            vec![QuoteStreamMessage::Unsubscribed { id }]
+                        } else {
+                            vec![QuoteStreamMessage::Error {
+                                id: Some(id),
+                                message: "Unknown subscription".to_string(),
+                            }]
+                        }
+                    }
+                    Err(e) => vec![QuoteStreamMessage::Error {
+                        id: None,
+                        message: format!("Invalid request: {}", e),
+                    }],
+                };
+                if !send_all(&mut socket, replies).await {
+                    break;
+                }
+            }
+            Some(event) = event_rx.recv() => {
+                // Coalesce everything already queued so each subscription is quoted once per batch
+                let mut touched = HashSet::new();
+                mark_touched(&subscriptions, event, &mut touched);
+                while let Ok(event) = event_rx.try_recv() {
+                    mark_touched(&subscriptions, event, &mut touched);
+                }
+
+                let mut messages = Vec::with_capacity(touched.len());
+                for id in touched {
+                    if let Some(subscription) = subscriptions.get(&id) {
+                        let (block_number, quote) = quote(&processor, subscription).await;
+                        messages.push(QuoteStreamMessage::Quote { id, block_number, quote });
+                    }
+                }
+                if !send_all(&mut socket, messages).await {
+                    break;
+                }
+            }
+        }
+    }
+    for forwarder in forwarders.into_values() {
+        forwarder.abort();
+    }
+    info!(
+        "Quote stream connection closed with {} subscriptions",
+        subscriptions.len()
+    );
+}
+
+/// Abort the forwarders of chains that no subscription is on anymore
+fn stop_idle_forwarders(
+    forwarders: &mut HashMap<u64, JoinHandle<()>>,
+    subscriptions: &HashMap<String, ActiveSubscription>,
+) {
+    forwarders.retain(|network_id, forwarder| {
+        let watched = subscriptions
+            .values()
+            .any(|subscription| subscription.network_id == *network_id);
+        if !watched {
+            forwarder.abort();
+        }
+        watched
+    });
+}
+
+async fn forward_updates(
+    network_id: u64,
+    mut updates: broadcast::Receiver<PoolUpdate>,
+    event_tx: mpsc::Sender<StreamEvent>,
+) {
+    loop {
+        // The bounded send is the backpressure: a slow client stops draining the broadcast
+        let event = match updates.recv().await {
+            Ok(update) => StreamEvent::Update(update),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("Quote stream lagged by {} updates", skipped);
+                StreamEvent::Lagged { network_id }
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        if event_tx.send(event).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn mark_touched(
+    subscriptions: &HashMap<String, ActiveSubscription>,
+    event: StreamEvent,
+    touched: &mut HashSet<String>,
+) {
+    for (id, subscription) in subscriptions {
+        let affected = match &event {
+            StreamEvent::Update(update) => {
+                update.network_id == subscription.network_id
+                    && subscription.pools.contains(&update.pool)
+            }
+            StreamEvent::Lagged { network_id } => *network_id == subscription.network_id,
+        };
+        if affected {
+            touched.insert(id.clone());
+        }
+    }
+}
+
+async fn subscribe(
+    processor: &Proccessor,
+    request: QuoteSubscription,
+    subscriptions: &HashMap<String, ActiveSubscription>,
+    limits: &RequestLimits,
+) -> Result<ActiveSubscription, String> {
+    if !subscriptions.contains_key(&request.id)
+        && subscriptions.len() >= MAX_SUBSCRIPTIONS_PER_CONNECTION
+    {
+        return Err(format!(
+            "Subscription limit of {} reached",
+            MAX_SUBSCRIPTIONS_PER_CONNECTION
+        ));
+    }
+    // Routes are held to the caller's hop limit, like the path quote endpoints
+    if request.pools.is_empty() || request.pools.len() > limits.max_hops {
+        return Err(format!("Route must have 1 to {} pools", limits.max_hops));
+    }
+
+    let pools = request
+        .pools
+        .iter()
+        .map(|pool| pool.parse::<Address>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid pool address: {}", e))?;
+    let token_in = request
+        .token_in
+        .parse::<Address>()
+        .map_err(|e| format!("Invalid token_in: {}", e))?;
+    let token_out = request
+        .token_out
+        .parse::<Address>()
+        .map_err(|e| format!("Invalid token_out: {}", e))?;
+    let amount = request
+        .amount
+        .parse::<U256>()
+        .map_err(|e| format!("Invalid amount: {}", e))?;
+
+    let pool_registry = processor
+        .pool_registry()
+        .get_pool_registry(request.network_id)
+        .await
+        .ok_or_else(|| format!("Unknown network {}", request.network_id))?;
+    for pool in &pools {
+        if pool_registry.get_pool(pool).await.is_none() {
+            return Err(format!("Pool {:?} not found", pool));
+        }
+    }
+
+    Ok(ActiveSubscription {
+        network_id: request.network_id,
+        pools,
+        token_in,
+        token_out,
+        amount,
+        quote_type: request.quote_type,
+    })
+}
+
+/// Quote a subscription, along with the block of the snapshot the quote was computed from
+async fn quote(processor: &Proccessor, subscription: &ActiveSubscription) -> (u64, QuoteResponse) {
+    match processor
+        .quote_amount_token_with_path_raw(
+            subscription.network_id,
+            &subscription.pools,
+            subscription.amount,
+            &subscription.quote_type,
+            subscription.token_in,
+            subscription.token_out,
+        )
+        .await
+    {
+        Ok(result) => {
+            let amount = match subscription.quote_type {
+                QuoteType::ExactIn => result.output.amount,
+                QuoteType::ExactOut => result.input.amount,
+            };
+            let quote = match amount.parse::<U256>() {
+                Ok(amount) => QuoteResponse::success(amount, result.block_number),
+                Err(e) => QuoteResponse::error(e.to_string()),
+            };
+            (result.block_number, quote)
+        }
+        Err(e) => (
+            last_processed_block(processor, subscription.network_id).await,
+            QuoteResponse::error(e.to_string()),
+        ),
+    }
+}
+
+async fn last_processed_block(processor: &Proccessor, network_id: u64) -> u64 {
+    match processor
+        .pool_registry()
+        .get_pool_registry(network_id)
+        .await
+    {
+        Some(registry) => registry.get_last_processed_block().await,
+        None => 0,
+    }
+}
+
+/// Send messages in order, returning false if the client is gone or too slow
+async fn send_all(socket: &mut WebSocket, messages: Vec<QuoteStreamMessage>) -> bool {
+    for message in messages {
+        let text = match serde_json::to_string(&message) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Failed to serialize quote stream message: {}", e);
+                continue;
+            }
+        };
+        match tokio::time::timeout(SEND_TIMEOUT, socket.send(Message::Text(text))).await {
+            Ok(Ok(())) => {}
+            Ok(Err(_)) => return false,
+            Err(_) => {
+                warn!("Quote stream client too slow, disconnecting");
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::auth::DEFAULT_MAX_HOPS;
+    use crate::api::create_router;
+    use crate::models::pool::{
+        multichain_registry::MultichainPoolRegistry, MockPool, PoolRegistry,
+    };
+    use crate::models::price::MultichainPriceRegistry;
+    use crate::models::token::{MultichainTokenRegistry, Token, TokenRegistry};
+    use alloy::rpc::types::Log;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message as ClientMessage;
+
+    async fn next_json<S>(client: &mut S) -> serde_json::Value
+    where
+        S: StreamExt<Item = Result<ClientMessage, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        loop {
+            match client.next().await.unwrap().unwrap() {
+                ClientMessage::Text(text) => return serde_json::from_str(&text).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quote_stream_pushes_on_pool_update() {
+        let token0 = Address::repeat_byte(1);
+        let token1 = Address::repeat_byte(2);
+        let pool = Address::repeat_byte(3);
+
+        let pool_registry = Arc::new(PoolRegistry::new(1));
+        pool_registry
+            .add_pool(Box::new(MockPool::new_v2(
+                pool,
+                token0,
+                token1,
+                U256::from(1_000_000u64),
+                U256::from(2_000_000u64),
+            )))
+            .await;
+        let multichain_pool_registry = Arc::new(MultichainPoolRegistry::new());
+        multichain_pool_registry
+            .add_pool_registry(1, pool_registry.clone())
+            .await;
+        let mut token_registry = TokenRegistry::new(1);
+        token_registry.add_token(Token::new(token0, 1, "A".into(), "Token A".into(), 18));
+        token_registry.add_token(Token::new(token1, 1, "B".into(), "Token B".into(), 18));
+        let multichain_token_registry = Arc::new(MultichainTokenRegistry::new());
+        multichain_token_registry
+            .add_token_registry(1, Arc::new(tokio::sync::RwLock::new(token_registry)))
+            .await;
+        let processor = Arc::new(Proccessor::new(
+            multichain_pool_registry,
+            multichain_token_registry,
+            Arc::new(MultichainPriceRegistry::new()),
+        ));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                create_router(processor, Default::default(), Default::default()),
+            )
+            .await
+            .unwrap()
+        });
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws/quotes", addr))
+            .await
+            .unwrap();
+
+        let subscribe = serde_json::json!({
+            "type": "subscribe",
+            "id": "q1",
+            "network_id": 1,
+            "pools": [format!("{:?}", pool)],
+            "token_in": format!("{:?}", token0),
+            "token_out": format!("{:?}", token1),
+            "amount": "1000",
+            "quote_type": "exact_in",
+        });
+        client
+            .send(ClientMessage::Text(subscribe.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(next_json(&mut client).await["type"], "subscribed");
+        let initial = next_json(&mut client).await;
+        assert_eq!(initial["type"], "quote");
+        assert_eq!(initial["quote"]["success"], true);
+
+        // Routes longer than the hop limit are rejected
+        let too_long = serde_json::json!({
+            "type": "subscribe",
+            "id": "q2",
+            "network_id": 1,
+            "pools": vec![format!("{:?}", pool); DEFAULT_MAX_HOPS + 1],
+            "token_in": format!("{:?}", token0),
+            "token_out": format!("{:?}", token1),
+            "amount": "1000",
+            "quote_type": "exact_in",
+        });
+        client
+            .send(ClientMessage::Text(too_long.to_string()))
+            .await
+            .unwrap();
+        let error = next_json(&mut client).await;
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["id"], "q2");
+
+        let mut event = Log::default();
+        event.inner.address = pool;
+        event.block_number = Some(42);
+        pool_registry.apply_log(&event).await.unwrap();
+
+        let update = next_json(&mut client).await;
+        assert_eq!(update["type"], "quote");
+        assert_eq!(update["id"], "q1");
+        assert_eq!(update["block_number"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_stop_idle_forwarders() {
+        let subscription = |network_id| ActiveSubscription {
+            network_id,
+            pools: vec![Address::ZERO],
+            token_in: Address::ZERO,
+            token_out: Address::ZERO,
+            amount: U256::from(1),
+            quote_type: QuoteType::ExactIn,
+        };
+        let mut forwarders = HashMap::new();
+        forwarders.insert(1, tokio::spawn(std::future::pending::<()>()));
+        forwarders.insert(2, tokio::spawn(std::future::pending::<()>()));
+        let mut subscriptions = HashMap::new();
+        subscriptions.insert("a".to_string(), subscription(1));
+
+        stop_idle_forwarders(&mut forwarders, &subscriptions);
+        assert_eq!(forwarders.keys().collect::<Vec<_>>(), vec![&1]);
+
+        subscriptions.clear();
+        let forwarder = forwarders.get(&1).unwrap().abort_handle();
+        stop_idle_forwarders(&mut forwarders, &subscriptions);
+        assert!(forwarders.is_empty());
+        tokio::task::yield_now().await;
+        assert!(forwarder.is_finished());
+    }
+}
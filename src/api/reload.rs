@@ -0,0 +1,116 @@
+use axum::{Extension, Json};
+use log::info;
+use std::sync::Arc;
+
+use crate::api::auth::ApiCaller;
+use crate::api::errors::ApiError;
+use crate::api::models::{ChainReloadInfo, ReloadResponse};
+use crate::core::chain_manager::{ChainManager, ReloadReport};
+
+impl From<ReloadReport> for ReloadResponse {
+    fn from(report: ReloadReport) -> Self {
+        Self {
+            started: report.started,
+            stopped: report.stopped,
+            updated: report
+                .updated
+                .into_iter()
+                .map(|update| ChainReloadInfo {
+                    network_id: update.network_id,
+                    pools_added: update
+                        .pools_added
+                        .iter()
+                        .map(|pool| format!("{:?}", pool))
+                        .collect(),
+                    pools_removed: update
+                        .pools_removed
+                        .iter()
+                        .map(|pool| format!("{:?}", pool))
+                        .collect(),
+                })
+                .collect(),
+            unchanged: report.unchanged,
+            errors: report.errors,
+        }
+    }
+}
+
+/// Same as sending SIGHUP to the process: added chains are started, removed ones stopped and
+/// pools of running chains added or removed in place
+#[utoipa::path(
+    post,
+    path = "/admin/reload",
+    tag = "admin",
+    summary = "Reload the chains and pools from the config files, for admin keys only",
+    responses((status = 200, body = ReloadResponse))
+)]
+pub async fn reload_config(
+    Extension(chain_manager): Extension<Arc<ChainManager>>,
+    Extension(caller): Extension<ApiCaller>,
+) -> Result<Json<ReloadResponse>, ApiError> {
+    if !caller.admin {
+        return Err(ApiError::Forbidden(
+            "An admin API key is required".to_string(),
+        ));
+    }
+    info!("POST /admin/reload requested by {}", caller.name);
+    let report = chain_manager
+        .reload()
+        .await
+        .map_err(|e| ApiError::Internal(format!("{:#}", e)))?;
+    Ok(Json(report.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::auth::{ApiKeyRegistry, API_KEY_HEADER};
+    use crate::api::create_admin_router;
+    use crate::utils::config::ApiKeyConfig;
+    use crate::utils::metrics::Metrics;
+
+    #[tokio::test]
+    async fn test_reload_config() {
+        let key = |key: &str, admin: bool| ApiKeyConfig {
+            key: key.to_string(),
+            name: key.to_string(),
+            admin,
+            requests_per_minute: 0,
+            max_batch_amounts: 1,
+            max_hops: 1,
+        };
+        let api_keys = Arc::new(ApiKeyRegistry::new(&[
+            key("user", false),
+            key("admin", true),
+        ]));
+        let metrics = Arc::new(Metrics::new());
+        let mut chain_manager =
+            ChainManager::new(Default::default(), Default::default(), metrics.clone());
+        chain_manager.set_config_paths(vec![std::env::temp_dir().join("missing-config.toml")]);
+        let router = create_admin_router(api_keys, metrics, Arc::new(chain_manager));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let client = reqwest::Client::new();
+        let reload = |key: &'static str| {
+            client
+                .post(format!("http://{}/admin/reload", addr))
+                .header(API_KEY_HEADER, key)
+                .send()
+        };
+
+        let response = reload("user").await.unwrap();
+        assert_eq!(response.status(), 403);
+
+        // A config that cannot be read leaves the running chains alone
+        let response = reload("admin").await.unwrap();
+        assert_eq!(response.status(), 500);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "INTERNAL_ERROR");
+        assert!(body["error"]
+            .as_str()
+            .unwrap()
+            .contains("missing-config.toml"));
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use axum::{
+    extract::{DefaultBodyLimit, Request, State},
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use log::info;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::api::auth::API_KEY_HEADER;
+use crate::api::errors::ApiError;
+use crate::utils::config::{ServerConfig, TlsConfig};
+
+/// Address of the public quote API
+pub const DEFAULT_BIND_ADDRESS: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 3001);
+/// Address of the admin API, only reachable from the host by default
+pub const DEFAULT_ADMIN_BIND_ADDRESS: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 3002);
+/// Seconds a request may take before it is answered with a timeout
+pub const DEFAULT_REQUEST_TIMEOUT: u64 = 30;
+/// Maximum request body size in bytes
+pub const DEFAULT_BODY_LIMIT: usize = 1024 * 1024;
+
+/// Wraps a router with the CORS, body limit, compression and timeout settings of the server
+pub fn apply_server_config(router: Router, config: &ServerConfig) -> Result<Router> {
+    let mut router = router.layer(DefaultBodyLimit::max(config.body_limit));
+    if config.request_timeout > 0 {
+        router = router.layer(middleware::from_fn_with_state(
+            Duration::from_secs(config.request_timeout),
+            timeout,
+        ));
+    }
+    // Event streams are never compressed so updates are not held back in the encoder
+    if config.compression {
+        router = router.layer(CompressionLayer::new());
+    }
+    if let Some(cors) = cors_layer(&config.cors_origins)? {
+        router = router.layer(cors);
+    }
+    Ok(router)
+}
+
+/// CORS policy for the configured origins, none when no origin is allowed
+fn cors_layer(origins: &[String]) -> Result<Option<CorsLayer>> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin)
+                    .with_context(|| format!("Invalid CORS origin {:?}", origin))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::POST])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                HeaderName::from_static(API_KEY_HEADER),
+            ])
+            .expose_headers([header::RETRY_AFTER]),
+    ))
+}
+
+/// Answers requests that take longer than the configured timeout. Streams are only timed
+/// until their response starts, so quote and pool streams stay open.
+async fn timeout(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    tokio::time::timeout(timeout, next.run(request))
+        .await
+        .map_err(|_| ApiError::Timeout(timeout.as_secs()))
+}
+
+/// Serves a router on `addr`, over TLS when a certificate is configured
+pub async fn serve(router: Router, addr: SocketAddr, tls: Option<&TlsConfig>) -> Result<()> {
+    match tls {
+        Some(tls) => {
+            // Fails when another part of the process already installed a provider, which is fine
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to load TLS certificate {} and key {}",
+                        tls.cert_path.display(),
+                        tls.key_path.display()
+                    )
+                })?;
+            info!("Listening on https://{}", addr);
+            axum_server::bind_rustls(addr, config)
+                .serve(router.into_make_service())
+                .await?;
+        }
+        None => {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .with_context(|| format!("Failed to bind {}", addr))?;
+            info!("Listening on http://{}", addr);
+            axum::serve(listener, router).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::errors::ApiJson;
+    use axum::routing::{get, post};
+
+    #[tokio::test]
+    async fn test_server_config_layers() {
+        let config = ServerConfig {
+            bind_address: DEFAULT_BIND_ADDRESS,
+            admin_bind_address: DEFAULT_ADMIN_BIND_ADDRESS,
+            cors_origins: vec!["https://app.example.com".to_string()],
+            request_timeout: 1,
+            body_limit: 64,
+            compression: true,
+            tls: None,
+        };
+        let router = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "done"
+                }),
+            )
+            .route(
+                "/echo",
+                post(|ApiJson(body): ApiJson<serde_json::Value>| async move { body.to_string() }),
+            );
+        let router = apply_server_config(router, &config).unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        let client = reqwest::Client::new();
+        let url = |path: &str| format!("http://{}{}", addr, path);
+
+        let response = client.get(url("/slow")).send().await.unwrap();
+        assert_eq!(response.status(), 503);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "TIMEOUT");
+
+        let response = client
+            .post(url("/echo"))
+            .json(&serde_json::json!({ "padding": "x".repeat(100) }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 413);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "PAYLOAD_TOO_LARGE");
+
+        // Only the configured origin is allowed
+        let preflight = |origin: &str| {
+            client
+                .request(reqwest::Method::OPTIONS, url("/echo"))
+                .header("origin", origin)
+                .header("access-control-request-method", "POST")
+                .send()
+        };
+        let response = preflight("https://app.example.com").await.unwrap();
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "https://app.example.com"
+        );
+        let response = preflight("https://other.example.com").await.unwrap();
+        assert!(!response
+            .headers()
+            .contains_key("access-control-allow-origin"));
+    }
+}
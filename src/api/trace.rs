@@ -0,0 +1,104 @@
+use alloy::primitives::Address;
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+use tracing::{field, info, info_span, Instrument, Span};
+
+/// Header carrying the id of a request, taken from the caller or generated
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest caller supplied request id that is kept, longer ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Runs every request in a `request` span and echoes its id in the `x-request-id` header.
+/// Handlers fill in the network and pool fields once they parsed them.
+pub async fn trace_request(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .and_then(|id| id.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+        network_id = field::Empty,
+        pool = field::Empty,
+        status = field::Empty,
+    );
+
+    let start = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    span.record("status", response.status().as_u16());
+    span.in_scope(|| info!(elapsed = ?start.elapsed(), "Request completed"));
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Records the pool a request is about on its span
+pub fn record_pool(network_id: u64, pool: Address) {
+    Span::current()
+        .record("network_id", network_id)
+        .record("pool", field::display(pool));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+
+    #[tokio::test]
+    async fn test_request_id() {
+        let app = Router::new()
+            .route(
+                "/pool",
+                get(|| async {
+                    record_pool(1, Address::ZERO);
+                    "ok"
+                }),
+            )
+            .layer(middleware::from_fn(trace_request));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/pool", addr);
+
+        // A generated id is returned
+        let response = client.get(&url).send().await.unwrap();
+        let request_id = response.headers()["x-request-id"].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(request_id).is_ok());
+
+        // The caller's id is kept
+        let response = client
+            .get(&url)
+            .header("x-request-id", "trace-abc")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-request-id"], "trace-abc");
+
+        // Unless it is too long
+        let response = client
+            .get(&url)
+            .header("x-request-id", "a".repeat(MAX_REQUEST_ID_LEN + 1))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()["x-request-id"].len(),
+            uuid::Uuid::nil().to_string().len()
+        );
+    }
+}
@@ -101,3 +101,15 @@ sol! {
     IVerioIP,
     "contracts/ABI/IVerioIP.json"
 }
+
+sol! {
+    #[sol(rpc)]
+    IChainlinkAggregator,
+    "contracts/ABI/IChainlinkAggregator.json"
+}
+
+sol! {
+    #[sol(rpc)]
+    IUniswapUniversalRouter,
+    "contracts/ABI/IUniswapUniversalRouter.json"
+}
@@ -106,6 +106,11 @@ impl EventQueue {
         events
     }
 
+    /// Number of events waiting in the channel
+    pub fn depth(&self) -> usize {
+        self.sender.inner.max_capacity() - self.sender.inner.capacity()
+    }
+
     /// Checks if an event with the given transaction hash and log index exists
     pub async fn has_event(&self, transaction_hash: TxHash, log_index: u64) -> bool {
         self.sender
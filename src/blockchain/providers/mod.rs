@@ -3,6 +3,7 @@ mod network_configurator;
 pub mod pool_fetcher;
 mod pool_updater_latest_block;
 pub mod pool_updater_websocket;
+mod rpc_metrics;
 pub mod token_fetcher;
 pub mod utils;
 pub mod websocket_listener;
@@ -11,6 +12,7 @@ pub use network_configurator::*;
 pub use pool_fetcher::*;
 pub use pool_updater_latest_block::*;
 pub use pool_updater_websocket::*;
+pub use rpc_metrics::RpcMetricsService;
 pub use token_fetcher::*;
 pub use utils::*;
 pub use websocket_listener::WebsocketListener;
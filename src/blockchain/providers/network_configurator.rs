@@ -4,6 +4,7 @@ use log::{error, info};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tokio::time::interval;
 
 #[derive(Debug, Clone)]
@@ -30,7 +31,8 @@ impl<P: Provider + Send + Sync + 'static> NetworkConfigurator<P> {
         }
     }
 
-    pub async fn start(&self) -> Result<()> {
+    /// Fetches the gas price, then refreshes it in a background task until the task is aborted
+    pub async fn start(&self) -> Result<JoinHandle<()>> {
         let provider = self.provider.clone();
         let gas_info = self.gas_info.clone();
         let update_interval = self.update_interval;
@@ -41,7 +43,7 @@ impl<P: Provider + Send + Sync + 'static> NetworkConfigurator<P> {
         info!("Initial gas info: {:?}", self.gas_info.read().await);
 
         // Spawn update task
-        tokio::spawn(async move {
+        let handle = tokio::spawn(async move {
             info!("Starting network configurator");
             let mut interval = interval(update_interval);
             loop {
@@ -52,7 +54,7 @@ impl<P: Provider + Send + Sync + 'static> NetworkConfigurator<P> {
             }
         });
 
-        Ok(())
+        Ok(handle)
     }
 
     async fn update_gas_info(gas_info: &Arc<RwLock<GasInfo>>, provider: &Arc<P>) -> Result<()> {
@@ -87,12 +87,10 @@ pub async fn fetch_and_display_pool_info<P: Provider + Send + Sync>(
     wait_time_for_startup: u64,
     multicall_address: Address,
 ) -> Result<()> {
-    info!(
-        "Starting pool fetch at block: {}",
-        block_number.as_number().unwrap()
-    );
-    let chain_id = provider.get_chain_id().await?;
+    let fetched_at = block_number.as_number().unwrap();
+    info!("Starting pool fetch at block: {}", fetched_at);
     let mut pool_types_present = HashSet::new();
+    let mut pools: Vec<Box<dyn PoolInterface + Send + Sync>> = Vec::new();
     for (i, pool_address) in pool_addresses.iter().enumerate() {
         info!("\nFetching pool information for address: {}", pool_address);
 
@@ -101,7 +99,7 @@ pub async fn fetch_and_display_pool_info<P: Provider + Send + Sync>(
 
         // Identify pool type
         let pool_type = identify_pool_type(provider, address).await?;
-        info!("CHAIN ID: {} Pool type: {:?}", chain_id, pool_type);
+        info!("Pool type: {:?}", pool_type);
 
         // Fetch the pool
         match pool_type {
@@ -114,7 +112,7 @@ pub async fn fetch_and_display_pool_info<P: Provider + Send + Sync>(
                     multicall_address,
                 )
                 .await?;
-                pool_registry.add_pool(Box::new(pool.clone())).await;
+                pools.push(Box::new(pool));
                 // path_registry.add_pool(&pool).await;
                 pool_types_present.insert(PoolType::UniswapV2);
             }
@@ -127,7 +125,7 @@ pub async fn fetch_and_display_pool_info<P: Provider + Send + Sync>(
                     multicall_address,
                 )
                 .await?;
-                pool_registry.add_pool(Box::new(pool.clone())).await;
+                pools.push(Box::new(pool));
                 // path_registry.add_pool(&pool).await;
                 pool_types_present.insert(PoolType::UniswapV3);
             }
@@ -140,7 +138,7 @@ pub async fn fetch_and_display_pool_info<P: Provider + Send + Sync>(
                     token_registry,
                 )
                 .await?;
-                pool_registry.add_pool(pool.clone_box()).await;
+                pools.push(pool);
                 // path_registry.add_pool(&*pool).await;
                 pool_types_present.insert(PoolType::ERC4626(pool_type));
             }
@@ -151,10 +149,10 @@ pub async fn fetch_and_display_pool_info<P: Provider + Send + Sync>(
             tokio::time::sleep(tokio::time::Duration::from_millis(wait_time_for_startup)).await;
         }
     }
+    // Publish the fetched pools at once
+    pool_registry.add_pools_at(pools, fetched_at).await;
     // Set last processed block
-    pool_registry
-        .set_last_processed_block(block_number.as_number().unwrap())
-        .await;
+    pool_registry.set_last_processed_block(fetched_at).await;
 
     for pool_type in pool_types_present {
         pool_registry.add_topics(pool_type.topics()).await;
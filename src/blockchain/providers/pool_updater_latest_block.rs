@@ -1,15 +1,14 @@
 use crate::models::pool::base::Topic;
 use crate::models::pool::PoolRegistry;
 use alloy::eips::BlockNumberOrTag;
-use alloy::primitives::Address;
 use alloy::providers::Provider;
 use anyhow::Result;
-use log::{debug, error, info};
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
+use tracing::{debug, error, info, info_span, Instrument};
 
-use super::fetch_events;
+use super::{fetch_events, fill_block_timestamps};
 
 pub struct PoolUpdaterLatestBlock<P: Provider + Send + Sync + 'static> {
     network_id: u64,
@@ -18,7 +17,6 @@ pub struct PoolUpdaterLatestBlock<P: Provider + Send + Sync + 'static> {
     // metrics: Arc<RwLock<Metrics>>,
     max_blocks_per_batch: u64,
     // swap_event_tx: mpsc::Sender<PendingEvent>,
-    topics: Arc<Vec<Topic>>,
     profitable_topics: Arc<HashSet<Topic>>,
 }
 
@@ -39,19 +37,17 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlock<P> {
                 let current_block = pool_registry.get_last_processed_block().await;
                 if current_block == 0 {
                     pool_registry.set_last_processed_block(start_block).await;
-                    info!(
-                        "CHAIN ID: {} Initialized last processed block to {}",
-                        network_id, start_block
-                    );
+                    info!("Initialized last processed block to {}", start_block);
                 } else if start_block > 0 && start_block > current_block {
                     // Override existing block if a higher start_block is provided
                     pool_registry.set_last_processed_block(start_block).await;
                     info!(
-                        "CHAIN ID: {} Updated last processed block from {} to {}",
-                        network_id, current_block, start_block
+                        "Updated last processed block from {} to {}",
+                        current_block, start_block
                     );
                 }
             }
+            .instrument(info_span!("pool_updater", chain_id = network_id))
         });
         Self {
             network_id,
@@ -60,12 +56,16 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlock<P> {
             //metrics: metrics.clone(),
             max_blocks_per_batch,
             //swap_event_tx,
-            topics: Arc::new(pool_registry.get_topics().await.clone()),
             profitable_topics: Arc::new(pool_registry.get_profitable_topics().await.clone()),
         }
     }
 
     pub async fn start(&mut self) -> Result<()> {
+        let span = info_span!("pool_updater", chain_id = self.network_id);
+        self.run().instrument(span).await
+    }
+
+    async fn run(&mut self) -> Result<()> {
         loop {
             // Get latest block number with retry logic
             let mut backoff = Duration::from_millis(500);
@@ -77,17 +77,22 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlock<P> {
                     }
                     Err(e) => {
                         error!(
-                            "CHAIN ID: {} Error fetching block number, retrying in {}s: {}",
-                            self.network_id,
+                            "Error fetching block number, retrying in {}s: {}",
                             backoff.as_secs(),
                             e
                         );
+                        self.pool_registry
+                            .sync_status()
+                            .record_error(format!("Error fetching block number: {}", e));
                         tokio::time::sleep(backoff).await;
                         backoff = std::cmp::min(backoff * 2, max_backoff);
                     }
                 }
             };
 
+            self.pool_registry.record_chain_head(latest_block);
+            let metrics = self.pool_registry.metrics();
+
             // Get the last processed block from registry
             let last_processed_block = self.pool_registry.get_last_processed_block().await;
 
@@ -106,9 +111,11 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlock<P> {
                 let batch_end =
                     std::cmp::min(current_block + self.max_blocks_per_batch - 1, latest_block);
 
+                // Pools are only added live between batches, and can bring new event types
+                let _updates = self.pool_registry.lock_updates().await;
+                let topics = Arc::new(self.pool_registry.get_topics().await);
                 // Process pools for confirmed blocks
                 match proccess_pools(
-                    self.network_id,
                     &self.provider,
                     &self.pool_registry,
                     // &self.metrics,
@@ -116,24 +123,36 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlock<P> {
                     BlockNumberOrTag::Number(current_block),
                     BlockNumberOrTag::Number(batch_end),
                     batch_end == latest_block,
-                    self.topics.clone(),
+                    topics,
                     self.profitable_topics.clone(),
                 )
+                .instrument(info_span!(
+                    "update_batch",
+                    from_block = current_block,
+                    to_block = batch_end
+                ))
                 .await
                 {
                     Ok(_) => {
                         // Update last processed block in registry
                         self.pool_registry.set_last_processed_block(batch_end).await;
+                        if let Some(metrics) = &metrics {
+                            metrics.add_blocks_processed(batch_end - current_block + 1);
+                        }
                         info!(
-                            "CHAIN ID: {} Successfully processed blocks: {} - {}",
-                            self.network_id, current_block, batch_end
+                            "Successfully processed blocks: {} - {}",
+                            current_block, batch_end
                         );
                     }
                     Err(e) => {
                         error!(
-                            "CHAIN ID: {} Error processing blocks {} - {}: {}",
-                            self.network_id, current_block, batch_end, e
+                            "Error processing blocks {} - {}: {}",
+                            current_block, batch_end, e
                         );
+                        self.pool_registry.sync_status().record_error(format!(
+                            "Error processing blocks {} - {}: {}",
+                            current_block, batch_end, e
+                        ));
                         // Don't update last_processed_block on error
                         break;
                     }
@@ -149,7 +168,6 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlock<P> {
 }
 
 async fn proccess_pools<P: Provider + Send + Sync + 'static>(
-    network_id: u64,
     provider: &Arc<P>,
     pool_registry: &Arc<PoolRegistry>,
     //metrics: &Arc<RwLock<Metrics>>,
@@ -160,7 +178,7 @@ async fn proccess_pools<P: Provider + Send + Sync + 'static>(
     topics: Arc<Vec<Topic>>,
     _profitable_topics: Arc<HashSet<Topic>>,
 ) -> Result<()> {
-    let addresses: Vec<Address> = pool_registry.get_all_addresses().await;
+    let addresses = pool_registry.get_all_addresses().await;
     let addresses_len = addresses.len();
     if addresses_len == 0 {
         return Ok(());
@@ -171,43 +189,42 @@ async fn proccess_pools<P: Provider + Send + Sync + 'static>(
 
     let topics = topics.clone().to_vec();
     loop {
-        match fetch_events(
-            provider,
-            addresses.clone(),
-            topics.clone(),
-            from_block,
-            to_block,
-        )
-        .await
-        {
-            Ok(events) => {
+        match fetch_events(provider, &addresses, topics.clone(), from_block, to_block).await {
+            Ok(mut events) => {
+                // Candles need the block timestamps most nodes leave out of logs
+                if pool_registry.candle_registry().is_some() {
+                    fill_block_timestamps(provider, &mut events).await;
+                }
                 // let mut swap_events = Vec::new();
                 debug!(
-                    "CHAIN ID: {} Processing {} events from {} to {}",
-                    network_id,
+                    "Processing {} events from {} to {}",
                     events.len(),
                     from_block.as_number().unwrap(),
                     to_block.as_number().unwrap()
                 );
-                for event in events {
-                    if let Some(pool) = pool_registry.get_pool(&event.address()).await {
-                        if let Err(e) = pool.write().await.apply_log(&event) {
-                            error!(
-                                "CHAIN ID: {} Error applying event {} for pool {}, event {}",
-                                network_id,
-                                e,
-                                event.address(),
-                                event.transaction_hash.unwrap()
-                            );
-                        }
-
-                        // SKIP FOR NOW
-                        // if is_latest_block && profitable_topics.contains(event.topic0().unwrap()) {
-                        //     swap_events.push(event);
-                        // }
-                        //     swap_events.push(event);
-                        // }
+                // Apply the whole range at once so quotes never see part of it
+                let results = pool_registry.apply_logs(&events).await;
+                for (event, result) in events.iter().zip(results) {
+                    if let Err(e) = result {
+                        error!(
+                            pool = %event.address(),
+                            tx = %event.transaction_hash.unwrap(),
+                            "Error applying event {}",
+                            e
+                        );
+                        pool_registry.sync_status().record_error(format!(
+                            "Error applying event {} for pool {}",
+                            e,
+                            event.address()
+                        ));
                     }
+
+                    // SKIP FOR NOW
+                    // if is_latest_block && profitable_topics.contains(event.topic0().unwrap()) {
+                    //     swap_events.push(event);
+                    // }
+                    //     swap_events.push(event);
+                    // }
                 }
                 // SKIP FOR NOW
                 // if is_latest_block && from_block == to_block {
@@ -237,11 +254,13 @@ async fn proccess_pools<P: Provider + Send + Sync + 'static>(
             }
             Err(e) => {
                 error!(
-                    "CHAIN ID: {} Error fetching events, retrying in {}s: {}",
-                    network_id,
+                    "Error fetching events, retrying in {}s: {}",
                     backoff.as_secs(),
                     e
                 );
+                pool_registry
+                    .sync_status()
+                    .record_error(format!("Error fetching events: {}", e));
                 tokio::time::sleep(backoff).await;
                 backoff = std::cmp::min(backoff * 2, max_backoff);
             }
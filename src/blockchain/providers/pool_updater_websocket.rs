@@ -2,13 +2,17 @@ use crate::models::pool::base::Topic;
 use crate::models::pool::PoolRegistry;
 use alloy::eips::BlockNumberOrTag;
 use alloy::providers::Provider;
+use alloy::rpc::types::Log;
 use anyhow::Result;
-use log::{debug, error, info};
 use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::time::Duration;
+use tracing::{debug, error, info, info_span, Instrument};
 
-use super::{fetch_events, EventQueue};
+use super::{fetch_events, fill_block_timestamps, EventQueue};
+
+/// How often the chain head is polled for the sync lag, events arrive over websockets
+const HEAD_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct PoolUpdaterLatestBlockWs<P: Provider + Send + Sync + 'static> {
     network_id: u64,
@@ -17,7 +21,6 @@ pub struct PoolUpdaterLatestBlockWs<P: Provider + Send + Sync + 'static> {
     pool_registry: Arc<PoolRegistry>,
     // swap_event_tx: mpsc::Sender<PendingEvent>,
     max_blocks_per_batch: u64,
-    topics: Arc<Vec<Topic>>,
     _profitable_topics: Arc<HashSet<Topic>>,
 }
 
@@ -28,7 +31,6 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlockWs<P> {
         pool_registry: Arc<PoolRegistry>,
         max_blocks_per_batch: u64,
     ) -> Self {
-        let topics = pool_registry.get_topics().await.clone();
         let profitable_topics = pool_registry.get_profitable_topics().await.clone();
         let network_id = pool_registry.get_network_id();
 
@@ -38,24 +40,32 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlockWs<P> {
             event_queue,
             pool_registry,
             max_blocks_per_batch,
-            topics: Arc::new(topics),
             _profitable_topics: Arc::new(profitable_topics),
         }
     }
 
     pub async fn start(&mut self) -> Result<()> {
+        let span = info_span!("pool_updater", chain_id = self.network_id);
+        // The head is polled for as long as the updater runs
+        let poll_head = poll_chain_head(self.provider.clone(), self.pool_registry.clone());
+        async {
+            tokio::select! {
+                result = self.run() => result,
+                () = poll_head => Ok(()),
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn run(&mut self) -> Result<()> {
+        let metrics = self.pool_registry.metrics();
+
         // Bootstrap
         let latest_block = self.provider.get_block_number().await?;
-        info!(
-            "CHAIN ID: {} Latest block: {}",
-            self.network_id, latest_block
-        );
-        let events = self.event_queue.get_all_available_events().await;
-        info!(
-            "CHAIN ID: {} Found {} events in EventQueue",
-            self.network_id,
-            events.len()
-        );
+        info!("Latest block: {}", latest_block);
+        let mut events = self.event_queue.get_all_available_events().await;
+        info!("Found {} events in EventQueue", events.len());
         let mut first_event_block = latest_block;
         let mut first_event_index = 0;
         let mut first_event_log_index = 0;
@@ -66,8 +76,8 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlockWs<P> {
             first_event_log_index = first_event.log_index.unwrap();
         }
         info!(
-            "CHAIN ID: {} First event block: {}; index: {}",
-            self.network_id, first_event_block, first_event_index
+            "First event block: {}; index: {}",
+            first_event_block, first_event_index
         );
 
         // Calculate block ranges to fetch in batches
@@ -75,69 +85,58 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlockWs<P> {
 
         let mut start_block = last_processed_block;
 
-        let topics = self.topics.clone().to_vec();
+        let topics = self.pool_registry.get_topics().await;
 
-        info!(
-            "CHAIN ID: {} Catching up to first event block {}",
-            self.network_id, first_event_block
-        );
+        info!("Catching up to first event block {}", first_event_block);
         while start_block < first_event_block {
             let end_block =
                 std::cmp::min(start_block + self.max_blocks_per_batch, first_event_block);
             let mut should_break = false;
+            let batch_span = info_span!(
+                "update_batch",
+                from_block = start_block,
+                to_block = end_block
+            );
             match fetch_events(
                 &self.provider,
-                self.pool_registry.get_all_addresses().await,
+                &self.pool_registry.get_all_addresses().await,
                 topics.clone(),
                 BlockNumberOrTag::Number(start_block),
                 BlockNumberOrTag::Number(end_block),
             )
+            .instrument(batch_span.clone())
             .await
             {
                 Ok(events) => {
                     info!(
-                        "CHAIN ID: {} Fetched {} events in batch {} - {}",
-                        self.network_id,
+                        "Fetched {} events in batch {} - {}",
                         events.len(),
                         start_block,
                         end_block
                     );
+                    let mut batch = Vec::with_capacity(events.len());
                     for event in events {
-                        if let Some(pool) = self.pool_registry.get_pool(&event.address()).await {
-                            if event.block_number.unwrap() >= first_event_block
-                                && event.transaction_index.unwrap() >= first_event_index
-                            {
-                                if event.log_index.unwrap() >= first_event_log_index {
-                                    // We've reached the first event, break
-                                    info!(
-                                        "CHAIN ID: {} Reached first event {} block {} index {}, breaking",
-                                        self.network_id,
-                                        event.transaction_hash.unwrap(),
-                                        event.block_number.unwrap(),
-                                        event.transaction_index.unwrap()
-                                    );
-                                    should_break = true;
-                                    break;
-                                }
-                            }
-
-                            if let Err(e) = pool.write().await.apply_log(&event) {
-                                error!(
-                                    "CHAIN ID: {} Error applying event {} for pool {}, event {}",
-                                    self.network_id,
-                                    e,
-                                    event.address(),
-                                    event.transaction_hash.unwrap()
-                                );
-                            }
+                        if event.block_number.unwrap() >= first_event_block
+                            && event.transaction_index.unwrap() >= first_event_index
+                            && event.log_index.unwrap() >= first_event_log_index
+                        {
+                            // We've reached the first event, break
+                            info!(
+                                "Reached first event {} block {} index {}, breaking",
+                                event.transaction_hash.unwrap(),
+                                event.block_number.unwrap(),
+                                event.transaction_index.unwrap()
+                            );
+                            should_break = true;
+                            break;
                         }
+
+                        batch.push(event);
                     }
+                    self.apply_events(&mut batch).instrument(batch_span).await;
 
                     if end_block >= first_event_block {
-                        info!(
-                            "CHAIN ID: {} Reached first event block {}, breaking",
-                            self.network_id, first_event_block
-                        );
+                        info!("Reached first event block {}, breaking", first_event_block);
                         should_break = true;
                     }
 
@@ -147,9 +146,13 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlockWs<P> {
                 }
                 Err(e) => {
                     error!(
-                        "CHAIN ID: {} Error fetching events in batch {}-{}: {}",
-                        self.network_id, start_block, end_block, e
+                        "Error fetching events in batch {}-{}: {}",
+                        start_block, end_block, e
                     );
+                    self.pool_registry.sync_status().record_error(format!(
+                        "Error fetching events in batch {}-{}: {}",
+                        start_block, end_block, e
+                    ));
                     continue;
                 }
             };
@@ -161,55 +164,41 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlockWs<P> {
             // Move to next batch
             start_block = end_block;
         }
-        for event in events {
-            if let Some(pool) = self.pool_registry.get_pool(&event.address()).await {
-                if let Err(e) = pool.write().await.apply_log(&event) {
-                    error!(
-                        "CHAIN ID: {} Error applying event {} for pool {}, event {}",
-                        self.network_id,
-                        e,
-                        event.address(),
-                        event.transaction_hash.unwrap()
-                    );
-                }
-            }
-        }
+        self.apply_events(&mut events).await;
 
         // Process events from EventQueue
         loop {
             // Get a batch of events from EventQueue
-            let events = self.event_queue.get_all_available_events().await;
+            if let Some(metrics) = &metrics {
+                metrics.set_event_queue_depth(self.network_id, self.event_queue.depth());
+            }
+            // Read before draining the queue, so every event of the block is already queued
+            let delivered_block = self.pool_registry.sync_status().delivered_block();
+            let mut events = self.event_queue.get_all_available_events().await;
             if events.is_empty() {
+                advance_to_delivered_block(&self.pool_registry, delivered_block).await;
                 // Small delay to prevent tight loop
                 tokio::time::sleep(Duration::from_millis(10)).await;
                 continue;
             }
             let events_len = events.len();
 
-            debug!(
-                "CHAIN ID: {} Processing {} events from EventQueue",
-                self.network_id, events_len
-            );
+            debug!("Processing {} events from EventQueue", events_len);
             // SKIP FOR NOW
             // let mut swap_events = Vec::new();
 
-            for event in events {
-                if let Some(pool) = self.pool_registry.get_pool(&event.address()).await {
-                    if let Err(e) = pool.write().await.apply_log(&event) {
-                        error!(
-                            "CHAIN ID: {} Error applying event {} for pool {}, event {}",
-                            self.network_id,
-                            e,
-                            event.address(),
-                            event.transaction_hash.unwrap()
-                        );
-                    }
-                    // SKIP FOR NOW
-                    // if self.profitable_topics.contains(event.topic0().unwrap()) {
-                    //     swap_events.push(event);
-                    // }
-                }
-            }
+            let batch_span = info_span!(
+                "update_batch",
+                from_block = events.first().and_then(|event| event.block_number),
+                to_block = events.last().and_then(|event| event.block_number)
+            );
+            self.apply_events(&mut events).instrument(batch_span).await;
+            // SKIP FOR NOW
+            // for event in &events {
+            //     if self.profitable_topics.contains(event.topic0().unwrap()) {
+            //         swap_events.push(event);
+            //     }
+            // }
 
             // SKIP FOR NOW
             // for event in swap_events {
@@ -231,11 +220,92 @@ impl<P: Provider + Send + Sync + 'static> PoolUpdaterLatestBlockWs<P> {
             //     }
             // }
 
-            info!(
-                "CHAIN ID: {} Processed {} events",
-                self.network_id, events_len
-            );
+            info!("Processed {} events", events_len);
             tokio::time::sleep(Duration::from_millis(10)).await;
         }
     }
+
+    /// Apply a batch of events as one snapshot, logging the ones that fail
+    async fn apply_events(&self, events: &mut [Log]) {
+        // Websocket logs come without block timestamps, candles need them
+        if self.pool_registry.candle_registry().is_some() {
+            fill_block_timestamps(&self.provider, events).await;
+        }
+        // Pools added live join between batches
+        let _updates = self.pool_registry.lock_updates().await;
+        let results = self.pool_registry.apply_logs(events).await;
+        for (event, result) in events.iter().zip(results) {
+            if let Err(e) = result {
+                error!(
+                    pool = %event.address(),
+                    tx = %event.transaction_hash.unwrap(),
+                    "Error applying event {}",
+                    e
+                );
+                self.pool_registry.sync_status().record_error(format!(
+                    "Error applying event {} for pool {}",
+                    e,
+                    event.address()
+                ));
+            }
+        }
+    }
+}
+
+/// With every queued event applied, the pool states are current up to the newest block the
+/// websocket feeds delivered in full. A feed that is connected but stalled delivers no blocks,
+/// so the chain falls behind its head.
+async fn advance_to_delivered_block(pool_registry: &PoolRegistry, delivered_block: Option<u64>) {
+    if let Some(block) = delivered_block {
+        if block > pool_registry.get_last_processed_block().await {
+            pool_registry.set_last_processed_block(block).await;
+        }
+    }
+}
+
+/// Records the chain head while the updater is fed by websockets
+async fn poll_chain_head<P: Provider + Send + Sync + 'static>(
+    provider: Arc<P>,
+    pool_registry: Arc<PoolRegistry>,
+) {
+    let mut interval = tokio::time::interval(HEAD_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        match provider.get_block_number().await {
+            Ok(head) => pool_registry.record_chain_head(head),
+            Err(e) => {
+                error!("Error fetching block number: {}", e);
+                pool_registry
+                    .sync_status()
+                    .record_error(format!("Error fetching block number: {}", e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_advance_to_delivered_block() {
+        let pool_registry = PoolRegistry::new(1);
+        pool_registry.set_last_processed_block(100).await;
+        pool_registry.record_chain_head(110);
+        let sync_status = pool_registry.sync_status();
+        sync_status.set_websocket_connected("eth.example.com", true);
+
+        // A connected feed that delivered nothing does not move the chain to its head
+        advance_to_delivered_block(&pool_registry, sync_status.delivered_block()).await;
+        assert_eq!(pool_registry.get_last_processed_block().await, 100);
+
+        sync_status.set_delivered_block(105);
+        advance_to_delivered_block(&pool_registry, sync_status.delivered_block()).await;
+        assert_eq!(pool_registry.get_last_processed_block().await, 105);
+        assert_eq!(pool_registry.snapshot().block_number(), 105);
+
+        // Never moves back
+        advance_to_delivered_block(&pool_registry, Some(103)).await;
+        assert_eq!(pool_registry.get_last_processed_block().await, 105);
+    }
 }
@@ -0,0 +1,86 @@
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket};
+use alloy::transports::{TransportError, TransportFut};
+use std::fmt;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower::Service;
+use tracing::{field, info_span, Instrument};
+use url::Url;
+
+use super::endpoint_host;
+use crate::utils::metrics::Metrics;
+
+/// Transport that records the latency and errors of every request sent to one RPC endpoint,
+/// and runs each request in an `rpc` span
+#[derive(Clone)]
+pub struct RpcMetricsService<S> {
+    inner: S,
+    endpoint: String,
+    metrics: Arc<Metrics>,
+}
+
+impl<S> RpcMetricsService<S> {
+    /// Wraps the transport of `url`. Requests are labelled with the URL's host only, so
+    /// API keys in the path never end up in metrics.
+    pub fn new(inner: S, url: &Url, metrics: Arc<Metrics>) -> Self {
+        Self {
+            inner,
+            endpoint: endpoint_host(url.as_str()),
+            metrics,
+        }
+    }
+}
+
+impl<S> fmt::Debug for RpcMetricsService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcMetricsService")
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+impl<S> Service<RequestPacket> for RpcMetricsService<S>
+where
+    S: Service<
+            RequestPacket,
+            Response = ResponsePacket,
+            Error = TransportError,
+            Future = TransportFut<'static>,
+        > + Send
+        + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        // Batches are named after their first method
+        let span = info_span!(
+            "rpc",
+            endpoint = %self.endpoint,
+            method = request.method_names().next().unwrap_or_default(),
+            requests = request.len(),
+            success = field::Empty,
+        );
+        let future = self.inner.call(request);
+        let endpoint = self.endpoint.clone();
+        let metrics = self.metrics.clone();
+        Box::pin(
+            async move {
+                let start = Instant::now();
+                let response = future.await;
+                // JSON-RPC errors arrive in successful responses and count as errors too
+                let success = response.as_ref().is_ok_and(|response| !response.is_error());
+                metrics.record_rpc_request(&endpoint, start.elapsed(), success);
+                tracing::Span::current().record("success", success);
+                response
+            }
+            .instrument(span),
+        )
+    }
+}
@@ -3,10 +3,24 @@ use alloy::primitives::{Address, FixedBytes};
 use alloy::providers::Provider;
 use alloy::rpc::types::{Filter, Log};
 use anyhow::Result;
+use futures_util::{stream, StreamExt};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
+use tracing::warn;
+use url::Url;
+
+/// Host of an RPC or websocket URL, used to name endpoints in metrics and health checks. API
+/// keys some providers carry in the path are left out.
+pub fn endpoint_host(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
 pub async fn fetch_events<P: Provider + Send + Sync>(
     provider: &Arc<P>,
-    addresses: Vec<Address>,
+    addresses: &[Address],
     topics: Vec<FixedBytes<32>>,
     from_block: BlockNumberOrTag,
     to_block: BlockNumberOrTag,
@@ -14,9 +28,51 @@ pub async fn fetch_events<P: Provider + Send + Sync>(
     let filter = Filter::new()
         .from_block(from_block)
         .to_block(to_block)
-        .address(addresses)
+        .address(addresses.to_vec())
         .event_signature(topics);
 
     let events = provider.get_logs(&filter).await?;
     Ok(events)
 }
+
+/// Most block headers requested at once by `fill_block_timestamps`
+const MAX_CONCURRENT_HEADER_REQUESTS: usize = 8;
+
+/// Set the block timestamp of logs the RPC returned without one, most nodes leave it out.
+/// Each block's header is fetched once. Only candles need the timestamps, so headers that fail
+/// to load are logged and their logs keep no timestamp.
+pub async fn fill_block_timestamps<P: Provider + Send + Sync>(provider: &Arc<P>, logs: &mut [Log]) {
+    let blocks: BTreeSet<u64> = logs
+        .iter()
+        .filter(|log| log.block_timestamp.is_none())
+        .filter_map(|log| log.block_number)
+        .collect();
+    if blocks.is_empty() {
+        return;
+    }
+
+    let timestamps: HashMap<u64, u64> = stream::iter(blocks)
+        .map(|block| async move {
+            let header = provider
+                .get_block_by_number(BlockNumberOrTag::Number(block))
+                .await;
+            (block, header)
+        })
+        .buffer_unordered(MAX_CONCURRENT_HEADER_REQUESTS)
+        .filter_map(|(block, header)| async move {
+            match header {
+                Ok(header) => Some((block, header?.header.timestamp)),
+                Err(e) => {
+                    warn!("Failed to fetch the header of block {}: {}", block, e);
+                    None
+                }
+            }
+        })
+        .collect()
+        .await;
+    for log in logs.iter_mut().filter(|log| log.block_timestamp.is_none()) {
+        log.block_timestamp = log
+            .block_number
+            .and_then(|block| timestamps.get(&block).copied());
+    }
+}
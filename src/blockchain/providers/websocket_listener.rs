@@ -1,80 +1,92 @@
 use crate::blockchain::event_queue::EventSender;
-use crate::models::pool::base::Topic;
-use alloy::primitives::Address;
+use crate::blockchain::providers::endpoint_host;
+use crate::models::pool::SyncStatus;
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::rpc::types::Filter;
+use alloy::pubsub::SubscriptionStream;
+use alloy::rpc::types::{Filter, Log};
 use alloy::transports::ws::WsConnect;
 use anyhow::{Context, Result};
-use futures_util::stream::StreamExt;
+use futures_util::{stream::StreamExt, FutureExt};
 use log::{debug, error, info, warn};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time::{interval, sleep, Duration, Instant, MissedTickBehavior};
+use tracing::{Instrument, Span};
 
 pub struct WebsocketListener {
     ws_url: String,
-    pool_addresses: Vec<Address>,
+    filter: watch::Receiver<Filter>, // Pools and events subscribed to, resubscribed on change
     event_sender: Arc<EventSender>,
     is_running: Arc<RwLock<bool>>,
     last_event_time: Arc<RwLock<Instant>>,
-    topics: Arc<RwLock<Vec<Topic>>>,
+    sync_status: Arc<SyncStatus>,
 }
 
 impl WebsocketListener {
     /// Creates a new WebSocket listener
     pub fn new(
         ws_url: String,
-        pool_addresses: Vec<Address>,
+        filter: watch::Receiver<Filter>,
         event_sender: Arc<EventSender>,
-        topics: Vec<Topic>,
+        sync_status: Arc<SyncStatus>,
     ) -> Self {
         Self {
             ws_url,
-            pool_addresses,
+            filter,
             event_sender,
             is_running: Arc::new(RwLock::new(false)),
             last_event_time: Arc::new(RwLock::new(Instant::now())),
-            topics: Arc::new(RwLock::new(topics)),
+            sync_status,
         }
     }
 
-    /// Starts the WebSocket listener in a background task
-    pub async fn start(&self) -> Result<()> {
+    /// Starts the WebSocket listener in a background task, aborting the task stops it
+    pub async fn start(&self) -> Result<JoinHandle<()>> {
         *self.is_running.write().await = true;
         info!("Starting WebSocket listener for {}", self.ws_url);
 
         let ws_url = self.ws_url.clone();
-        let pool_addresses = self.pool_addresses.clone();
+        let mut filter = self.filter.clone();
         let event_sender = Arc::clone(&self.event_sender);
         let is_running = Arc::clone(&self.is_running);
         let last_event_time = Arc::clone(&self.last_event_time);
-        let topics = self.topics.read().await.clone();
-
-        tokio::spawn(async move {
-            while *is_running.read().await {
-                match Self::connect_and_listen(
-                    &ws_url,
-                    &pool_addresses,
-                    &event_sender,
-                    &last_event_time,
-                    topics.clone(),
-                )
-                .await
-                {
-                    Ok(_) => {
-                        info!("WebSocket connection closed for {}", ws_url);
-                    }
-                    Err(e) => {
-                        error!("WebSocket connection error for {}: {}", ws_url, e);
+        let sync_status = Arc::clone(&self.sync_status);
+        let endpoint = endpoint_host(&ws_url);
+        sync_status.set_websocket_connected(&endpoint, false);
+
+        let handle = tokio::spawn(
+            async move {
+                while *is_running.read().await {
+                    match Self::connect_and_listen(
+                        &ws_url,
+                        &mut filter,
+                        &event_sender,
+                        &last_event_time,
+                        &sync_status,
+                        &endpoint,
+                    )
+                    .await
+                    {
+                        Ok(_) => {
+                            info!("WebSocket connection closed for {}", ws_url);
+                        }
+                        Err(e) => {
+                            error!("WebSocket connection error for {}: {}", ws_url, e);
+                            sync_status
+                                .record_error(format!("WebSocket error for {}: {:#}", endpoint, e));
+                        }
                     }
-                }
+                    sync_status.set_websocket_connected(&endpoint, false);
 
-                sleep(Duration::from_secs(2)).await;
-                info!("Attempting to reconnect to WebSocket at {}", ws_url);
+                    sleep(Duration::from_secs(2)).await;
+                    info!("Attempting to reconnect to WebSocket at {}", ws_url);
+                }
             }
-        });
+            .instrument(Span::current()),
+        );
 
-        Ok(())
+        Ok(handle)
     }
 
     /// Stops the WebSocket listener
@@ -84,13 +96,15 @@ impl WebsocketListener {
         Ok(())
     }
 
-    /// Connects to the WebSocket, subscribes, and listens for events
+    /// Connects to the WebSocket, subscribes, and listens for events until the connection
+    /// ends or the heartbeat gives up on it
     async fn connect_and_listen(
         ws_url: &str,
-        pool_addresses: &[Address],
+        filter: &mut watch::Receiver<Filter>,
         event_sender: &Arc<EventSender>,
         last_event_time: &Arc<RwLock<Instant>>,
-        topics: Vec<Topic>,
+        sync_status: &SyncStatus,
+        endpoint: &str,
     ) -> Result<()> {
         // Connect to the WebSocket
         let ws_connect = WsConnect::new(ws_url);
@@ -102,87 +116,139 @@ impl WebsocketListener {
         info!("Connected to WebSocket at {}", ws_url);
 
         // Subscribe to logs (starts from current block)
+        let current_filter = filter.borrow_and_update().clone();
+        let mut stream = Self::subscribe(&ws_provider, &current_filter, ws_url).await?;
+        // A block's logs are sent before the next block's header, so each header confirms
+        // that the block before it was delivered in full
+        let mut heads = ws_provider
+            .subscribe_blocks()
+            .await
+            .context("Failed to subscribe to new heads")?
+            .into_stream();
+        sync_status.set_websocket_connected(endpoint, true);
+
+        let heartbeat = Self::heartbeat(&ws_provider, last_event_time, ws_url);
+        tokio::pin!(heartbeat);
+
+        // Position of the last log sent, logs the new subscription repeats after a
+        // resubscription are skipped up to it
+        let mut last_sent: Option<(u64, u64)> = None;
+        let mut skip_until: Option<(u64, u64)> = None;
+        loop {
+            tokio::select! {
+                log = stream.next() => {
+                    let Some(log) = log else {
+                        break;
+                    };
+                    let position = log.block_number.zip(log.log_index);
+                    if let (Some(skip_until), Some(position)) = (skip_until, position) {
+                        if position <= skip_until {
+                            continue;
+                        }
+                    }
+                    skip_until = None;
+                    last_sent = position.or(last_sent);
+                    Self::forward(log, event_sender, last_event_time).await;
+                }
+                header = heads.next() => {
+                    let Some(header) = header else {
+                        break;
+                    };
+                    sync_status.set_delivered_block(header.number.saturating_sub(1));
+                }
+                Ok(()) = filter.changed() => {
+                    // The new subscription is open before the old one is dropped, so no
+                    // block falls between them
+                    let current_filter = filter.borrow_and_update().clone();
+                    let next = Self::subscribe(&ws_provider, &current_filter, ws_url).await?;
+                    while let Some(Some(log)) = stream.next().now_or_never() {
+                        last_sent = log.block_number.zip(log.log_index).or(last_sent);
+                        Self::forward(log, event_sender, last_event_time).await;
+                    }
+                    stream = next;
+                    skip_until = last_sent;
+                }
+                () = &mut heartbeat => break,
+            }
+        }
 
-        let filter = Filter::new()
-            .address(pool_addresses.to_vec())
-            .event_signature(topics);
+        info!("WebSocket subscription ended for {}", ws_url);
+        Ok(())
+    }
 
+    async fn subscribe(
+        ws_provider: &impl Provider,
+        filter: &Filter,
+        ws_url: &str,
+    ) -> Result<SubscriptionStream<Log>> {
         let subscription = ws_provider
-            .subscribe_logs(&filter)
+            .subscribe_logs(filter)
             .await
             .context("Failed to subscribe to logs")?;
 
         info!(
             "Subscribed to logs for {} pool addresses at {}",
-            pool_addresses.len(),
+            filter.address.len(),
             ws_url
         );
+        Ok(subscription.into_stream())
+    }
 
-        // Start pinging and stall detection task
-        let provider_clone = ws_provider.clone();
-        let is_running = Arc::new(RwLock::new(true));
-        let ping_running = Arc::clone(&is_running);
-        let last_event_time_clone = Arc::clone(last_event_time);
-        let ws_url_clone = ws_url.to_string();
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(30));
-            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
-            let mut ping_failures = 0;
-            const MAX_PING_FAILURES: u32 = 3;
-
-            while *ping_running.read().await {
-                interval.tick().await;
-
-                if last_event_time_clone.read().await.elapsed() > Duration::from_secs(180) {
-                    warn!(
-                        "No events received for 180 seconds at {}; forcing reconnect",
-                        ws_url_clone
-                    );
-                    break;
-                }
-
-                match provider_clone.get_block_number().await {
-                    Ok(_) => {
-                        ping_failures = 0;
-                        debug!("Sent heartbeat ping for {}", ws_url_clone);
-                    }
-                    Err(e) => {
-                        ping_failures += 1;
-                        error!("Ping failed for {}: {}", ws_url_clone, e);
-                        if ping_failures >= MAX_PING_FAILURES {
-                            warn!(
-                                "Max ping failures ({}) reached for {}; forcing reconnect",
-                                MAX_PING_FAILURES, ws_url_clone
-                            );
-                            break;
-                        }
-                    }
-                }
-            }
+    /// Queues a received log for the updater
+    async fn forward(log: Log, event_sender: &EventSender, last_event_time: &RwLock<Instant>) {
+        debug!(
+            "Received log: address={}, topics={:?}",
+            log.address(),
+            log.topics()
+        );
 
-            *ping_running.write().await = false;
-        });
+        // Update last event time
+        *last_event_time.write().await = Instant::now();
 
-        // Process WebSocket events
-        let mut stream = subscription.into_stream();
-        while let Some(log) = stream.next().await {
-            debug!(
-                "Received log: address={}, topics={:?}",
-                log.address(),
-                log.topics()
-            );
+        if let Err(e) = event_sender.send(log).await {
+            error!("Failed to send event to queue: {}", e);
+        }
+    }
 
-            // Update last event time
-            *last_event_time.write().await = Instant::now();
+    /// Pings the node and detects stalls, returns once the connection should be replaced
+    async fn heartbeat(
+        ws_provider: &impl Provider,
+        last_event_time: &RwLock<Instant>,
+        ws_url: &str,
+    ) {
+        let mut interval = interval(Duration::from_secs(30));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut ping_failures = 0;
+        const MAX_PING_FAILURES: u32 = 3;
+
+        loop {
+            interval.tick().await;
+
+            if last_event_time.read().await.elapsed() > Duration::from_secs(180) {
+                warn!(
+                    "No events received for 180 seconds at {}; forcing reconnect",
+                    ws_url
+                );
+                return;
+            }
 
-            if let Err(e) = event_sender.send(log).await {
-                error!("Failed to send event to queue: {}", e);
+            match ws_provider.get_block_number().await {
+                Ok(_) => {
+                    ping_failures = 0;
+                    debug!("Sent heartbeat ping for {}", ws_url);
+                }
+                Err(e) => {
+                    ping_failures += 1;
+                    error!("Ping failed for {}: {}", ws_url, e);
+                    if ping_failures >= MAX_PING_FAILURES {
+                        warn!(
+                            "Max ping failures ({}) reached for {}; forcing reconnect",
+                            MAX_PING_FAILURES, ws_url
+                        );
+                        return;
+                    }
+                }
             }
         }
-
-        // Stop pinging task
-        *is_running.write().await = false;
-        info!("WebSocket subscription ended for {}", ws_url);
-        Ok(())
     }
 }
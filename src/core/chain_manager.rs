@@ -0,0 +1,972 @@
+use alloy::eips::{BlockId, BlockNumberOrTag};
+use alloy::primitives::Address;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder, MULTICALL3_ADDRESS};
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::types::Filter;
+use alloy::transports::http::Http;
+use alloy::transports::layers::FallbackLayer;
+use anyhow::Result;
+use futures_util::future::join_all;
+use log::{error, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tower::ServiceBuilder;
+use tracing::{info_span, Instrument};
+use url::Url;
+
+use crate::blockchain::pool_fetcher::{fetch_and_display_pool_info, fetch_pool};
+use crate::blockchain::{
+    endpoint_host, EventQueue, NetworkConfigurator, PoolUpdaterLatestBlock,
+    PoolUpdaterLatestBlockWs, RpcMetricsService, WebsocketListener,
+};
+use crate::core::Database;
+use crate::models::candle::CandleRegistry;
+use crate::models::gas::GasRegistry;
+use crate::models::pool::base::Topic;
+use crate::models::pool::multichain_registry::MultichainPoolRegistry;
+use crate::models::pool::{PoolRegistry, PoolType};
+use crate::models::price::{MultichainPriceRegistry, PriceRegistry};
+use crate::models::profit_token::price_updater::PriceUpdater;
+use crate::models::profit_token::ProfitTokenRegistry;
+use crate::models::router::{Router, RouterRegistry};
+use crate::models::token::{MultichainTokenRegistry, TokenRegistry};
+use crate::utils::config::{AppConfig, CandleConfig, ChainConfigs, PoolConfig, PricingConfig};
+use crate::utils::errors::ArbitrageError;
+use crate::utils::metrics::Metrics;
+
+/// Pools to add to and remove from a running chain
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolDiff {
+    pub added: Vec<PoolConfig>,
+    pub removed: Vec<Address>, // Removed before the additions, a pool whose type changed is in both
+}
+
+impl PoolDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Changes bringing the running chains in line with a new config. A running chain is matched by
+/// a configured chain with the same settings apart from its pools, any other change restarts it.
+#[derive(Debug, Default)]
+pub struct ChainDiff {
+    pub unchanged: Vec<u64>,
+    pub updated: Vec<(u64, PoolDiff)>,
+    pub stopped: Vec<u64>,
+    pub started: Vec<ChainConfigs>,
+}
+
+/// Diff the configs of the running chains against the configured chains
+pub fn diff_chains(
+    running: &HashMap<u64, ChainConfigs>,
+    configured: Vec<ChainConfigs>,
+) -> ChainDiff {
+    let mut diff = ChainDiff::default();
+    let mut unmatched: Vec<u64> = running.keys().copied().collect();
+    unmatched.sort_unstable();
+
+    for config in configured {
+        let matched = unmatched
+            .iter()
+            .position(|chain_id| same_settings(&running[chain_id], &config));
+        match matched {
+            Some(index) => {
+                let chain_id = unmatched.remove(index);
+                let pools = diff_pools(&running[&chain_id].pools, &config.pools);
+                if pools.is_empty() {
+                    diff.unchanged.push(chain_id);
+                } else {
+                    diff.updated.push((chain_id, pools));
+                }
+            }
+            None => diff.started.push(config),
+        }
+    }
+    diff.stopped = unmatched;
+    diff
+}
+
+fn same_settings(a: &ChainConfigs, b: &ChainConfigs) -> bool {
+    let without_pools = |config: &ChainConfigs| ChainConfigs {
+        pools: Vec::new(),
+        ..config.clone()
+    };
+    without_pools(a) == without_pools(b)
+}
+
+/// Pools are compared by address and type, configs are validated so their addresses parse
+fn diff_pools(running: &[PoolConfig], configured: &[PoolConfig]) -> PoolDiff {
+    let key = |pool: &PoolConfig| {
+        pool.address
+            .parse::<Address>()
+            .ok()
+            .map(|address| (address, pool.pool_type))
+    };
+    let running_keys: HashSet<_> = running.iter().filter_map(key).collect();
+    let configured_keys: HashSet<_> = configured.iter().filter_map(key).collect();
+    PoolDiff {
+        added: configured
+            .iter()
+            .filter(|pool| key(pool).is_some_and(|key| !running_keys.contains(&key)))
+            .cloned()
+            .collect(),
+        removed: running
+            .iter()
+            .filter_map(key)
+            .filter(|key| !configured_keys.contains(key))
+            .map(|(address, _)| address)
+            .collect(),
+    }
+}
+
+/// Pools added to and removed from a running chain by a reload
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChainUpdate {
+    pub network_id: u64,
+    pub pools_added: Vec<Address>,
+    pub pools_removed: Vec<Address>,
+}
+
+/// Outcome of a reload. Chains whose settings changed are both stopped and started.
+#[derive(Debug, Clone, Default)]
+pub struct ReloadReport {
+    pub started: Vec<u64>,
+    pub stopped: Vec<u64>,
+    pub updated: Vec<ChainUpdate>,
+    pub unchanged: Vec<u64>,
+    pub errors: Vec<String>, // Chains that failed to start and pools that failed to be added
+}
+
+/// A chain being indexed. Dropping it aborts its background tasks.
+struct RunningChain {
+    config: ChainConfigs,
+    provider: Arc<DynProvider>,
+    pool_registry: Arc<PoolRegistry>,
+    token_registry: Arc<RwLock<TokenRegistry>>,
+    multicall_address: Address,
+    filter: Option<watch::Sender<Filter>>, // Subscription of the websocket listeners
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for RunningChain {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl RunningChain {
+    /// Stop the background tasks, then save the candles still in memory. Closed candles are
+    /// saved as they close.
+    async fn stop(self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        if let Some(candle_registry) = self.pool_registry.candle_registry() {
+            if let Err(e) = candle_registry.save_to_db().await {
+                error!(
+                    "Error saving candles for chain {}: {}",
+                    self.pool_registry.get_network_id(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Point the websocket listeners at `addresses`, they resubscribe without reconnecting
+    fn subscribe(&self, addresses: &[Address], topics: Vec<Topic>) {
+        if let Some(filter) = &self.filter {
+            filter.send_replace(
+                Filter::new()
+                    .address(addresses.to_vec())
+                    .event_signature(topics),
+            );
+        }
+    }
+
+    /// Remove pools from the registry and then from the websocket subscription
+    async fn remove_pools(&self, addresses: &[Address]) -> Vec<Address> {
+        let mut removed = Vec::new();
+        for address in addresses {
+            if self.pool_registry.remove_pool(*address).await.is_some() {
+                removed.push(*address);
+            }
+        }
+        self.subscribe(
+            &self.pool_registry.get_all_addresses().await,
+            self.pool_registry.get_topics().await,
+        );
+        removed
+    }
+
+    /// Fetch pools at the current head and add them while the updater waits, so they join at a
+    /// batch boundary. Returns the pools added and an error for each one that failed.
+    async fn add_pools(&self, pools: &[PoolConfig]) -> (Vec<Address>, Vec<String>) {
+        let network_id = self.pool_registry.get_network_id();
+        let mut added = Vec::new();
+        let mut errors = Vec::new();
+        let mut new_pools = Vec::new();
+        for pool in pools {
+            match pool.address.parse::<Address>() {
+                Ok(address) if self.pool_registry.get_pool(&address).await.is_some() => {
+                    // Already indexed, e.g. loaded from the snapshot
+                    added.push(address)
+                }
+                Ok(address) => new_pools.push((address, pool.pool_type)),
+                Err(e) => errors.push(format!(
+                    "Invalid pool address {} on chain {}: {}",
+                    pool.address, network_id, e
+                )),
+            }
+        }
+        if new_pools.is_empty() {
+            return (added, errors);
+        }
+
+        // Listeners subscribe to the new pools first, their events queue up while the states
+        // are fetched and the ones up to the fetched block are skipped
+        let mut addresses = self.pool_registry.get_all_addresses().await.to_vec();
+        let mut topics = self.pool_registry.get_topics().await;
+        for (address, pool_type) in &new_pools {
+            addresses.push(*address);
+            topics.extend(
+                pool_type
+                    .topics()
+                    .into_iter()
+                    .filter(|topic| !topics.contains(topic))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        self.subscribe(&addresses, topics);
+
+        let updates = self.pool_registry.lock_updates().await;
+        match self.provider.get_block_number().await {
+            Ok(block_number) => {
+                for (address, pool_type) in new_pools {
+                    match self.fetch_pool(address, pool_type, block_number).await {
+                        Ok(()) => added.push(address),
+                        Err(e) => errors.push(format!(
+                            "Failed to add pool {} on chain {}: {:#}",
+                            address, network_id, e
+                        )),
+                    }
+                }
+            }
+            Err(e) => errors.push(format!(
+                "Failed to add pools on chain {}: {}",
+                network_id, e
+            )),
+        }
+        drop(updates);
+
+        // Drop the pools that failed from the subscription
+        self.subscribe(
+            &self.pool_registry.get_all_addresses().await,
+            self.pool_registry.get_topics().await,
+        );
+        (added, errors)
+    }
+
+    async fn fetch_pool(
+        &self,
+        address: Address,
+        pool_type: PoolType,
+        block_number: u64,
+    ) -> Result<()> {
+        let pool = fetch_pool(
+            &self.provider,
+            address,
+            BlockId::from(block_number),
+            pool_type,
+            &self.token_registry,
+            self.multicall_address,
+        )
+        .await?;
+        self.pool_registry.add_topics(pool_type.topics()).await;
+        self.pool_registry
+            .add_profitable_topics(pool_type.profitable_topics())
+            .await;
+        self.pool_registry.add_pool_at(pool, block_number).await;
+        Ok(())
+    }
+}
+
+/// Starts the configured chains and reloads them from the config files. Each chain gets its own
+/// registries, updater and background tasks; a reload only touches the chains that changed.
+pub struct ChainManager {
+    pool_registries: Arc<MultichainPoolRegistry>,
+    token_registries: Arc<MultichainTokenRegistry>,
+    price_registries: Arc<MultichainPriceRegistry>,
+    router_registry: Arc<RouterRegistry>,
+    gas_registry: Arc<GasRegistry>,
+    profit_token_registries: RwLock<HashMap<u64, Arc<ProfitTokenRegistry>>>, // Chains with profit tokens
+    metrics: Arc<Metrics>,
+    db: Option<Database>,
+    load_snapshot: bool, // Load pools from the database instead of fetching them
+    candles: CandleConfig,
+    pricing: PricingConfig,
+    config_paths: Vec<PathBuf>, // Config files a reload reads, in layering order
+    chains: Mutex<HashMap<u64, RunningChain>>, // Also serializes reloads
+}
+
+impl ChainManager {
+    pub fn new(candles: CandleConfig, pricing: PricingConfig, metrics: Arc<Metrics>) -> Self {
+        Self {
+            pool_registries: Arc::new(MultichainPoolRegistry::new()),
+            token_registries: Arc::new(MultichainTokenRegistry::new()),
+            price_registries: Arc::new(MultichainPriceRegistry::new()),
+            router_registry: Arc::new(RouterRegistry::new()),
+            gas_registry: Arc::new(GasRegistry::new()),
+            profit_token_registries: RwLock::new(HashMap::new()),
+            metrics,
+            db: None,
+            load_snapshot: false,
+            candles,
+            pricing,
+            config_paths: Vec::new(),
+            chains: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Save fetched pools to `db`, or load them from it when `load_snapshot` is set
+    pub fn set_database(&mut self, db: Database, load_snapshot: bool) {
+        self.db = Some(db);
+        self.load_snapshot = load_snapshot;
+    }
+
+    pub fn set_config_paths(&mut self, config_paths: Vec<PathBuf>) {
+        self.config_paths = config_paths;
+    }
+
+    pub fn pool_registries(&self) -> Arc<MultichainPoolRegistry> {
+        self.pool_registries.clone()
+    }
+
+    pub fn token_registries(&self) -> Arc<MultichainTokenRegistry> {
+        self.token_registries.clone()
+    }
+
+    pub fn price_registries(&self) -> Arc<MultichainPriceRegistry> {
+        self.price_registries.clone()
+    }
+
+    pub fn router_registry(&self) -> Arc<RouterRegistry> {
+        self.router_registry.clone()
+    }
+
+    pub fn gas_registry(&self) -> Arc<GasRegistry> {
+        self.gas_registry.clone()
+    }
+
+    /// Profit tokens of a chain, `None` when the chain configures none
+    pub async fn profit_token_registry(&self, chain_id: u64) -> Option<Arc<ProfitTokenRegistry>> {
+        self.profit_token_registries
+            .read()
+            .await
+            .get(&chain_id)
+            .cloned()
+    }
+
+    /// Ids of the chains being indexed
+    pub async fn running_chains(&self) -> Vec<u64> {
+        let mut chain_ids: Vec<u64> = self.chains.lock().await.keys().copied().collect();
+        chain_ids.sort_unstable();
+        chain_ids
+    }
+
+    /// Start chains concurrently for a faster startup, failing if any of them fails
+    pub async fn start_chains(&self, configs: Vec<ChainConfigs>) -> Result<()> {
+        let mut chains = self.chains.lock().await;
+        let connected = join_all(configs.into_iter().map(|config| async {
+            let (provider, chain_id) = self.connect(&config).await?;
+            Ok::<_, anyhow::Error>((config, provider, chain_id))
+        }))
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+        let mut chain_ids: HashSet<u64> = chains.keys().copied().collect();
+        for (_, _, chain_id) in &connected {
+            if !chain_ids.insert(*chain_id) {
+                return Err(ArbitrageError::ConfigError(format!(
+                    "chain {} is configured twice",
+                    chain_id
+                ))
+                .into());
+            }
+        }
+
+        let started = join_all(
+            connected
+                .into_iter()
+                .map(|(config, provider, chain_id)| self.launch(config, provider, chain_id)),
+        )
+        .await;
+        for chain in started {
+            let chain = chain?;
+            chains.insert(chain.pool_registry.get_network_id(), chain);
+        }
+        Ok(())
+    }
+
+    /// Stop every chain, saving their candles
+    pub async fn shutdown(&self) {
+        let mut chains = self.chains.lock().await;
+        for (chain_id, chain) in chains.drain() {
+            chain.stop().instrument(info_span!("chain", chain_id)).await;
+            info!("Stopped chain {}", chain_id);
+        }
+    }
+
+    /// Read the config files again and apply their chains
+    pub async fn reload(&self) -> Result<ReloadReport> {
+        info!("Reloading chains from {:?}", self.config_paths);
+        let config = AppConfig::load(&self.config_paths)?;
+        Ok(self.apply(config.chain_configs).await)
+    }
+
+    /// Start, stop and update the running chains to match `configs`. Chains that are not in the
+    /// diff keep running undisturbed.
+    pub async fn apply(&self, configs: Vec<ChainConfigs>) -> ReloadReport {
+        let mut chains = self.chains.lock().await;
+        let running = chains
+            .iter()
+            .map(|(chain_id, chain)| (*chain_id, chain.config.clone()))
+            .collect();
+        let diff = diff_chains(&running, configs);
+        let mut report = ReloadReport {
+            unchanged: diff.unchanged,
+            ..Default::default()
+        };
+
+        for chain_id in diff.stopped {
+            if let Some(chain) = chains.remove(&chain_id) {
+                chain.stop().instrument(info_span!("chain", chain_id)).await;
+                self.remove_registries(chain_id).await;
+                info!("Stopped chain {}", chain_id);
+                report.stopped.push(chain_id);
+            }
+        }
+
+        for (chain_id, pools) in diff.updated {
+            let Some(chain) = chains.get_mut(&chain_id) else {
+                continue;
+            };
+            let span = info_span!("chain", chain_id);
+            let pools_removed = chain
+                .remove_pools(&pools.removed)
+                .instrument(span.clone())
+                .await;
+            let (pools_added, errors) = chain.add_pools(&pools.added).instrument(span).await;
+            info!(
+                "Updated chain {}: {} pools added, {} removed",
+                chain_id,
+                pools_added.len(),
+                pools_removed.len()
+            );
+            // Pools that failed are retried by the next reload
+            chain.config.pools.retain(|pool| {
+                pool.address
+                    .parse::<Address>()
+                    .is_ok_and(|address| !pools.removed.contains(&address))
+            });
+            chain
+                .config
+                .pools
+                .extend(pools.added.into_iter().filter(|pool| {
+                    pool.address
+                        .parse::<Address>()
+                        .is_ok_and(|address| pools_added.contains(&address))
+                }));
+            report.errors.extend(errors);
+            report.updated.push(ChainUpdate {
+                network_id: chain_id,
+                pools_added,
+                pools_removed,
+            });
+        }
+
+        for config in diff.started {
+            let first_rpc = config.rpc_urls.first().cloned().unwrap_or_default();
+            let chain = match self.connect(&config).await {
+                Ok((_, chain_id)) if chains.contains_key(&chain_id) => Err(
+                    ArbitrageError::ConfigError(format!("chain {} is configured twice", chain_id))
+                        .into(),
+                ),
+                Ok((provider, chain_id)) => self.launch(config, provider, chain_id).await,
+                Err(e) => Err(e),
+            };
+            match chain {
+                Ok(chain) => {
+                    let chain_id = chain.pool_registry.get_network_id();
+                    info!("Started chain {}", chain_id);
+                    report.started.push(chain_id);
+                    chains.insert(chain_id, chain);
+                }
+                Err(e) => {
+                    let message = format!(
+                        "Failed to start chain {}: {:#}",
+                        endpoint_host(&first_rpc),
+                        e
+                    );
+                    error!("{}", message);
+                    report.errors.push(message);
+                }
+            }
+        }
+
+        if !report.errors.is_empty() {
+            warn!("Reload finished with {} errors", report.errors.len());
+        }
+        report
+    }
+
+    /// Connect to the chain's RPC endpoints and read its id
+    async fn connect(&self, config: &ChainConfigs) -> Result<(Arc<DynProvider>, u64)> {
+        let rpc_len = config.rpc_urls.len();
+        let fallback_layer = FallbackLayer::default()
+            .with_active_transport_count(NonZeroUsize::new(rpc_len).unwrap());
+
+        let transports = config
+            .rpc_urls
+            .iter()
+            .map(|url| {
+                let url = Url::parse(url)?;
+                Ok(RpcMetricsService::new(
+                    Http::new(url.clone()),
+                    &url,
+                    self.metrics.clone(),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let transport = ServiceBuilder::new()
+            .layer(fallback_layer)
+            .service(transports);
+        let client = RpcClient::builder().transport(transport, false);
+        let provider = Arc::new(ProviderBuilder::new().connect_client(client).erased());
+        let chain_id = provider.get_chain_id().await?;
+
+        info!("Connected to chain with ID: {}", chain_id);
+        Ok((provider, chain_id))
+    }
+
+    /// Create the chain's registries, fetch or load its pools and start its updater. If this
+    /// fails, the tasks already started are aborted and the registries removed again.
+    async fn launch(
+        &self,
+        config: ChainConfigs,
+        provider: Arc<DynProvider>,
+        chain_id: u64,
+    ) -> Result<RunningChain> {
+        let result = self
+            .launch_chain(config, provider, chain_id)
+            .instrument(info_span!("chain", chain_id))
+            .await;
+        if result.is_err() {
+            self.remove_registries(chain_id).await;
+        }
+        result
+    }
+
+    async fn launch_chain(
+        &self,
+        chain_config: ChainConfigs,
+        provider: Arc<DynProvider>,
+        chain_id: u64,
+    ) -> Result<RunningChain> {
+        info!("Initializing chain {}...", chain_id);
+
+        // 1. Create registries for this chain
+        let candle_registry = Arc::new(CandleRegistry::new(
+            chain_id,
+            self.candles.intervals.clone(),
+            self.candles.max_candles,
+            self.db.clone(),
+        ));
+        let mut pool_registry = PoolRegistry::new(chain_id);
+        pool_registry.set_candle_registry(candle_registry);
+        pool_registry.set_metrics(self.metrics.clone());
+        pool_registry.set_max_sync_lag(chain_config.max_sync_lag);
+        let pool_registry = Arc::new(pool_registry);
+        let token_registry = Arc::new(RwLock::new(TokenRegistry::new(chain_id)));
+        let stablecoins = chain_config
+            .stablecoins
+            .iter()
+            .map(|addr| addr.parse::<Address>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let price_registry = Arc::new(PriceRegistry::new(
+            chain_id,
+            stablecoins,
+            self.pricing.min_liquidity_usd,
+            self.pricing.max_hops,
+        ));
+        let multicall_address = match chain_config.custom_multicall_address.as_ref() {
+            Some(addr) => addr.parse::<Address>()?,
+            None => MULTICALL3_ADDRESS,
+        };
+        let mut chain = RunningChain {
+            config: chain_config.clone(),
+            provider: provider.clone(),
+            pool_registry: pool_registry.clone(),
+            token_registry: token_registry.clone(),
+            multicall_address,
+            filter: None,
+            tasks: Vec::new(),
+        };
+
+        // 2. Add to multichain registries
+        self.pool_registries
+            .add_pool_registry(chain_id, pool_registry.clone())
+            .await;
+        self.token_registries
+            .add_token_registry(chain_id, token_registry.clone())
+            .await;
+        self.price_registries
+            .add_price_registry(chain_id, price_registry.clone())
+            .await;
+        let routers = chain_config
+            .routers
+            .iter()
+            .map(|router| {
+                Ok(Router {
+                    address: router.address.parse::<Address>()?,
+                    router_type: router.router_type,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.router_registry.add_routers(chain_id, routers).await;
+
+        // Gas prices are refreshed in the background and used to rank routes
+        let wrap_native = chain_config
+            .wrap_native
+            .as_ref()
+            .map(|addr| addr.parse::<Address>())
+            .transpose()?;
+        let network_configurator = Arc::new(NetworkConfigurator::new(
+            provider.clone(),
+            Duration::from_secs(self.pricing.gas_update_interval),
+        ));
+        match network_configurator.start().await {
+            Ok(task) => chain.tasks.push(task),
+            Err(e) => error!(
+                "Failed to start network configurator for chain {}: {}",
+                chain_id, e
+            ),
+        }
+        self.gas_registry
+            .add_network(chain_id, network_configurator, wrap_native)
+            .await;
+
+        // 3. Load pools from database if available and if load_snapshot is enabled
+        if self.load_snapshot {
+            if let Some(ref db) = self.db {
+                info!("Loading pools from database for chain {}...", chain_id);
+                if let Err(e) = pool_registry.load_from_db(db).await {
+                    error!(
+                        "Error loading pools from database for chain {}: {}",
+                        chain_id, e
+                    );
+                }
+                if let Err(e) = token_registry.write().await.load_from_db(db).await {
+                    error!(
+                        "Error loading tokens from database for chain {}: {}",
+                        chain_id, e
+                    );
+                }
+            }
+        } else {
+            info!(
+                "Snapshot loading disabled for chain {}, starting with empty pool registry",
+                chain_id
+            );
+            // 4. Fetch pool information at the current block
+            let start_block = provider.get_block_number().await?;
+            info!("Starting block for chain {}: {}", chain_id, start_block);
+            info!("Fetching pool information from chain {}...", chain_id);
+            fetch_and_display_pool_info(
+                &provider,
+                &chain_config
+                    .pools
+                    .iter()
+                    .map(|p| p.address.clone())
+                    .collect(),
+                BlockNumberOrTag::Number(start_block),
+                &token_registry,
+                &pool_registry,
+                chain_config.wait_time_for_startup,
+                multicall_address,
+            )
+            .await?;
+
+            // 5. Save pool registry to database
+            if let Some(db) = &self.db {
+                info!(
+                    "Saving pool registry to database for chain {} at block: {}",
+                    chain_id,
+                    pool_registry.get_last_processed_block().await
+                );
+                pool_registry.save_to_db(db).await?;
+                token_registry.write().await.save_to_db(db).await?;
+            }
+        }
+
+        // 6. Start price registry
+        chain.tasks.push(price_registry.start(
+            pool_registry.clone(),
+            token_registry.clone(),
+            self.pricing.update_interval,
+        ));
+
+        // Profit token prices come from the sources configured for the chain
+        if !chain_config.profit_tokens.is_empty() {
+            let price_updater = PriceUpdater::from_config(
+                &chain_config.price_sources,
+                provider.clone(),
+                multicall_address,
+                price_registry.clone(),
+            )
+            .await?;
+            let profit_token_registry = Arc::new(ProfitTokenRegistry::new(
+                wrap_native.unwrap_or_default(),
+                token_registry.clone(),
+                Arc::new(RwLock::new(price_updater)),
+                chain_config.min_profit_usd,
+            ));
+            for token in chain_config.profit_tokens.iter().cloned() {
+                profit_token_registry.add_token_from_config(token).await;
+            }
+            chain.tasks.push(profit_token_registry.start());
+            self.profit_token_registries
+                .write()
+                .await
+                .insert(chain_id, profit_token_registry);
+        }
+
+        // 7. Start pool updater
+        if chain_config.use_websocket {
+            info!(
+                "Starting pool updater with websocket for chain {}",
+                chain_id
+            );
+            let event_queue = EventQueue::new(1000, 1000);
+            let (filter, _) = watch::channel(
+                Filter::new()
+                    .address(pool_registry.get_all_addresses().await.to_vec())
+                    .event_signature(pool_registry.get_topics().await),
+            );
+
+            for url in chain_config.websocket_urls {
+                let span = info_span!(
+                    "websocket_listener",
+                    chain_id,
+                    endpoint = %endpoint_host(&url)
+                );
+                let ws = WebsocketListener::new(
+                    url,
+                    filter.subscribe(),
+                    event_queue.get_sender().clone(),
+                    pool_registry.sync_status(),
+                );
+                match ws.start().instrument(span).await {
+                    Ok(task) => chain.tasks.push(task),
+                    Err(e) => error!("Websocket listener error for chain {}: {}", chain_id, e),
+                }
+            }
+            chain.filter = Some(filter);
+
+            let mut pool_updater = PoolUpdaterLatestBlockWs::new(
+                Arc::clone(&provider),
+                event_queue,
+                pool_registry.clone(),
+                chain_config.max_blocks_per_batch,
+            )
+            .await;
+            chain.tasks.push(tokio::spawn(async move {
+                if let Err(e) = pool_updater.start().await {
+                    error!("Pool updater error for chain {}: {}", chain_id, e);
+                }
+            }));
+        } else {
+            info!(
+                "Starting pool updater with latest block for chain {}",
+                chain_id
+            );
+            let mut pool_updater = PoolUpdaterLatestBlock::new(
+                Arc::clone(&provider),
+                pool_registry.clone(),
+                pool_registry.get_last_processed_block().await,
+                chain_config.max_blocks_per_batch,
+            )
+            .await;
+            chain.tasks.push(tokio::spawn(async move {
+                if let Err(e) = pool_updater.start().await {
+                    error!("Pool updater error for chain {}: {}", chain_id, e);
+                }
+            }));
+        }
+
+        info!("Chain {} initialized successfully!", chain_id);
+        Ok(chain)
+    }
+
+    /// Take a stopped chain out of the registries the API reads
+    async fn remove_registries(&self, chain_id: u64) {
+        self.pool_registries.remove_pool_registry(chain_id).await;
+        self.token_registries.remove_token_registry(chain_id).await;
+        self.price_registries.remove_price_registry(chain_id).await;
+        self.router_registry.remove_routers(chain_id).await;
+        self.gas_registry.remove_network(chain_id).await;
+        self.profit_token_registries.write().await.remove(&chain_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(rpc_url: &str, pools: &[(&str, PoolType)]) -> ChainConfigs {
+        ChainConfigs {
+            rpc_urls: vec![rpc_url.to_string()],
+            websocket_urls: Vec::new(),
+            start_block: 0,
+            max_blocks_per_batch: 100,
+            wait_time_for_startup: 0,
+            use_websocket: false,
+            wrap_native: None,
+            custom_multicall_address: None,
+            min_profit_usd: 0.0,
+            profit_tokens: Vec::new(),
+            price_sources: Default::default(),
+            pools: pools
+                .iter()
+                .map(|(address, pool_type)| PoolConfig {
+                    address: address.to_string(),
+                    pool_type: *pool_type,
+                })
+                .collect(),
+            stablecoins: Vec::new(),
+            routers: Vec::new(),
+            max_sync_lag: 10,
+        }
+    }
+
+    #[test]
+    fn test_diff_chains() {
+        let pool_a = "0x000000000000000000000000000000000000000a";
+        let pool_b = "0x000000000000000000000000000000000000000b";
+        let pool_c = "0x000000000000000000000000000000000000000c";
+        let running = HashMap::from([
+            (1, chain("http://mainnet", &[(pool_a, PoolType::UniswapV2)])),
+            (
+                10,
+                chain(
+                    "http://optimism",
+                    &[(pool_a, PoolType::UniswapV2), (pool_b, PoolType::UniswapV2)],
+                ),
+            ),
+            (56, chain("http://bsc", &[])),
+            (137, chain("http://polygon", &[])),
+        ]);
+
+        let mut polygon = chain("http://polygon", &[]);
+        polygon.max_blocks_per_batch = 50;
+        let diff = diff_chains(
+            &running,
+            vec![
+                // Unchanged, the address case does not matter
+                chain(
+                    "http://mainnet",
+                    &[(
+                        &pool_a.to_uppercase().replace("0X", "0x"),
+                        PoolType::UniswapV2,
+                    )],
+                ),
+                // Pools changed, one of them only its type
+                chain(
+                    "http://optimism",
+                    &[(pool_b, PoolType::UniswapV3), (pool_c, PoolType::UniswapV2)],
+                ),
+                // Settings changed, restarted
+                polygon.clone(),
+                // New
+                chain("http://base", &[(pool_a, PoolType::UniswapV2)]),
+            ],
+        );
+
+        assert_eq!(diff.unchanged, vec![1]);
+        assert_eq!(diff.updated.len(), 1);
+        let (chain_id, pools) = &diff.updated[0];
+        assert_eq!(*chain_id, 10);
+        assert_eq!(
+            pools.removed,
+            vec![Address::with_last_byte(0xa), Address::with_last_byte(0xb)]
+        );
+        assert_eq!(
+            pools
+                .added
+                .iter()
+                .map(|pool| (pool.address.as_str(), pool.pool_type))
+                .collect::<Vec<_>>(),
+            vec![(pool_b, PoolType::UniswapV3), (pool_c, PoolType::UniswapV2)]
+        );
+        assert_eq!(diff.stopped, vec![56, 137]);
+        assert_eq!(diff.started.len(), 2);
+        assert_eq!(diff.started[0], polygon);
+        assert_eq!(diff.started[1].rpc_urls, vec!["http://base".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_apply_stops_removed_chains() {
+        let manager = ChainManager::new(
+            CandleConfig::default(),
+            PricingConfig::default(),
+            Arc::new(Metrics::new()),
+        );
+        // A chain that never connects, standing in for one started earlier
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .connect_http("http://127.0.0.1:1".parse().unwrap())
+                .erased(),
+        );
+        let pool_registry = Arc::new(PoolRegistry::new(1));
+        manager
+            .pool_registries
+            .add_pool_registry(1, pool_registry.clone())
+            .await;
+        let task = tokio::spawn(std::future::pending::<()>());
+        manager.chains.lock().await.insert(
+            1,
+            RunningChain {
+                config: chain("http://127.0.0.1:1", &[]),
+                provider,
+                pool_registry,
+                token_registry: Arc::new(RwLock::new(TokenRegistry::new(1))),
+                multicall_address: MULTICALL3_ADDRESS,
+                filter: None,
+                tasks: vec![task],
+            },
+        );
+        let task = manager.chains.lock().await[&1].tasks[0].abort_handle();
+
+        // Unchanged
+        let report = manager.apply(vec![chain("http://127.0.0.1:1", &[])]).await;
+        assert_eq!(report.unchanged, vec![1]);
+        assert!(!task.is_finished());
+
+        let report = manager.apply(Vec::new()).await;
+        assert_eq!(report.stopped, vec![1]);
+        assert!(report.errors.is_empty());
+        assert!(manager.running_chains().await.is_empty());
+        assert!(!manager.pool_registries.contains_pool_registry(1).await);
+        tokio::task::yield_now().await;
+        assert!(task.is_finished());
+    }
+}
@@ -5,7 +5,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Database {
     db: Arc<Db>,
 }
@@ -89,6 +89,36 @@ impl Database {
         Ok(iter)
     }
 
+    pub fn scan_prefix<K, V>(
+        &self,
+        tree_name: &str,
+        prefix: K,
+    ) -> Result<impl Iterator<Item = Result<(Vec<u8>, V)>>>
+    where
+        K: AsRef<[u8]>,
+        V: serde::de::DeserializeOwned,
+    {
+        let tree = self.get_tree(tree_name)?;
+        let iter = tree.scan_prefix(prefix).map(|res| {
+            res.map_err(|e| anyhow!(e))
+                .and_then(|(k, v)| Ok((k.to_vec(), Self::deserialize(&v)?)))
+        });
+        Ok(iter)
+    }
+
+    /// Names of every tree, sled's default tree included
+    pub fn tree_names(&self) -> Vec<String> {
+        self.db
+            .tree_names()
+            .iter()
+            .map(|name| String::from_utf8_lossy(name).to_string())
+            .collect()
+    }
+
+    pub fn size_on_disk(&self) -> Result<u64> {
+        Ok(self.db.size_on_disk()?)
+    }
+
     pub fn snapshot(&self) -> Result<()> {
         self.db.flush()?;
         Ok(())
@@ -1,4 +1,6 @@
+pub mod chain_manager;
 pub mod database;
+pub mod offline;
 
 pub mod proccessor;
 
@@ -0,0 +1,588 @@
+use alloy::eips::{BlockId, BlockNumberOrTag};
+use alloy::primitives::{hex, Address, U256};
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use anyhow::{anyhow, Result};
+use log::info;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::blockchain::{fetch_pool, identify_pool_type};
+use crate::core::proccessor::{Proccessor, QuoteData, QuoteType};
+use crate::core::Database;
+use crate::models::candle::Candle;
+use crate::models::path::{PathRegistry, PoolTokenPair};
+use crate::models::pool::base::Topic;
+use crate::models::pool::erc4626::VerioIP;
+use crate::models::pool::multichain_registry::MultichainPoolRegistry;
+use crate::models::pool::{PoolInterface, PoolRegistry, PoolType, UniswapV2Pool, UniswapV3Pool};
+use crate::models::price::MultichainPriceRegistry;
+use crate::models::profit_token::{ProfitToken, ProfitTokenRegistry};
+use crate::models::token::{MultichainTokenRegistry, Token, TokenRegistry};
+use crate::utils::config::ChainConfigs;
+
+/// Longest cycle `export_cycles` searches for by default
+pub const DEFAULT_MAX_PATH_LENGTH: usize = 4;
+
+/// Suffixes of the per-chain trees pools are saved in
+const POOL_TREE_SUFFIXES: [&str; 3] = ["-v2_pools", "-v3_pools", "-verio_ip_pools"];
+
+/// Length of the zero padded start time ending every candle key
+const CANDLE_START_LEN: usize = 20;
+
+/// Entries and size of a database tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeStats {
+    pub name: String,
+    pub entries: usize,
+    pub bytes: u64, // Keys and values, without sled's overhead
+}
+
+/// What `prune_db` removed, or would remove on a dry run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub pools_removed: Vec<(String, Address)>, // Tree and address of pools no longer configured
+    pub path_entries_removed: usize, // Path index entries dropped or rewritten for removed pools
+    pub candles_removed: usize,
+}
+
+/// Fetch a pool's state at a block, the latest one if none is given. The pool type is detected
+/// when not given, which tells V2 and V3 pools apart but not vaults.
+pub async fn fetch_pool_at(
+    rpc_url: &str,
+    address: Address,
+    block: Option<u64>,
+    pool_type: Option<PoolType>,
+    multicall_address: Address,
+) -> Result<(Box<dyn PoolInterface>, u64)> {
+    let provider = Arc::new(
+        ProviderBuilder::new()
+            .connect_http(Url::parse(rpc_url)?)
+            .erased(),
+    );
+    let block = match block {
+        Some(block) => block,
+        None => provider.get_block_number().await?,
+    };
+    let pool_type = match pool_type {
+        Some(pool_type) => pool_type,
+        None => identify_pool_type::<DynProvider>(&provider, address).await?,
+    };
+    let chain_id = provider.get_chain_id().await?;
+    let token_registry = Arc::new(RwLock::new(TokenRegistry::new(chain_id)));
+    let pool = fetch_pool(
+        &provider,
+        address,
+        BlockId::Number(BlockNumberOrTag::Number(block)),
+        pool_type,
+        &token_registry,
+        multicall_address,
+    )
+    .await?;
+    Ok((pool, block))
+}
+
+/// Load a chain's pools and tokens from a snapshot database, as the indexer does on startup
+pub async fn load_snapshot(
+    db: &Database,
+    network_id: u64,
+) -> Result<(Arc<PoolRegistry>, Arc<RwLock<TokenRegistry>>)> {
+    let pool_registry = Arc::new(PoolRegistry::new(network_id));
+    pool_registry.load_from_db(db).await?;
+    if pool_registry.get_all_addresses().await.is_empty() {
+        return Err(anyhow!("No pools of chain {} in the database", network_id));
+    }
+    let mut token_registry = TokenRegistry::new(network_id);
+    token_registry.load_from_db(db).await?;
+    Ok((pool_registry, Arc::new(RwLock::new(token_registry))))
+}
+
+/// Quote a route through the pools of a snapshot database, without any RPC
+pub async fn quote_from_snapshot(
+    db: &Database,
+    network_id: u64,
+    path: &[Address],
+    token_in: Address,
+    token_out: Address,
+    amount: U256,
+    quote_type: QuoteType,
+) -> Result<QuoteData> {
+    let (pool_registry, token_registry) = load_snapshot(db, network_id).await?;
+    let pool_registries = Arc::new(MultichainPoolRegistry::new());
+    pool_registries
+        .add_pool_registry(network_id, pool_registry)
+        .await;
+    let token_registries = Arc::new(MultichainTokenRegistry::new());
+    token_registries
+        .add_token_registry(network_id, token_registry)
+        .await;
+    let processor = Proccessor::new(
+        pool_registries,
+        token_registries,
+        Arc::new(MultichainPriceRegistry::new()),
+    );
+    processor
+        .quote_amount_token_with_path_raw(
+            network_id,
+            path,
+            amount,
+            &quote_type,
+            token_in,
+            token_out,
+        )
+        .await
+}
+
+/// Find the cycles through a snapshot's pools that start and end at one of `profit_tokens`
+/// and write them to `file_path`. Returns the number of cycles found.
+pub async fn export_cycles(
+    db: &Database,
+    network_id: u64,
+    profit_tokens: &[Address],
+    max_path_length: usize,
+    file_path: &str,
+) -> Result<usize> {
+    let (pool_registry, _) = load_snapshot(db, network_id).await?;
+    let profit_token_registry = ProfitTokenRegistry::default();
+    for token in profit_tokens {
+        profit_token_registry
+            .add_token(
+                *token,
+                ProfitToken {
+                    address: *token,
+                    min_profit: U256::ZERO,
+                    price_source: None,
+                    price: None,
+                    default_price: 0.0,
+                },
+            )
+            .await;
+    }
+
+    let path_registry = PathRegistry::new(Arc::new(profit_token_registry), max_path_length);
+    for pool in pool_registry.snapshot().pools().values() {
+        path_registry.add_pool(pool.as_ref()).await;
+    }
+    path_registry.find_all_cycles().await;
+    path_registry.export_cycles_to_txt(file_path).await?;
+    Ok(path_registry.get_cycle_count().await)
+}
+
+/// Count the entries and bytes of every non-empty tree
+pub fn db_stats(db: &Database) -> Result<Vec<TreeStats>> {
+    let mut stats = Vec::new();
+    for name in db.tree_names() {
+        let tree = db.get_tree(&name)?;
+        let mut entries = 0;
+        let mut bytes = 0;
+        for entry in tree.iter() {
+            let (key, value) = entry?;
+            entries += 1;
+            bytes += (key.len() + value.len()) as u64;
+        }
+        if entries > 0 {
+            stats.push(TreeStats {
+                name,
+                entries,
+                bytes,
+            });
+        }
+    }
+    stats.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(stats)
+}
+
+/// Write the entries of a tree as JSON lines. Values of trees the indexer writes are decoded,
+/// others are written as hex. Returns the number of entries written.
+pub fn dump_tree(db: &Database, tree_name: &str, out: &mut impl Write) -> Result<usize> {
+    if !db.tree_names().iter().any(|name| name == tree_name) {
+        return Err(anyhow!("No tree named {} in the database", tree_name));
+    }
+    let tree = db.get_tree(tree_name)?;
+    let mut count = 0;
+    for entry in tree.iter() {
+        let (key, value) = entry?;
+        let key = String::from_utf8_lossy(&key).to_string();
+        let value = decode_value(tree_name, &key, &value)?;
+        serde_json::to_writer(
+            &mut *out,
+            &serde_json::json!({ "key": key, "value": value }),
+        )?;
+        writeln!(out)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn decode_value(tree_name: &str, key: &str, bytes: &[u8]) -> Result<serde_json::Value> {
+    fn decode<T: DeserializeOwned + Serialize>(bytes: &[u8]) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(Database::deserialize::<T>(bytes)?)?)
+    }
+
+    match (tree_name, key) {
+        (tree, _) if tree.ends_with("-v2_pools") => decode::<UniswapV2Pool>(bytes),
+        (tree, _) if tree.ends_with("-v3_pools") => decode::<UniswapV3Pool>(bytes),
+        (tree, _) if tree.ends_with("-verio_ip_pools") => decode::<VerioIP>(bytes),
+        (tree, _) if tree.ends_with("-tokens") => decode::<Token>(bytes),
+        (tree, _) if tree.ends_with("-candles") => decode::<Candle>(bytes),
+        ("metadata", "last_processed_block") => decode::<u64>(bytes),
+        ("metadata", _) => decode::<Vec<Topic>>(bytes),
+        ("token_to_pools" | "pool_to_tokens", _) => decode::<HashSet<Address>>(bytes),
+        ("cycles_cache", _) => {
+            decode::<Vec<(Vec<PoolTokenPair>, Vec<PoolTokenPair>, usize)>>(bytes)
+        }
+        _ => Ok(serde_json::Value::String(hex::encode_prefixed(bytes))),
+    }
+}
+
+/// Configured pools of each chain, keyed by the chain id its first RPC reports, as
+/// `prune_db` takes them
+pub async fn configured_pools(
+    chain_configs: &[ChainConfigs],
+) -> Result<HashMap<u64, HashSet<Address>>> {
+    let mut configured = HashMap::new();
+    for chain in chain_configs {
+        let rpc_url = chain
+            .rpc_urls
+            .first()
+            .ok_or_else(|| anyhow!("A chain has no RPC URL to read its chain id from"))?;
+        let provider = ProviderBuilder::new().connect_http(Url::parse(rpc_url)?);
+        let chain_id = provider.get_chain_id().await?;
+        let pools: &mut HashSet<Address> = configured.entry(chain_id).or_default();
+        for pool in &chain.pools {
+            pools.insert(pool.address.parse()?);
+        }
+    }
+    Ok(configured)
+}
+
+/// Remove the saved pools that are not configured for their chain, so they are not loaded with
+/// the snapshot again, and keep the latest `max_candles` of every candle series.
+/// `configured_pools` holds the pools of each chain; pools saved for a chain missing from it are
+/// all removed. The shared path indexes drop the removed pools unless another chain still
+/// configures them.
+pub fn prune_db(
+    db: &Database,
+    configured_pools: &HashMap<u64, HashSet<Address>>,
+    max_candles: usize,
+    dry_run: bool,
+) -> Result<PruneReport> {
+    let mut report = PruneReport::default();
+    let no_pools = HashSet::new();
+    for name in db.tree_names() {
+        let tree = db.get_tree(&name)?;
+        if POOL_TREE_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+        {
+            // Pool trees are named after their chain, like `1-v2_pools`
+            let Some(chain_id) = name
+                .split_once('-')
+                .and_then(|(chain_id, _)| chain_id.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let configured = configured_pools.get(&chain_id).unwrap_or(&no_pools);
+            for key in tree.iter().keys() {
+                let key = key?;
+                let address = Address::from_str(&String::from_utf8_lossy(&key))?;
+                if !configured.contains(&address) {
+                    if !dry_run {
+                        tree.remove(&key)?;
+                    }
+                    report.pools_removed.push((name.clone(), address));
+                }
+            }
+        } else if name.ends_with("-candles") {
+            // Keys sort by series, then by start time
+            let mut series: BTreeMap<Vec<u8>, Vec<sled::IVec>> = BTreeMap::new();
+            for key in tree.iter().keys() {
+                let key = key?;
+                let prefix = key[..key.len().saturating_sub(CANDLE_START_LEN)].to_vec();
+                series.entry(prefix).or_default().push(key);
+            }
+            for keys in series.values() {
+                let excess = keys.len().saturating_sub(max_candles);
+                for key in &keys[..excess] {
+                    if !dry_run {
+                        tree.remove(key)?;
+                    }
+                    report.candles_removed += 1;
+                }
+            }
+        }
+    }
+
+    // The path indexes are shared by every chain
+    let removed: HashSet<Address> = report
+        .pools_removed
+        .iter()
+        .map(|(_, address)| *address)
+        .filter(|address| {
+            !configured_pools
+                .values()
+                .any(|pools| pools.contains(address))
+        })
+        .collect();
+    if !removed.is_empty() {
+        report.path_entries_removed = prune_path_indexes(db, &removed, dry_run)?;
+    }
+
+    if !dry_run {
+        db.snapshot()?;
+    }
+    info!(
+        "Pruned {} pools, {} path index entries and {} candles{}",
+        report.pools_removed.len(),
+        report.path_entries_removed,
+        report.candles_removed,
+        if dry_run { " (dry run)" } else { "" }
+    );
+    Ok(report)
+}
+
+/// Drop `removed` pools from the `pool_to_tokens` and `token_to_pools` trees of the path
+/// registry, returning the number of entries removed or changed
+fn prune_path_indexes(db: &Database, removed: &HashSet<Address>, dry_run: bool) -> Result<usize> {
+    let mut entries = 0;
+    for result in db.iter::<HashSet<Address>>("pool_to_tokens")? {
+        let (key, _) = result?;
+        let pool = Address::from_str(&String::from_utf8_lossy(&key))?;
+        if removed.contains(&pool) {
+            if !dry_run {
+                db.remove("pool_to_tokens", &key)?;
+            }
+            entries += 1;
+        }
+    }
+    for result in db.iter::<HashSet<Address>>("token_to_pools")? {
+        let (key, mut pools) = result?;
+        let before = pools.len();
+        pools.retain(|pool| !removed.contains(pool));
+        if pools.len() == before {
+            continue;
+        }
+        if !dry_run {
+            if pools.is_empty() {
+                db.remove("token_to_pools", &key)?;
+            } else {
+                db.insert("token_to_pools", &key, &pools)?;
+            }
+        }
+        entries += 1;
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::candle::CandleKey;
+
+    fn temp_db() -> Database {
+        let path = std::env::temp_dir().join(format!("offline-{}", uuid::Uuid::new_v4()));
+        Database::new(path).unwrap()
+    }
+
+    fn token(address: Address, symbol: &str) -> Token {
+        Token::new(address, 1, symbol.to_string(), symbol.to_string(), 18)
+    }
+
+    async fn save_snapshot(db: &Database, pools: &[UniswapV2Pool]) {
+        let pool_registry = PoolRegistry::new(1);
+        let mut token_registry = TokenRegistry::new(1);
+        for pool in pools {
+            pool_registry.add_pool(Box::new(pool.clone())).await;
+            token_registry.add_token(token(pool.token0, "T0"));
+            token_registry.add_token(token(pool.token1, "T1"));
+        }
+        pool_registry.set_last_processed_block(100).await;
+        pool_registry.save_to_db(db).await.unwrap();
+        token_registry.save_to_db(db).await.unwrap();
+    }
+
+    fn v2_pool(address: u8, token0: u8, token1: u8) -> UniswapV2Pool {
+        UniswapV2Pool::new(
+            Address::with_last_byte(address),
+            Address::with_last_byte(token0),
+            Address::with_last_byte(token1),
+            U256::from(1_000_000_000u64),
+            U256::from(2_000_000_000u64),
+            U256::from(3000),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_quote_from_snapshot() {
+        let db = temp_db();
+        let pool = v2_pool(0xa, 1, 2);
+        save_snapshot(&db, std::slice::from_ref(&pool)).await;
+
+        let (token_in, token_out) = (pool.token0, pool.token1);
+        let amount = U256::from(1000);
+        let quote = quote_from_snapshot(
+            &db,
+            1,
+            &[pool.address],
+            token_in,
+            token_out,
+            amount,
+            QuoteType::ExactIn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(quote.block_number, 100);
+        assert_eq!(
+            quote.output.amount,
+            pool.calculate_output(&token_in, amount)
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(quote.route[0].token_out, "T1");
+
+        // Chains without a snapshot are reported instead of quoted as empty
+        let err = quote_from_snapshot(
+            &db,
+            2,
+            &[pool.address],
+            token_in,
+            token_out,
+            amount,
+            QuoteType::ExactIn,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("No pools of chain 2"));
+    }
+
+    #[tokio::test]
+    async fn test_export_cycles() {
+        let db = temp_db();
+        // Two pools of the same pair form a cycle through token 1
+        save_snapshot(&db, &[v2_pool(0xa, 1, 2), v2_pool(0xb, 1, 2)]).await;
+        let output = std::env::temp_dir().join(format!("cycles-{}.txt", uuid::Uuid::new_v4()));
+        let output = output.to_str().unwrap();
+
+        let cycles = export_cycles(&db, 1, &[Address::with_last_byte(1)], 3, output)
+            .await
+            .unwrap();
+        assert!(cycles > 0);
+        let exported = std::fs::read_to_string(output).unwrap();
+        assert!(exported.contains(&format!("Total cycles found: {}", cycles)));
+
+        // Without a profit token no cycle is kept
+        let cycles = export_cycles(&db, 1, &[], 3, output).await.unwrap();
+        assert_eq!(cycles, 0);
+        std::fs::remove_file(output).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_db_stats_dump_and_prune() {
+        let db = temp_db();
+        let kept = v2_pool(0xa, 1, 2);
+        let removed = v2_pool(0xb, 2, 3);
+        save_snapshot(&db, &[kept.clone(), removed.clone()]).await;
+        let candle_tree = "1-candles";
+        let key = CandleKey::pair(kept.token0, kept.token1);
+        for start in [60u64, 120, 180] {
+            let candle = Candle::new(start, 60, 1.0, 0);
+            let db_key = format!("{}-{}-{:020}", key, 60, start);
+            db.insert(candle_tree, db_key, &candle).unwrap();
+        }
+
+        let stats = db_stats(&db).unwrap();
+        let entries = |name: &str| {
+            stats
+                .iter()
+                .find(|tree| tree.name == name)
+                .map(|tree| tree.entries)
+        };
+        assert_eq!(entries("1-v2_pools"), Some(2));
+        assert_eq!(entries("1-tokens"), Some(3));
+        assert_eq!(entries(candle_tree), Some(3));
+
+        let mut out = Vec::new();
+        assert_eq!(dump_tree(&db, "1-v2_pools", &mut out).unwrap(), 2);
+        let line: serde_json::Value =
+            serde_json::from_str(String::from_utf8(out).unwrap().lines().next().unwrap()).unwrap();
+        assert_eq!(line["key"], kept.address.to_string());
+        assert!(dump_tree(&db, "missing", &mut Vec::new()).is_err());
+
+        // The path indexes still list both pools
+        for pool in [&kept, &removed] {
+            let tokens = HashSet::from([pool.token0, pool.token1]);
+            db.insert("pool_to_tokens", pool.address.to_string(), &tokens)
+                .unwrap();
+        }
+        let token_pools = |pools: &[Address]| pools.iter().copied().collect::<HashSet<_>>();
+        let shared = token_pools(&[kept.address, removed.address]);
+        db.insert(
+            "token_to_pools",
+            kept.token0.to_string(),
+            &token_pools(&[kept.address]),
+        )
+        .unwrap();
+        db.insert("token_to_pools", kept.token1.to_string(), &shared)
+            .unwrap();
+        db.insert(
+            "token_to_pools",
+            removed.token1.to_string(),
+            &token_pools(&[removed.address]),
+        )
+        .unwrap();
+        // A pool configured on chain 1 is still pruned from another chain's tree
+        db.insert("2-v2_pools", kept.address.to_string(), &kept)
+            .unwrap();
+
+        // A dry run reports without removing
+        let configured = HashMap::from([(1, HashSet::from([kept.address]))]);
+        let report = prune_db(&db, &configured, 2, true).unwrap();
+        let mut pools_removed = report.pools_removed.clone();
+        pools_removed.sort();
+        assert_eq!(
+            pools_removed,
+            vec![
+                ("1-v2_pools".to_string(), removed.address),
+                ("2-v2_pools".to_string(), kept.address),
+            ]
+        );
+        // Only the removed pool's entries, the kept pool is still configured on chain 1
+        assert_eq!(report.path_entries_removed, 3);
+        assert_eq!(report.candles_removed, 1);
+        assert_eq!(entries("1-v2_pools"), Some(2));
+
+        prune_db(&db, &configured, 2, false).unwrap();
+        let stats = db_stats(&db).unwrap();
+        let tree = |name: &str| stats.iter().find(|tree| tree.name == name).unwrap();
+        assert_eq!(tree("1-v2_pools").entries, 1);
+        // Emptied trees are not listed
+        assert!(!stats.iter().any(|tree| tree.name == "2-v2_pools"));
+        assert_eq!(tree("pool_to_tokens").entries, 1);
+        let token_to_pools: HashMap<Address, HashSet<Address>> = db
+            .iter::<HashSet<Address>>("token_to_pools")
+            .unwrap()
+            .map(|entry| {
+                let (key, pools) = entry.unwrap();
+                (String::from_utf8(key).unwrap().parse().unwrap(), pools)
+            })
+            .collect();
+        assert_eq!(
+            token_to_pools,
+            HashMap::from([
+                (kept.token0, token_pools(&[kept.address])),
+                (kept.token1, token_pools(&[kept.address])),
+            ])
+        );
+        assert_eq!(tree(candle_tree).entries, 2);
+        let candles: Vec<Candle> = db
+            .iter::<Candle>(candle_tree)
+            .unwrap()
+            .map(|entry| entry.unwrap().1)
+            .collect();
+        assert_eq!(candles[0].start, 120);
+    }
+}
@@ -3,13 +3,21 @@ use anyhow::anyhow;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::ToSchema;
 
 use crate::models::{
-    pool::multichain_registry::MultichainPoolRegistry,
+    gas::{native_to_token_rate, GasRegistry},
+    pool::{
+        multichain_registry::MultichainPoolRegistry, PoolInterface, PoolSnapshot, QuoteCache,
+        QuoteCacheKey, RouteSnapshot,
+    },
+    price::MultichainPriceRegistry,
+    router::RouterRegistry,
     token::multichain_registry::MultichainTokenRegistry,
 };
+use crate::utils::errors::ArbitrageError;
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Deserialize, PartialEq, Eq, ToSchema)]
 pub enum QuoteType {
     #[serde(rename = "exact_in")]
     ExactIn,
@@ -17,26 +25,27 @@ pub enum QuoteType {
     ExactOut,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QuoteData {
+    pub block_number: u64, // Block of the pool states the quote was computed from
     pub input: InputToken,
     pub output: OutputToken,
     pub route: Vec<RouteStep>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct InputToken {
     pub amount: String,
     pub token: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct OutputToken {
     pub amount: String,
     pub token: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RouteStep {
     pub address: String,
     pub token_in: String,
@@ -48,19 +57,43 @@ pub struct RouteStep {
 pub struct Proccessor {
     pool_registry: Arc<MultichainPoolRegistry>,
     token_registry: Arc<MultichainTokenRegistry>,
+    price_registry: Arc<MultichainPriceRegistry>,
+    router_registry: Arc<RouterRegistry>,
+    gas_registry: Arc<GasRegistry>,
+    quote_cache: Arc<QuoteCache>,
 }
 
 impl Proccessor {
     pub fn new(
         pool_registry: Arc<MultichainPoolRegistry>,
         token_registry: Arc<MultichainTokenRegistry>,
+        price_registry: Arc<MultichainPriceRegistry>,
     ) -> Self {
         Self {
             pool_registry,
             token_registry,
+            price_registry,
+            router_registry: Arc::new(RouterRegistry::new()),
+            gas_registry: Arc::new(GasRegistry::new()),
+            quote_cache: Arc::new(QuoteCache::default()),
         }
     }
 
+    /// Set the routers swap calldata is encoded for
+    pub fn set_router_registry(&mut self, router_registry: Arc<RouterRegistry>) {
+        self.router_registry = router_registry;
+    }
+
+    /// Set the gas prices routes are ranked with
+    pub fn set_gas_registry(&mut self, gas_registry: Arc<GasRegistry>) {
+        self.gas_registry = gas_registry;
+    }
+
+    /// Set the cache route quotes are served from
+    pub fn set_quote_cache(&mut self, quote_cache: Arc<QuoteCache>) {
+        self.quote_cache = quote_cache;
+    }
+
     pub fn pool_registry(&self) -> &Arc<MultichainPoolRegistry> {
         &self.pool_registry
     }
@@ -69,6 +102,43 @@ impl Proccessor {
         &self.token_registry
     }
 
+    pub fn price_registry(&self) -> &Arc<MultichainPriceRegistry> {
+        &self.price_registry
+    }
+
+    pub fn router_registry(&self) -> &Arc<RouterRegistry> {
+        &self.router_registry
+    }
+
+    pub fn gas_registry(&self) -> &Arc<GasRegistry> {
+        &self.gas_registry
+    }
+
+    pub fn quote_cache(&self) -> &Arc<QuoteCache> {
+        &self.quote_cache
+    }
+
+    /// Price of one unit of gas in raw units of `token`, converted through the USD prices of the
+    /// token and the chain's wrapped native token. None if any of the prices is unknown.
+    pub async fn gas_price_in_token(&self, network_id: u64, token: Address) -> Option<f64> {
+        let gas_price = self.gas_registry.get_gas_price(network_id).await? as f64;
+        let wrap_native = self.gas_registry.get_wrap_native(network_id).await?;
+        if token == wrap_native {
+            return Some(gas_price);
+        }
+
+        let price_registry = self.price_registry.get_price_registry(network_id).await?;
+        let native_usd = price_registry.get_price(&wrap_native).await?.price_usd;
+        let token_usd = price_registry.get_price(&token).await?.price_usd;
+        let native = self
+            .token_registry
+            .get_token(network_id, wrap_native)
+            .await?;
+        let token = self.token_registry.get_token(network_id, token).await?;
+        let rate = native_to_token_rate(native_usd, native.decimals, token_usd, token.decimals)?;
+        Some(gas_price * rate)
+    }
+
     pub async fn quote_amount_in_token_in_raw(
         &self,
         network_id: u64,
@@ -76,18 +146,10 @@ impl Proccessor {
         token_in: Address,
         amount_out: U256,
     ) -> Result<U256> {
-        let pool = self
-            .pool_registry
-            .get_pool_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
-            .get_pool(&pool)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
-        let (token0, token1) = pool.read().await.tokens();
-        let token_out = if token_in == token0 { token1 } else { token0 };
-        let amount_in = pool.read().await.calculate_input(&token_out, amount_out)?;
-        Ok(amount_in)
+        let (pool, _, token_out) = self
+            .pool_swap(network_id, pool, Some(token_in), None)
+            .await?;
+        pool.calculate_input(&token_out, amount_out)
     }
 
     pub async fn quote_amount_token_with_path_raw(
@@ -99,187 +161,118 @@ impl Proccessor {
         token_in: Address,
         token_out: Address,
     ) -> Result<QuoteData> {
-        let mut current_amount = amount;
-        let mut path_steps = Vec::new();
-
-        // For exact output, we need to process the path in reverse
-        let path_to_process = match quote_type {
-            QuoteType::ExactIn => path.iter().collect::<Vec<_>>(),
-            QuoteType::ExactOut => path.iter().rev().collect::<Vec<_>>(),
-        };
-
-        let mut current_token = match quote_type {
-            QuoteType::ExactIn => token_in,   // Start with input token
-            QuoteType::ExactOut => token_out, // Start with output token for reverse calculation
-        };
-        for &pool_address in path_to_process.iter() {
-            let pool_arc = self
-                .pool_registry
-                .get_pool_registry(network_id)
-                .await
-                .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
-                .get_pool(&pool_address)
-                .await
-                .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
-
-            let (token0, token1) = pool_arc.read().await.tokens();
-
-            // Determine the correct token pair for this step
-            let (step_token_in, step_token_out) = match quote_type {
-                QuoteType::ExactIn => {
-                    // For exact input, current_token is the input token
-                    if current_token == token0 {
-                        (token0, token1)
-                    } else if current_token == token1 {
-                        (token1, token0)
-                    } else {
-                        return Err(anyhow!(
-                            "Token {:?} not found in pool {:?} with tokens {:?}, {:?}",
-                            current_token,
-                            pool_address,
-                            token0,
-                            token1
-                        ));
-                    }
-                }
-                QuoteType::ExactOut => {
-                    // For exact output (reverse path), current_token is the output token
-                    if current_token == token0 {
-                        (token1, token0) // Reverse: output token becomes input for next step
-                    } else if current_token == token1 {
-                        (token0, token1) // Reverse: output token becomes input for next step
-                    } else {
-                        return Err(anyhow!(
-                            "Token {:?} not found in pool {:?} with tokens {:?}, {:?}",
-                            current_token,
-                            pool_address,
-                            token0,
-                            token1
-                        ));
-                    }
-                }
-            };
-
-            // Calculate the quote for this step
-            let step_amount_out = match quote_type {
-                QuoteType::ExactIn => {
-                    let output = pool_arc
-                        .read()
-                        .await
-                        .calculate_output(&step_token_in, current_amount)?;
-
-                    output
-                }
-                QuoteType::ExactOut => {
-                    // For exact out (reverse path), calculate input required for desired output
-                    let input_amount = pool_arc
-                        .read()
-                        .await
-                        .calculate_input(&step_token_out, current_amount)?;
-
-                    input_amount
-                }
-            };
-
-            let token_registry = self
-                .token_registry
-                .get_token_registry(network_id)
-                .await
-                .ok_or_else(|| anyhow::anyhow!("Token registry not found"))?;
-            let token_registry_guard = token_registry.read().await;
-            // Get token info from token registry
-            let token_in_info = {
-                let token_registry = token_registry_guard.get_token(step_token_in);
-                if let Some(token) = token_registry {
-                    token.symbol.clone()
-                } else {
-                    "UNKNOWN".to_string()
-                }
-            };
-
-            let token_out_info = {
-                let token_registry = token_registry_guard.get_token(step_token_out);
-                if let Some(token) = token_registry {
-                    token.symbol.clone()
-                } else {
-                    "UNKNOWN".to_string()
-                }
-            };
-            drop(token_registry_guard);
-
-            // Create route step
-            let route_step = RouteStep {
-                address: format!("{:?}", pool_address),
-                token_in: token_in_info,
-                token_out: token_out_info,
-                amount_in: match quote_type {
-                    QuoteType::ExactIn => current_amount.to_string(),
-                    QuoteType::ExactOut => step_amount_out.to_string(), // For reverse path, step_amount_out is the input to this step
-                },
-                amount_out: match quote_type {
-                    QuoteType::ExactIn => step_amount_out.to_string(),
-                    QuoteType::ExactOut => current_amount.to_string(), // For reverse path, current_amount is the output from this step
-                },
-            };
-
-            path_steps.push(route_step);
-
-            // Update current amount and token for next iteration
-            match quote_type {
-                QuoteType::ExactIn => {
-                    current_amount = step_amount_out;
-                    current_token = step_token_out;
-                }
-                QuoteType::ExactOut => {
-                    current_amount = step_amount_out;
-                    current_token = step_token_in; // For reverse path, step_token_in is the next token
-                }
-            }
-        }
-
-        // Verify we end up with the correct final token
-        let expected_final_token = match quote_type {
-            QuoteType::ExactIn => token_out,
-            QuoteType::ExactOut => token_in,
-        };
-
-        if current_token != expected_final_token {
+        // Quote every step from the same snapshot so the path sees one block's states
+        let snapshot = self
+            .pool_registry
+            .get_pool_registry(network_id)
+            .await
+            .ok_or(ArbitrageError::NetworkNotFound(network_id))?
+            .snapshot();
+        let route = snapshot.route(path, token_in)?;
+        if route.token_out() != token_out {
             return Err(anyhow!(
                 "Path does not end with expected token. Expected: {:?}, Got: {:?}",
-                expected_final_token,
-                current_token
+                token_out,
+                route.token_out()
             ));
         }
+        let amounts = self.route_hop_amounts(
+            network_id,
+            &snapshot,
+            &route,
+            amount,
+            *quote_type == QuoteType::ExactIn,
+            false,
+        )?;
+        self.quote_data(network_id, snapshot.block_number(), &route, &amounts)
+            .await
+    }
 
-        // Create input and output token info
-        let (input_amount, output_amount) = match quote_type {
-            QuoteType::ExactIn => (amount, current_amount),
-            QuoteType::ExactOut => (current_amount, amount), // For exact out, we calculated the required input
-        };
-
-        let input_token = InputToken {
-            amount: input_amount.to_string(),
-            token: format!("{:?}", token_in),
-        };
-
-        let output_token = OutputToken {
-            amount: output_amount.to_string(),
-            token: format!("{:?}", token_out),
-        };
-
-        // For exact output, reverse the path steps to show them in correct order
-        let final_path_steps = match quote_type {
-            QuoteType::ExactIn => path_steps,
-            QuoteType::ExactOut => path_steps.into_iter().rev().collect(),
+    /// Describe a route quote, given the amounts entering and leaving each pool
+    pub async fn quote_data(
+        &self,
+        network_id: u64,
+        block_number: u64,
+        route: &RouteSnapshot,
+        amounts: &[U256],
+    ) -> Result<QuoteData> {
+        let token_registry = self
+            .token_registry
+            .get_token_registry(network_id)
+            .await
+            .ok_or(ArbitrageError::NetworkNotFound(network_id))?;
+        let token_registry = token_registry.read().await;
+        let symbol = |token: Address| {
+            token_registry
+                .get_token(token)
+                .map(|token| token.symbol.clone())
+                .unwrap_or_else(|| "UNKNOWN".to_string())
         };
+        let route_steps = route
+            .hops()
+            .iter()
+            .zip(amounts.windows(2))
+            .map(|(hop, amounts)| RouteStep {
+                address: format!("{:?}", hop.pool.address()),
+                token_in: symbol(hop.token_in),
+                token_out: symbol(hop.token_out),
+                amount_in: amounts[0].to_string(),
+                amount_out: amounts[1].to_string(),
+            })
+            .collect();
 
         Ok(QuoteData {
-            input: input_token,
-            output: output_token,
-            route: final_path_steps,
+            block_number,
+            input: InputToken {
+                amount: amounts[0].to_string(),
+                token: format!("{:?}", route.token_in()),
+            },
+            output: OutputToken {
+                amount: amounts[amounts.len() - 1].to_string(),
+                token: format!("{:?}", route.token_out()),
+            },
+            route: route_steps,
         })
     }
 
+    /// Amounts entering and leaving each pool of `route` as of `snapshot`, served from the quote
+    /// cache while none of the route's pools changed
+    pub fn route_hop_amounts(
+        &self,
+        network_id: u64,
+        snapshot: &PoolSnapshot,
+        route: &RouteSnapshot,
+        amount: U256,
+        exact_input: bool,
+        bypass_cache: bool,
+    ) -> Result<Arc<Vec<U256>>> {
+        let key = QuoteCacheKey::new(network_id, snapshot, route, amount, exact_input);
+        self.quote_cache
+            .get_or_compute(key, bypass_cache, || route.hop_amounts(amount, exact_input))
+    }
+
+    /// Like `route_hop_amounts` for each of `amounts`, quoting the amounts missing from the
+    /// cache through each pool in one call
+    pub fn route_hop_amounts_many(
+        &self,
+        network_id: u64,
+        snapshot: &PoolSnapshot,
+        route: &RouteSnapshot,
+        amounts: &[U256],
+        exact_input: bool,
+        bypass_cache: bool,
+    ) -> Result<Vec<Arc<Vec<U256>>>> {
+        let keys = amounts
+            .iter()
+            .map(|&amount| QuoteCacheKey::new(network_id, snapshot, route, amount, exact_input))
+            .collect();
+        self.quote_cache
+            .get_or_compute_many(keys, bypass_cache, |missing| {
+                let amounts: Vec<U256> = missing.iter().map(|&index| amounts[index]).collect();
+                route.hop_amounts_many(&amounts, exact_input)
+            })
+    }
+
     pub async fn quote_amount_in_token_in(
         &self,
         network_id: u64,
@@ -287,28 +280,13 @@ impl Proccessor {
         token_in: Address,
         amount_out_str: String,
     ) -> Result<U256> {
-        let pool = self
-            .pool_registry
-            .get_pool_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
-            .get_pool(&pool)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
-        let (token0, token1) = pool.read().await.tokens();
-        let token_out = if token_in == token0 { token1 } else { token0 };
+        let (pool, _, token_out) = self
+            .pool_swap(network_id, pool, Some(token_in), None)
+            .await?;
         let amount_out = self
-            .token_registry
-            .get_token_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Token registry not found"))?
-            .read()
-            .await
-            .get_token(token_out)
-            .ok_or_else(|| anyhow::anyhow!("Token not found"))?
-            .to_raw_amount(&amount_out_str)?;
-        let amount_in = pool.read().await.calculate_input(&token_out, amount_out)?;
-        Ok(amount_in)
+            .raw_amount(network_id, token_out, &amount_out_str)
+            .await?;
+        pool.calculate_input(&token_out, amount_out)
     }
 
     pub async fn quote_amount_in_token_out_raw(
@@ -318,16 +296,10 @@ impl Proccessor {
         token_out: Address,
         amount_out: U256,
     ) -> Result<U256> {
-        let pool = self
-            .pool_registry
-            .get_pool_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
-            .get_pool(&pool)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
-        let amount_in = pool.read().await.calculate_input(&token_out, amount_out)?;
-        Ok(amount_in)
+        let (pool, _, token_out) = self
+            .pool_swap(network_id, pool, None, Some(token_out))
+            .await?;
+        pool.calculate_input(&token_out, amount_out)
     }
 
     pub async fn quote_amount_in_token_out(
@@ -337,26 +309,13 @@ impl Proccessor {
         token_out: Address,
         amount_out_str: String,
     ) -> Result<U256> {
-        let pool = self
-            .pool_registry
-            .get_pool_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
-            .get_pool(&pool)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
+        let (pool, _, token_out) = self
+            .pool_swap(network_id, pool, None, Some(token_out))
+            .await?;
         let amount_out = self
-            .token_registry
-            .get_token_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Token registry not found"))?
-            .read()
-            .await
-            .get_token(token_out)
-            .ok_or_else(|| anyhow::anyhow!("Token not found"))?
-            .to_raw_amount(&amount_out_str)?;
-        let amount_in = pool.read().await.calculate_input(&token_out, amount_out)?;
-        Ok(amount_in)
+            .raw_amount(network_id, token_out, &amount_out_str)
+            .await?;
+        pool.calculate_input(&token_out, amount_out)
     }
 
     pub async fn quote_amount_out_token_in(
@@ -366,26 +325,13 @@ impl Proccessor {
         token_in: Address,
         amount_in_str: String,
     ) -> Result<U256> {
-        let pool = self
-            .pool_registry
-            .get_pool_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
-            .get_pool(&pool)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
+        let (pool, token_in, _) = self
+            .pool_swap(network_id, pool, Some(token_in), None)
+            .await?;
         let amount_in = self
-            .token_registry
-            .get_token_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Token registry not found"))?
-            .read()
-            .await
-            .get_token(token_in)
-            .ok_or_else(|| anyhow::anyhow!("Token not found"))?
-            .to_raw_amount(&amount_in_str)?;
-        let amount_out = pool.read().await.calculate_output(&token_in, amount_in)?;
-        Ok(amount_out)
+            .raw_amount(network_id, token_in, &amount_in_str)
+            .await?;
+        pool.calculate_output(&token_in, amount_in)
     }
 
     pub async fn quote_amount_out_token_in_raw(
@@ -395,16 +341,10 @@ impl Proccessor {
         token_in: Address,
         amount_in: U256,
     ) -> Result<U256> {
-        let pool = self
-            .pool_registry
-            .get_pool_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
-            .get_pool(&pool)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
-        let amount_out = pool.read().await.calculate_output(&token_in, amount_in)?;
-        Ok(amount_out)
+        let (pool, token_in, _) = self
+            .pool_swap(network_id, pool, Some(token_in), None)
+            .await?;
+        pool.calculate_output(&token_in, amount_in)
     }
 
     pub async fn quote_amount_out_token_out(
@@ -414,28 +354,13 @@ impl Proccessor {
         token_out: Address,
         amount_in_str: String,
     ) -> Result<U256> {
-        let pool = self
-            .pool_registry
-            .get_pool_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
-            .get_pool(&pool)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
-        let (token0, token1) = pool.read().await.tokens();
-        let token_in = if token_out == token0 { token1 } else { token0 };
+        let (pool, token_in, _) = self
+            .pool_swap(network_id, pool, None, Some(token_out))
+            .await?;
         let amount_in = self
-            .token_registry
-            .get_token_registry(network_id)
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Token registry not found"))?
-            .read()
-            .await
-            .get_token(token_in)
-            .ok_or_else(|| anyhow::anyhow!("Token not found"))?
-            .to_raw_amount(&amount_in_str)?;
-        let amount_out = pool.read().await.calculate_output(&token_in, amount_in)?;
-        Ok(amount_out)
+            .raw_amount(network_id, token_in, &amount_in_str)
+            .await?;
+        pool.calculate_output(&token_in, amount_in)
     }
 
     pub async fn quote_amount_out_token_out_raw(
@@ -445,17 +370,127 @@ impl Proccessor {
         token_out: Address,
         amount_in: U256,
     ) -> Result<U256> {
-        let pool = self
+        let (pool, token_in, _) = self
+            .pool_swap(network_id, pool, None, Some(token_out))
+            .await?;
+        pool.calculate_output(&token_in, amount_in)
+    }
+
+    /// A pool from the latest snapshot and the (token_in, token_out) of a swap through it, given
+    /// either token
+    async fn pool_swap(
+        &self,
+        network_id: u64,
+        pool: Address,
+        token_in: Option<Address>,
+        token_out: Option<Address>,
+    ) -> Result<(Arc<dyn PoolInterface + Send + Sync>, Address, Address)> {
+        let snapshot = self.latest_snapshot(network_id).await?;
+        Self::snapshot_pool_swap(&snapshot, pool, token_in, token_out)
+    }
+
+    /// Latest pool snapshot of a network
+    async fn latest_snapshot(&self, network_id: u64) -> Result<Arc<PoolSnapshot>> {
+        Ok(self
             .pool_registry
             .get_pool_registry(network_id)
             .await
-            .ok_or_else(|| anyhow::anyhow!("Pool registry not found"))?
+            .ok_or(ArbitrageError::NetworkNotFound(network_id))?
+            .snapshot())
+    }
+
+    /// Like `pool_swap`, with the pool from the given snapshot
+    pub fn snapshot_pool_swap(
+        snapshot: &PoolSnapshot,
+        pool: Address,
+        token_in: Option<Address>,
+        token_out: Option<Address>,
+    ) -> Result<(Arc<dyn PoolInterface + Send + Sync>, Address, Address)> {
+        let pool_state = snapshot
             .get_pool(&pool)
+            .ok_or(ArbitrageError::PoolNotFound(pool))?;
+        let token = token_in
+            .or(token_out)
+            .ok_or_else(|| anyhow!("Either token_in or token_out must be provided"))?;
+        if !pool_state.contains_token(&token) {
+            return Err(ArbitrageError::TokenNotInPool { token, pool }.into());
+        }
+
+        let (token0, token1) = pool_state.tokens();
+        let other = |token| if token == token0 { token1 } else { token0 };
+        let (token_in, token_out) = match token_in {
+            Some(token_in) => (token_in, other(token_in)),
+            None => (other(token), token),
+        };
+        Ok((pool_state, token_in, token_out))
+    }
+
+    /// Convert an amount in token units to raw units of `token`
+    async fn raw_amount(&self, network_id: u64, token: Address, amount: &str) -> Result<U256> {
+        self.token_registry
+            .get_token(network_id, token)
+            .await
+            .ok_or(ArbitrageError::TokenNotFound(token))?
+            .to_raw_amount(amount)
+            .map_err(|e| ArbitrageError::InvalidAmount(e.to_string()).into())
+    }
+
+    /// Quote every raw amount through one pool state in a single call: outputs for exact input
+    /// amounts, inputs for exact output amounts. The swap direction is given by either token.
+    /// Returns the quotes with the block of the pool state.
+    pub async fn quote_pool_amounts_raw(
+        &self,
+        network_id: u64,
+        pool: Address,
+        token_in: Option<Address>,
+        token_out: Option<Address>,
+        amounts: &[U256],
+        exact_input: bool,
+    ) -> Result<(Vec<U256>, u64)> {
+        let snapshot = self.latest_snapshot(network_id).await?;
+        let (pool, token_in, token_out) =
+            Self::snapshot_pool_swap(&snapshot, pool, token_in, token_out)?;
+        let quotes = if exact_input {
+            pool.calculate_outputs(&token_in, amounts)?
+        } else {
+            pool.calculate_inputs(&token_out, amounts)?
+        };
+        Ok((quotes, snapshot.block_number()))
+    }
+
+    /// Like `quote_pool_amounts_raw`, with amounts in token units of the input (exact input) or
+    /// output (exact output) token
+    pub async fn quote_pool_amounts(
+        &self,
+        network_id: u64,
+        pool: Address,
+        token_in: Option<Address>,
+        token_out: Option<Address>,
+        amounts: &[String],
+        exact_input: bool,
+    ) -> Result<(Vec<U256>, u64)> {
+        let snapshot = self.latest_snapshot(network_id).await?;
+        let (pool, token_in, token_out) =
+            Self::snapshot_pool_swap(&snapshot, pool, token_in, token_out)?;
+        let amount_token = if exact_input { token_in } else { token_out };
+        let token = self
+            .token_registry
+            .get_token(network_id, amount_token)
             .await
-            .ok_or_else(|| anyhow::anyhow!("Pool not found"))?;
-        let (token0, token1) = pool.read().await.tokens();
-        let token_in = if token_out == token0 { token1 } else { token0 };
-        let amount_out = pool.read().await.calculate_output(&token_in, amount_in)?;
-        Ok(amount_out)
+            .ok_or(ArbitrageError::TokenNotFound(amount_token))?;
+        let amounts = amounts
+            .iter()
+            .map(|amount| {
+                token
+                    .to_raw_amount(amount)
+                    .map_err(|e| ArbitrageError::InvalidAmount(e.to_string()).into())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let quotes = if exact_input {
+            pool.calculate_outputs(&token_in, &amounts)?
+        } else {
+            pool.calculate_inputs(&token_out, &amounts)?
+        };
+        Ok((quotes, snapshot.block_number()))
     }
 }
@@ -0,0 +1,144 @@
+pub mod registry;
+
+pub use registry::CandleRegistry;
+
+use alloy::primitives::{Address, U256};
+use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::blockchain::{IAlgebraPoolSei, IPancakeV3Pool, IUniswapV2Pair, IUniswapV3Pool};
+
+/// Default candle intervals in seconds (1m, 5m, 1h, 1d)
+pub const DEFAULT_CANDLE_INTERVALS: [u64; 4] = [60, 300, 3600, 86400];
+
+/// Default number of candles kept in memory per series
+pub const DEFAULT_MAX_CANDLES: usize = 1000;
+
+/// Most candles returned for one request
+pub const MAX_CANDLES_PER_REQUEST: usize = 5000;
+
+/// Identifies a price series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleKey {
+    /// Candles for a single pool, priced as token0 in token1
+    Pool(Address),
+    /// Candles for a token pair across all pools, priced as the lower address in the higher one
+    Pair(Address, Address),
+}
+
+impl CandleKey {
+    /// Build a pair key with the tokens in canonical (sorted) order
+    pub fn pair(token_a: Address, token_b: Address) -> Self {
+        if token_a < token_b {
+            Self::Pair(token_a, token_b)
+        } else {
+            Self::Pair(token_b, token_a)
+        }
+    }
+}
+
+impl fmt::Display for CandleKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pool(pool) => write!(f, "pool:{}", pool),
+            Self::Pair(token0, token1) => write!(f, "pair:{}:{}", token0, token1),
+        }
+    }
+}
+
+/// OHLCV candle; prices are in raw (undecimalized) units
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Candle {
+    /// Bucket start timestamp in seconds
+    pub start: u64,
+    /// Bucket length in seconds
+    pub interval: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Swapped token0 volume in raw units
+    pub volume0: U256,
+    /// Swapped token1 volume in raw units
+    pub volume1: U256,
+    /// Number of swaps in the bucket
+    pub trades: u64,
+    pub first_block: u64,
+    pub last_block: u64,
+}
+
+impl Candle {
+    pub fn new(start: u64, interval: u64, price: f64, block_number: u64) -> Self {
+        Self {
+            start,
+            interval,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume0: U256::ZERO,
+            volume1: U256::ZERO,
+            trades: 0,
+            first_block: block_number,
+            last_block: block_number,
+        }
+    }
+
+    /// Fold a price observation and optional swap volume into the candle
+    pub fn update(&mut self, price: f64, volume: Option<(U256, U256)>, block_number: u64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.last_block = self.last_block.max(block_number);
+        if let Some((volume0, volume1)) = volume {
+            self.volume0 = self.volume0.saturating_add(volume0);
+            self.volume1 = self.volume1.saturating_add(volume1);
+            self.trades += 1;
+        }
+    }
+
+    /// Return the candle with prices inverted (token1 in token0) and volumes swapped
+    pub fn inverted(&self) -> Self {
+        Self {
+            start: self.start,
+            interval: self.interval,
+            open: 1.0 / self.open,
+            high: 1.0 / self.low,
+            low: 1.0 / self.high,
+            close: 1.0 / self.close,
+            volume0: self.volume1,
+            volume1: self.volume0,
+            trades: self.trades,
+            first_block: self.first_block,
+            last_block: self.last_block,
+        }
+    }
+}
+
+/// Extract the absolute (token0, token1) amounts swapped by a swap log
+pub fn swap_volume(log: &Log) -> Option<(U256, U256)> {
+    match log.topic0() {
+        Some(&IUniswapV2Pair::Swap::SIGNATURE_HASH) => {
+            let swap: IUniswapV2Pair::Swap = log.log_decode().ok()?.inner.data;
+            Some((
+                swap.amount0In.saturating_add(swap.amount0Out),
+                swap.amount1In.saturating_add(swap.amount1Out),
+            ))
+        }
+        Some(&IUniswapV3Pool::Swap::SIGNATURE_HASH) => {
+            let swap: IUniswapV3Pool::Swap = log.log_decode().ok()?.inner.data;
+            Some((swap.amount0.unsigned_abs(), swap.amount1.unsigned_abs()))
+        }
+        Some(&IPancakeV3Pool::Swap::SIGNATURE_HASH) => {
+            let swap: IPancakeV3Pool::Swap = log.log_decode().ok()?.inner.data;
+            Some((swap.amount0.unsigned_abs(), swap.amount1.unsigned_abs()))
+        }
+        Some(&IAlgebraPoolSei::Swap::SIGNATURE_HASH) => {
+            let swap: IAlgebraPoolSei::Swap = log.log_decode().ok()?.inner.data;
+            Some((swap.amount0.unsigned_abs(), swap.amount1.unsigned_abs()))
+        }
+        _ => None,
+    }
+}
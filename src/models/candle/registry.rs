@@ -0,0 +1,310 @@
+use crate::core::Database;
+use crate::models::candle::{Candle, CandleKey, DEFAULT_CANDLE_INTERVALS, MAX_CANDLES_PER_REQUEST};
+use alloy::primitives::{Address, U256};
+use anyhow::Result;
+use log::{debug, error, info};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+type CandleSeries = BTreeMap<u64, Candle>;
+
+/// Builds and stores OHLCV candles for the pools of a single chain
+#[derive(Debug)]
+pub struct CandleRegistry {
+    network_id: u64,
+    intervals: Vec<u64>,
+    max_candles: usize,
+    series: Arc<RwLock<HashMap<(CandleKey, u64), CandleSeries>>>,
+    db: Option<Database>,
+}
+
+impl CandleRegistry {
+    pub fn new(
+        network_id: u64,
+        intervals: Vec<u64>,
+        max_candles: usize,
+        db: Option<Database>,
+    ) -> Self {
+        let mut intervals: Vec<u64> = intervals.into_iter().filter(|i| *i > 0).collect();
+        intervals.sort_unstable();
+        intervals.dedup();
+        if intervals.is_empty() {
+            intervals = DEFAULT_CANDLE_INTERVALS.to_vec();
+        }
+        Self {
+            network_id,
+            intervals,
+            max_candles: max_candles.max(1),
+            series: Arc::new(RwLock::new(HashMap::new())),
+            db,
+        }
+    }
+
+    /// Get network ID
+    pub fn get_network_id(&self) -> u64 {
+        self.network_id
+    }
+
+    /// Get the configured candle intervals in seconds
+    pub fn intervals(&self) -> &[u64] {
+        &self.intervals
+    }
+
+    /// Record a pool price observation into the pool and pair series for every interval
+    pub async fn record(
+        &self,
+        pool: Address,
+        (token0, token1): (Address, Address),
+        price: f64,
+        volume: Option<(U256, U256)>,
+        block_number: u64,
+        timestamp: u64,
+    ) {
+        if !price.is_finite() || price <= 0.0 {
+            return;
+        }
+
+        // Pair series are keyed by sorted tokens, so flip the observation when the pool is not
+        let pair_key = CandleKey::pair(token0, token1);
+        let (pair_price, pair_volume) = if token0 < token1 {
+            (price, volume)
+        } else {
+            (1.0 / price, volume.map(|(v0, v1)| (v1, v0)))
+        };
+
+        let mut closed = Vec::new();
+        let mut series = self.series.write().await;
+        for &interval in &self.intervals {
+            for (key, price, volume) in [
+                (CandleKey::Pool(pool), price, volume),
+                (pair_key, pair_price, pair_volume),
+            ] {
+                let candles = series.entry((key, interval)).or_default();
+                let start = timestamp - timestamp % interval;
+                match candles.get_mut(&start) {
+                    Some(candle) => candle.update(price, volume, block_number),
+                    None => {
+                        // A new bucket closes the previous one
+                        if let Some((_, previous)) = candles.iter().next_back() {
+                            closed.push((key, previous.clone()));
+                        }
+                        // Continue a bucket saved before a restart instead of overwriting it
+                        let mut candle = self
+                            .stored(&key, interval, start)
+                            .unwrap_or_else(|| Candle::new(start, interval, price, block_number));
+                        candle.update(price, volume, block_number);
+                        candles.insert(start, candle);
+                    }
+                }
+                while candles.len() > self.max_candles {
+                    candles.pop_first();
+                }
+            }
+        }
+        drop(series);
+
+        for (key, candle) in closed {
+            if let Err(e) = self.persist(&key, &candle) {
+                error!(
+                    "Error saving candle {} {} to database: {}",
+                    key, candle.start, e
+                );
+            }
+        }
+    }
+
+    /// Get candles for a series within [from, to], keeping the latest `limit` entries, at most
+    /// `MAX_CANDLES_PER_REQUEST`
+    pub async fn get_candles(
+        &self,
+        key: CandleKey,
+        interval: u64,
+        from: Option<u64>,
+        to: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<Candle>> {
+        let limit = limit.min(MAX_CANDLES_PER_REQUEST);
+        let range = from.unwrap_or(0)..=to.unwrap_or(u64::MAX);
+        let mut candles = CandleSeries::new();
+        if let Some(db) = &self.db {
+            for result in
+                db.scan_prefix::<_, Candle>(&self.tree_name(), Self::series_prefix(&key, interval))?
+            {
+                let (_, candle) = result?;
+                if range.contains(&candle.start) {
+                    candles.insert(candle.start, candle);
+                    if candles.len() > limit {
+                        candles.pop_first();
+                    }
+                }
+            }
+        }
+        // In-memory buckets include the stored candle they continue
+        if let Some(series) = self.series.read().await.get(&(key, interval)) {
+            candles.extend(
+                series
+                    .range(range)
+                    .map(|(start, candle)| (*start, candle.clone())),
+            );
+        }
+
+        let skip = candles.len().saturating_sub(limit);
+        Ok(candles.into_values().skip(skip).collect())
+    }
+
+    /// Save all in-memory candles to database
+    pub async fn save_to_db(&self) -> Result<()> {
+        if self.db.is_none() {
+            return Ok(());
+        }
+        let series = self.series.read().await;
+        let mut count = 0;
+        for ((key, _), candles) in series.iter() {
+            for candle in candles.values() {
+                self.persist(key, candle)?;
+                count += 1;
+            }
+        }
+        info!("Saved {} candles to database", count);
+        Ok(())
+    }
+
+    fn persist(&self, key: &CandleKey, candle: &Candle) -> Result<()> {
+        if let Some(db) = &self.db {
+            let db_key = Self::db_key(key, candle.interval, candle.start);
+            db.insert(&self.tree_name(), db_key, candle)?;
+            debug!("Saved candle {} {} to database", key, candle.start);
+        }
+        Ok(())
+    }
+
+    /// The saved candle of a bucket, if any
+    fn stored(&self, key: &CandleKey, interval: u64, start: u64) -> Option<Candle> {
+        let db = self.db.as_ref()?;
+        match db.get(&self.tree_name(), Self::db_key(key, interval, start)) {
+            Ok(candle) => candle,
+            Err(e) => {
+                error!(
+                    "Error loading candle {} {} from database: {}",
+                    key, start, e
+                );
+                None
+            }
+        }
+    }
+
+    fn tree_name(&self) -> String {
+        format!("{}-candles", self.network_id)
+    }
+
+    fn series_prefix(key: &CandleKey, interval: u64) -> String {
+        format!("{}-{}-", key, interval)
+    }
+
+    fn db_key(key: &CandleKey, interval: u64, start: u64) -> String {
+        format!("{}{:020}", Self::series_prefix(key, interval), start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_candle_aggregation() {
+        let token0 = Address::repeat_byte(1);
+        let token1 = Address::repeat_byte(2);
+        let pool = Address::repeat_byte(3);
+        let registry = CandleRegistry::new(1, vec![60], 10, None);
+
+        registry
+            .record(pool, (token0, token1), 2.0, None, 100, 120)
+            .await;
+        registry
+            .record(
+                pool,
+                (token0, token1),
+                3.0,
+                Some((U256::from(10), U256::from(30))),
+                101,
+                150,
+            )
+            .await;
+        registry
+            .record(pool, (token0, token1), 1.5, None, 102, 179)
+            .await;
+        registry
+            .record(pool, (token0, token1), 2.5, None, 103, 180)
+            .await;
+
+        let candles = registry
+            .get_candles(CandleKey::Pool(pool), 60, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].start, 120);
+        assert_eq!(candles[0].open, 2.0);
+        assert_eq!(candles[0].high, 3.0);
+        assert_eq!(candles[0].low, 1.5);
+        assert_eq!(candles[0].close, 1.5);
+        assert_eq!(candles[0].volume1, U256::from(30));
+        assert_eq!(candles[0].trades, 1);
+        assert_eq!(candles[0].last_block, 102);
+        assert_eq!(candles[1].open, 2.5);
+
+        // Pair series mirrors the pool because token0 < token1
+        let pair = registry
+            .get_candles(CandleKey::pair(token1, token0), 60, None, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(pair.len(), 1);
+        assert_eq!(pair[0].start, 180);
+    }
+
+    #[tokio::test]
+    async fn test_candles_continue_after_restart() {
+        let token0 = Address::repeat_byte(1);
+        let token1 = Address::repeat_byte(2);
+        let pool = Address::repeat_byte(3);
+        let path = std::env::temp_dir().join(format!("candles-{}", uuid::Uuid::new_v4()));
+        let db = Database::new(path).unwrap();
+
+        let registry = CandleRegistry::new(1, vec![60], 10, Some(db.clone()));
+        let volume = Some((U256::from(10), U256::from(20)));
+        registry
+            .record(pool, (token0, token1), 2.0, volume, 100, 120)
+            .await;
+        registry
+            .record(pool, (token0, token1), 4.0, volume, 101, 130)
+            .await;
+        registry.save_to_db().await.unwrap();
+
+        // The restarted registry adds to the saved bucket instead of replacing it
+        let registry = CandleRegistry::new(1, vec![60], 10, Some(db));
+        registry
+            .record(pool, (token0, token1), 3.0, volume, 102, 150)
+            .await;
+        let candles = registry
+            .get_candles(CandleKey::Pool(pool), 60, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].open, 2.0);
+        assert_eq!(candles[0].high, 4.0);
+        assert_eq!(candles[0].close, 3.0);
+        assert_eq!(candles[0].trades, 3);
+        assert_eq!(candles[0].volume0, U256::from(30));
+        assert_eq!(candles[0].first_block, 100);
+        assert_eq!(candles[0].last_block, 102);
+
+        // Saving again keeps the merged candle
+        registry.save_to_db().await.unwrap();
+        let registry = CandleRegistry::new(1, vec![60], 10, registry.db.clone());
+        let candles = registry
+            .get_candles(CandleKey::Pool(pool), 60, Some(120), Some(120), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(candles[0].trades, 3);
+    }
+}
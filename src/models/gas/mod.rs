@@ -0,0 +1,239 @@
+pub mod registry;
+
+use alloy::primitives::U256;
+use anyhow::Result;
+
+use crate::models::pool::{PoolType, RouteSnapshot, UniswapV3Pool};
+
+pub use registry::GasRegistry;
+
+/// Default interval between gas price refreshes in seconds
+pub const DEFAULT_GAS_UPDATE_INTERVAL: u64 = 15;
+
+/// Gas of a swap transaction outside its pools: intrinsic cost, calldata and router overhead
+pub const BASE_SWAP_GAS: u64 = 50_000;
+/// Gas of a swap through a Uniswap V2 style pool
+pub const V2_SWAP_GAS: u64 = 60_000;
+/// Gas of a swap through a Uniswap V3 style pool that stays within its current tick range
+pub const V3_SWAP_GAS: u64 = 90_000;
+/// Extra gas for each initialized tick a V3 swap crosses
+pub const V3_TICK_CROSS_GAS: u64 = 25_000;
+/// Gas of an ERC4626 deposit or redeem
+pub const ERC4626_SWAP_GAS: u64 = 80_000;
+
+/// Gas of a swap through a pool of this type, before any V3 tick crossings
+pub fn pool_swap_gas(pool_type: PoolType) -> u64 {
+    match pool_type {
+        PoolType::UniswapV2 => V2_SWAP_GAS,
+        PoolType::UniswapV3 => V3_SWAP_GAS,
+        PoolType::ERC4626(_) => ERC4626_SWAP_GAS,
+    }
+}
+
+/// Estimated gas of a swap along `route`, given the amounts entering and leaving each pool
+pub fn estimate_route_gas(route: &RouteSnapshot, hop_amounts: &[U256]) -> u64 {
+    let pools_gas: u64 = route
+        .hops()
+        .iter()
+        .zip(hop_amounts)
+        .map(|(hop, amount_in)| {
+            let ticks_crossed = hop
+                .pool
+                .downcast_ref::<UniswapV3Pool>()
+                .and_then(|pool| pool.ticks_crossed(&hop.token_in, *amount_in).ok())
+                .unwrap_or(0);
+            pool_swap_gas(hop.pool.pool_type()) + ticks_crossed as u64 * V3_TICK_CROSS_GAS
+        })
+        .sum();
+    BASE_SWAP_GAS + pools_gas
+}
+
+/// Raw units of a token worth one raw unit of the native token, from both USD prices
+pub fn native_to_token_rate(
+    native_usd: f64,
+    native_decimals: u8,
+    token_usd: f64,
+    token_decimals: u8,
+) -> Option<f64> {
+    if native_usd <= 0.0 || token_usd <= 0.0 {
+        return None;
+    }
+    Some(native_usd / token_usd * 10f64.powi(token_decimals as i32 - native_decimals as i32))
+}
+
+/// A quoted route with the cost of executing it, for ranking routes against each other
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteCost {
+    pub exact_input: bool,
+    /// Output for exact input quotes, input for exact output quotes
+    pub amount: U256,
+    pub gas: u64,
+    /// Gas cost in raw units of the quoted token, zero if the gas price is unknown
+    pub gas_cost: U256,
+}
+
+impl RouteCost {
+    /// Quote `amount` along `route` and price its gas at `gas_price_in_token`, the price of
+    /// one unit of gas in raw units of the output token (exact input) or input token (exact output)
+    pub fn new(
+        route: &RouteSnapshot,
+        amount: U256,
+        exact_input: bool,
+        gas_price_in_token: Option<f64>,
+    ) -> Result<Self> {
+        let amounts = route.hop_amounts(amount, exact_input)?;
+        Ok(Self::from_hop_amounts(
+            route,
+            &amounts,
+            exact_input,
+            gas_price_in_token,
+        ))
+    }
+
+    /// Cost of a route quote already computed with `RouteSnapshot::hop_amounts`
+    pub fn from_hop_amounts(
+        route: &RouteSnapshot,
+        amounts: &[U256],
+        exact_input: bool,
+        gas_price_in_token: Option<f64>,
+    ) -> Self {
+        let gas = estimate_route_gas(route, amounts);
+        let gas_cost = gas_price_in_token
+            .map(|price| U256::from((gas as f64 * price) as u128))
+            .unwrap_or_default();
+        let amount = if exact_input {
+            amounts[amounts.len() - 1]
+        } else {
+            amounts[0]
+        };
+        Self {
+            exact_input,
+            amount,
+            gas,
+            gas_cost,
+        }
+    }
+
+    /// Output minus gas for exact input quotes, input plus gas for exact output quotes
+    pub fn net_amount(&self) -> U256 {
+        if self.exact_input {
+            self.amount.saturating_sub(self.gas_cost)
+        } else {
+            self.amount.saturating_add(self.gas_cost)
+        }
+    }
+
+    /// Whether this route leaves the trader better off than `other` once gas is paid
+    pub fn is_better_than(&self, other: &RouteCost) -> bool {
+        if self.exact_input {
+            self.net_amount() > other.net_amount()
+        } else {
+            self.net_amount() < other.net_amount()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::v3::{Tick, V3PoolType};
+    use crate::models::pool::MockPool;
+    use alloy::primitives::{aliases::U24, Address, U160};
+    use std::sync::Arc;
+
+    fn pool(address: u8, token0: u8, token1: u8, reserve0: u64, reserve1: u64) -> MockPool {
+        MockPool::new_v2(
+            Address::repeat_byte(address),
+            Address::repeat_byte(token0),
+            Address::repeat_byte(token1),
+            U256::from(reserve0),
+            U256::from(reserve1),
+        )
+    }
+
+    #[test]
+    fn test_native_to_token_rate() {
+        // 1 ETH at 2000 USD is 2000 USDC, so 1 wei is 2000e6 / 1e18 raw USDC
+        let rate = native_to_token_rate(2000.0, 18, 1.0, 6).unwrap();
+        assert!((rate - 2e-9).abs() < 1e-18);
+        assert_eq!(native_to_token_rate(0.0, 18, 1.0, 6), None);
+    }
+
+    #[test]
+    fn test_gas_changes_route_ranking() {
+        let direct = RouteSnapshot::new(
+            vec![Arc::new(pool(0xa, 1, 3, 1_000_000_000, 1_000_000_000))],
+            Address::repeat_byte(1),
+        )
+        .unwrap();
+        // Deeper liquidity across three hops gives more output before gas
+        let long = RouteSnapshot::new(
+            vec![
+                Arc::new(pool(0xb, 1, 2, 100_000_000_000, 100_000_000_000)),
+                Arc::new(pool(0xc, 2, 4, 100_000_000_000, 100_000_000_000)),
+                Arc::new(pool(0xd, 4, 3, 100_000_000_000, 100_000_000_000)),
+            ],
+            Address::repeat_byte(1),
+        )
+        .unwrap();
+        let amount_in = U256::from(10_000_000u64);
+
+        let direct_cost = RouteCost::new(&direct, amount_in, true, None).unwrap();
+        let long_cost = RouteCost::new(&long, amount_in, true, None).unwrap();
+        assert_eq!(direct_cost.gas, BASE_SWAP_GAS + V2_SWAP_GAS);
+        assert_eq!(long_cost.gas, BASE_SWAP_GAS + 3 * V2_SWAP_GAS);
+        assert!(long_cost.is_better_than(&direct_cost));
+
+        // At one raw output unit per gas the extra hops cost more than they gain
+        let direct_cost = RouteCost::new(&direct, amount_in, true, Some(1.0)).unwrap();
+        let long_cost = RouteCost::new(&long, amount_in, true, Some(1.0)).unwrap();
+        assert!(direct_cost.is_better_than(&long_cost));
+
+        let exact_out =
+            RouteCost::new(&direct, U256::from(1_000_000u64), false, Some(1.0)).unwrap();
+        assert_eq!(
+            exact_out.net_amount(),
+            exact_out.amount + exact_out.gas_cost
+        );
+    }
+
+    #[test]
+    fn test_v3_tick_crossing_gas() {
+        let mut pool = UniswapV3Pool::new(
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            U24::from(3000),
+            60,
+            U160::from(1u128 << 96),
+            0,
+            1_000_000_000_000_000_000,
+            Address::ZERO,
+            V3PoolType::UniswapV3,
+        );
+        for (index, liquidity_net) in [
+            (-120, 500_000_000_000_000_000i128),
+            (-60, 500_000_000_000_000_000),
+            (60, -500_000_000_000_000_000),
+            (120, -500_000_000_000_000_000),
+        ] {
+            pool.ticks.insert(
+                index,
+                Tick {
+                    index,
+                    liquidity_net,
+                    liquidity_gross: liquidity_net.unsigned_abs(),
+                },
+            );
+        }
+        let route = RouteSnapshot::new(vec![Arc::new(pool)], Address::repeat_byte(1)).unwrap();
+
+        // A small swap stays between the ticks around the current price
+        let small = RouteCost::new(&route, U256::from(1_000_000_000u64), true, None).unwrap();
+        assert_eq!(small.gas, BASE_SWAP_GAS + V3_SWAP_GAS);
+
+        // Moves the price past tick -60 but not -120
+        let large = RouteCost::new(&route, U256::from(4 * 10u128.pow(15)), true, None).unwrap();
+        assert_eq!(large.gas, BASE_SWAP_GAS + V3_SWAP_GAS + V3_TICK_CROSS_GAS);
+    }
+}
@@ -0,0 +1,64 @@
+use alloy::primitives::Address;
+use alloy::providers::DynProvider;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::blockchain::NetworkConfigurator;
+
+struct ChainGas {
+    network_configurator: Arc<NetworkConfigurator<DynProvider>>,
+    /// Wrapped native token, used to price gas in other tokens
+    wrap_native: Option<Address>,
+}
+
+/// Gas prices per chain, refreshed by each chain's network configurator
+#[derive(Default)]
+pub struct GasRegistry {
+    chains: Arc<RwLock<HashMap<u64, ChainGas>>>,
+}
+
+impl GasRegistry {
+    pub fn new() -> Self {
+        Self {
+            chains: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn add_network(
+        &self,
+        network_id: u64,
+        network_configurator: Arc<NetworkConfigurator<DynProvider>>,
+        wrap_native: Option<Address>,
+    ) {
+        self.chains.write().await.insert(
+            network_id,
+            ChainGas {
+                network_configurator,
+                wrap_native,
+            },
+        );
+    }
+
+    /// Forget a chain's gas prices, the caller stops its network configurator
+    pub async fn remove_network(&self, network_id: u64) {
+        self.chains.write().await.remove(&network_id);
+    }
+
+    /// Get the latest gas price in wei, None until the first refresh succeeds
+    pub async fn get_gas_price(&self, network_id: u64) -> Option<u128> {
+        let network_configurator = self
+            .chains
+            .read()
+            .await
+            .get(&network_id)?
+            .network_configurator
+            .clone();
+        let gas_info = network_configurator.get_gas_info().await;
+        (gas_info.gas_price > 0).then_some(gas_info.gas_price)
+    }
+
+    pub async fn get_wrap_native(&self, network_id: u64) -> Option<Address> {
+        self.chains.read().await.get(&network_id)?.wrap_native
+    }
+}
@@ -1,4 +1,8 @@
+pub mod candle;
+pub mod gas;
 pub mod path;
 pub mod pool;
+pub mod price;
 pub mod profit_token;
+pub mod router;
 pub mod token;
@@ -9,6 +9,7 @@ use std::any::Any;
 use crate::{
     models::pool::{
         erc4626::{ERC4626Pool, VerioIP},
+        update::PoolState,
         UniswapV3Pool,
     },
     UniswapV2Pool,
@@ -34,6 +35,23 @@ pub trait PoolInterface: std::fmt::Debug + Send + Sync + PoolTypeTrait + EventAp
     /// Calculate input amount for a swap given an output amount and token
     fn calculate_input(&self, token_out: &Address, amount_out: U256) -> Result<U256>;
 
+    /// Calculate the output amount of a swap for each input amount, in the order given.
+    /// Pools override this when the amounts can share work, like a V3 tick walk.
+    fn calculate_outputs(&self, token_in: &Address, amounts_in: &[U256]) -> Result<Vec<U256>> {
+        amounts_in
+            .iter()
+            .map(|amount_in| self.calculate_output(token_in, *amount_in))
+            .collect()
+    }
+
+    /// Calculate the input amount of a swap for each output amount, in the order given
+    fn calculate_inputs(&self, token_out: &Address, amounts_out: &[U256]) -> Result<Vec<U256>> {
+        amounts_out
+            .iter()
+            .map(|amount_out| self.calculate_input(token_out, *amount_out))
+            .collect()
+    }
+
     /// Apply a swap to the pool state
     fn apply_swap(&mut self, token_in: &Address, amount_in: U256, amount_out: U256) -> Result<()>;
 
@@ -56,6 +74,22 @@ pub trait PoolInterface: std::fmt::Debug + Send + Sync + PoolTypeTrait + EventAp
     /// Get the pool fee as a fraction (e.g., 0.003 for 0.3%)
     fn fee(&self) -> f64;
 
+    /// Get the spot price of token0 denominated in token1, in raw (undecimalized) units
+    fn spot_price(&self) -> Option<f64> {
+        None
+    }
+
+    /// Get the (token0, token1) reserves backing the current price, in raw units.
+    /// Concentrated liquidity pools report virtual reserves at the current price.
+    fn reserves(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// Get the typed state that pool events update
+    fn state(&self) -> Option<PoolState> {
+        None
+    }
+
     /// Get a unique identifier for the pool
     fn id(&self) -> String;
 
@@ -107,6 +141,18 @@ impl Default for PoolType {
 }
 
 impl PoolType {
+    /// Names of every pool type, as returned by `name`
+    pub const NAMES: [&'static str; 3] = ["UniswapV2", "UniswapV3", "ERC4626"];
+
+    /// Name used in configs and API filters
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UniswapV2 => "UniswapV2",
+            Self::UniswapV3 => "UniswapV3",
+            Self::ERC4626(_) => "ERC4626",
+        }
+    }
+
     pub fn topics(&self) -> Vec<FixedBytes<32>> {
         match self {
             Self::UniswapV2 => UniswapV2Pool::topics(),
@@ -0,0 +1,270 @@
+use alloy::primitives::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::models::pool::route::RouteSnapshot;
+use crate::models::pool::v3::{add_delta, UniswapV3Pool, MAX_TICK_I32, MIN_TICK_I32};
+
+/// Inputs above this are not searched when looking for a price impact
+const MAX_SEARCH_AMOUNT: U256 = U256::from_limbs([0, 0, 0, 1]);
+
+/// Bisection stops once the bracket is narrower than 1 / SEARCH_PRECISION of the input
+const SEARCH_PRECISION: u64 = 10_000;
+
+const MAX_SEARCH_STEPS: usize = 128;
+
+/// Route output and prices at one input size. Prices are output per input, in raw units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthPoint {
+    pub amount_in: U256,
+    /// None if the route cannot fill the input
+    pub amount_out: Option<U256>,
+    pub effective_price: Option<f64>,
+    /// Price paid for the next unit of input after this trade
+    pub marginal_price: Option<f64>,
+    /// Shortfall of the effective price against the mid price, as a fraction
+    pub price_impact: Option<f64>,
+}
+
+/// Active liquidity of a V3 pool between two ticks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityBucket {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+}
+
+/// Shortfall of the effective price of a trade against the mid price, as a fraction
+pub(crate) fn price_impact(amount_in: U256, amount_out: U256, mid_price: f64) -> f64 {
+    let effective_price = f64::from(amount_out) / f64::from(amount_in);
+    (1.0 - effective_price / mid_price).max(0.0)
+}
+
+/// Quote the route at `amount_in` and derive its prices against `mid_price`
+pub fn depth_point(route: &RouteSnapshot, mid_price: f64, amount_in: U256) -> DepthPoint {
+    let amount_out = route
+        .quote_exact_in(amount_in)
+        .ok()
+        .filter(|_| !amount_in.is_zero());
+    let Some(amount_out) = amount_out else {
+        return DepthPoint {
+            amount_in,
+            amount_out: None,
+            effective_price: None,
+            marginal_price: None,
+            price_impact: None,
+        };
+    };
+
+    let step = (amount_in / U256::from(1000)).max(route.probe_amount());
+    let marginal_price = route
+        .quote_exact_in(amount_in + step)
+        .ok()
+        .map(|next_out| f64::from(next_out.saturating_sub(amount_out)) / f64::from(step));
+
+    DepthPoint {
+        amount_in,
+        amount_out: Some(amount_out),
+        effective_price: Some(f64::from(amount_out) / f64::from(amount_in)),
+        marginal_price,
+        price_impact: Some(price_impact(amount_in, amount_out, mid_price)),
+    }
+}
+
+/// Largest input whose price impact stays within `target`, or None if the route
+/// cannot be pushed that far
+pub fn input_for_price_impact(route: &RouteSnapshot, mid_price: f64, target: f64) -> Option<U256> {
+    // A route that cannot fill the input is treated as infinitely deep in impact
+    let impact_at = |amount_in: U256| match route.quote_exact_in(amount_in) {
+        Ok(amount_out) if !amount_out.is_zero() => price_impact(amount_in, amount_out, mid_price),
+        _ => f64::INFINITY,
+    };
+
+    let mut low = route.probe_amount();
+    if impact_at(low) > target {
+        return None;
+    }
+
+    // Double until the target is crossed, then bisect
+    let mut high = low;
+    loop {
+        high = high.checked_mul(U256::from(2))?;
+        if high > MAX_SEARCH_AMOUNT {
+            return None;
+        }
+        if impact_at(high) > target {
+            break;
+        }
+        low = high;
+    }
+
+    for _ in 0..MAX_SEARCH_STEPS {
+        if high - low <= low / U256::from(SEARCH_PRECISION) {
+            break;
+        }
+        let middle = low + (high - low) / U256::from(2);
+        if impact_at(middle) > target {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    Some(low)
+}
+
+/// Active liquidity between initialized ticks within `tick_range` tick spacings of the
+/// current tick, in ascending tick order. Stops early where the tick data is inconsistent.
+pub fn liquidity_distribution(pool: &UniswapV3Pool, tick_range: u32) -> Vec<LiquidityBucket> {
+    let span = (tick_range as i32).saturating_mul(pool.tick_spacing.max(1));
+    let lower_bound = pool.tick.saturating_sub(span).max(MIN_TICK_I32);
+    let upper_bound = pool.tick.saturating_add(span).min(MAX_TICK_I32);
+
+    let below: Vec<_> = pool
+        .ticks
+        .range(lower_bound..=pool.tick)
+        .rev()
+        .map(|(_, tick)| tick)
+        .collect();
+    let above: Vec<_> = pool
+        .ticks
+        .range(pool.tick + 1..=upper_bound)
+        .map(|(_, tick)| tick)
+        .collect();
+
+    // Walk down: crossing a tick downwards removes the liquidity it adds
+    let mut lower_buckets = Vec::new();
+    let mut liquidity = pool.liquidity;
+    for (i, tick) in below.iter().enumerate() {
+        let Ok(next) = add_delta(liquidity, tick.liquidity_net.saturating_neg()) else {
+            break;
+        };
+        liquidity = next;
+        let tick_lower = below.get(i + 1).map_or(lower_bound, |tick| tick.index);
+        if tick_lower < tick.index {
+            lower_buckets.push(LiquidityBucket {
+                tick_lower,
+                tick_upper: tick.index,
+                liquidity,
+            });
+        }
+    }
+
+    let mut buckets: Vec<_> = lower_buckets.into_iter().rev().collect();
+    buckets.push(LiquidityBucket {
+        tick_lower: below.first().map_or(lower_bound, |tick| tick.index),
+        tick_upper: above.first().map_or(upper_bound, |tick| tick.index),
+        liquidity: pool.liquidity,
+    });
+
+    // Walk up: crossing a tick upwards adds its liquidity
+    let mut liquidity = pool.liquidity;
+    for (i, tick) in above.iter().enumerate() {
+        let Ok(next) = add_delta(liquidity, tick.liquidity_net) else {
+            break;
+        };
+        liquidity = next;
+        let tick_upper = above.get(i + 1).map_or(upper_bound, |tick| tick.index);
+        if tick.index < tick_upper {
+            buckets.push(LiquidityBucket {
+                tick_lower: tick.index,
+                tick_upper,
+                liquidity,
+            });
+        }
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::v3::{Tick, V3PoolType};
+    use crate::models::pool::MockPool;
+    use alloy::primitives::{aliases::U24, Address, U160};
+    use std::sync::Arc;
+
+    fn route() -> RouteSnapshot {
+        let pool = MockPool::new_v2(
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            U256::from(1_000_000_000_000u64),
+            U256::from(2_000_000_000_000u64),
+        );
+        RouteSnapshot::new(vec![Arc::new(pool)], Address::repeat_byte(1)).unwrap()
+    }
+
+    #[test]
+    fn test_depth_point_prices() {
+        let route = route();
+        let mid_price = route.mid_price().unwrap();
+        assert!((mid_price - 2.0 * 0.997).abs() < 1e-3);
+
+        let point = depth_point(&route, mid_price, U256::from(10_000_000_000u64));
+        let impact = point.price_impact.unwrap();
+        // 1% of the reserve moves a constant product pool by about 1%
+        assert!((0.009..0.011).contains(&impact), "impact {}", impact);
+        assert!(point.marginal_price.unwrap() < point.effective_price.unwrap());
+    }
+
+    #[test]
+    fn test_input_for_price_impact() {
+        let route = route();
+        let mid_price = route.mid_price().unwrap();
+        for target in [0.005, 0.01, 0.02] {
+            let amount_in = input_for_price_impact(&route, mid_price, target).unwrap();
+            let impact = depth_point(&route, mid_price, amount_in)
+                .price_impact
+                .unwrap();
+            assert!(impact <= target);
+            assert!(
+                impact > target * 0.99,
+                "target {} impact {}",
+                target,
+                impact
+            );
+        }
+    }
+
+    #[test]
+    fn test_liquidity_distribution() {
+        let mut pool = UniswapV3Pool::new(
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            U24::from(3000),
+            60,
+            U160::from(1u128 << 96),
+            0,
+            300,
+            Address::ZERO,
+            V3PoolType::UniswapV3,
+        );
+        for (index, liquidity_net) in [(-120, 100), (-60, 200), (60, -200), (120, -100)] {
+            pool.ticks.insert(
+                index,
+                Tick {
+                    index,
+                    liquidity_net,
+                    liquidity_gross: liquidity_net.unsigned_abs(),
+                },
+            );
+        }
+
+        let buckets = liquidity_distribution(&pool, 3);
+        let summary: Vec<_> = buckets
+            .iter()
+            .map(|bucket| (bucket.tick_lower, bucket.tick_upper, bucket.liquidity))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (-180, -120, 0),
+                (-120, -60, 100),
+                (-60, 60, 300),
+                (60, 120, 100),
+                (120, 180, 0),
+            ]
+        );
+    }
+}
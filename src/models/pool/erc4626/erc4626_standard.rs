@@ -2,6 +2,7 @@ use crate::blockchain::IERC4626;
 use crate::models::pool::base::TopicList;
 use crate::models::pool::erc4626::verio_ip::fetch_verio_ip_pool;
 use crate::models::pool::erc4626::ERC4626Pool;
+use crate::models::pool::update::PoolState;
 use crate::models::pool::EventApplicable;
 use crate::PoolInterface;
 use alloy::eips::BlockId;
@@ -143,6 +144,27 @@ impl ERC4626Standard {
         self.deposit_fee as f64 / FEE_DENOMINATOR as f64
     }
 
+    /// Get the price of one vault token in asset tokens, in raw units
+    pub fn spot_price(&self) -> Option<f64> {
+        if self.vault_reserve.is_zero() {
+            return None;
+        }
+        Some(f64::from(self.asset_reserve) / f64::from(self.vault_reserve))
+    }
+
+    /// Get the (vault, asset) reserves, in raw units
+    pub fn reserves(&self) -> Option<(f64, f64)> {
+        Some((f64::from(self.vault_reserve), f64::from(self.asset_reserve)))
+    }
+
+    /// Get the vault share supply and underlying assets
+    pub fn state(&self) -> PoolState {
+        PoolState::Vault {
+            vault_reserve: self.vault_reserve,
+            asset_reserve: self.asset_reserve,
+        }
+    }
+
     /// Get a unique identifier for the pool
     pub fn id(&self) -> String {
         self.address.to_string()
@@ -2,6 +2,7 @@ use crate::blockchain::{IVerioIP, IERC4626};
 use crate::core::Database;
 use crate::models::pool::base::{PoolTypeTrait, TopicList};
 use crate::models::pool::erc4626::{ERC4626Pool, ERC4626Standard};
+use crate::models::pool::update::PoolState;
 use crate::models::pool::EventApplicable;
 use crate::models::token::TokenRegistry;
 use crate::{PoolInterface, PoolType};
@@ -128,6 +129,20 @@ impl PoolInterface for VerioIP {
         self.base.fee()
     }
 
+    /// Get the price of one vault token in asset tokens, in raw units
+    fn spot_price(&self) -> Option<f64> {
+        self.base.spot_price()
+    }
+
+    /// Get the (vault, asset) reserves, in raw units
+    fn reserves(&self) -> Option<(f64, f64)> {
+        self.base.reserves()
+    }
+
+    fn state(&self) -> Option<PoolState> {
+        Some(self.base.state())
+    }
+
     /// Get a unique identifier for the pool
     fn id(&self) -> String {
         self.base.id()
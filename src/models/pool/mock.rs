@@ -2,12 +2,14 @@ use alloy::{
     primitives::{Address, FixedBytes, U256},
     rpc::types::Log,
 };
-use anyhow::{anyhow, Ok, Result};
+use anyhow::{Ok, Result};
 use std::any::Any;
 
 use crate::models::pool::base::TopicList;
+use crate::utils::errors::ArbitrageError;
 
 use super::base::{EventApplicable, PoolInterface, PoolType, PoolTypeTrait};
+use super::update::PoolState;
 
 #[derive(Debug, Clone)]
 pub struct MockPool {
@@ -101,7 +103,11 @@ impl PoolInterface for MockPool {
             let denominator = self.reserve1 * U256::from(1000) + amount_in_with_fee;
             Ok(numerator / denominator)
         } else {
-            Err(anyhow!("Token not in pool"))
+            Err(ArbitrageError::TokenNotInPool {
+                token: *token_in,
+                pool: self.address,
+            }
+            .into())
         }
     }
 
@@ -115,25 +121,39 @@ impl PoolInterface for MockPool {
             let denominator = (self.reserve1 - amount_out) * U256::from(997);
             Ok((numerator / denominator) + U256::from(1))
         } else {
-            Err(anyhow!("Token not in pool"))
+            Err(ArbitrageError::TokenNotInPool {
+                token: *token_out,
+                pool: self.address,
+            }
+            .into())
         }
     }
 
     fn apply_swap(&mut self, token_in: &Address, amount_in: U256, amount_out: U256) -> Result<()> {
         if token_in == &self.token0 {
             if amount_out >= self.reserve1 {
-                return Err(anyhow!("Insufficient liquidity for swap"));
+                return Err(ArbitrageError::InsufficientLiquidity(
+                    "swap exceeds the pool reserves".to_string(),
+                )
+                .into());
             }
             self.reserve0 += amount_in;
             self.reserve1 -= amount_out;
         } else if token_in == &self.token1 {
             if amount_out >= self.reserve0 {
-                return Err(anyhow!("Insufficient liquidity for swap"));
+                return Err(ArbitrageError::InsufficientLiquidity(
+                    "swap exceeds the pool reserves".to_string(),
+                )
+                .into());
             }
             self.reserve1 += amount_in;
             self.reserve0 -= amount_out;
         } else {
-            return Err(anyhow!("Token not in pool"));
+            return Err(ArbitrageError::TokenNotInPool {
+                token: *token_in,
+                pool: self.address,
+            }
+            .into());
         }
 
         self.last_updated = chrono::Utc::now().timestamp() as u64;
@@ -156,6 +176,24 @@ impl PoolInterface for MockPool {
         self.fee
     }
 
+    fn spot_price(&self) -> Option<f64> {
+        if self.reserve0.is_zero() {
+            return None;
+        }
+        Some(f64::from(self.reserve1) / f64::from(self.reserve0))
+    }
+
+    fn reserves(&self) -> Option<(f64, f64)> {
+        Some((f64::from(self.reserve0), f64::from(self.reserve1)))
+    }
+
+    fn state(&self) -> Option<PoolState> {
+        Some(PoolState::Reserves {
+            reserve0: self.reserve0,
+            reserve1: self.reserve1,
+        })
+    }
+
     fn id(&self) -> String {
         format!(
             "mock-{}-{}-{}-{:?}",
@@ -1,16 +1,29 @@
 pub mod base;
+pub mod depth;
 // pub mod simulator;
 
 pub mod erc4626;
 pub mod mock;
 pub mod multichain_registry;
+pub mod quote;
+pub mod quote_cache;
 pub mod registry;
+pub mod route;
+pub mod snapshot;
+pub mod sync;
+pub mod update;
 pub mod v2;
 pub mod v3;
 
 pub use base::{EventApplicable, PoolInterface, PoolType};
 // pub use simulator::{PoolCache, PoolSimulator};
 pub use mock::MockPool;
+pub use quote::RouteQuote;
+pub use quote_cache::{QuoteCache, QuoteCacheKey, QuoteCacheStats};
 pub use registry::PoolRegistry;
+pub use route::RouteSnapshot;
+pub use snapshot::PoolSnapshot;
+pub use sync::{SyncError, SyncStatus, MAX_HEAD_AGE_SECS};
+pub use update::{PoolState, PoolUpdate, PoolUpdateLog};
 pub use v2::UniswapV2Pool;
 pub use v3::UniswapV3Pool;
@@ -0,0 +1,226 @@
+use alloy::primitives::{Address, U256};
+use anyhow::Result;
+
+use crate::models::pool::depth::price_impact;
+use crate::models::pool::route::RouteSnapshot;
+use crate::utils::errors::ArbitrageError;
+
+/// Basis points denominator for slippage tolerances
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Pool fees are applied in millionths, the finest granularity of a V3 fee tier
+const FEE_PRECISION: f64 = 1_000_000.0;
+
+/// Lower the quoted output (exact input) or raise the quoted input (exact output) by a tolerance
+pub fn apply_slippage(amount: U256, slippage_bps: u64, exact_input: bool) -> U256 {
+    let bps = U256::from(slippage_bps.min(BPS_DENOMINATOR));
+    let denominator = U256::from(BPS_DENOMINATOR);
+    if exact_input {
+        amount * (denominator - bps) / denominator
+    } else {
+        // Round up so the limit never falls below the tolerance
+        (amount * (denominator + bps)).div_ceil(denominator)
+    }
+}
+
+/// Amounts swapped through one pool of a quoted route
+#[derive(Debug, Clone, PartialEq)]
+pub struct HopQuote {
+    pub pool: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    /// Pool fee taken from the input, in `token_in`
+    pub fee_amount: U256,
+}
+
+/// A route quote with its prices. Prices are output per input, in raw units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteQuote {
+    pub exact_input: bool,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub hops: Vec<HopQuote>,
+    /// Price of a probe-sized trade, net of fees
+    pub mid_price: f64,
+    pub effective_price: f64,
+    /// Shortfall of the effective price against the mid price, as a fraction
+    pub price_impact: f64,
+}
+
+impl RouteQuote {
+    /// Quote `amount` along `route`: the input for exact input quotes, the output otherwise
+    pub fn new(route: &RouteSnapshot, amount: U256, exact_input: bool) -> Result<Self> {
+        if amount.is_zero() {
+            return Err(
+                ArbitrageError::InvalidAmount("must be greater than zero".to_string()).into(),
+            );
+        }
+        Self::from_hop_amounts(route, &route.hop_amounts(amount, exact_input)?, exact_input)
+    }
+
+    /// Build a quote from amounts already computed with `RouteSnapshot::hop_amounts`
+    pub fn from_hop_amounts(
+        route: &RouteSnapshot,
+        amounts: &[U256],
+        exact_input: bool,
+    ) -> Result<Self> {
+        let fixed = if exact_input {
+            amounts[0]
+        } else {
+            amounts[amounts.len() - 1]
+        };
+        if fixed.is_zero() {
+            return Err(
+                ArbitrageError::InvalidAmount("must be greater than zero".to_string()).into(),
+            );
+        }
+
+        let hops: Vec<HopQuote> = route
+            .hops()
+            .iter()
+            .zip(amounts.windows(2))
+            .map(|(hop, amounts)| HopQuote {
+                pool: hop.pool.address(),
+                token_in: hop.token_in,
+                token_out: hop.token_out,
+                amount_in: amounts[0],
+                amount_out: amounts[1],
+                fee_amount: amounts[0]
+                    * U256::from((hop.pool.fee() * FEE_PRECISION).round() as u64)
+                    / U256::from(FEE_PRECISION as u64),
+            })
+            .collect();
+
+        let amount_in = amounts[0];
+        let amount_out = amounts[amounts.len() - 1];
+        if amount_out.is_zero() {
+            return Err(
+                ArbitrageError::InsufficientLiquidity("route has no output".to_string()).into(),
+            );
+        }
+        let mid_price = route.mid_price()?;
+
+        Ok(Self {
+            exact_input,
+            amount_in,
+            amount_out,
+            hops,
+            mid_price,
+            effective_price: f64::from(amount_out) / f64::from(amount_in),
+            price_impact: price_impact(amount_in, amount_out, mid_price),
+        })
+    }
+
+    /// Minimum output within `slippage_bps`. Exact output quotes always receive the full output.
+    pub fn amount_out_min(&self, slippage_bps: u64) -> U256 {
+        if self.exact_input {
+            apply_slippage(self.amount_out, slippage_bps, true)
+        } else {
+            self.amount_out
+        }
+    }
+
+    /// Maximum input within `slippage_bps`. Exact input quotes always spend the full input.
+    pub fn amount_in_max(&self, slippage_bps: u64) -> U256 {
+        if self.exact_input {
+            self.amount_in
+        } else {
+            apply_slippage(self.amount_in, slippage_bps, false)
+        }
+    }
+
+    /// The limit a swap of this quote is protected by: minimum output for exact input,
+    /// maximum input for exact output
+    pub fn amount_limit(&self, slippage_bps: u64) -> U256 {
+        if self.exact_input {
+            self.amount_out_min(slippage_bps)
+        } else {
+            self.amount_in_max(slippage_bps)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::MockPool;
+    use std::sync::Arc;
+
+    fn route() -> RouteSnapshot {
+        let first = MockPool::new_v2(
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            U256::from(1_000_000_000_000u64),
+            U256::from(2_000_000_000_000u64),
+        );
+        let second = MockPool::new_v2(
+            Address::repeat_byte(0xb),
+            Address::repeat_byte(2),
+            Address::repeat_byte(3),
+            U256::from(2_000_000_000_000u64),
+            U256::from(2_000_000_000_000u64),
+        );
+        RouteSnapshot::new(
+            vec![Arc::new(first), Arc::new(second)],
+            Address::repeat_byte(1),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_slippage() {
+        assert_eq!(
+            apply_slippage(U256::from(10_000), 50, true),
+            U256::from(9_950)
+        );
+        assert_eq!(
+            apply_slippage(U256::from(10_001), 50, false),
+            U256::from(10_052)
+        );
+    }
+
+    #[test]
+    fn test_exact_input_quote() {
+        let route = route();
+        let amount_in = U256::from(10_000_000_000u64);
+        let quote = RouteQuote::new(&route, amount_in, true).unwrap();
+
+        assert_eq!(quote.amount_in, amount_in);
+        assert_eq!(quote.amount_out, route.quote_exact_in(amount_in).unwrap());
+        assert_eq!(quote.hops.len(), 2);
+        assert_eq!(quote.hops[0].amount_out, quote.hops[1].amount_in);
+        assert_eq!(quote.hops[1].amount_out, quote.amount_out);
+        assert_eq!(quote.hops[0].fee_amount, U256::from(30_000_000u64));
+        assert!(quote.effective_price < quote.mid_price);
+        assert!(quote.price_impact > 0.0);
+
+        assert_eq!(quote.amount_in_max(50), amount_in);
+        assert_eq!(
+            quote.amount_out_min(50),
+            apply_slippage(quote.amount_out, 50, true)
+        );
+        assert_eq!(quote.amount_limit(50), quote.amount_out_min(50));
+    }
+
+    #[test]
+    fn test_exact_output_quote() {
+        let route = route();
+        let amount_out = U256::from(10_000_000_000u64);
+        let quote = RouteQuote::new(&route, amount_out, false).unwrap();
+
+        assert_eq!(quote.amount_out, amount_out);
+        assert_eq!(quote.amount_in, route.quote_exact_out(amount_out).unwrap());
+        assert_eq!(quote.hops[0].amount_in, quote.amount_in);
+        assert_eq!(quote.hops[0].amount_out, quote.hops[1].amount_in);
+
+        assert_eq!(quote.amount_out_min(50), amount_out);
+        assert_eq!(
+            quote.amount_in_max(50),
+            apply_slippage(quote.amount_in, 50, false)
+        );
+        assert_eq!(quote.amount_limit(50), quote.amount_in_max(50));
+    }
+}
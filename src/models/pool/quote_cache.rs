@@ -0,0 +1,292 @@
+use alloy::primitives::{Address, U256};
+use anyhow::Result;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::models::pool::route::RouteSnapshot;
+use crate::models::pool::snapshot::PoolSnapshot;
+
+/// Default number of route quotes kept per process
+pub const DEFAULT_QUOTE_CACHE_SIZE: usize = 10_000;
+
+/// A route quote is identified by its pools, direction, amount and the state version of every
+/// pool, so an entry can never be served once any of its pools changed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuoteCacheKey {
+    pub network_id: u64,
+    pub pools: Vec<Address>,
+    pub token_in: Address,
+    pub amount: U256,
+    pub exact_input: bool,
+    pub versions: Vec<u64>,
+}
+
+impl QuoteCacheKey {
+    /// Key for quoting `route` as of `snapshot`, None if a pool is not in the snapshot
+    pub fn new(
+        network_id: u64,
+        snapshot: &PoolSnapshot,
+        route: &RouteSnapshot,
+        amount: U256,
+        exact_input: bool,
+    ) -> Option<Self> {
+        let pools: Vec<Address> = route.hops().iter().map(|hop| hop.pool.address()).collect();
+        let versions = pools
+            .iter()
+            .map(|pool| snapshot.pool_version(pool))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            network_id,
+            pools,
+            token_in: route.token_in(),
+            amount,
+            exact_input,
+            versions,
+        })
+    }
+}
+
+/// Hit and miss counters of a quote cache
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuoteCacheStats {
+    pub capacity: usize,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub bypassed: u64,
+}
+
+impl QuoteCacheStats {
+    /// Fraction of lookups served from the cache, zero before the first lookup
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Least recently used cache of route hop amounts. Entries are keyed by pool state versions,
+/// so updates never need to invalidate them; stale entries simply age out.
+#[derive(Debug)]
+pub struct QuoteCache {
+    entries: Option<Mutex<LruCache<QuoteCacheKey, Arc<Vec<U256>>>>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    bypassed: AtomicU64,
+}
+
+impl Default for QuoteCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUOTE_CACHE_SIZE)
+    }
+}
+
+impl QuoteCache {
+    /// Create a cache holding up to `capacity` quotes, zero disables caching
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: NonZeroUsize::new(capacity).map(|size| Mutex::new(LruCache::new(size))),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            bypassed: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the hop amounts for `key`, computing and storing them on a miss. Without a key,
+    /// or when `bypass` is set, the amounts are always computed and never stored.
+    pub fn get_or_compute(
+        &self,
+        key: Option<QuoteCacheKey>,
+        bypass: bool,
+        compute: impl FnOnce() -> Result<Vec<U256>>,
+    ) -> Result<Arc<Vec<U256>>> {
+        let (Some(entries), Some(key), false) = (&self.entries, key, bypass) else {
+            self.bypassed.fetch_add(1, Ordering::Relaxed);
+            return compute().map(Arc::new);
+        };
+
+        if let Some(amounts) = entries.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Arc::clone(amounts));
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        // Computed without holding the lock, a concurrent miss for the same key just stores
+        // the same amounts twice
+        let amounts = Arc::new(compute()?);
+        entries.lock().unwrap().put(key, Arc::clone(&amounts));
+        Ok(amounts)
+    }
+
+    /// Like `get_or_compute` for several keys at once. `compute` gets the indexes of the keys
+    /// that missed and returns their hop amounts in that order.
+    pub fn get_or_compute_many(
+        &self,
+        keys: Vec<Option<QuoteCacheKey>>,
+        bypass: bool,
+        compute: impl FnOnce(&[usize]) -> Result<Vec<Vec<U256>>>,
+    ) -> Result<Vec<Arc<Vec<U256>>>> {
+        let mut results: Vec<Option<Arc<Vec<U256>>>> = vec![None; keys.len()];
+        let mut missing = Vec::new();
+        match (&self.entries, bypass) {
+            (Some(entries), false) => {
+                let mut entries = entries.lock().unwrap();
+                for (index, key) in keys.iter().enumerate() {
+                    match key.as_ref().and_then(|key| entries.get(key)) {
+                        Some(amounts) => {
+                            self.hits.fetch_add(1, Ordering::Relaxed);
+                            results[index] = Some(Arc::clone(amounts));
+                        }
+                        None if key.is_some() => {
+                            self.misses.fetch_add(1, Ordering::Relaxed);
+                            missing.push(index);
+                        }
+                        None => {
+                            self.bypassed.fetch_add(1, Ordering::Relaxed);
+                            missing.push(index);
+                        }
+                    }
+                }
+            }
+            _ => {
+                self.bypassed
+                    .fetch_add(keys.len() as u64, Ordering::Relaxed);
+                missing.extend(0..keys.len());
+            }
+        }
+
+        if !missing.is_empty() {
+            let computed = compute(&missing)?;
+            let mut entries = match (&self.entries, bypass) {
+                (Some(entries), false) => Some(entries.lock().unwrap()),
+                _ => None,
+            };
+            for (index, amounts) in missing.into_iter().zip(computed) {
+                let amounts = Arc::new(amounts);
+                if let (Some(entries), Some(key)) = (entries.as_mut(), keys[index].clone()) {
+                    entries.put(key, Arc::clone(&amounts));
+                }
+                results[index] = Some(amounts);
+            }
+        }
+        Ok(results.into_iter().map(Option::unwrap).collect())
+    }
+
+    pub fn stats(&self) -> QuoteCacheStats {
+        QuoteCacheStats {
+            capacity: self.capacity,
+            entries: self
+                .entries
+                .as_ref()
+                .map_or(0, |entries| entries.lock().unwrap().len()),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            bypassed: self.bypassed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::MockPool;
+    use std::collections::HashMap;
+
+    fn snapshot(version: u64) -> PoolSnapshot {
+        let pool = MockPool::new_v2(
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            U256::from(1_000_000_000u64),
+            U256::from(2_000_000_000u64),
+        );
+        PoolSnapshot::new(
+            1,
+            HashMap::from([(Address::repeat_byte(0xa), Arc::new(pool) as _)]),
+            HashMap::from([(Address::repeat_byte(0xa), version)]),
+        )
+    }
+
+    #[test]
+    fn test_cache_is_keyed_by_pool_versions() {
+        let cache = QuoteCache::new(2);
+        let amount = U256::from(1_000_000u64);
+        let quote = |snapshot: &PoolSnapshot, bypass: bool| {
+            let route = snapshot
+                .route(&[Address::repeat_byte(0xa)], Address::repeat_byte(1))
+                .unwrap();
+            let key = QuoteCacheKey::new(1, snapshot, &route, amount, true);
+            cache
+                .get_or_compute(key, bypass, || route.hop_amounts(amount, true))
+                .unwrap()
+        };
+
+        let first = snapshot(1);
+        let amounts = quote(&first, false);
+        assert!(Arc::ptr_eq(&amounts, &quote(&first, false)));
+        assert!(!Arc::ptr_eq(&amounts, &quote(&first, true)));
+
+        // A new state version misses even though the route and amount are unchanged
+        quote(&snapshot(2), false);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.bypassed), (1, 2, 1));
+        assert_eq!(stats.entries, 2);
+        assert!((stats.hit_rate() - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_batch_only_computes_misses() {
+        let cache = QuoteCache::new(10);
+        let snapshot = snapshot(1);
+        let route = snapshot
+            .route(&[Address::repeat_byte(0xa)], Address::repeat_byte(1))
+            .unwrap();
+        let amounts = [1_000u64, 2_000, 3_000].map(U256::from);
+        let keys = |amounts: &[U256]| {
+            amounts
+                .iter()
+                .map(|&amount| QuoteCacheKey::new(1, &snapshot, &route, amount, true))
+                .collect::<Vec<_>>()
+        };
+        let first = cache
+            .get_or_compute(keys(&amounts[1..2]).pop().unwrap(), false, || {
+                route.hop_amounts(amounts[1], true)
+            })
+            .unwrap();
+
+        let quotes = cache
+            .get_or_compute_many(keys(&amounts), false, |missing| {
+                assert_eq!(missing, [0, 2]);
+                let missing: Vec<U256> = missing.iter().map(|&index| amounts[index]).collect();
+                route.hop_amounts_many(&missing, true)
+            })
+            .unwrap();
+        assert!(Arc::ptr_eq(&quotes[1], &first));
+        for (quote, amount) in quotes.iter().zip(amounts) {
+            assert_eq!(**quote, route.hop_amounts(amount, true).unwrap());
+        }
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 3));
+    }
+
+    #[test]
+    fn test_disabled_cache_always_computes() {
+        let cache = QuoteCache::new(0);
+        for _ in 0..2 {
+            cache
+                .get_or_compute(None, false, || Ok(vec![U256::from(1)]))
+                .unwrap();
+        }
+        assert_eq!(cache.stats().bypassed, 2);
+        assert_eq!(cache.stats().hit_rate(), 0.0);
+    }
+}
@@ -1,39 +1,132 @@
 use crate::core::Database;
+use crate::models::candle::{swap_volume, CandleRegistry};
 use crate::models::pool::base::{PoolInterface, PoolType, Topic};
 use crate::models::pool::erc4626::{ERC4626Pool, VerioIP};
+use crate::models::pool::snapshot::PoolSnapshot;
+use crate::models::pool::sync::{SyncStatus, DEFAULT_MAX_SYNC_LAG};
+use crate::models::pool::update::{PoolUpdate, PoolUpdateLog};
 use crate::models::pool::v2::UniswapV2Pool;
 use crate::models::pool::v3::UniswapV3Pool;
+use crate::utils::metrics::Metrics;
 use alloy::primitives::Address;
+use alloy::rpc::types::Log;
 use anyhow::Result;
-use log::info;
+use dashmap::DashMap;
+use log::{debug, info};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, Mutex, MutexGuard, RwLock};
 
-#[derive(Debug, Default)]
+/// Last pool state version handed out, shared by every registry so a chain started again never
+/// reuses the versions its cached quotes were keyed by
+static STATE_VERSION: AtomicU64 = AtomicU64::new(0);
+
+/// Live state of a pool, shared with the updaters that write it
+pub type LivePool = Arc<RwLock<Box<dyn PoolInterface + Send + Sync>>>;
+
+#[derive(Debug)]
 pub struct PoolRegistry {
-    by_address: Arc<RwLock<HashMap<Address, Arc<RwLock<Box<dyn PoolInterface + Send + Sync>>>>>>,
+    by_address: Arc<DashMap<Address, LivePool>>, // Live pool states, written by the updaters
     by_type: Arc<RwLock<HashMap<PoolType, Vec<Address>>>>,
     token_graph: Arc<RwLock<HashMap<Address, HashMap<Address, Vec<Address>>>>>, // New: token -> neighbor -> pools
     last_processed_block: Arc<RwLock<u64>>,
     topics: Arc<RwLock<Vec<Topic>>>,
     profitable_topics: Arc<RwLock<HashSet<Topic>>>,
+    candle_registry: Option<Arc<CandleRegistry>>,
+    metrics: Option<Arc<Metrics>>,
+    sync_status: Arc<SyncStatus>, // Head, feeds and errors of the chain's updater
+    max_sync_lag: u64,            // Blocks behind the head before the chain is not ready
+    update_log: Arc<PoolUpdateLog>,
+    last_updated_blocks: Arc<RwLock<HashMap<Address, u64>>>,
+    synced_blocks: Arc<RwLock<HashMap<Address, u64>>>, // Block pools added live were fetched at
+    snapshot: Arc<std::sync::RwLock<Arc<PoolSnapshot>>>, // Latest published pool states
+    publish_lock: Arc<Mutex<()>>,                      // Serializes registry writers
+    updates_lock: Arc<Mutex<()>>, // Held by the updater for a batch and while pools are added live
     network_id: u64,
 }
 
+impl Default for PoolRegistry {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
 impl PoolRegistry {
     pub fn new(network_id: u64) -> Self {
         Self {
-            by_address: Arc::new(RwLock::new(HashMap::new())),
+            by_address: Arc::new(DashMap::new()),
             by_type: Arc::new(RwLock::new(HashMap::new())),
             token_graph: Arc::new(RwLock::new(HashMap::new())), // Initialize token_graph
             last_processed_block: Arc::new(RwLock::new(0)),
             topics: Arc::new(RwLock::new(Vec::new())),
             profitable_topics: Arc::new(RwLock::new(HashSet::new())),
+            candle_registry: None,
+            metrics: None,
+            sync_status: Arc::new(SyncStatus::default()),
+            max_sync_lag: DEFAULT_MAX_SYNC_LAG,
+            update_log: Arc::new(PoolUpdateLog::default()),
+            last_updated_blocks: Arc::new(RwLock::new(HashMap::new())),
+            synced_blocks: Arc::new(RwLock::new(HashMap::new())),
+            snapshot: Arc::new(std::sync::RwLock::new(Arc::new(PoolSnapshot::default()))),
+            publish_lock: Arc::new(Mutex::new(())),
+            updates_lock: Arc::new(Mutex::new(())),
             network_id,
         }
     }
 
+    /// Subscribe to notifications for every event applied to a pool
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<PoolUpdate> {
+        self.update_log.subscribe()
+    }
+
+    /// Get the ordered log of applied updates, used to resume streams from a cursor
+    pub fn update_log(&self) -> Arc<PoolUpdateLog> {
+        Arc::clone(&self.update_log)
+    }
+
+    /// Set the candle registry fed by applied events
+    pub fn set_candle_registry(&mut self, candle_registry: Arc<CandleRegistry>) {
+        self.candle_registry = Some(candle_registry);
+    }
+
+    /// Get the candle registry, if candles are enabled
+    pub fn candle_registry(&self) -> Option<Arc<CandleRegistry>> {
+        self.candle_registry.clone()
+    }
+
+    /// Record applied events and processed blocks in the given metrics
+    pub fn set_metrics(&mut self, metrics: Arc<Metrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Get the metrics, if the registry records them
+    pub fn metrics(&self) -> Option<Arc<Metrics>> {
+        self.metrics.clone()
+    }
+
+    /// Sync progress of the chain, updated by its updater and websocket feeds
+    pub fn sync_status(&self) -> Arc<SyncStatus> {
+        self.sync_status.clone()
+    }
+
+    /// Set how many blocks the chain may fall behind its head and still be ready
+    pub fn set_max_sync_lag(&mut self, max_sync_lag: u64) {
+        self.max_sync_lag = max_sync_lag;
+    }
+
+    pub fn max_sync_lag(&self) -> u64 {
+        self.max_sync_lag
+    }
+
+    /// Record the latest block reported by the chain
+    pub fn record_chain_head(&self, head: u64) {
+        self.sync_status.set_head_block(head);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_chain_head(self.network_id, head);
+        }
+    }
+
     /// Set network ID for this registry
     pub fn set_network_id(&mut self, network_id: u64) {
         self.network_id = network_id;
@@ -46,17 +139,104 @@ impl PoolRegistry {
 
     /// Get total pool count
     pub async fn pool_count(&self) -> usize {
-        self.by_address.read().await.len()
+        self.by_address.len()
+    }
+
+    /// Get the latest published pool states. The snapshot never changes, so reads across
+    /// several pools see them all as of the same block.
+    pub fn snapshot(&self) -> Arc<PoolSnapshot> {
+        Arc::clone(&self.snapshot.read().unwrap())
+    }
+
+    /// Publish a snapshot with fresh copies of the `changed` pools, sharing the rest with the
+    /// previous snapshot. Changed pools get a new state version and the block only moves
+    /// forward. Callers hold the publish lock.
+    async fn publish_snapshot(&self, changed: &HashSet<Address>, block_number: Option<u64>) {
+        let previous = self.snapshot();
+        let block_number = block_number
+            .unwrap_or_default()
+            .max(previous.block_number());
+
+        let next = if changed.is_empty() {
+            previous.at_block(block_number)
+        } else {
+            let version = STATE_VERSION.fetch_add(1, Ordering::Relaxed) + 1;
+            let mut pools = previous.pools().clone();
+            let mut versions = previous.versions().clone();
+            let mut membership_changed = false;
+            for address in changed {
+                match self.get_pool(address).await {
+                    Some(pool) => {
+                        let state = Arc::from(pool.read().await.clone_box());
+                        membership_changed |= pools.insert(*address, state).is_none();
+                        versions.insert(*address, version);
+                    }
+                    None => {
+                        membership_changed |= pools.remove(address).is_some();
+                        versions.remove(address);
+                    }
+                }
+            }
+            if membership_changed {
+                PoolSnapshot::new(block_number, pools, versions)
+            } else {
+                previous.with_pools(block_number, pools, versions)
+            }
+        };
+
+        *self.snapshot.write().unwrap() = Arc::new(next);
     }
 
     pub async fn add_pool(&self, pool: Box<dyn PoolInterface + Send + Sync>) {
+        let _publish = self.publish_lock.lock().await;
+        let address = pool.address();
+        self.insert_pool(pool).await;
+        self.publish_snapshot(&HashSet::from([address]), None).await;
+    }
+
+    /// Add a pool fetched at `block_number`. Its events up to that block are already in the
+    /// fetched state, so they are skipped. While the chain is being updated, callers hold
+    /// `lock_updates` from fetching the pool until it is added, so no later event is missed.
+    pub async fn add_pool_at(&self, pool: Box<dyn PoolInterface + Send + Sync>, block_number: u64) {
+        self.add_pools_at(vec![pool], block_number).await;
+    }
+
+    /// Add pools all fetched at `block_number` like `add_pool_at`, publishing them in a
+    /// single snapshot
+    pub async fn add_pools_at(
+        &self,
+        pools: Vec<Box<dyn PoolInterface + Send + Sync>>,
+        block_number: u64,
+    ) {
+        let _publish = self.publish_lock.lock().await;
+        let added: HashSet<Address> = pools.iter().map(|pool| pool.address()).collect();
+        {
+            let mut synced_blocks = self.synced_blocks.write().await;
+            let mut last_updated_blocks = self.last_updated_blocks.write().await;
+            for address in &added {
+                synced_blocks.insert(*address, block_number);
+                last_updated_blocks.insert(*address, block_number);
+            }
+        }
+        for pool in pools {
+            self.insert_pool(pool).await;
+        }
+        self.publish_snapshot(&added, None).await;
+    }
+
+    /// Wait for the updater to finish its batch and keep it from starting the next one
+    pub async fn lock_updates(&self) -> MutexGuard<'_, ()> {
+        self.updates_lock.lock().await
+    }
+
+    /// Add a pool without publishing a snapshot
+    async fn insert_pool(&self, pool: Box<dyn PoolInterface + Send + Sync>) {
         let address = pool.address();
         let pool_type = pool.pool_type();
 
         let (token0, token1) = pool.tokens();
         // Add to address map
-        let mut address_map = self.by_address.write().await;
-        address_map.insert(address, Arc::new(RwLock::new(pool)));
+        self.by_address.insert(address, Arc::new(RwLock::new(pool)));
 
         // Add to type map
         let mut type_map = self.by_type.write().await;
@@ -82,21 +262,17 @@ impl PoolRegistry {
             .push(address);
     }
 
-    pub async fn get_pool(
-        &self,
-        address: &Address,
-    ) -> Option<Arc<RwLock<Box<dyn PoolInterface + Send + Sync>>>> {
-        let pools = self.by_address.read().await;
-        pools.get(address).map(Arc::clone)
+    /// Get the live state of a pool, for writers. Readers should quote from `snapshot`.
+    pub async fn get_pool(&self, address: &Address) -> Option<LivePool> {
+        self.by_address
+            .get(address)
+            .map(|pool| Arc::clone(pool.value()))
     }
 
-    pub async fn remove_pool(
-        &self,
-        address: Address,
-    ) -> Option<Arc<RwLock<Box<dyn PoolInterface + Send + Sync>>>> {
+    pub async fn remove_pool(&self, address: Address) -> Option<LivePool> {
+        let _publish = self.publish_lock.lock().await;
         // Remove from address map
-        let mut address_map = self.by_address.write().await;
-        let pool = address_map.remove(&address)?;
+        let (_, pool) = self.by_address.remove(&address)?;
         let pool_type = pool.read().await.pool_type();
 
         // Remove from type map
@@ -135,57 +311,164 @@ impl PoolRegistry {
             }
         }
 
+        drop(type_map);
+        drop(token_graph);
+        self.last_updated_blocks.write().await.remove(&address);
+        self.synced_blocks.write().await.remove(&address);
+        self.publish_snapshot(&HashSet::from([address]), None).await;
+
         Some(pool)
     }
 
-    pub async fn get_all_pools(&self) -> Vec<Arc<RwLock<Box<dyn PoolInterface + Send + Sync>>>> {
-        let pools = self.by_address.read().await;
-        pools.values().map(Arc::clone).collect()
+    /// Apply an event to the pool it was emitted by, returning false if the pool is unknown
+    pub async fn apply_log(&self, event: &Log) -> Result<bool> {
+        self.apply_logs(std::slice::from_ref(event)).await.remove(0)
     }
 
-    pub async fn get_pools_by_type(
-        &self,
-        pool_type: PoolType,
-    ) -> Vec<Arc<RwLock<Box<dyn PoolInterface + Send + Sync>>>> {
+    /// Apply a batch of events in order and publish one snapshot for the whole batch. Each
+    /// result is false if the event's pool is unknown. Updates are announced only after the
+    /// snapshot is published, so subscribers always read states that include them.
+    pub async fn apply_logs(&self, events: &[Log]) -> Vec<Result<bool>> {
+        let _publish = self.publish_lock.lock().await;
+        let mut results = Vec::with_capacity(events.len());
+        let mut updates = Vec::new();
+        let mut changed = HashSet::new();
+        let mut block_number = None;
+
+        for event in events {
+            let result = self.apply_event(event).await;
+            if let Ok(Some(update)) = &result {
+                updates.push(update.clone());
+                changed.insert(event.address());
+                block_number = block_number.max(event.block_number);
+            }
+            results.push(result.map(|update| update.is_some()));
+        }
+
+        if !changed.is_empty() {
+            self.publish_snapshot(&changed, block_number).await;
+            if let Some(metrics) = &self.metrics {
+                metrics.add_pools_updated(changed.len() as u64);
+                if let Some(block_number) = block_number {
+                    metrics.record_processed_block(self.network_id, block_number);
+                }
+            }
+        }
+        for update in updates {
+            self.update_log.publish(update);
+        }
+
+        results
+    }
+
+    /// Apply an event to the live pool state and record its candle, without publishing
+    async fn apply_event(&self, event: &Log) -> Result<Option<PoolUpdate>> {
+        let Some(pool) = self.get_pool(&event.address()).await else {
+            return Ok(None);
+        };
+        if let (Some(synced_block), Some(block_number)) = (
+            self.synced_blocks.read().await.get(&event.address()),
+            event.block_number,
+        ) {
+            if block_number <= *synced_block {
+                return Ok(None);
+            }
+        }
+
+        let (tokens, price, update) = {
+            let mut pool = pool.write().await;
+            let applied = pool.apply_log(event);
+            if let Some(metrics) = &self.metrics {
+                metrics.record_event(self.network_id, pool.pool_type(), applied.is_ok());
+            }
+            applied?;
+            let update =
+                PoolUpdate::from_log(self.network_id, event, pool.pool_type(), pool.state());
+            (pool.tokens(), pool.spot_price(), update)
+        };
+
+        if let (Some(candle_registry), Some(price)) = (&self.candle_registry, price) {
+            // The updaters fill in block timestamps, an event still without one has no bucket
+            match event.block_timestamp {
+                Some(timestamp) => {
+                    candle_registry
+                        .record(
+                            event.address(),
+                            tokens,
+                            price,
+                            swap_volume(event),
+                            event.block_number.unwrap_or_default(),
+                            timestamp,
+                        )
+                        .await
+                }
+                None => debug!(
+                    "No block timestamp for event of pool {}, skipping its candle",
+                    event.address()
+                ),
+            }
+        }
+
+        if let Some(block_number) = event.block_number {
+            self.last_updated_blocks
+                .write()
+                .await
+                .insert(event.address(), block_number);
+        }
+
+        Ok(Some(update))
+    }
+
+    /// Get the block of the last event applied to a pool, or the block it was fetched or loaded
+    /// at if none was applied since
+    pub async fn get_last_updated_block(&self, address: &Address) -> Option<u64> {
+        self.last_updated_blocks.read().await.get(address).copied()
+    }
+
+    pub async fn get_all_pools(&self) -> Vec<LivePool> {
+        self.by_address
+            .iter()
+            .map(|pool| Arc::clone(pool.value()))
+            .collect()
+    }
+
+    pub async fn get_pools_by_type(&self, pool_type: PoolType) -> Vec<LivePool> {
         let type_map = self.by_type.read().await;
-        let address_map = self.by_address.read().await;
 
         type_map
             .get(&pool_type)
             .map(|addresses| {
                 addresses
                     .iter()
-                    .filter_map(|addr| address_map.get(addr).map(Arc::clone))
+                    .filter_map(|addr| self.by_address.get(addr).map(|pool| Arc::clone(&pool)))
                     .collect()
             })
             .unwrap_or_default()
     }
 
-    pub async fn get_v2_pools(&self) -> Vec<Arc<RwLock<Box<dyn PoolInterface + Send + Sync>>>> {
+    pub async fn get_v2_pools(&self) -> Vec<LivePool> {
         let type_map = self.by_type.read().await;
-        let address_map = self.by_address.read().await;
 
         type_map
             .get(&PoolType::UniswapV2)
             .map(|addresses| {
                 addresses
                     .iter()
-                    .filter_map(|addr| address_map.get(addr).map(Arc::clone))
+                    .filter_map(|addr| self.by_address.get(addr).map(|pool| Arc::clone(&pool)))
                     .collect()
             })
             .unwrap_or_default()
     }
 
-    pub async fn get_v3_pools(&self) -> Vec<Arc<RwLock<Box<dyn PoolInterface + Send + Sync>>>> {
+    pub async fn get_v3_pools(&self) -> Vec<LivePool> {
         let type_map = self.by_type.read().await;
-        let address_map = self.by_address.read().await;
 
         type_map
             .get(&PoolType::UniswapV3)
             .map(|addresses| {
                 addresses
                     .iter()
-                    .filter_map(|addr| address_map.get(addr).map(Arc::clone))
+                    .filter_map(|addr| self.by_address.get(addr).map(|pool| Arc::clone(&pool)))
                     .collect()
             })
             .unwrap_or_default()
@@ -207,8 +490,18 @@ impl PoolRegistry {
         self.get_addresses_by_type(PoolType::UniswapV3).await
     }
 
-    pub async fn get_all_addresses(&self) -> Vec<Address> {
-        self.by_address.read().await.keys().cloned().collect()
+    /// Get the addresses of all published pools, shared with the snapshot
+    pub async fn get_all_addresses(&self) -> Arc<[Address]> {
+        Arc::clone(self.snapshot().addresses())
+    }
+
+    /// Get the addresses of all pools containing a token
+    pub async fn get_addresses_by_token(&self, token: &Address) -> Vec<Address> {
+        let token_graph = self.token_graph.read().await;
+        token_graph
+            .get(token)
+            .map(|neighbors| neighbors.values().flatten().copied().collect())
+            .unwrap_or_default()
     }
 
     pub async fn log_summary(&self) -> String {
@@ -216,22 +509,20 @@ impl PoolRegistry {
         summary.push_str("Pool Registry Summary:\n");
         summary.push_str("--------------------------------\n");
 
-        let pools = self.by_address.read().await;
-        for (_, pool) in &*pools {
-            summary.push_str(&format!("Pool: {}\n", pool.read().await.log_summary()));
+        for pool in self.snapshot().pools().values() {
+            summary.push_str(&format!("Pool: {}\n", pool.log_summary()));
         }
         summary
     }
 
     /// Save all pools to database
     pub async fn save_to_db(&self, db: &Database) -> Result<()> {
-        let pools = self.by_address.read().await;
+        // Save the published states, which never hold part of a batch of events
+        let snapshot = self.snapshot();
         let mut v2_count = 0;
         let mut v3_count = 0;
 
-        for (_, pool_arc) in pools.iter() {
-            let pool = pool_arc.read().await;
-
+        for pool in snapshot.pools().values() {
             // Determine pool type and save accordingly
             match pool.pool_type() {
                 PoolType::UniswapV2 => {
@@ -270,28 +561,37 @@ impl PoolRegistry {
         db.snapshot()?;
 
         info!(
-            "CHAIN ID: {} Saved {} V2 pools, {} V3 pools, and last processed block {} to database",
-            self.network_id, v2_count, v3_count, last_block
+            "Saved {} V2 pools, {} V3 pools, and last processed block {} to database",
+            v2_count, v3_count, last_block
         );
         Ok(())
     }
 
     /// Load pools from database
     pub async fn load_from_db(&self, db: &Database) -> Result<()> {
+        let publish = self.publish_lock.lock().await;
+        let mut loaded = HashSet::new();
+
         // Load V2 pools
         let v2_pools = UniswapV2Pool::load_all_from_db(self.network_id, db)?;
         for pool in v2_pools {
             let boxed_pool: Box<dyn PoolInterface + Send + Sync> = Box::new(pool);
-            self.add_pool(boxed_pool).await;
+            loaded.insert(boxed_pool.address());
+            self.insert_pool(boxed_pool).await;
         }
 
         // Load V3 pools
         let v3_pools = UniswapV3Pool::load_all_from_db(self.network_id, db)?;
         for pool in v3_pools {
             let boxed_pool: Box<dyn PoolInterface + Send + Sync> = Box::new(pool);
-            self.add_pool(boxed_pool).await;
+            loaded.insert(boxed_pool.address());
+            self.insert_pool(boxed_pool).await;
         }
 
+        // Publish all loaded pools at once
+        self.publish_snapshot(&loaded, None).await;
+        drop(publish);
+
         // Load topics
         if let Ok(Some(topics)) = db.get::<_, Vec<Topic>>("metadata", "topics") {
             self.add_topics(topics).await;
@@ -304,15 +604,20 @@ impl PoolRegistry {
             self.add_profitable_topics(profitable_topics).await;
         }
 
-        // Load the last processed block
+        // Load the last processed block, the block the loaded states are current at
         if let Ok(Some(last_block)) = db.get::<_, u64>("metadata", "last_processed_block") {
+            let mut last_updated_blocks = self.last_updated_blocks.write().await;
+            for address in loaded {
+                last_updated_blocks.entry(address).or_insert(last_block);
+            }
+            drop(last_updated_blocks);
             self.set_last_processed_block(last_block).await;
             info!("Loaded last processed block: {}", last_block);
         } else {
             info!("No last processed block found in database");
         }
 
-        let total_pools = self.by_address.read().await.len();
+        let total_pools = self.by_address.len();
         info!("Loaded {} pools from database", total_pools);
         Ok(())
     }
@@ -322,15 +627,25 @@ impl PoolRegistry {
         *self.last_processed_block.read().await
     }
 
-    // Set the last processed block
+    // Set the last processed block, which also advances the snapshot block
     pub async fn set_last_processed_block(&self, block_number: u64) {
-        let mut block = self.last_processed_block.write().await;
-        *block = block_number;
+        let _publish = self.publish_lock.lock().await;
+        *self.last_processed_block.write().await = block_number;
+        self.publish_snapshot(&HashSet::new(), Some(block_number))
+            .await;
+        if let Some(metrics) = &self.metrics {
+            metrics.record_processed_block(self.network_id, block_number);
+        }
     }
 
+    /// Add the topics of a pool type, skipping the ones already subscribed to
     pub async fn add_topics(&self, topics: Vec<Topic>) {
         let mut topics_lock = self.topics.write().await;
-        topics_lock.extend(topics);
+        for topic in topics {
+            if !topics_lock.contains(&topic) {
+                topics_lock.push(topic);
+            }
+        }
     }
 
     pub async fn add_profitable_topics(&self, topics: Vec<Topic>) {
@@ -433,7 +748,208 @@ impl Clone for PoolRegistry {
             token_graph: Arc::clone(&self.token_graph),
             topics: Arc::clone(&self.topics),
             profitable_topics: Arc::clone(&self.profitable_topics),
+            candle_registry: self.candle_registry.clone(),
+            metrics: self.metrics.clone(),
+            sync_status: Arc::clone(&self.sync_status),
+            max_sync_lag: self.max_sync_lag,
+            update_log: Arc::clone(&self.update_log),
+            last_updated_blocks: Arc::clone(&self.last_updated_blocks),
+            synced_blocks: Arc::clone(&self.synced_blocks),
+            snapshot: Arc::clone(&self.snapshot),
+            publish_lock: Arc::clone(&self.publish_lock),
+            updates_lock: Arc::clone(&self.updates_lock),
             network_id: self.network_id.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::MockPool;
+    use alloy::primitives::U256;
+
+    fn mock_pool(address: Address) -> Box<dyn PoolInterface + Send + Sync> {
+        Box::new(MockPool::new_v2(
+            address,
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            U256::from(1_000_000u64),
+            U256::from(2_000_000u64),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_isolated_from_later_events() {
+        let pool_a = Address::repeat_byte(0xa);
+        let pool_b = Address::repeat_byte(0xb);
+        let registry = PoolRegistry::new(1);
+        registry.add_pool(mock_pool(pool_a)).await;
+        registry.add_pool(mock_pool(pool_b)).await;
+        registry.set_last_processed_block(10).await;
+
+        let before = registry.snapshot();
+        assert_eq!(before.block_number(), 10);
+        assert_eq!(before.len(), 2);
+
+        registry
+            .get_pool(&pool_a)
+            .await
+            .unwrap()
+            .write()
+            .await
+            .downcast_mut::<MockPool>()
+            .unwrap()
+            .set_reserves(U256::from(3_000_000u64), U256::from(4_000_000u64));
+        // The live state changed, but nothing is published until its event is applied
+        assert_eq!(
+            registry.snapshot().get_pool(&pool_a).unwrap().reserves(),
+            Some((1_000_000.0, 2_000_000.0))
+        );
+
+        let mut event = Log::default();
+        event.inner.address = pool_a;
+        event.block_number = Some(12);
+        let unknown = Log::default();
+        let results = registry.apply_logs(&[event, unknown]).await;
+        assert!(results[0].as_ref().unwrap());
+        assert!(!results[1].as_ref().unwrap());
+
+        let after = registry.snapshot();
+        assert_eq!(after.block_number(), 12);
+        assert!(after.pool_version(&pool_a) > before.pool_version(&pool_a));
+        assert_eq!(after.pool_version(&pool_b), before.pool_version(&pool_b));
+        assert_eq!(
+            after.get_pool(&pool_a).unwrap().reserves(),
+            Some((3_000_000.0, 4_000_000.0))
+        );
+        assert_eq!(
+            before.get_pool(&pool_a).unwrap().reserves(),
+            Some((1_000_000.0, 2_000_000.0))
+        );
+        // Untouched pools are shared between snapshots
+        assert!(Arc::ptr_eq(
+            &before.get_pool(&pool_b).unwrap(),
+            &after.get_pool(&pool_b).unwrap()
+        ));
+
+        // Updates to known pools keep the published address list
+        assert_eq!(after.addresses().len(), 2);
+        assert!(Arc::ptr_eq(before.addresses(), after.addresses()));
+
+        registry.remove_pool(pool_b).await;
+        assert!(registry.snapshot().get_pool(&pool_b).is_none());
+        assert_eq!(*registry.get_all_addresses().await, [pool_a]);
+        assert_eq!(registry.snapshot().block_number(), 12);
+    }
+
+    #[tokio::test]
+    async fn test_pool_added_live_skips_fetched_events() {
+        let pool = Address::repeat_byte(0xa);
+        let registry = PoolRegistry::new(1);
+        registry.add_pool_at(mock_pool(pool), 20).await;
+
+        let event = |block_number| {
+            let mut event = Log::default();
+            event.inner.address = pool;
+            event.block_number = Some(block_number);
+            event
+        };
+        // Events up to the fetched block are already in its state
+        let results = registry.apply_logs(&[event(19), event(20)]).await;
+        assert!(results.iter().all(|result| !result.as_ref().unwrap()));
+        assert_eq!(registry.get_last_updated_block(&pool).await, Some(20));
+
+        assert!(registry.apply_log(&event(21)).await.unwrap());
+        assert_eq!(registry.get_last_updated_block(&pool).await, Some(21));
+
+        // Added again at an older block, the pool only skips up to that one
+        registry.remove_pool(pool).await;
+        registry.add_pool_at(mock_pool(pool), 10).await;
+        assert!(registry.apply_log(&event(11)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_pools_added_together_publish_once() {
+        let addresses: Vec<Address> = (1..=3u8).map(Address::repeat_byte).collect();
+        let registry = PoolRegistry::new(1);
+        let before = registry.snapshot();
+        registry
+            .add_pools_at(addresses.iter().map(|&a| mock_pool(a)).collect(), 20)
+            .await;
+
+        let after = registry.snapshot();
+        assert_eq!(after.len(), 3);
+        let version = after.pool_version(&addresses[0]);
+        assert!(version > before.pool_version(&addresses[0]));
+        for address in &addresses {
+            assert_eq!(after.pool_version(address), version);
+            assert_eq!(registry.get_last_updated_block(address).await, Some(20));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_adds_and_removes_keep_indexes_consistent() {
+        let registry = Arc::new(PoolRegistry::new(1));
+        let addresses: Vec<Address> = (1..=64u8).map(Address::repeat_byte).collect();
+
+        let mut tasks = Vec::new();
+        for &address in &addresses {
+            let registry = Arc::clone(&registry);
+            tasks.push(tokio::spawn(async move {
+                registry.add_pool(mock_pool(address)).await;
+                let mut event = Log::default();
+                event.inner.address = address;
+                event.block_number = Some(1);
+                registry.apply_log(&event).await.unwrap();
+                assert!(registry.get_pool(&address).await.is_some());
+                assert!(registry.get_all_addresses().await.contains(&address));
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        // Drop every other pool while readers walk the published list
+        let (removed, kept): (Vec<Address>, Vec<Address>) =
+            addresses.iter().partition(|address| address.0[0] % 2 == 0);
+        let mut tasks = Vec::new();
+        for &address in &removed {
+            let writer = Arc::clone(&registry);
+            tasks.push(tokio::spawn(async move {
+                assert!(writer.remove_pool(address).await.is_some());
+            }));
+            let reader = Arc::clone(&registry);
+            tasks.push(tokio::spawn(async move {
+                for address in reader.get_all_addresses().await.iter() {
+                    reader.snapshot().get_pool(address);
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let mut expected = kept.clone();
+        expected.sort();
+        let mut published = registry.get_all_addresses().await.to_vec();
+        published.sort();
+        assert_eq!(published, expected);
+        assert_eq!(registry.pool_count().await, kept.len());
+        assert_eq!(registry.get_all_pools().await.len(), kept.len());
+        assert_eq!(registry.snapshot().len(), kept.len());
+
+        let mut by_type = registry.get_v2_addresses().await;
+        by_type.sort();
+        assert_eq!(by_type, expected);
+        let mut by_token = registry
+            .get_addresses_by_token(&Address::repeat_byte(1))
+            .await;
+        by_token.sort();
+        assert_eq!(by_token, expected);
+        for address in &removed {
+            assert!(registry.get_pool(address).await.is_none());
+            assert!(registry.snapshot().get_pool(address).is_none());
+        }
+    }
+}
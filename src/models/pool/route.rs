@@ -0,0 +1,155 @@
+use alloy::primitives::{Address, U256};
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+
+use crate::models::pool::base::PoolInterface;
+use crate::utils::errors::ArbitrageError;
+
+/// Fraction of the first pool's input reserve used to probe the price at the top of the book
+const PROBE_FRACTION: f64 = 1e-6;
+
+/// A pool in a route, with the direction it is traded in
+#[derive(Debug)]
+pub struct RouteHop {
+    pub pool: Arc<dyn PoolInterface + Send + Sync>,
+    pub token_in: Address,
+    pub token_out: Address,
+}
+
+/// Immutable states of a route's pools, so it can be quoted repeatedly without holding registry locks
+#[derive(Debug)]
+pub struct RouteSnapshot {
+    hops: Vec<RouteHop>,
+}
+
+impl RouteSnapshot {
+    /// Build a route from pools in swap order, starting with `token_in`
+    pub fn new(
+        pools: Vec<Arc<dyn PoolInterface + Send + Sync>>,
+        token_in: Address,
+    ) -> Result<Self> {
+        if pools.is_empty() {
+            return Err(anyhow!("Route has no pools"));
+        }
+
+        let mut current_token = token_in;
+        let mut hops = Vec::with_capacity(pools.len());
+        for pool in pools {
+            let (token0, token1) = pool.tokens();
+            let token_out = if current_token == token0 {
+                token1
+            } else if current_token == token1 {
+                token0
+            } else {
+                return Err(ArbitrageError::TokenNotInPool {
+                    token: current_token,
+                    pool: pool.address(),
+                }
+                .into());
+            };
+            hops.push(RouteHop {
+                pool,
+                token_in: current_token,
+                token_out,
+            });
+            current_token = token_out;
+        }
+
+        Ok(Self { hops })
+    }
+
+    pub fn hops(&self) -> &[RouteHop] {
+        &self.hops
+    }
+
+    pub fn token_in(&self) -> Address {
+        self.hops[0].token_in
+    }
+
+    pub fn token_out(&self) -> Address {
+        self.hops[self.hops.len() - 1].token_out
+    }
+
+    /// Calculate the route output for an exact input
+    pub fn quote_exact_in(&self, amount_in: U256) -> Result<U256> {
+        self.hops.iter().try_fold(amount_in, |amount, hop| {
+            hop.pool.calculate_output(&hop.token_in, amount)
+        })
+    }
+
+    /// Calculate the route input needed for an exact output
+    pub fn quote_exact_out(&self, amount_out: U256) -> Result<U256> {
+        self.hops.iter().rev().try_fold(amount_out, |amount, hop| {
+            hop.pool.calculate_input(&hop.token_out, amount)
+        })
+    }
+
+    /// Amounts entering and leaving each pool, from the route input to the route output
+    pub fn hop_amounts(&self, amount: U256, exact_input: bool) -> Result<Vec<U256>> {
+        let mut amounts = Vec::with_capacity(self.hops.len() + 1);
+        amounts.push(amount);
+        if exact_input {
+            for hop in &self.hops {
+                let amount_in = amounts[amounts.len() - 1];
+                amounts.push(hop.pool.calculate_output(&hop.token_in, amount_in)?);
+            }
+        } else {
+            for hop in self.hops.iter().rev() {
+                let amount_out = amounts[amounts.len() - 1];
+                amounts.push(hop.pool.calculate_input(&hop.token_out, amount_out)?);
+            }
+            amounts.reverse();
+        }
+        Ok(amounts)
+    }
+
+    /// Like `hop_amounts` for each of `amounts`, quoting every pool once for all of them so
+    /// the amounts can share work like a V3 tick walk
+    pub fn hop_amounts_many(&self, amounts: &[U256], exact_input: bool) -> Result<Vec<Vec<U256>>> {
+        let mut steps = Vec::with_capacity(self.hops.len() + 1);
+        steps.push(amounts.to_vec());
+        if exact_input {
+            for hop in &self.hops {
+                let amounts_in = &steps[steps.len() - 1];
+                steps.push(hop.pool.calculate_outputs(&hop.token_in, amounts_in)?);
+            }
+        } else {
+            for hop in self.hops.iter().rev() {
+                let amounts_out = &steps[steps.len() - 1];
+                steps.push(hop.pool.calculate_inputs(&hop.token_out, amounts_out)?);
+            }
+            steps.reverse();
+        }
+        Ok((0..amounts.len())
+            .map(|index| steps.iter().map(|step| step[index]).collect())
+            .collect())
+    }
+
+    /// Small input, relative to the first pool's reserves, that barely moves the price
+    pub fn probe_amount(&self) -> U256 {
+        let first = &self.hops[0];
+        let reserve_in = first.pool.reserves().map(|(reserve0, reserve1)| {
+            if first.token_in == first.pool.token0() {
+                reserve0
+            } else {
+                reserve1
+            }
+        });
+        match reserve_in {
+            Some(reserve_in) => U256::from((reserve_in * PROBE_FRACTION).max(1.0) as u128),
+            None => U256::from(1_000_000u64),
+        }
+    }
+
+    /// Output per unit of input for a probe-sized trade, in raw units and net of fees
+    pub fn mid_price(&self) -> Result<f64> {
+        let probe = self.probe_amount();
+        let output = self.quote_exact_in(probe)?;
+        if output.is_zero() {
+            return Err(
+                ArbitrageError::InsufficientLiquidity("route has no output".to_string()).into(),
+            );
+        }
+        Ok(f64::from(output) / f64::from(probe))
+    }
+}
@@ -0,0 +1,109 @@
+use alloy::primitives::Address;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::pool::base::PoolInterface;
+use crate::models::pool::route::RouteSnapshot;
+use crate::utils::errors::ArbitrageError;
+
+/// Immutable states of every pool in a registry as of a block. The registry publishes a new
+/// snapshot after each batch of events, sharing the states of pools the batch did not touch.
+#[derive(Debug, Clone, Default)]
+pub struct PoolSnapshot {
+    block_number: u64,
+    pools: Arc<HashMap<Address, Arc<dyn PoolInterface + Send + Sync>>>,
+    versions: Arc<HashMap<Address, u64>>, // State version of each pool, see `pool_version`
+    addresses: Arc<[Address]>,            // Rebuilt only when pools are added or removed
+}
+
+impl PoolSnapshot {
+    pub fn new(
+        block_number: u64,
+        pools: HashMap<Address, Arc<dyn PoolInterface + Send + Sync>>,
+        versions: HashMap<Address, u64>,
+    ) -> Self {
+        let addresses: Arc<[Address]> = pools.keys().copied().collect();
+        Self {
+            block_number,
+            pools: Arc::new(pools),
+            versions: Arc::new(versions),
+            addresses,
+        }
+    }
+
+    /// New states for the same set of pools, keeping the address list
+    pub fn with_pools(
+        &self,
+        block_number: u64,
+        pools: HashMap<Address, Arc<dyn PoolInterface + Send + Sync>>,
+        versions: HashMap<Address, u64>,
+    ) -> Self {
+        debug_assert_eq!(pools.len(), self.addresses.len());
+        Self {
+            block_number,
+            pools: Arc::new(pools),
+            versions: Arc::new(versions),
+            addresses: Arc::clone(&self.addresses),
+        }
+    }
+
+    /// The same pool states, as of a later block with no events for them
+    pub fn at_block(&self, block_number: u64) -> Self {
+        Self {
+            block_number,
+            pools: Arc::clone(&self.pools),
+            versions: Arc::clone(&self.versions),
+            addresses: Arc::clone(&self.addresses),
+        }
+    }
+
+    /// Latest block whose events are included
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    pub fn pools(&self) -> &HashMap<Address, Arc<dyn PoolInterface + Send + Sync>> {
+        &self.pools
+    }
+
+    pub fn versions(&self) -> &HashMap<Address, u64> {
+        &self.versions
+    }
+
+    /// Version of a pool's state. It changes every time an applied event changes the pool and
+    /// is never reused within a registry, so equal versions mean equal states.
+    pub fn pool_version(&self, address: &Address) -> Option<u64> {
+        self.versions.get(address).copied()
+    }
+
+    /// Addresses of the snapshot's pools, shared with the snapshots published after it until a
+    /// pool is added or removed
+    pub fn addresses(&self) -> &Arc<[Address]> {
+        &self.addresses
+    }
+
+    pub fn get_pool(&self, address: &Address) -> Option<Arc<dyn PoolInterface + Send + Sync>> {
+        self.pools.get(address).map(Arc::clone)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    /// Build a route from pools in swap order, starting with `token_in`
+    pub fn route(&self, pools: &[Address], token_in: Address) -> Result<RouteSnapshot> {
+        let pools = pools
+            .iter()
+            .map(|address| {
+                self.get_pool(address)
+                    .ok_or_else(|| ArbitrageError::PoolNotFound(*address).into())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        RouteSnapshot::new(pools, token_in)
+    }
+}
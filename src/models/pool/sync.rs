@@ -0,0 +1,99 @@
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+/// Blocks a chain may fall behind its head before it is reported as not ready
+pub const DEFAULT_MAX_SYNC_LAG: u64 = 10;
+
+/// Seconds without a head refresh before a chain is reported as not ready, so a chain whose
+/// head lookups fail or hang is not judged by a lag that stopped moving
+pub const MAX_HEAD_AGE_SECS: u64 = 60;
+
+/// Sync progress of a chain's pool updater and websocket feeds, read by the readiness check
+#[derive(Debug, Default)]
+pub struct SyncStatus {
+    head_block: AtomicU64,                 // 0 until the head was fetched
+    head_refreshed_at: AtomicU64,          // Unix seconds of the latest head fetch, 0 before
+    delivered_block: AtomicU64,            // 0 until a websocket feed delivered a whole block
+    websockets: DashMap<String, bool>,     // Endpoint host -> connected
+    last_error: RwLock<Option<SyncError>>, // Latest updater or feed error
+}
+
+/// An error of a chain's updater or feeds
+#[derive(Debug, Clone)]
+pub struct SyncError {
+    pub message: String,
+    pub timestamp: u64, // Unix seconds
+}
+
+impl SyncStatus {
+    /// Latest block reported by the chain, None until it was fetched
+    pub fn head_block(&self) -> Option<u64> {
+        match self.head_block.load(Ordering::Relaxed) {
+            0 => None,
+            block => Some(block),
+        }
+    }
+
+    pub fn set_head_block(&self, block: u64) {
+        self.set_head_block_at(block, chrono::Utc::now().timestamp() as u64);
+    }
+
+    /// Record the head fetched at `timestamp`, in Unix seconds
+    pub fn set_head_block_at(&self, block: u64, timestamp: u64) {
+        self.head_block.fetch_max(block, Ordering::Relaxed);
+        self.head_refreshed_at
+            .fetch_max(timestamp, Ordering::Relaxed);
+    }
+
+    /// Seconds since the head was last fetched, None until it was
+    pub fn head_age(&self) -> Option<u64> {
+        match self.head_refreshed_at.load(Ordering::Relaxed) {
+            0 => None,
+            at => Some((chrono::Utc::now().timestamp() as u64).saturating_sub(at)),
+        }
+    }
+
+    /// Newest block whose logs a websocket feed delivered in full, None until one did
+    pub fn delivered_block(&self) -> Option<u64> {
+        match self.delivered_block.load(Ordering::Relaxed) {
+            0 => None,
+            block => Some(block),
+        }
+    }
+
+    pub fn set_delivered_block(&self, block: u64) {
+        self.delivered_block.fetch_max(block, Ordering::Relaxed);
+    }
+
+    /// Connection state of every websocket feed, sorted by endpoint
+    pub fn websockets(&self) -> Vec<(String, bool)> {
+        let mut websockets = self
+            .websockets
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect::<Vec<_>>();
+        websockets.sort();
+        websockets
+    }
+
+    pub fn set_websocket_connected(&self, endpoint: &str, connected: bool) {
+        self.websockets.insert(endpoint.to_string(), connected);
+    }
+
+    /// Whether any websocket feed is connected
+    pub fn has_connected_websocket(&self) -> bool {
+        self.websockets.iter().any(|entry| *entry.value())
+    }
+
+    pub fn last_error(&self) -> Option<SyncError> {
+        self.last_error.read().unwrap().clone()
+    }
+
+    pub fn record_error(&self, message: impl Into<String>) {
+        *self.last_error.write().unwrap() = Some(SyncError {
+            message: message.into(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        });
+    }
+}
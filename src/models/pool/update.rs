@@ -0,0 +1,186 @@
+use alloy::primitives::{Address, B256, U256};
+use alloy::rpc::types::Log;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use crate::models::pool::base::PoolType;
+
+/// Default capacity of the per-chain pool update broadcast channel
+pub const POOL_UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+/// Default number of recent updates kept per chain for clients resuming from a cursor
+pub const POOL_UPDATE_HISTORY_CAPACITY: usize = 10_000;
+
+/// Pool state after an event was applied
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PoolState {
+    /// Constant product pool reserves
+    Reserves { reserve0: U256, reserve1: U256 },
+    /// Concentrated liquidity pool price and in-range liquidity
+    ConcentratedLiquidity {
+        sqrt_price_x96: U256,
+        tick: i32,
+        liquidity: u128,
+    },
+    /// ERC4626 vault share supply and underlying assets
+    Vault {
+        vault_reserve: U256,
+        asset_reserve: U256,
+    },
+}
+
+/// Notification published after an event was applied to a pool
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PoolUpdate {
+    /// Position in the chain's update log, starting at 1. Resets when the process restarts.
+    pub sequence: u64,
+    pub network_id: u64,
+    pub pool: Address,
+    pub pool_type: PoolType,
+    pub block_number: u64,
+    pub transaction_hash: Option<B256>,
+    pub log_index: Option<u64>,
+    pub state: Option<PoolState>,
+}
+
+impl PoolUpdate {
+    /// Build an update for an applied log; the sequence is assigned when published
+    pub fn from_log(
+        network_id: u64,
+        event: &Log,
+        pool_type: PoolType,
+        state: Option<PoolState>,
+    ) -> Self {
+        Self {
+            sequence: 0,
+            network_id,
+            pool: event.address(),
+            pool_type,
+            block_number: event.block_number.unwrap_or_default(),
+            transaction_hash: event.transaction_hash,
+            log_index: event.log_index,
+            state,
+        }
+    }
+}
+
+/// Ordered log of a chain's pool updates: a live broadcast plus a bounded history to resume from
+#[derive(Debug)]
+pub struct PoolUpdateLog {
+    sender: broadcast::Sender<PoolUpdate>,
+    history: Mutex<UpdateHistory>,
+    history_capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct UpdateHistory {
+    last_sequence: u64,
+    updates: VecDeque<PoolUpdate>,
+}
+
+impl PoolUpdateLog {
+    pub fn new(channel_capacity: usize, history_capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(channel_capacity).0,
+            history: Mutex::new(UpdateHistory::default()),
+            history_capacity,
+        }
+    }
+
+    /// Assign the next sequence number, record the update and broadcast it
+    pub fn publish(&self, mut update: PoolUpdate) -> u64 {
+        // Held across the send so sequence order and broadcast order always agree
+        let mut history = self.history.lock().unwrap();
+        history.last_sequence += 1;
+        update.sequence = history.last_sequence;
+        if self.history_capacity > 0 {
+            if history.updates.len() == self.history_capacity {
+                history.updates.pop_front();
+            }
+            history.updates.push_back(update.clone());
+        }
+        // Nobody listening is not an error
+        let _ = self.sender.send(update);
+        history.last_sequence
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PoolUpdate> {
+        self.sender.subscribe()
+    }
+
+    /// Updates after `sequence` still in history. If some in between are missing, also the
+    /// sequence the returned updates resume after.
+    pub fn since(&self, sequence: u64) -> (Vec<PoolUpdate>, Option<u64>) {
+        let history = self.history.lock().unwrap();
+        let oldest = history
+            .updates
+            .front()
+            .map_or(history.last_sequence + 1, |first| first.sequence);
+        // A cursor ahead of the log comes from before a restart, everything still held is new
+        let ahead = sequence > history.last_sequence;
+        let sequence = if ahead { 0 } else { sequence };
+        let updates = history
+            .updates
+            .iter()
+            .filter(|update| update.sequence > sequence)
+            .cloned()
+            .collect();
+        let gap = (ahead || oldest > sequence + 1).then_some(oldest - 1);
+        (updates, gap)
+    }
+
+    /// Sequence of the most recent update, 0 if none was published yet
+    pub fn latest_sequence(&self) -> u64 {
+        self.history.lock().unwrap().last_sequence
+    }
+}
+
+impl Default for PoolUpdateLog {
+    fn default() -> Self {
+        Self::new(POOL_UPDATE_CHANNEL_CAPACITY, POOL_UPDATE_HISTORY_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_log_sequence_and_resume() {
+        let log = PoolUpdateLog::new(16, 3);
+        let mut receiver = log.subscribe();
+        let mut event = Log::default();
+        event.inner.address = Address::repeat_byte(1);
+
+        for block in 1..=5 {
+            event.block_number = Some(block);
+            let update = PoolUpdate::from_log(1, &event, PoolType::UniswapV2, None);
+            assert_eq!(log.publish(update), block);
+        }
+        assert_eq!(receiver.try_recv().unwrap().sequence, 1);
+        assert_eq!(log.latest_sequence(), 5);
+
+        let (updates, gap) = log.since(3);
+        assert_eq!(gap, None);
+        assert_eq!(
+            updates.iter().map(|u| u.sequence).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+
+        // Sequence 2 was evicted, so resuming from 1 reports a gap
+        let (updates, gap) = log.since(1);
+        assert_eq!(gap, Some(2));
+        assert_eq!(updates.len(), 3);
+
+        // A cursor from before a restart replays what is still held
+        let (updates, gap) = log.since(9);
+        assert_eq!(gap, Some(2));
+        assert_eq!(updates.len(), 3);
+        let (updates, gap) = PoolUpdateLog::new(16, 3).since(9);
+        assert_eq!(gap, Some(0));
+        assert!(updates.is_empty());
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
     core::Database,
     models::pool::{
         base::{EventApplicable, PoolInterface, PoolTypeTrait, TopicList},
+        update::PoolState,
         v2::{default_factory_fee_by_chain_id, get_v2_factory_fee},
     },
     PoolType,
@@ -13,6 +14,7 @@ use std::{str::FromStr, sync::Arc};
 use tokio::sync::RwLock;
 
 use crate::models::token::TokenRegistry;
+use crate::utils::errors::ArbitrageError;
 use alloy::sol_types::SolEvent;
 use alloy::{
     primitives::{Address, FixedBytes, U256},
@@ -90,7 +92,9 @@ impl UniswapV2Pool {
     /// Calculate the output amount for a swap (token0 -> token1)
     fn calculate_output_0_to_1(&self, amount_in: U256) -> Result<U256> {
         if amount_in.is_zero() {
-            return Err(anyhow!("Input amount cannot be zero"));
+            return Err(
+                ArbitrageError::InvalidAmount("input amount cannot be zero".to_string()).into(),
+            );
         }
 
         if !self.is_valid() {
@@ -104,7 +108,10 @@ impl UniswapV2Pool {
         // Can't return more than all reserves
         let output = numerator / denominator;
         if output >= self.reserve1 {
-            return Err(anyhow!("Insufficient liquidity for swap"));
+            return Err(ArbitrageError::InsufficientLiquidity(
+                "swap exceeds the pool reserves".to_string(),
+            )
+            .into());
         }
 
         Ok(output)
@@ -113,7 +120,9 @@ impl UniswapV2Pool {
     /// Calculate the output amount for a swap (token1 -> token0)
     fn calculate_output_1_to_0(&self, amount_in: U256) -> Result<U256> {
         if amount_in.is_zero() {
-            return Err(anyhow!("Input amount cannot be zero"));
+            return Err(
+                ArbitrageError::InvalidAmount("input amount cannot be zero".to_string()).into(),
+            );
         }
 
         if !self.is_valid() {
@@ -128,7 +137,10 @@ impl UniswapV2Pool {
         // Can't return more than all reserves
         let output = numerator / denominator;
         if output >= self.reserve0 {
-            return Err(anyhow!("Insufficient liquidity for swap"));
+            return Err(ArbitrageError::InsufficientLiquidity(
+                "swap exceeds the pool reserves".to_string(),
+            )
+            .into());
         }
 
         Ok(output)
@@ -136,7 +148,9 @@ impl UniswapV2Pool {
 
     fn calculate_input_0_to_1(&self, amount_out: U256) -> Result<U256> {
         if amount_out.is_zero() {
-            return Err(anyhow!("Output amount cannot be zero"));
+            return Err(
+                ArbitrageError::InvalidAmount("output amount cannot be zero".to_string()).into(),
+            );
         }
 
         if !self.is_valid() {
@@ -144,7 +158,10 @@ impl UniswapV2Pool {
         }
 
         if amount_out >= self.reserve1 {
-            return Err(anyhow!("Insufficient liquidity for swap"));
+            return Err(ArbitrageError::InsufficientLiquidity(
+                "swap exceeds the pool reserves".to_string(),
+            )
+            .into());
         }
 
         let numerator = self.reserve0 * amount_out * U256::from(FEE_DENOMINATOR);
@@ -158,7 +175,9 @@ impl UniswapV2Pool {
 
     fn calculate_input_1_to_0(&self, amount_out: U256) -> Result<U256> {
         if amount_out.is_zero() {
-            return Err(anyhow!("Output amount cannot be zero"));
+            return Err(
+                ArbitrageError::InvalidAmount("output amount cannot be zero".to_string()).into(),
+            );
         }
 
         if !self.is_valid() {
@@ -166,7 +185,10 @@ impl UniswapV2Pool {
         }
 
         if amount_out >= self.reserve0 {
-            return Err(anyhow!("Insufficient liquidity for swap"));
+            return Err(ArbitrageError::InsufficientLiquidity(
+                "swap exceeds the pool reserves".to_string(),
+            )
+            .into());
         }
 
         let numerator = self.reserve1 * amount_out * U256::from(FEE_DENOMINATOR);
@@ -220,7 +242,11 @@ impl PoolInterface for UniswapV2Pool {
         } else if token_in == &self.token1 {
             self.calculate_output_1_to_0(amount_in)
         } else {
-            Err(anyhow!("Token not in pool"))
+            Err(ArbitrageError::TokenNotInPool {
+                token: *token_in,
+                pool: self.address,
+            }
+            .into())
         }
     }
 
@@ -230,7 +256,11 @@ impl PoolInterface for UniswapV2Pool {
         } else if token_out == &self.token1 {
             self.calculate_input_0_to_1(amount_out)
         } else {
-            Err(anyhow!("Token not in pool"))
+            Err(ArbitrageError::TokenNotInPool {
+                token: *token_out,
+                pool: self.address,
+            }
+            .into())
         }
     }
 
@@ -238,19 +268,29 @@ impl PoolInterface for UniswapV2Pool {
         if token_in == &self.token0 {
             // Token0 -> Token1 swap
             if amount_out >= self.reserve1 {
-                return Err(anyhow!("Insufficient liquidity for swap"));
+                return Err(ArbitrageError::InsufficientLiquidity(
+                    "swap exceeds the pool reserves".to_string(),
+                )
+                .into());
             }
             self.reserve0 += amount_in;
             self.reserve1 -= amount_out;
         } else if token_in == &self.token1 {
             // Token1 -> Token0 swap
             if amount_out >= self.reserve0 {
-                return Err(anyhow!("Insufficient liquidity for swap"));
+                return Err(ArbitrageError::InsufficientLiquidity(
+                    "swap exceeds the pool reserves".to_string(),
+                )
+                .into());
             }
             self.reserve1 += amount_in;
             self.reserve0 -= amount_out;
         } else {
-            return Err(anyhow!("Token not in pool"));
+            return Err(ArbitrageError::TokenNotInPool {
+                token: *token_in,
+                pool: self.address,
+            }
+            .into());
         }
 
         self.last_updated = chrono::Utc::now().timestamp() as u64;
@@ -269,6 +309,24 @@ impl PoolInterface for UniswapV2Pool {
         self.fee.to::<u128>() as f64 / FEE_DENOMINATOR as f64
     }
 
+    fn spot_price(&self) -> Option<f64> {
+        if !self.is_valid() {
+            return None;
+        }
+        Some(f64::from(self.reserve1) / f64::from(self.reserve0))
+    }
+
+    fn reserves(&self) -> Option<(f64, f64)> {
+        Some((f64::from(self.reserve0), f64::from(self.reserve1)))
+    }
+
+    fn state(&self) -> Option<PoolState> {
+        Some(PoolState::Reserves {
+            reserve0: self.reserve0,
+            reserve1: self.reserve1,
+        })
+    }
+
     fn id(&self) -> String {
         format!("v2-{}-{}-{}", self.address, self.token0, self.token1)
     }
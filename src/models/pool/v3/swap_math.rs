@@ -163,18 +163,12 @@ pub fn compute_swap_step<const BITS: usize, const LIMBS: usize>(
     Ok((sqrt_ratio_next_x96, amount_in, amount_out, fee_amount))
 }
 
-#[inline]
-#[allow(clippy::too_many_arguments)]
-pub fn v3_swap(
-    fee: U24,
+/// The price a swap stops at, checked against the current price
+fn price_limit(
     sqrt_price_x96: U160,
-    tick_current: i32,
-    liquidity: u128,
-    tick_data_provider: &TickMap,
     zero_for_one: bool,
-    amount_specified: I256,
     sqrt_price_limit_x96: Option<U160>,
-) -> Result<SwapState<i32>> {
+) -> Result<U160> {
     let sqrt_price_limit_x96 = sqrt_price_limit_x96.unwrap_or(if zero_for_one {
         MIN_SQRT_RATIO + ONE
     } else {
@@ -196,6 +190,104 @@ pub fn v3_swap(
             return Err(anyhow!("RATIO_CURRENT"));
         }
     }
+    Ok(sqrt_price_limit_x96)
+}
+
+/// One iteration of the swap loop: swap up to the next initialized tick or the price limit.
+/// Returns false when the swap ran out of liquidity and cannot continue.
+#[inline]
+fn swap_step(
+    state: &mut SwapState<i32>,
+    fee: U24,
+    tick_data_provider: &TickMap,
+    zero_for_one: bool,
+    exact_input: bool,
+    sqrt_price_limit_x96: U160,
+) -> Result<bool> {
+    let mut step = StepComputations {
+        sqrt_price_start_x96: state.sqrt_price_x96,
+        ..Default::default()
+    };
+    // because each iteration of the while loop rounds, we can't optimize this code
+    // (relative to the smart contract) by simply traversing to the next available tick, we
+    // instead need to exactly replicate
+    (step.tick_next, step.initialized) = tick_data_provider
+        .next_initialized_tick_within_one_word(state.tick_current, zero_for_one)?;
+    step.tick_next = step.tick_next.clamp(MIN_TICK_I32, MAX_TICK_I32);
+    step.sqrt_price_next_x96 = get_sqrt_ratio_at_tick(step.tick_next.to_i24())?;
+
+    (
+        state.sqrt_price_x96,
+        step.amount_in,
+        step.amount_out,
+        step.fee_amount,
+    ) = compute_swap_step(
+        state.sqrt_price_x96,
+        if zero_for_one {
+            step.sqrt_price_next_x96.max(sqrt_price_limit_x96)
+        } else {
+            step.sqrt_price_next_x96.min(sqrt_price_limit_x96)
+        },
+        state.liquidity,
+        state.amount_specified_remaining,
+        fee,
+    )?;
+
+    if exact_input {
+        state.amount_specified_remaining = I256::from_raw(
+            state.amount_specified_remaining.into_raw() - step.amount_in - step.fee_amount,
+        );
+        state.amount_calculated =
+            I256::from_raw(state.amount_calculated.into_raw() - step.amount_out);
+    } else {
+        state.amount_specified_remaining =
+            I256::from_raw(state.amount_specified_remaining.into_raw() + step.amount_out);
+        state.amount_calculated =
+            I256::from_raw(state.amount_calculated.into_raw() + step.amount_in + step.fee_amount);
+    }
+
+    if state.sqrt_price_x96 == step.sqrt_price_next_x96 {
+        // if the tick is initialized, run the tick transition
+        if step.initialized {
+            let mut liquidity_net = tick_data_provider.get_tick(step.tick_next)?.liquidity_net;
+            // if we're moving leftward, we interpret liquidityNet as the opposite sign
+            // safe because liquidityNet cannot be type(int128).min
+            if zero_for_one {
+                liquidity_net = -liquidity_net;
+            }
+            state.liquidity = add_delta(state.liquidity, liquidity_net)?;
+        }
+        state.tick_current = if zero_for_one {
+            step.tick_next - i32::ONE
+        } else {
+            step.tick_next
+        };
+
+        if state.liquidity == 0 {
+            // If liquidity becomes zero, we can't continue the swap
+            return Ok(false);
+        }
+    } else if state.sqrt_price_x96 != step.sqrt_price_start_x96 {
+        // recompute unless we're on a lower tick boundary (i.e. already transitioned
+        // ticks), and haven't moved
+        state.tick_current = TickIndex::from_i24(state.sqrt_price_x96.get_tick_at_sqrt_ratio()?);
+    }
+    Ok(true)
+}
+
+#[inline]
+#[allow(clippy::too_many_arguments)]
+pub fn v3_swap(
+    fee: U24,
+    sqrt_price_x96: U160,
+    tick_current: i32,
+    liquidity: u128,
+    tick_data_provider: &TickMap,
+    zero_for_one: bool,
+    amount_specified: I256,
+    sqrt_price_limit_x96: Option<U160>,
+) -> Result<SwapState<i32>> {
+    let sqrt_price_limit_x96 = price_limit(sqrt_price_x96, zero_for_one, sqrt_price_limit_x96)?;
 
     let exact_input = amount_specified >= I256::ZERO;
 
@@ -212,79 +304,88 @@ pub fn v3_swap(
     while !state.amount_specified_remaining.is_zero()
         && state.sqrt_price_x96 != sqrt_price_limit_x96
     {
-        let mut step = StepComputations {
-            sqrt_price_start_x96: state.sqrt_price_x96,
-            ..Default::default()
-        };
-        // because each iteration of the while loop rounds, we can't optimize this code
-        // (relative to the smart contract) by simply traversing to the next available tick, we
-        // instead need to exactly replicate
-        (step.tick_next, step.initialized) = tick_data_provider
-            .next_initialized_tick_within_one_word(state.tick_current, zero_for_one)?;
-        step.tick_next = step.tick_next.clamp(MIN_TICK_I32, MAX_TICK_I32);
-        step.sqrt_price_next_x96 = get_sqrt_ratio_at_tick(step.tick_next.to_i24())?;
-
-        (
-            state.sqrt_price_x96,
-            step.amount_in,
-            step.amount_out,
-            step.fee_amount,
-        ) = compute_swap_step(
-            state.sqrt_price_x96,
-            if zero_for_one {
-                step.sqrt_price_next_x96.max(sqrt_price_limit_x96)
-            } else {
-                step.sqrt_price_next_x96.min(sqrt_price_limit_x96)
-            },
-            state.liquidity,
-            state.amount_specified_remaining,
+        if !swap_step(
+            &mut state,
             fee,
-        )?;
-
-        if exact_input {
-            state.amount_specified_remaining = I256::from_raw(
-                state.amount_specified_remaining.into_raw() - step.amount_in - step.fee_amount,
-            );
-            state.amount_calculated =
-                I256::from_raw(state.amount_calculated.into_raw() - step.amount_out);
-        } else {
-            state.amount_specified_remaining =
-                I256::from_raw(state.amount_specified_remaining.into_raw() + step.amount_out);
-            state.amount_calculated = I256::from_raw(
-                state.amount_calculated.into_raw() + step.amount_in + step.fee_amount,
-            );
+            tick_data_provider,
+            zero_for_one,
+            exact_input,
+            sqrt_price_limit_x96,
+        )? {
+            break;
         }
+    }
 
-        if state.sqrt_price_x96 == step.sqrt_price_next_x96 {
-            // if the tick is initialized, run the tick transition
-            if step.initialized {
-                let mut liquidity_net = tick_data_provider.get_tick(step.tick_next)?.liquidity_net;
-                // if we're moving leftward, we interpret liquidityNet as the opposite sign
-                // safe because liquidityNet cannot be type(int128).min
-                if zero_for_one {
-                    liquidity_net = -liquidity_net;
-                }
-                state.liquidity = add_delta(state.liquidity, liquidity_net)?;
-            }
-            state.tick_current = if zero_for_one {
-                step.tick_next - i32::ONE
+    Ok(state)
+}
+
+/// Swap each of `amounts`, which must be sorted in ascending order, with one walk over the ticks.
+/// A step that reaches its next tick without using up an amount is the same for every larger
+/// amount, so it is computed once and each amount only computes its final partial step. The
+/// states are identical to calling `v3_swap` for each amount.
+#[allow(clippy::too_many_arguments)]
+pub fn v3_swap_many(
+    fee: U24,
+    sqrt_price_x96: U160,
+    tick_current: i32,
+    liquidity: u128,
+    tick_data_provider: &TickMap,
+    zero_for_one: bool,
+    amounts: &[U256],
+    exact_input: bool,
+) -> Result<Vec<SwapState<i32>>> {
+    if amounts.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err(anyhow!("Amounts must be sorted in ascending order"));
+    }
+    let sqrt_price_limit_x96 = price_limit(sqrt_price_x96, zero_for_one, None)?;
+
+    // State after the steps every remaining amount takes, and the amount they used
+    let mut shared = SwapState {
+        amount_specified_remaining: I256::ZERO,
+        amount_calculated: I256::ZERO,
+        sqrt_price_x96,
+        tick_current,
+        liquidity,
+    };
+    let mut used = U256::ZERO;
+    let mut out_of_liquidity = false;
+
+    let mut states = Vec::with_capacity(amounts.len());
+    for &amount in amounts {
+        let remaining = amount - used;
+        let mut state = SwapState {
+            amount_specified_remaining: if exact_input {
+                I256::from_raw(remaining)
             } else {
-                step.tick_next
-            };
+                I256::from_raw(remaining).saturating_neg()
+            },
+            ..shared
+        };
 
-            if state.liquidity == 0 {
-                // If liquidity becomes zero, we can't continue the swap
-                break;
+        let mut running = !out_of_liquidity;
+        while running
+            && !state.amount_specified_remaining.is_zero()
+            && state.sqrt_price_x96 != sqrt_price_limit_x96
+        {
+            running = swap_step(
+                &mut state,
+                fee,
+                tick_data_provider,
+                zero_for_one,
+                exact_input,
+                sqrt_price_limit_x96,
+            )?;
+            if !state.amount_specified_remaining.is_zero() {
+                // The step stopped at its target with some of the amount left, which does not
+                // depend on how much was left
+                used = amount - state.amount_specified_remaining.unsigned_abs();
+                shared = state;
+                out_of_liquidity = !running;
             }
-        } else if state.sqrt_price_x96 != step.sqrt_price_start_x96 {
-            // recompute unless we're on a lower tick boundary (i.e. already transitioned
-            // ticks), and haven't moved
-            state.tick_current =
-                TickIndex::from_i24(state.sqrt_price_x96.get_tick_at_sqrt_ratio()?);
         }
+        states.push(state);
     }
-
-    Ok(state)
+    Ok(states)
 }
 
 #[cfg(test)]
@@ -316,4 +417,89 @@ mod tests {
         assert_eq!(amount_out, U256::from_limbs([4846, 0, 0, 0]));
         assert_eq!(fee_amount, U256::from_limbs([14, 0, 0, 0]));
     }
+
+    #[test]
+    fn test_v3_swap_many_matches_v3_swap() {
+        use crate::models::pool::v3::Tick;
+
+        let mut ticks = TickMap::new();
+        for (index, liquidity_net) in [
+            (-120, 500_000_000_000_000_000i128),
+            (-60, 500_000_000_000_000_000),
+            (60, -500_000_000_000_000_000),
+            (120, -500_000_000_000_000_000),
+        ] {
+            ticks.insert(
+                index,
+                Tick {
+                    index,
+                    liquidity_net,
+                    liquidity_gross: liquidity_net.unsigned_abs(),
+                },
+            );
+        }
+        let sqrt_price_x96 = U160::from(1u128 << 96);
+        let liquidity = 1_000_000_000_000_000_000;
+        let fee = U24::from(3000);
+
+        // Small swaps stay in the current range, large ones cross ticks or drain the pool
+        let mut amounts: Vec<U256> = (0..40u32)
+            .map(|exponent| U256::from(10u128.pow(exponent / 2) * (1 + exponent as u128 % 2 * 4)))
+            .collect();
+        amounts.push(amounts[10]);
+        amounts.sort();
+
+        for zero_for_one in [true, false] {
+            for exact_input in [true, false] {
+                let states = v3_swap_many(
+                    fee,
+                    sqrt_price_x96,
+                    0,
+                    liquidity,
+                    &ticks,
+                    zero_for_one,
+                    &amounts,
+                    exact_input,
+                )
+                .unwrap();
+                for (amount, state) in amounts.iter().zip(states) {
+                    let specified = if exact_input {
+                        I256::from_raw(*amount)
+                    } else {
+                        I256::from_raw(*amount).saturating_neg()
+                    };
+                    let expected = v3_swap(
+                        fee,
+                        sqrt_price_x96,
+                        0,
+                        liquidity,
+                        &ticks,
+                        zero_for_one,
+                        specified,
+                        None,
+                    )
+                    .unwrap();
+                    assert_eq!(state.amount_calculated, expected.amount_calculated);
+                    assert_eq!(
+                        state.amount_specified_remaining,
+                        expected.amount_specified_remaining
+                    );
+                    assert_eq!(state.tick_current, expected.tick_current);
+                }
+            }
+        }
+
+        let unsorted = [U256::from(2), U256::from(1)];
+        assert!(v3_swap_many(
+            fee,
+            sqrt_price_x96,
+            0,
+            liquidity,
+            &ticks,
+            true,
+            &unsorted,
+            true
+        )
+        .is_err());
+    }
 }
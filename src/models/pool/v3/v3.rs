@@ -6,6 +6,7 @@ use crate::core::Database;
 use crate::models::pool::base::{
     EventApplicable, PoolInterface, PoolType, PoolTypeTrait, TopicList,
 };
+use crate::models::pool::update::PoolState;
 use crate::models::pool::v3::{get_ramses_quoter, is_ramses_factory, MAX_TICK_I32, MIN_TICK_I32};
 use alloy::primitives::{aliases::U24, Address, Signed, U160, U256};
 use alloy::primitives::{FixedBytes, Uint, U128};
@@ -24,8 +25,9 @@ use std::{collections::BTreeMap, fmt};
 
 use tokio::sync::RwLock;
 
-use super::{v3_swap, Tick, TickMap};
+use super::{v3_swap, v3_swap_many, Tick, TickMap};
 use crate::models::token::TokenRegistry;
+use crate::utils::errors::ArbitrageError;
 
 /// The Q64.96 precision used by Uniswap V3
 pub const Q96_U128: u128 = 1 << 96;
@@ -155,7 +157,7 @@ impl UniswapV3Pool {
     pub fn get_price_from_sqrt_price(&self) -> Result<f64> {
         // Convert sqrtPriceX96 to a human-readable price
         // Price = (sqrtPriceX96 / 2^96)^2
-        let sqrt_price: f64 = self.sqrt_price_x96.to::<u128>() as f64 / Q96_U128 as f64;
+        let sqrt_price: f64 = f64::from(self.sqrt_price_x96) / Q96_U128 as f64;
         Ok(sqrt_price * sqrt_price)
     }
 
@@ -199,6 +201,68 @@ impl UniswapV3Pool {
         Ok(swap_state.amount_calculated.abs().into_raw())
     }
 
+    /// Calculate the amount swapped for each of `amounts` with one tick walk, in the order given
+    fn calculate_many(
+        &self,
+        zero_for_one: bool,
+        amounts: &[U256],
+        is_exact_input: bool,
+    ) -> Result<Vec<U256>> {
+        let mut order: Vec<usize> = (0..amounts.len()).collect();
+        order.sort_unstable_by_key(|&index| amounts[index]);
+        let sorted: Vec<U256> = order.iter().map(|&index| amounts[index]).collect();
+        let states = v3_swap_many(
+            self.fee,
+            self.sqrt_price_x96,
+            self.tick,
+            self.liquidity,
+            &self.ticks,
+            zero_for_one,
+            &sorted,
+            is_exact_input,
+        )?;
+
+        let mut results = vec![U256::ZERO; amounts.len()];
+        for (index, state) in order.into_iter().zip(states) {
+            results[index] = state.amount_calculated.abs().into_raw();
+        }
+        Ok(results)
+    }
+
+    /// Number of initialized ticks an exact input swap crosses, each of which adds to its gas cost
+    pub fn ticks_crossed(&self, token_in: &Address, amount_in: U256) -> Result<usize> {
+        let zero_for_one = if token_in == &self.token0 {
+            true
+        } else if token_in == &self.token1 {
+            false
+        } else {
+            return Err(ArbitrageError::TokenNotInPool {
+                token: *token_in,
+                pool: self.address,
+            }
+            .into());
+        };
+        let swap_state = v3_swap(
+            self.fee,
+            self.sqrt_price_x96,
+            self.tick,
+            self.liquidity,
+            &self.ticks,
+            zero_for_one,
+            Signed::from_raw(amount_in),
+            None,
+        )?;
+        let (lower, upper) = if zero_for_one {
+            (swap_state.tick_current, self.tick)
+        } else {
+            (self.tick, swap_state.tick_current)
+        };
+        if upper <= lower {
+            return Ok(0);
+        }
+        Ok(self.ticks.range(lower + 1..=upper).count())
+    }
+
     /// Get the adjacent initialized ticks for a given tick
     pub fn get_adjacent_ticks(&self, tick: i32) -> (Option<&Tick>, Option<&Tick>) {
         let below = self.ticks.range(..tick).next_back().map(|(_, tick)| tick);
@@ -219,7 +283,11 @@ impl UniswapV3Pool {
         } else if token_in == &self.token1 {
             result = self.calculate_one_for_zero(amount_in, true)?;
         } else {
-            return Err(anyhow!("Token not in pool"));
+            return Err(ArbitrageError::TokenNotInPool {
+                token: *token_in,
+                pool: self.address,
+            }
+            .into());
         }
         if self.pool_type == V3PoolType::RamsesV2 {
             Ok(result * self.ratio_conversion_factor / U256::from(RAMSES_FACTOR))
@@ -235,7 +303,57 @@ impl UniswapV3Pool {
         } else if token_out == &self.token1 {
             self.calculate_zero_for_one(amount_in, false)
         } else {
-            Err(anyhow!("Token not in pool"))
+            Err(ArbitrageError::TokenNotInPool {
+                token: *token_out,
+                pool: self.address,
+            }
+            .into())
+        }
+    }
+
+    /// Calculate the amount out for each amount in, sharing one tick walk across them
+    pub fn calculate_exact_inputs(
+        &self,
+        token_in: &Address,
+        amounts_in: &[U256],
+    ) -> Result<Vec<U256>> {
+        let results = if token_in == &self.token0 {
+            self.calculate_many(true, amounts_in, true)?
+        } else if token_in == &self.token1 {
+            self.calculate_many(false, amounts_in, true)?
+        } else {
+            return Err(ArbitrageError::TokenNotInPool {
+                token: *token_in,
+                pool: self.address,
+            }
+            .into());
+        };
+        if self.pool_type == V3PoolType::RamsesV2 {
+            Ok(results
+                .into_iter()
+                .map(|result| result * self.ratio_conversion_factor / U256::from(RAMSES_FACTOR))
+                .collect())
+        } else {
+            Ok(results)
+        }
+    }
+
+    /// Calculate the amount in for each amount out, sharing one tick walk across them
+    pub fn calculate_exact_outputs(
+        &self,
+        token_out: &Address,
+        amounts_out: &[U256],
+    ) -> Result<Vec<U256>> {
+        if token_out == &self.token0 {
+            self.calculate_many(false, amounts_out, false)
+        } else if token_out == &self.token1 {
+            self.calculate_many(true, amounts_out, false)
+        } else {
+            Err(ArbitrageError::TokenNotInPool {
+                token: *token_out,
+                pool: self.address,
+            }
+            .into())
         }
     }
 
@@ -251,7 +369,11 @@ impl UniswapV3Pool {
 
         // Verify token
         if !self.contains_token(token_in) {
-            return Err(anyhow!("Token not in pool"));
+            return Err(ArbitrageError::TokenNotInPool {
+                token: *token_in,
+                pool: self.address,
+            }
+            .into());
         }
 
         // TODO: Implement this
@@ -352,6 +474,14 @@ impl PoolInterface for UniswapV3Pool {
         self.calculate_exact_output(token_out, amount_out)
     }
 
+    fn calculate_outputs(&self, token_in: &Address, amounts_in: &[U256]) -> Result<Vec<U256>> {
+        self.calculate_exact_inputs(token_in, amounts_in)
+    }
+
+    fn calculate_inputs(&self, token_out: &Address, amounts_out: &[U256]) -> Result<Vec<U256>> {
+        self.calculate_exact_outputs(token_out, amounts_out)
+    }
+
     fn apply_swap(&mut self, token_in: &Address, amount_in: U256, amount_out: U256) -> Result<()> {
         self.apply_swap_internal(token_in, amount_in, amount_out)
     }
@@ -368,6 +498,28 @@ impl PoolInterface for UniswapV3Pool {
         self.fee.to::<u128>() as f64 / FEE_DENOMINATOR as f64
     }
 
+    fn spot_price(&self) -> Option<f64> {
+        self.get_price_from_sqrt_price().ok()
+    }
+
+    fn reserves(&self) -> Option<(f64, f64)> {
+        // Virtual reserves of the active range: x = L / sqrt(P), y = L * sqrt(P)
+        let sqrt_price = f64::from(self.sqrt_price_x96) / Q96_U128 as f64;
+        if sqrt_price == 0.0 {
+            return None;
+        }
+        let liquidity = self.liquidity as f64;
+        Some((liquidity / sqrt_price, liquidity * sqrt_price))
+    }
+
+    fn state(&self) -> Option<PoolState> {
+        Some(PoolState::ConcentratedLiquidity {
+            sqrt_price_x96: U256::from(self.sqrt_price_x96),
+            tick: self.tick,
+            liquidity: self.liquidity,
+        })
+    }
+
     fn id(&self) -> String {
         format!(
             "v3-{}-{}-{}-{}",
@@ -0,0 +1,199 @@
+pub mod multichain_registry;
+pub mod registry;
+
+pub use multichain_registry::MultichainPriceRegistry;
+pub use registry::PriceRegistry;
+
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Default minimum pool liquidity (USD) for a pool to contribute to a price
+pub const DEFAULT_MIN_LIQUIDITY_USD: f64 = 1000.0;
+
+/// Default maximum number of pool hops from a stablecoin anchor
+pub const DEFAULT_MAX_PRICE_HOPS: usize = 4;
+
+/// Default interval between price recomputations in seconds
+pub const DEFAULT_PRICE_UPDATE_INTERVAL: u64 = 10;
+
+/// USD price of a token derived from the pool graph
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TokenUsdPrice {
+    /// Token address
+    pub address: Address,
+    /// Price of one whole token in USD
+    pub price_usd: f64,
+    /// USD liquidity of the pools the price was derived from
+    pub liquidity_usd: f64,
+    /// Number of pool hops from the nearest stablecoin anchor
+    pub hops: usize,
+    /// Number of pools the price was averaged over
+    pub pools: usize,
+}
+
+/// Pool state needed to propagate prices across the graph
+#[derive(Debug, Clone)]
+pub struct PricedPool {
+    pub address: Address,
+    pub token0: Address,
+    pub token1: Address,
+    /// Spot price of token0 in token1, raw units
+    pub price: f64,
+    /// Reserves of (token0, token1), raw units
+    pub reserves: (f64, f64),
+}
+
+/// Derive USD prices for every token reachable from the anchors.
+///
+/// Prices are propagated one hop at a time: a token first reached at hop `n` is priced
+/// from all pools connecting it to tokens priced at earlier hops, weighted by the USD
+/// liquidity on the already priced side. Pools below `min_liquidity_usd` are ignored.
+pub fn compute_usd_prices(
+    pools: &[PricedPool],
+    decimals: &HashMap<Address, u8>,
+    anchors: &[Address],
+    min_liquidity_usd: f64,
+    max_hops: usize,
+) -> HashMap<Address, TokenUsdPrice> {
+    let mut prices: HashMap<Address, TokenUsdPrice> = HashMap::new();
+    for anchor in anchors {
+        if decimals.contains_key(anchor) {
+            prices.insert(
+                *anchor,
+                TokenUsdPrice {
+                    address: *anchor,
+                    price_usd: 1.0,
+                    liquidity_usd: 0.0,
+                    hops: 0,
+                    pools: 0,
+                },
+            );
+        }
+    }
+
+    for hop in 1..=max_hops {
+        // token -> (sum of price * weight, sum of weight, pool count)
+        let mut candidates: HashMap<Address, (f64, f64, usize)> = HashMap::new();
+        for pool in pools {
+            let (Some(&decimals0), Some(&decimals1)) =
+                (decimals.get(&pool.token0), decimals.get(&pool.token1))
+            else {
+                continue;
+            };
+            if !pool.price.is_finite() || pool.price <= 0.0 {
+                continue;
+            }
+            // Price of one whole token0 in whole token1
+            let price = pool.price * 10f64.powi(decimals0 as i32 - decimals1 as i32);
+
+            let sides = [
+                (pool.token0, pool.token1, pool.reserves.0, decimals0, true),
+                (pool.token1, pool.token0, pool.reserves.1, decimals1, false),
+            ];
+            for (known, other, reserve, known_decimals, known_is_token0) in sides {
+                let (Some(known_price), false) = (prices.get(&known), prices.contains_key(&other))
+                else {
+                    continue;
+                };
+                let liquidity_usd =
+                    2.0 * reserve / 10f64.powi(known_decimals as i32) * known_price.price_usd;
+                if !liquidity_usd.is_finite() || liquidity_usd < min_liquidity_usd {
+                    continue;
+                }
+                let other_price = if known_is_token0 {
+                    known_price.price_usd / price
+                } else {
+                    known_price.price_usd * price
+                };
+                if !other_price.is_finite() || other_price <= 0.0 {
+                    continue;
+                }
+                let entry = candidates.entry(other).or_insert((0.0, 0.0, 0));
+                entry.0 += other_price * liquidity_usd;
+                entry.1 += liquidity_usd;
+                entry.2 += 1;
+            }
+        }
+
+        if candidates.is_empty() {
+            break;
+        }
+        for (address, (weighted_sum, weight, pools)) in candidates {
+            prices.insert(
+                address,
+                TokenUsdPrice {
+                    address,
+                    price_usd: weighted_sum / weight,
+                    liquidity_usd: weight,
+                    hops: hop,
+                    pools,
+                },
+            );
+        }
+    }
+
+    prices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(
+        address: u8,
+        token0: Address,
+        token1: Address,
+        reserve0: f64,
+        reserve1: f64,
+    ) -> PricedPool {
+        PricedPool {
+            address: Address::repeat_byte(address),
+            token0,
+            token1,
+            price: reserve1 / reserve0,
+            reserves: (reserve0, reserve1),
+        }
+    }
+
+    #[test]
+    fn test_compute_usd_prices() {
+        let usdc = Address::repeat_byte(1);
+        let weth = Address::repeat_byte(2);
+        let token = Address::repeat_byte(3);
+        let unreachable = Address::repeat_byte(4);
+        let decimals = HashMap::from([(usdc, 6), (weth, 18), (token, 18), (unreachable, 18)]);
+
+        let pools = vec![
+            // 1,000 WETH / 2,000,000 USDC -> 2000
+            pool(10, weth, usdc, 1000e18, 2_000_000e6),
+            // 500 WETH / 1,100,000 USDC -> 2200 with half the liquidity
+            pool(11, usdc, weth, 1_100_000e6, 500e18),
+            // Thin pool with a wild price is ignored
+            pool(12, weth, usdc, 0.01e18, 100e6),
+            // 1,000,000 TOKEN / 100 WETH -> 0.0001 WETH each
+            pool(13, token, weth, 1_000_000e18, 100e18),
+        ];
+
+        let prices = compute_usd_prices(&pools, &decimals, &[usdc], 1000.0, 4);
+
+        assert_eq!(prices[&usdc].price_usd, 1.0);
+        assert_eq!(prices[&usdc].hops, 0);
+
+        let weth_price = prices[&weth].price_usd;
+        let expected = (2000.0 * 4_000_000.0 + 2200.0 * 2_200_000.0) / 6_200_000.0;
+        assert!((weth_price - expected).abs() < 1e-6);
+        assert_eq!(prices[&weth].pools, 2);
+        assert_eq!(prices[&weth].hops, 1);
+
+        let token_price = prices[&token].price_usd;
+        assert!((token_price - weth_price / 10_000.0).abs() < 1e-9);
+        assert_eq!(prices[&token].hops, 2);
+
+        assert!(!prices.contains_key(&unreachable));
+
+        // Limiting hops stops propagation
+        let prices = compute_usd_prices(&pools, &decimals, &[usdc], 1000.0, 1);
+        assert!(!prices.contains_key(&token));
+    }
+}
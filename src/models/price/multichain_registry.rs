@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::models::price::registry::PriceRegistry;
+
+#[derive(Debug, Default)]
+pub struct MultichainPriceRegistry {
+    registries: Arc<RwLock<HashMap<u64, Arc<PriceRegistry>>>>,
+}
+
+impl MultichainPriceRegistry {
+    pub fn new() -> Self {
+        Self {
+            registries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn add_price_registry(&self, network_id: u64, price_registry: Arc<PriceRegistry>) {
+        let mut registries = self.registries.write().await;
+        registries.insert(network_id, price_registry);
+    }
+
+    pub async fn get_price_registry(&self, network_id: u64) -> Option<Arc<PriceRegistry>> {
+        let registries = self.registries.read().await;
+        registries.get(&network_id).map(Arc::clone)
+    }
+
+    pub async fn get_all_network_ids(&self) -> Vec<u64> {
+        let registries = self.registries.read().await;
+        registries.keys().cloned().collect()
+    }
+
+    pub async fn remove_price_registry(&self, network_id: u64) -> Option<Arc<PriceRegistry>> {
+        let mut registries = self.registries.write().await;
+        registries.remove(&network_id)
+    }
+}
@@ -0,0 +1,184 @@
+use crate::models::pool::PoolRegistry;
+use crate::models::price::{compute_usd_prices, PricedPool, TokenUsdPrice};
+use crate::models::token::TokenRegistry;
+use alloy::primitives::Address;
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+use tracing::{info_span, Instrument};
+
+/// USD prices for the tokens of a single chain, derived from its pools
+#[derive(Debug)]
+pub struct PriceRegistry {
+    network_id: u64,
+    /// Stablecoins priced at 1 USD that seed the pool graph walk
+    anchors: Vec<Address>,
+    min_liquidity_usd: f64,
+    max_hops: usize,
+    prices: Arc<RwLock<HashMap<Address, TokenUsdPrice>>>,
+    /// Unix timestamp of the last recomputation
+    updated_at: Arc<RwLock<u64>>,
+}
+
+impl PriceRegistry {
+    pub fn new(
+        network_id: u64,
+        anchors: Vec<Address>,
+        min_liquidity_usd: f64,
+        max_hops: usize,
+    ) -> Self {
+        Self {
+            network_id,
+            anchors,
+            min_liquidity_usd,
+            max_hops,
+            prices: Arc::new(RwLock::new(HashMap::new())),
+            updated_at: Arc::new(RwLock::new(0)),
+        }
+    }
+
+    /// Get network ID
+    pub fn get_network_id(&self) -> u64 {
+        self.network_id
+    }
+
+    /// Get the stablecoin anchors
+    pub fn anchors(&self) -> &[Address] {
+        &self.anchors
+    }
+
+    /// Get the USD price of a token
+    pub async fn get_price(&self, token: &Address) -> Option<TokenUsdPrice> {
+        self.prices.read().await.get(token).cloned()
+    }
+
+    /// Get all known USD prices
+    pub async fn get_all_prices(&self) -> HashMap<Address, TokenUsdPrice> {
+        self.prices.read().await.clone()
+    }
+
+    /// Get the unix timestamp of the last recomputation
+    pub async fn updated_at(&self) -> u64 {
+        *self.updated_at.read().await
+    }
+
+    /// Recompute all prices from the current pool state, returning the number of priced tokens
+    pub async fn update(
+        &self,
+        pool_registry: &PoolRegistry,
+        token_registry: &RwLock<TokenRegistry>,
+    ) -> usize {
+        let mut pools = Vec::new();
+        for pool in pool_registry.get_all_pools().await {
+            let pool = pool.read().await;
+            let (Some(price), Some(reserves)) = (pool.spot_price(), pool.reserves()) else {
+                continue;
+            };
+            let (token0, token1) = pool.tokens();
+            pools.push(PricedPool {
+                address: pool.address(),
+                token0,
+                token1,
+                price,
+                reserves,
+            });
+        }
+
+        let decimals: HashMap<Address, u8> = token_registry
+            .read()
+            .await
+            .get_all_tokens()
+            .iter()
+            .map(|token| (token.address, token.decimals))
+            .collect();
+
+        let prices = compute_usd_prices(
+            &pools,
+            &decimals,
+            &self.anchors,
+            self.min_liquidity_usd,
+            self.max_hops,
+        );
+        let count = prices.len();
+        *self.prices.write().await = prices;
+        *self.updated_at.write().await = chrono::Utc::now().timestamp() as u64;
+        debug!(
+            "Priced {} of {} tokens from {} pools",
+            count,
+            decimals.len(),
+            pools.len()
+        );
+        count
+    }
+
+    /// Periodically recompute prices in the background, until the returned task is aborted
+    pub fn start(
+        self: &Arc<Self>,
+        pool_registry: Arc<PoolRegistry>,
+        token_registry: Arc<RwLock<TokenRegistry>>,
+        update_interval: u64,
+    ) -> JoinHandle<()> {
+        info!("Starting price registry with {}s interval", update_interval);
+        let registry = Arc::clone(self);
+        let span = info_span!("price_registry", chain_id = self.network_id);
+        tokio::spawn(
+            async move {
+                let mut interval = interval(Duration::from_secs(update_interval.max(1)));
+                loop {
+                    interval.tick().await;
+                    registry.update(&pool_registry, &token_registry).await;
+                }
+            }
+            .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::mock::MockPool;
+    use crate::models::token::Token;
+    use alloy::primitives::U256;
+
+    #[tokio::test]
+    async fn test_price_registry_update() {
+        let usdc = Address::repeat_byte(1);
+        let weth = Address::repeat_byte(2);
+
+        let pool_registry = PoolRegistry::new(1);
+        pool_registry
+            .add_pool(Box::new(MockPool::new_v2(
+                Address::repeat_byte(10),
+                weth,
+                usdc,
+                U256::from(1000) * U256::from(10).pow(U256::from(18)),
+                U256::from(2_000_000) * U256::from(10).pow(U256::from(6)),
+            )))
+            .await;
+
+        let mut token_registry = TokenRegistry::new(1);
+        token_registry.add_token(Token::new(usdc, 1, "USDC".into(), "USD Coin".into(), 6));
+        token_registry.add_token(Token::new(
+            weth,
+            1,
+            "WETH".into(),
+            "Wrapped Ether".into(),
+            18,
+        ));
+        let token_registry = RwLock::new(token_registry);
+
+        let price_registry = PriceRegistry::new(1, vec![usdc], 1000.0, 4);
+        assert_eq!(
+            price_registry.update(&pool_registry, &token_registry).await,
+            2
+        );
+
+        let weth_price = price_registry.get_price(&weth).await.unwrap();
+        assert!((weth_price.price_usd - 2000.0).abs() < 1e-6);
+        assert!(price_registry.updated_at().await > 0);
+    }
+}
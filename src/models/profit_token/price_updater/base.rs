@@ -3,16 +3,33 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum PriceSourceType {
     /// Geckoterminal
     GeckoTerminal,
+    /// Prices derived from indexed pools
+    OnChain,
+    /// Chainlink aggregator feeds
+    Chainlink,
+    /// Manually maintained prices loaded from a file
+    Static,
 }
 
 #[derive(Debug, Clone)]
 pub struct TokenPrice {
     pub address: Address,
     pub price: f64,
+    /// Unix timestamp the price was last updated at by its source
+    pub updated_at: u64,
+    /// Source the price came from
+    pub source: PriceSourceType,
+}
+
+impl TokenPrice {
+    /// Age of the price in seconds relative to `now`
+    pub fn age(&self, now: u64) -> u64 {
+        now.saturating_sub(self.updated_at)
+    }
 }
 
 #[async_trait::async_trait]
@@ -0,0 +1,218 @@
+use alloy::primitives::Address;
+use alloy::providers::{MulticallItem, Provider};
+use anyhow::Result;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::base::{PriceFetcher, PriceSourceType, TokenPrice};
+use crate::blockchain::IChainlinkAggregator;
+
+/// Reads USD prices from Chainlink aggregators, batching all feeds into one multicall
+pub struct ChainlinkPriceFetcher<P: Provider + Send + Sync> {
+    provider: Arc<P>,
+    multicall_address: Address,
+    /// token -> aggregator
+    feeds: Arc<RwLock<HashMap<Address, Address>>>,
+    /// aggregator -> answer decimals
+    decimals: Arc<RwLock<HashMap<Address, u8>>>,
+    token_addresses: Arc<RwLock<Vec<Address>>>,
+}
+
+impl<P: Provider + Send + Sync> ChainlinkPriceFetcher<P> {
+    pub fn new(provider: Arc<P>, multicall_address: Address) -> Self {
+        Self {
+            provider,
+            multicall_address,
+            feeds: Arc::new(RwLock::new(HashMap::new())),
+            decimals: Arc::new(RwLock::new(HashMap::new())),
+            token_addresses: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Register the USD aggregator for a token and start tracking it
+    pub async fn add_feed(&self, token: Address, aggregator: Address) {
+        self.feeds.write().await.insert(token, aggregator);
+        self.add_token(token).await;
+    }
+
+    /// Fetch and cache answer decimals for aggregators seen for the first time
+    async fn load_decimals(&self, aggregators: &[Address]) -> Result<()> {
+        let missing: Vec<Address> = {
+            let decimals = self.decimals.read().await;
+            aggregators
+                .iter()
+                .filter(|aggregator| !decimals.contains_key(*aggregator))
+                .copied()
+                .collect()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let mut multicall = self
+            .provider
+            .multicall()
+            .address(self.multicall_address)
+            .dynamic();
+        for aggregator in &missing {
+            multicall = multicall
+                .add_dynamic(IChainlinkAggregator::new(*aggregator, &self.provider).decimals());
+        }
+        let results = multicall.aggregate().await?;
+
+        let mut decimals = self.decimals.write().await;
+        for (aggregator, value) in missing.into_iter().zip(results) {
+            decimals.insert(aggregator, value);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<P: Provider + Send + Sync> PriceFetcher for ChainlinkPriceFetcher<P> {
+    async fn fetch_prices(&self) -> Result<HashMap<Address, TokenPrice>> {
+        let tracked: Vec<(Address, Address)> = {
+            let feeds = self.feeds.read().await;
+            self.token_addresses
+                .read()
+                .await
+                .iter()
+                .filter_map(|token| feeds.get(token).map(|aggregator| (*token, *aggregator)))
+                .collect()
+        };
+        if tracked.is_empty() {
+            return Ok(HashMap::new());
+        }
+        info!("Fetching {} Chainlink feeds", tracked.len());
+
+        let aggregators: Vec<Address> = tracked.iter().map(|(_, aggregator)| *aggregator).collect();
+        self.load_decimals(&aggregators).await?;
+
+        let mut multicall = self
+            .provider
+            .multicall()
+            .address(self.multicall_address)
+            .dynamic();
+        for aggregator in &aggregators {
+            // A single broken feed should not fail the whole batch
+            multicall = multicall.add_call_dynamic(
+                IChainlinkAggregator::new(*aggregator, &self.provider)
+                    .latestRoundData()
+                    .into_call(true),
+            );
+        }
+        let results = multicall.aggregate3().await?;
+
+        let decimals = self.decimals.read().await;
+        let mut prices = HashMap::with_capacity(tracked.len());
+        for ((token, aggregator), result) in tracked.into_iter().zip(results) {
+            let round = match result {
+                Ok(round) => round,
+                Err(_) => {
+                    warn!("Chainlink feed {} for token {} reverted", aggregator, token);
+                    continue;
+                }
+            };
+            if round.answer.is_negative() || round.answer.is_zero() {
+                warn!("Chainlink feed {} returned non-positive answer", aggregator);
+                continue;
+            }
+            let answer_decimals = decimals.get(&aggregator).copied().unwrap_or(8);
+            let price = f64::from(round.answer.unsigned_abs()) / 10f64.powi(answer_decimals as i32);
+            prices.insert(
+                token,
+                TokenPrice {
+                    address: token,
+                    price,
+                    updated_at: u64::try_from(round.updatedAt).unwrap_or(u64::MAX),
+                    source: PriceSourceType::Chainlink,
+                },
+            );
+        }
+        Ok(prices)
+    }
+
+    fn get_price_source_type(&self) -> PriceSourceType {
+        PriceSourceType::Chainlink
+    }
+
+    async fn add_token(&self, address: Address) {
+        let mut addresses = self.token_addresses.write().await;
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+
+    async fn remove_token(&self, address: Address) {
+        let mut addresses = self.token_addresses.write().await;
+        addresses.retain(|&addr| addr != address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{aliases::U80, Bytes, I256, U256};
+    use alloy::providers::{bindings::IMulticall3, ProviderBuilder, MULTICALL3_ADDRESS};
+    use alloy::sol_types::SolCall;
+    use alloy::transports::mock::Asserter;
+
+    fn encode_round(answer: i64, updated_at: u64) -> Bytes {
+        IChainlinkAggregator::latestRoundDataCall::abi_encode_returns(
+            &IChainlinkAggregator::latestRoundDataReturn {
+                roundId: U80::from(1),
+                answer: I256::try_from(answer).unwrap(),
+                startedAt: U256::from(updated_at),
+                updatedAt: U256::from(updated_at),
+                answeredInRound: U80::from(1),
+            },
+        )
+        .into()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_prices_from_mock_provider() {
+        let asserter = Asserter::new();
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .disable_recommended_fillers()
+                .connect_mocked_client(asserter.clone()),
+        );
+        let fetcher = ChainlinkPriceFetcher::new(provider, MULTICALL3_ADDRESS);
+
+        let weth = Address::repeat_byte(1);
+        let broken = Address::repeat_byte(2);
+        fetcher.add_feed(weth, Address::repeat_byte(0x10)).await;
+        fetcher.add_feed(broken, Address::repeat_byte(0x20)).await;
+
+        // decimals() for both aggregators
+        let decimals = IChainlinkAggregator::decimalsCall::abi_encode_returns(&8u8);
+        asserter.push_success(&Bytes::from(
+            IMulticall3::aggregateCall::abi_encode_returns(&IMulticall3::aggregateReturn {
+                blockNumber: U256::from(1),
+                returnData: vec![decimals.clone().into(), decimals.into()],
+            }),
+        ));
+        // latestRoundData() where the second feed reverts
+        asserter.push_success(&Bytes::from(
+            IMulticall3::aggregate3Call::abi_encode_returns(&vec![
+                IMulticall3::Result {
+                    success: true,
+                    returnData: encode_round(200_050_000_000, 1_700_000_000),
+                },
+                IMulticall3::Result {
+                    success: false,
+                    returnData: Bytes::new(),
+                },
+            ]),
+        ));
+
+        let prices = fetcher.fetch_prices().await.unwrap();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[&weth].price, 2000.5);
+        assert_eq!(prices[&weth].updated_at, 1_700_000_000);
+        assert_eq!(prices[&weth].source, PriceSourceType::Chainlink);
+    }
+}
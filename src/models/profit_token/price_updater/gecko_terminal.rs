@@ -9,6 +9,8 @@ use tokio::sync::RwLock;
 
 use super::base::{PriceFetcher, PriceSourceType, TokenPrice};
 
+const GECKO_TERMINAL_API_URL: &str = "https://api.geckoterminal.com/api/v2";
+
 #[derive(Debug, Serialize, Deserialize)]
 struct GeckoTerminalResponse {
     data: GeckoTerminalData,
@@ -29,14 +31,21 @@ struct GeckoTerminalAttributes {
 
 pub struct GeckoTerminalPriceFetcher {
     network: String,
+    base_url: String,
     token_addresses: Arc<RwLock<Vec<Address>>>,
     client: Client,
 }
 
 impl GeckoTerminalPriceFetcher {
     pub fn new(network: String) -> Self {
+        Self::with_base_url(network, GECKO_TERMINAL_API_URL.to_string())
+    }
+
+    /// Create a fetcher against a custom API base URL
+    pub fn with_base_url(network: String, base_url: String) -> Self {
         Self {
             network,
+            base_url: base_url.trim_end_matches('/').to_string(),
             token_addresses: Arc::new(RwLock::new(Vec::new())),
             client: Client::new(),
         }
@@ -68,12 +77,13 @@ impl PriceFetcher for GeckoTerminalPriceFetcher {
         );
 
         let url = format!(
-            "https://api.geckoterminal.com/api/v2/simple/networks/{}/token_price/{}",
-            self.network, address_str
+            "{}/simple/networks/{}/token_price/{}",
+            self.base_url, self.network, address_str
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.client.get(&url).send().await?.error_for_status()?;
         let data: GeckoTerminalResponse = response.json().await?;
+        let updated_at = chrono::Utc::now().timestamp() as u64;
         let mut prices = HashMap::with_capacity(addresses.len());
         for address in addresses.iter() {
             if let Some(price_str) = data
@@ -88,6 +98,8 @@ impl PriceFetcher for GeckoTerminalPriceFetcher {
                         TokenPrice {
                             address: *address,
                             price,
+                            updated_at,
+                            source: PriceSourceType::GeckoTerminal,
                         },
                     );
                 }
@@ -113,3 +125,45 @@ impl PriceFetcher for GeckoTerminalPriceFetcher {
         addresses.retain(|&addr| addr != address);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, routing::get, Json, Router};
+
+    #[tokio::test]
+    async fn test_fetch_prices_from_mock_server() {
+        let token = Address::repeat_byte(0xab);
+        let app = Router::new().route(
+            "/simple/networks/:network/token_price/:addresses",
+            get(
+                |Path((network, addresses)): Path<(String, String)>| async move {
+                    assert_eq!(network, "eth");
+                    let prices: HashMap<String, String> = addresses
+                        .split(',')
+                        .map(|address| (address.to_lowercase(), "1.25".to_string()))
+                        .collect();
+                    Json(serde_json::json!({
+                        "data": {
+                            "id": "1",
+                            "type": "simple_token_price",
+                            "attributes": { "token_prices": prices }
+                        }
+                    }))
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let fetcher =
+            GeckoTerminalPriceFetcher::with_base_url("eth".to_string(), format!("http://{}", addr));
+        assert!(fetcher.fetch_prices().await.unwrap().is_empty());
+
+        fetcher.add_token(token).await;
+        let prices = fetcher.fetch_prices().await.unwrap();
+        assert_eq!(prices[&token].price, 1.25);
+        assert_eq!(prices[&token].source, PriceSourceType::GeckoTerminal);
+    }
+}
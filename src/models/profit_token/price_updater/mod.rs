@@ -1,7 +1,13 @@
 pub mod base;
+pub mod chainlink;
 pub mod gecko_terminal;
+pub mod on_chain;
 pub mod price_updater;
+pub mod static_source;
 
 pub use base::*;
+pub use chainlink::*;
 pub use gecko_terminal::*;
+pub use on_chain::*;
 pub use price_updater::*;
+pub use static_source::*;
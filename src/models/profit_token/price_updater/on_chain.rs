@@ -0,0 +1,118 @@
+use alloy::primitives::Address;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::base::{PriceFetcher, PriceSourceType, TokenPrice};
+use crate::models::price::PriceRegistry;
+
+/// Serves prices derived from the indexed pools of a chain
+pub struct OnChainPriceFetcher {
+    price_registry: Arc<PriceRegistry>,
+    token_addresses: Arc<RwLock<Vec<Address>>>,
+}
+
+impl OnChainPriceFetcher {
+    pub fn new(price_registry: Arc<PriceRegistry>) -> Self {
+        Self {
+            price_registry,
+            token_addresses: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceFetcher for OnChainPriceFetcher {
+    async fn fetch_prices(&self) -> Result<HashMap<Address, TokenPrice>> {
+        let updated_at = self.price_registry.updated_at().await;
+        let addresses = self.token_addresses.read().await;
+        let mut prices = HashMap::with_capacity(addresses.len());
+        for address in addresses.iter() {
+            if let Some(price) = self.price_registry.get_price(address).await {
+                prices.insert(
+                    *address,
+                    TokenPrice {
+                        address: *address,
+                        price: price.price_usd,
+                        updated_at,
+                        source: PriceSourceType::OnChain,
+                    },
+                );
+            }
+        }
+        Ok(prices)
+    }
+
+    fn get_price_source_type(&self) -> PriceSourceType {
+        PriceSourceType::OnChain
+    }
+
+    async fn add_token(&self, address: Address) {
+        let mut addresses = self.token_addresses.write().await;
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+
+    async fn remove_token(&self, address: Address) {
+        let mut addresses = self.token_addresses.write().await;
+        addresses.retain(|&addr| addr != address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::mock::MockPool;
+    use crate::models::pool::PoolRegistry;
+    use crate::models::token::{Token, TokenRegistry};
+    use alloy::primitives::U256;
+
+    #[tokio::test]
+    async fn test_on_chain_prices_from_registry() {
+        let usdc = Address::repeat_byte(1);
+        let weth = Address::repeat_byte(2);
+        let unpriced = Address::repeat_byte(3);
+
+        let pool_registry = PoolRegistry::new(1);
+        pool_registry
+            .add_pool(Box::new(MockPool::new_v2(
+                Address::repeat_byte(10),
+                weth,
+                usdc,
+                U256::from(1000) * U256::from(10).pow(U256::from(18)),
+                U256::from(2_000_000) * U256::from(10).pow(U256::from(6)),
+            )))
+            .await;
+        let mut token_registry = TokenRegistry::new(1);
+        token_registry.add_token(Token::new(usdc, 1, "USDC".into(), "USD Coin".into(), 6));
+        token_registry.add_token(Token::new(
+            weth,
+            1,
+            "WETH".into(),
+            "Wrapped Ether".into(),
+            18,
+        ));
+        let token_registry = RwLock::new(token_registry);
+
+        let price_registry = Arc::new(PriceRegistry::new(1, vec![usdc], 1000.0, 4));
+        let fetcher = OnChainPriceFetcher::new(price_registry.clone());
+        fetcher.add_token(weth).await;
+        fetcher.add_token(unpriced).await;
+
+        // Nothing is priced before the registry's first update
+        assert!(fetcher.fetch_prices().await.unwrap().is_empty());
+
+        price_registry.update(&pool_registry, &token_registry).await;
+        let prices = fetcher.fetch_prices().await.unwrap();
+        assert_eq!(prices.len(), 1);
+        let price = &prices[&weth];
+        assert!((price.price - 2000.0).abs() < 1e-6);
+        assert_eq!(price.updated_at, price_registry.updated_at().await);
+        assert_eq!(price.source, PriceSourceType::OnChain);
+
+        fetcher.remove_token(weth).await;
+        assert!(fetcher.fetch_prices().await.unwrap().is_empty());
+    }
+}
@@ -1,12 +1,34 @@
 use alloy::primitives::Address;
-use anyhow::Result;
+use alloy::providers::Provider;
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use super::{base::PriceFetcher, GeckoTerminalPriceFetcher, PriceSourceType, TokenPrice};
+use super::{
+    base::PriceFetcher, ChainlinkPriceFetcher, GeckoTerminalPriceFetcher, OnChainPriceFetcher,
+    PriceSourceType, StaticPriceFetcher, TokenPrice,
+};
+use crate::models::price::PriceRegistry;
+use crate::utils::config::PriceSourcesConfig;
+
+/// Per-token price source preferences
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PricePolicy {
+    /// Sources to try in order; empty means every source in registration order
+    #[serde(default)]
+    pub sources: Vec<PriceSourceType>,
+    /// Maximum age of a price in seconds before falling back to the next source
+    pub max_staleness: Option<u64>,
+}
 
 #[derive(Default)]
 pub struct PriceUpdater {
     price_fetchers: Vec<Box<dyn PriceFetcher>>,
+    policies: HashMap<Address, PricePolicy>,
+    /// Staleness limit for tokens without their own
+    default_max_staleness: Option<u64>,
 }
 
 impl PriceUpdater {
@@ -18,7 +40,39 @@ impl PriceUpdater {
 
         Self {
             price_fetchers: vec![Box::new(gecko_terminal_price_fetcher)],
+            ..Default::default()
+        }
+    }
+
+    /// Register the sources a chain configures, in GeckoTerminal, on-chain, Chainlink and static
+    /// order
+    pub async fn from_config<P: Provider + Send + Sync + 'static>(
+        config: &PriceSourcesConfig,
+        provider: Arc<P>,
+        multicall_address: Address,
+        price_registry: Arc<PriceRegistry>,
+    ) -> Result<Self> {
+        let mut updater = Self::default();
+        if let Some(network) = &config.gecko_terminal_network {
+            updater.add_price_fetcher(Box::new(GeckoTerminalPriceFetcher::new(network.clone())));
+        }
+        if config.on_chain {
+            updater.add_price_fetcher(Box::new(OnChainPriceFetcher::new(price_registry)));
         }
+        if !config.chainlink_feeds.is_empty() {
+            let chainlink = ChainlinkPriceFetcher::new(provider, multicall_address);
+            for (token, aggregator) in &config.chainlink_feeds {
+                chainlink
+                    .add_feed(token.parse::<Address>()?, aggregator.parse::<Address>()?)
+                    .await;
+            }
+            updater.add_price_fetcher(Box::new(chainlink));
+        }
+        if let Some(path) = &config.static_prices_path {
+            updater.add_price_fetcher(Box::new(StaticPriceFetcher::new(path)));
+        }
+        updater.set_default_max_staleness(config.max_staleness);
+        Ok(updater)
     }
 
     pub async fn add_token(&self, token: Address, price_source: PriceSourceType) {
@@ -30,18 +84,211 @@ impl PriceUpdater {
         }
     }
 
+    /// Track a token on every source in its policy and use the policy when picking its price
+    pub async fn add_token_with_policy(&mut self, token: Address, policy: PricePolicy) {
+        for source in &policy.sources {
+            self.add_token(token, source.clone()).await;
+        }
+        self.policies.insert(token, policy);
+    }
+
+    pub fn get_policy(&self, token: &Address) -> Option<&PricePolicy> {
+        self.policies.get(token)
+    }
+
+    pub fn set_default_max_staleness(&mut self, max_staleness: Option<u64>) {
+        self.default_max_staleness = max_staleness;
+    }
+
     pub fn add_price_fetcher(&mut self, price_fetcher: Box<dyn PriceFetcher>) {
         self.price_fetchers.push(price_fetcher);
     }
 
+    /// Fetch from every source and pick, per token, the first fresh price in its fallback order.
+    /// Fails only when every source fails.
     pub async fn update_prices(&self) -> Result<HashMap<Address, TokenPrice>> {
-        let mut prices = HashMap::new();
+        let mut fetched: Vec<(PriceSourceType, HashMap<Address, TokenPrice>)> = Vec::new();
+        let mut last_error = None;
         for fetcher in &self.price_fetchers {
-            let returned_prices = fetcher.fetch_prices().await?;
-            for (address, token_price) in returned_prices {
-                prices.insert(address, token_price);
+            let source = fetcher.get_price_source_type();
+            match fetcher.fetch_prices().await {
+                Ok(prices) => fetched.push((source, prices)),
+                Err(e) => {
+                    warn!("Price source {:?} failed: {}", source, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        if fetched.is_empty() {
+            if let Some(e) = last_error {
+                return Err(anyhow!("All price sources failed, last error: {}", e));
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        let default_order: Vec<PriceSourceType> =
+            fetched.iter().map(|(source, _)| source.clone()).collect();
+        let mut prices = HashMap::new();
+        for (_, source_prices) in &fetched {
+            for address in source_prices.keys() {
+                if prices.contains_key(address) {
+                    continue;
+                }
+                let policy = self.policies.get(address);
+                let order = match policy {
+                    Some(policy) if !policy.sources.is_empty() => &policy.sources,
+                    _ => &default_order,
+                };
+                let max_staleness = policy
+                    .and_then(|policy| policy.max_staleness)
+                    .or(self.default_max_staleness);
+
+                let chosen = order.iter().find_map(|source| {
+                    let (_, source_prices) = fetched.iter().find(|(s, _)| s == source)?;
+                    let price = source_prices.get(address)?;
+                    match max_staleness {
+                        Some(limit) if price.age(now) > limit => {
+                            debug!(
+                                "Skipping stale {:?} price for {} ({}s old)",
+                                source,
+                                address,
+                                price.age(now)
+                            );
+                            None
+                        }
+                        _ => Some(price.clone()),
+                    }
+                });
+                if let Some(price) = chosen {
+                    prices.insert(*address, price);
+                }
             }
         }
         Ok(prices)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    struct FixedPriceFetcher {
+        source: PriceSourceType,
+        prices: Arc<RwLock<HashMap<Address, (f64, u64)>>>,
+        fail: bool,
+    }
+
+    impl FixedPriceFetcher {
+        fn new(source: PriceSourceType, prices: Vec<(Address, f64, u64)>) -> Self {
+            Self {
+                source,
+                prices: Arc::new(RwLock::new(
+                    prices
+                        .into_iter()
+                        .map(|(address, price, updated_at)| (address, (price, updated_at)))
+                        .collect(),
+                )),
+                fail: false,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PriceFetcher for FixedPriceFetcher {
+        async fn fetch_prices(&self) -> Result<HashMap<Address, TokenPrice>> {
+            if self.fail {
+                return Err(anyhow!("source down"));
+            }
+            Ok(self
+                .prices
+                .read()
+                .await
+                .iter()
+                .map(|(address, (price, updated_at))| {
+                    (
+                        *address,
+                        TokenPrice {
+                            address: *address,
+                            price: *price,
+                            updated_at: *updated_at,
+                            source: self.source.clone(),
+                        },
+                    )
+                })
+                .collect())
+        }
+
+        fn get_price_source_type(&self) -> PriceSourceType {
+            self.source.clone()
+        }
+
+        async fn add_token(&self, _token: Address) {}
+
+        async fn remove_token(&self, _token: Address) {}
+    }
+
+    #[tokio::test]
+    async fn test_fallback_order_and_staleness() {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let fresh_token = Address::repeat_byte(1);
+        let stale_token = Address::repeat_byte(2);
+        let default_token = Address::repeat_byte(3);
+
+        let mut updater = PriceUpdater::default();
+        updater.add_price_fetcher(Box::new(FixedPriceFetcher::new(
+            PriceSourceType::Chainlink,
+            vec![
+                (fresh_token, 10.0, now),
+                (stale_token, 20.0, now - 7200),
+                (default_token, 30.0, now),
+            ],
+        )));
+        updater.add_price_fetcher(Box::new(FixedPriceFetcher::new(
+            PriceSourceType::OnChain,
+            vec![
+                (fresh_token, 11.0, now),
+                (stale_token, 21.0, now),
+                (default_token, 31.0, now),
+            ],
+        )));
+        let mut failing = FixedPriceFetcher::new(PriceSourceType::GeckoTerminal, vec![]);
+        failing.fail = true;
+        updater.add_price_fetcher(Box::new(failing));
+
+        let policy = PricePolicy {
+            sources: vec![PriceSourceType::Chainlink, PriceSourceType::OnChain],
+            max_staleness: Some(3600),
+        };
+        updater
+            .add_token_with_policy(fresh_token, policy.clone())
+            .await;
+        updater.add_token_with_policy(stale_token, policy).await;
+        // Prefer on-chain for this token even though Chainlink was registered first
+        updater
+            .add_token_with_policy(
+                default_token,
+                PricePolicy {
+                    sources: vec![PriceSourceType::OnChain],
+                    max_staleness: None,
+                },
+            )
+            .await;
+
+        let prices = updater.update_prices().await.unwrap();
+        assert_eq!(prices[&fresh_token].price, 10.0);
+        assert_eq!(prices[&stale_token].price, 21.0);
+        assert_eq!(prices[&stale_token].source, PriceSourceType::OnChain);
+        assert_eq!(prices[&default_token].price, 31.0);
+
+        // A stale-only price is dropped entirely
+        let mut updater = PriceUpdater::default();
+        updater.add_price_fetcher(Box::new(FixedPriceFetcher::new(
+            PriceSourceType::Static,
+            vec![(stale_token, 20.0, now - 7200)],
+        )));
+        updater.set_default_max_staleness(Some(60));
+        assert!(updater.update_prices().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use alloy::primitives::Address;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::base::{PriceFetcher, PriceSourceType, TokenPrice};
+
+/// Serves manually maintained prices from a TOML file of `"<token address>" = <usd price>` entries.
+/// The file is re-read on every fetch so edits apply without a restart.
+pub struct StaticPriceFetcher {
+    path: PathBuf,
+    token_addresses: Arc<RwLock<Vec<Address>>>,
+}
+
+impl StaticPriceFetcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            token_addresses: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Load all prices from the file
+    pub fn load(&self) -> Result<HashMap<Address, f64>> {
+        let content = std::fs::read_to_string(&self.path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", self.path.display(), e))?;
+        let raw: HashMap<String, f64> = toml::from_str(&content)?;
+        raw.into_iter()
+            .map(|(address, price)| {
+                let address = Address::from_str(&address)
+                    .map_err(|e| anyhow!("Invalid token address {}: {}", address, e))?;
+                Ok((address, price))
+            })
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceFetcher for StaticPriceFetcher {
+    async fn fetch_prices(&self) -> Result<HashMap<Address, TokenPrice>> {
+        let addresses = self.token_addresses.read().await;
+        if addresses.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let file_prices = self.load()?;
+        // Manual prices never go stale on their own
+        let updated_at = chrono::Utc::now().timestamp() as u64;
+        let mut prices = HashMap::with_capacity(addresses.len());
+        for address in addresses.iter() {
+            if let Some(price) = file_prices.get(address) {
+                prices.insert(
+                    *address,
+                    TokenPrice {
+                        address: *address,
+                        price: *price,
+                        updated_at,
+                        source: PriceSourceType::Static,
+                    },
+                );
+            }
+        }
+        Ok(prices)
+    }
+
+    fn get_price_source_type(&self) -> PriceSourceType {
+        PriceSourceType::Static
+    }
+
+    async fn add_token(&self, address: Address) {
+        let mut addresses = self.token_addresses.write().await;
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+
+    async fn remove_token(&self, address: Address) {
+        let mut addresses = self.token_addresses.write().await;
+        addresses.retain(|&addr| addr != address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_static_prices_from_file() {
+        let token = Address::repeat_byte(0x11);
+        let other = Address::repeat_byte(0x22);
+        let path = std::env::temp_dir().join(format!("static-prices-{}.toml", std::process::id()));
+        std::fs::write(&path, format!("\"{}\" = 0.5\n\"{}\" = 3.0\n", token, other)).unwrap();
+
+        let fetcher = StaticPriceFetcher::new(&path);
+        fetcher.add_token(token).await;
+        let prices = fetcher.fetch_prices().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[&token].price, 0.5);
+        assert_eq!(prices[&token].source, PriceSourceType::Static);
+    }
+}
@@ -1,13 +1,13 @@
 use crate::{models::token::TokenRegistry, utils::config::ProfitTokenConfig};
 use alloy::primitives::{Address, U256};
-use anyhow::Result;
 use log::{error, info};
 use serde::{Deserialize, Serialize};
 use std::{str::FromStr, sync::Arc};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tokio::time::{interval, Duration};
 
-use super::price_updater::{PriceSourceType, PriceUpdater};
+use super::price_updater::{PricePolicy, PriceSourceType, PriceUpdater};
 /// Configuration for a profit token
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfitToken {
@@ -54,7 +54,8 @@ impl ProfitTokenRegistry {
         }
     }
 
-    pub fn start(&self) -> Result<()> {
+    /// Refresh prices hourly in the background, until the returned task is aborted
+    pub fn start(&self) -> JoinHandle<()> {
         info!("Starting price updater with 1-hour interval");
         let price_updater = self.price_updater.clone();
         let tokens = self.tokens.clone();
@@ -88,8 +89,7 @@ impl ProfitTokenRegistry {
                 }
                 interval.tick().await;
             }
-        });
-        Ok(())
+        })
     }
 
     /// Add a profit token with its configuration
@@ -107,10 +107,28 @@ impl ProfitTokenRegistry {
 
     /// Add a profit token with its configuration
     pub async fn add_token_from_config(&self, config: ProfitTokenConfig) {
+        let address = Address::from_str(&config.token).unwrap();
+        let mut sources: Vec<PriceSourceType> = config.price_source.iter().cloned().collect();
+        for source in config.fallback_sources {
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+        }
+        self.price_updater
+            .write()
+            .await
+            .add_token_with_policy(
+                address,
+                PricePolicy {
+                    sources,
+                    max_staleness: config.max_staleness,
+                },
+            )
+            .await;
         self.add_token(
-            Address::from_str(&config.token).unwrap(),
+            address,
             ProfitToken {
-                address: Address::from_str(&config.token).unwrap(),
+                address,
                 price_source: config.price_source,
                 price: None,
                 default_price: config.default_price,
@@ -0,0 +1,436 @@
+pub mod registry;
+
+use alloy::primitives::{address, aliases::U24, Address, Bytes, U256};
+use alloy::sol_types::{SolCall, SolValue};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::blockchain::{ISwapRouter, IUniswapUniversalRouter};
+use crate::models::pool::v3::V3PoolType;
+use crate::models::pool::{route::RouteHop, PoolType, RouteSnapshot, UniswapV3Pool};
+use crate::utils::{abi, encode_packed::SolidityDataType, encode_packed::TakeLastXBytes};
+
+pub use registry::RouterRegistry;
+
+/// Seconds from now a swap stays valid when no deadline is given
+pub const DEFAULT_DEADLINE_SECS: u64 = 1200;
+
+/// Universal Router placeholder for "the router itself", used between route segments
+const ADDRESS_THIS: Address = address!("0000000000000000000000000000000000000002");
+/// Universal Router placeholder amount for "the router's whole balance of the token"
+const CONTRACT_BALANCE: U256 = U256::from_limbs([0, 0, 0, 1 << 63]);
+
+const V3_SWAP_EXACT_IN: u8 = 0x00;
+const V3_SWAP_EXACT_OUT: u8 = 0x01;
+const V2_SWAP_EXACT_IN: u8 = 0x08;
+const V2_SWAP_EXACT_OUT: u8 = 0x09;
+
+/// Router contract interface swaps are encoded for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum RouterType {
+    /// Uniswap V3 `SwapRouter` (`exactInput` / `exactOutput`), V3 pools only. Routes of Algebra
+    /// pools are encoded for Algebra's `SwapRouter`, which takes the same calls.
+    SwapRouter,
+    /// Uniswap `UniversalRouter` (`execute` with V2 and V3 swap commands), tokens pulled via Permit2
+    UniversalRouter,
+}
+
+/// A router deployment on a chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Router {
+    pub address: Address,
+    pub router_type: RouterType,
+}
+
+/// Amounts and limits of a swap to encode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapParams {
+    pub recipient: Address,
+    pub exact_input: bool,
+    /// Input amount for exact input swaps, output amount for exact output swaps
+    pub amount: U256,
+    /// Minimum output for exact input swaps, maximum input for exact output swaps
+    pub amount_limit: U256,
+    /// Unix timestamp after which the swap reverts
+    pub deadline: u64,
+}
+
+/// Ready to sign transaction fields for a swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapCalldata {
+    pub to: Address,
+    pub data: Bytes,
+    pub value: U256,
+}
+
+/// Fee tier of a V3 hop, None for Algebra pools, whose fee is dynamic and not in their paths
+fn v3_fee(hop: &RouteHop) -> Result<Option<U24>> {
+    let pool = hop
+        .pool
+        .downcast_ref::<UniswapV3Pool>()
+        .ok_or_else(|| anyhow!("Pool {:?} is not a V3 pool", hop.pool.address()))?;
+    Ok(match pool.pool_type {
+        V3PoolType::AlgebraV3 | V3PoolType::AlgebraTwoSideFee => None,
+        V3PoolType::UniswapV3 | V3PoolType::RamsesV2 => Some(pool.fee),
+    })
+}
+
+/// Packed V3 path `token (20) | fee (3) | token (20) ...`, reversed for exact output swaps.
+/// Algebra paths are `token (20) | token (20) ...`, so they cannot share a path with other pools.
+fn encode_v3_path(hops: &[RouteHop], exact_input: bool) -> Result<Bytes> {
+    let fees = hops.iter().map(v3_fee).collect::<Result<Vec<_>>>()?;
+    if fees.iter().any(Option::is_some) && fees.iter().any(Option::is_none) {
+        return Err(anyhow!(
+            "Algebra pools cannot be routed together with fee tier V3 pools"
+        ));
+    }
+    let fee = |fee: Option<U24>| {
+        fee.map(|fee| SolidityDataType::NumberWithShift(U256::from(fee), TakeLastXBytes(24)))
+    };
+
+    let mut items = Vec::with_capacity(hops.len() * 2 + 1);
+    if exact_input {
+        items.push(SolidityDataType::Address(hops[0].token_in));
+        for (hop, hop_fee) in hops.iter().zip(fees) {
+            items.extend(fee(hop_fee));
+            items.push(SolidityDataType::Address(hop.token_out));
+        }
+    } else {
+        items.push(SolidityDataType::Address(hops[hops.len() - 1].token_out));
+        for (hop, hop_fee) in hops.iter().zip(fees).rev() {
+            items.extend(fee(hop_fee));
+            items.push(SolidityDataType::Address(hop.token_in));
+        }
+    }
+    Ok(abi::encode_packed(&items).0.into())
+}
+
+fn encode_v2_path(hops: &[RouteHop]) -> Vec<Address> {
+    std::iter::once(hops[0].token_in)
+        .chain(hops.iter().map(|hop| hop.token_out))
+        .collect()
+}
+
+/// Split a route into runs of consecutive pools of the same type
+fn segments(route: &RouteSnapshot) -> Result<Vec<(PoolType, &[RouteHop])>> {
+    let hops = route.hops();
+    let mut segments: Vec<(PoolType, &[RouteHop])> = Vec::new();
+    let mut start = 0;
+    for i in 1..=hops.len() {
+        let pool_type = hops[start].pool.pool_type();
+        if i == hops.len() || hops[i].pool.pool_type() != pool_type {
+            if !matches!(pool_type, PoolType::UniswapV2 | PoolType::UniswapV3) {
+                return Err(anyhow!(
+                    "Pool type {} is not supported by the router",
+                    pool_type.name()
+                ));
+            }
+            // The universal router's V3 commands decode fee tier paths only
+            if hops[start..i]
+                .iter()
+                .any(|hop| matches!(v3_fee(hop), Ok(None)))
+            {
+                return Err(anyhow!(
+                    "Algebra pools are not supported by the universal router"
+                ));
+            }
+            segments.push((pool_type, &hops[start..i]));
+            start = i;
+        }
+    }
+    Ok(segments)
+}
+
+fn encode_swap_router(route: &RouteSnapshot, params: &SwapParams) -> Result<Bytes> {
+    let path = encode_v3_path(route.hops(), params.exact_input)?;
+    let deadline = U256::from(params.deadline);
+    let data = if params.exact_input {
+        ISwapRouter::exactInputCall {
+            params: ISwapRouter::ExactInputParams {
+                path,
+                recipient: params.recipient,
+                deadline,
+                amountIn: params.amount,
+                amountOutMinimum: params.amount_limit,
+            },
+        }
+        .abi_encode()
+    } else {
+        ISwapRouter::exactOutputCall {
+            params: ISwapRouter::ExactOutputParams {
+                path,
+                recipient: params.recipient,
+                deadline,
+                amountOut: params.amount,
+                amountInMaximum: params.amount_limit,
+            },
+        }
+        .abi_encode()
+    };
+    Ok(data.into())
+}
+
+fn encode_universal_router(route: &RouteSnapshot, params: &SwapParams) -> Result<Bytes> {
+    let segments = segments(route)?;
+    let mut commands = Vec::with_capacity(segments.len());
+    let mut inputs = Vec::with_capacity(segments.len());
+
+    if params.exact_input {
+        // Intermediate segments leave their output in the router for the next one to spend
+        let last = segments.len() - 1;
+        for (i, (pool_type, hops)) in segments.into_iter().enumerate() {
+            let recipient = if i == last {
+                params.recipient
+            } else {
+                ADDRESS_THIS
+            };
+            let amount_in = if i == 0 {
+                params.amount
+            } else {
+                CONTRACT_BALANCE
+            };
+            let amount_out_min = if i == last {
+                params.amount_limit
+            } else {
+                U256::ZERO
+            };
+            let payer_is_user = i == 0;
+            let input = match pool_type {
+                PoolType::UniswapV3 => {
+                    commands.push(V3_SWAP_EXACT_IN);
+                    let path = encode_v3_path(hops, true)?;
+                    (recipient, amount_in, amount_out_min, path, payer_is_user).abi_encode_params()
+                }
+                _ => {
+                    commands.push(V2_SWAP_EXACT_IN);
+                    let path = encode_v2_path(hops);
+                    (recipient, amount_in, amount_out_min, path, payer_is_user).abi_encode_params()
+                }
+            };
+            inputs.push(Bytes::from(input));
+        }
+    } else {
+        // Exact output amounts cannot be chained through the router balance
+        let [(pool_type, hops)] = segments[..] else {
+            return Err(anyhow!(
+                "Exact output routes must use a single pool type on the universal router"
+            ));
+        };
+        let input = match pool_type {
+            PoolType::UniswapV3 => {
+                commands.push(V3_SWAP_EXACT_OUT);
+                let path = encode_v3_path(hops, false)?;
+                (
+                    params.recipient,
+                    params.amount,
+                    params.amount_limit,
+                    path,
+                    true,
+                )
+                    .abi_encode_params()
+            }
+            _ => {
+                commands.push(V2_SWAP_EXACT_OUT);
+                let path = encode_v2_path(hops);
+                (
+                    params.recipient,
+                    params.amount,
+                    params.amount_limit,
+                    path,
+                    true,
+                )
+                    .abi_encode_params()
+            }
+        };
+        inputs.push(Bytes::from(input));
+    }
+
+    Ok(IUniswapUniversalRouter::executeCall {
+        commands: commands.into(),
+        inputs,
+        deadline: U256::from(params.deadline),
+    }
+    .abi_encode()
+    .into())
+}
+
+impl Router {
+    /// Encode a swap along `route` for this router
+    pub fn encode_swap(&self, route: &RouteSnapshot, params: &SwapParams) -> Result<SwapCalldata> {
+        let data = match self.router_type {
+            RouterType::SwapRouter => encode_swap_router(route, params)?,
+            RouterType::UniversalRouter => encode_universal_router(route, params)?,
+        };
+        Ok(SwapCalldata {
+            to: self.address,
+            data,
+            value: U256::ZERO,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pool::MockPool;
+    use alloy::primitives::{hex, U160};
+    use std::sync::Arc;
+
+    fn v3_pool(address: u8, token0: u8, token1: u8, fee: u32) -> UniswapV3Pool {
+        typed_v3_pool(address, token0, token1, fee, V3PoolType::UniswapV3)
+    }
+
+    fn typed_v3_pool(
+        address: u8,
+        token0: u8,
+        token1: u8,
+        fee: u32,
+        pool_type: V3PoolType,
+    ) -> UniswapV3Pool {
+        UniswapV3Pool::new(
+            Address::repeat_byte(address),
+            Address::repeat_byte(token0),
+            Address::repeat_byte(token1),
+            U24::from(fee),
+            60,
+            U160::from(1u128 << 96),
+            0,
+            1_000_000,
+            Address::ZERO,
+            pool_type,
+        )
+    }
+
+    fn params(exact_input: bool) -> SwapParams {
+        SwapParams {
+            recipient: Address::repeat_byte(0xee),
+            exact_input,
+            amount: U256::from(1000),
+            amount_limit: U256::from(990),
+            deadline: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_swap_router_path_encoding() {
+        let route = RouteSnapshot::new(
+            vec![
+                Arc::new(v3_pool(0xa, 1, 2, 500)),
+                Arc::new(v3_pool(0xb, 3, 2, 3000)),
+            ],
+            Address::repeat_byte(1),
+        )
+        .unwrap();
+        let router = Router {
+            address: Address::repeat_byte(0xff),
+            router_type: RouterType::SwapRouter,
+        };
+
+        let calldata = router.encode_swap(&route, &params(true)).unwrap();
+        let call = ISwapRouter::exactInputCall::abi_decode(&calldata.data).unwrap();
+        let expected = format!(
+            "{}{}{}{}{}",
+            hex::encode([1u8; 20]),
+            "0001f4",
+            hex::encode([2u8; 20]),
+            "000bb8",
+            hex::encode([3u8; 20])
+        );
+        assert_eq!(hex::encode(&call.params.path), expected);
+        assert_eq!(call.params.amountOutMinimum, U256::from(990));
+        assert_eq!(call.params.deadline, U256::from(1_700_000_000u64));
+
+        // Exact output paths run from the output token back to the input
+        let calldata = router.encode_swap(&route, &params(false)).unwrap();
+        let call = ISwapRouter::exactOutputCall::abi_decode(&calldata.data).unwrap();
+        assert_eq!(&call.params.path[..20], &[3u8; 20]);
+        assert_eq!(call.params.amountInMaximum, U256::from(990));
+    }
+
+    #[test]
+    fn test_algebra_path_has_no_fees() {
+        let algebra = |address, token0, token1| {
+            Arc::new(typed_v3_pool(
+                address,
+                token0,
+                token1,
+                100,
+                V3PoolType::AlgebraV3,
+            ))
+        };
+        let route = RouteSnapshot::new(
+            vec![algebra(0xa, 1, 2), algebra(0xb, 2, 3)],
+            Address::repeat_byte(1),
+        )
+        .unwrap();
+        let router = Router {
+            address: Address::repeat_byte(0xff),
+            router_type: RouterType::SwapRouter,
+        };
+
+        let calldata = router.encode_swap(&route, &params(true)).unwrap();
+        let call = ISwapRouter::exactInputCall::abi_decode(&calldata.data).unwrap();
+        let expected = [[1u8; 20], [2u8; 20], [3u8; 20]].concat();
+        assert_eq!(call.params.path.to_vec(), expected);
+
+        // A path is either fee tiered or not
+        let mixed = RouteSnapshot::new(
+            vec![algebra(0xa, 1, 2), Arc::new(v3_pool(0xb, 2, 3, 500))],
+            Address::repeat_byte(1),
+        )
+        .unwrap();
+        assert!(router.encode_swap(&mixed, &params(true)).is_err());
+
+        let universal = Router {
+            address: Address::repeat_byte(0xff),
+            router_type: RouterType::UniversalRouter,
+        };
+        assert!(universal.encode_swap(&route, &params(true)).is_err());
+    }
+
+    #[test]
+    fn test_universal_router_mixed_route() {
+        let v2_pool = MockPool::new_v2(
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            U256::from(1_000_000),
+            U256::from(1_000_000),
+        );
+        let route = RouteSnapshot::new(
+            vec![Arc::new(v2_pool), Arc::new(v3_pool(0xb, 2, 3, 500))],
+            Address::repeat_byte(1),
+        )
+        .unwrap();
+        let router = Router {
+            address: Address::repeat_byte(0xff),
+            router_type: RouterType::UniversalRouter,
+        };
+
+        let calldata = router.encode_swap(&route, &params(true)).unwrap();
+        let call = IUniswapUniversalRouter::executeCall::abi_decode(&calldata.data).unwrap();
+        assert_eq!(
+            call.commands.to_vec(),
+            vec![V2_SWAP_EXACT_IN, V3_SWAP_EXACT_IN]
+        );
+
+        let (recipient, amount_in, amount_out_min, path, payer_is_user) =
+            <(Address, U256, U256, Vec<Address>, bool)>::abi_decode_params(&call.inputs[0])
+                .unwrap();
+        assert_eq!(recipient, ADDRESS_THIS);
+        assert_eq!(amount_in, U256::from(1000));
+        assert_eq!(amount_out_min, U256::ZERO);
+        assert_eq!(path, vec![Address::repeat_byte(1), Address::repeat_byte(2)]);
+        assert!(payer_is_user);
+
+        let (recipient, amount_in, amount_out_min, _, payer_is_user) =
+            <(Address, U256, U256, Bytes, bool)>::abi_decode_params(&call.inputs[1]).unwrap();
+        assert_eq!(recipient, Address::repeat_byte(0xee));
+        assert_eq!(amount_in, CONTRACT_BALANCE);
+        assert_eq!(amount_out_min, U256::from(990));
+        assert!(!payer_is_user);
+
+        assert!(router.encode_swap(&route, &params(false)).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use alloy::primitives::Address;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::models::router::Router;
+
+/// Routers configured per chain, used to encode swap calldata
+#[derive(Debug, Default)]
+pub struct RouterRegistry {
+    routers: Arc<RwLock<HashMap<u64, Vec<Router>>>>,
+}
+
+impl RouterRegistry {
+    pub fn new() -> Self {
+        Self {
+            routers: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn add_routers(&self, network_id: u64, routers: Vec<Router>) {
+        let mut registry = self.routers.write().await;
+        registry.entry(network_id).or_default().extend(routers);
+    }
+
+    /// Get a router by address, or the chain's first configured router if no address is given
+    pub async fn get_router(&self, network_id: u64, address: Option<Address>) -> Option<Router> {
+        let registry = self.routers.read().await;
+        let routers = registry.get(&network_id)?;
+        match address {
+            Some(address) => routers
+                .iter()
+                .find(|router| router.address == address)
+                .copied(),
+            None => routers.first().copied(),
+        }
+    }
+
+    pub async fn remove_routers(&self, network_id: u64) -> Vec<Router> {
+        let mut registry = self.routers.write().await;
+        registry.remove(&network_id).unwrap_or_default()
+    }
+
+    pub async fn get_routers(&self, network_id: u64) -> Vec<Router> {
+        let registry = self.routers.read().await;
+        registry.get(&network_id).cloned().unwrap_or_default()
+    }
+}
@@ -85,11 +85,7 @@ impl TokenRegistry {
         // Final database snapshot to ensure everything is flushed
         db.snapshot()?;
 
-        info!(
-            "CHAIN ID: {} Saved {} tokens to database",
-            self.network_id,
-            self.tokens.len()
-        );
+        info!("Saved {} tokens to database", self.tokens.len());
         Ok(())
     }
 
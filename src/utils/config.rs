@@ -1,11 +1,45 @@
+use crate::api::auth::{DEFAULT_MAX_BATCH_AMOUNTS, DEFAULT_MAX_HOPS, DEFAULT_REQUESTS_PER_MINUTE};
+use crate::api::server::{
+    DEFAULT_ADMIN_BIND_ADDRESS, DEFAULT_BIND_ADDRESS, DEFAULT_BODY_LIMIT, DEFAULT_REQUEST_TIMEOUT,
+};
+use crate::models::candle::{DEFAULT_CANDLE_INTERVALS, DEFAULT_MAX_CANDLES};
+use crate::models::gas::DEFAULT_GAS_UPDATE_INTERVAL;
 use crate::models::pool::base::PoolType;
+use crate::models::pool::quote_cache::DEFAULT_QUOTE_CACHE_SIZE;
+use crate::models::pool::sync::DEFAULT_MAX_SYNC_LAG;
+use crate::models::price::{
+    DEFAULT_MAX_PRICE_HOPS, DEFAULT_MIN_LIQUIDITY_USD, DEFAULT_PRICE_UPDATE_INTERVAL,
+};
 use crate::models::profit_token::price_updater::base::PriceSourceType;
+use crate::models::router::RouterType;
+use crate::utils::errors::ArbitrageError;
 use alloy::primitives::Address;
 use alloy::signers::local::PrivateKeySigner;
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Config file read when no other file is given
+pub const DEFAULT_CONFIG_PATH: &str = "configs/config.toml";
+
+/// Prefix of the environment variables overriding config values, e.g.
+/// `EVM_INDEXER__CHAINS__0__RPC_URLS` sets `chains[0].rpc_urls`
+pub const ENV_OVERRIDE_PREFIX: &str = "EVM_INDEXER__";
+
+/// Config keys holding lists, overridden by comma separated values
+const LIST_KEYS: [&str; 6] = [
+    "rpc_urls",
+    "websocket_urls",
+    "pool_addresses",
+    "stablecoins",
+    "candle_intervals",
+    "cors_origins",
+];
 
 /// Main application configuration
 #[derive(Debug, Clone)]
@@ -14,6 +48,16 @@ pub struct AppConfig {
     pub database: DatabaseConfig,
     /// Chain configurations
     pub chain_configs: Vec<ChainConfigs>,
+    /// Candle configuration
+    pub candles: CandleConfig,
+    /// USD pricing configuration
+    pub pricing: PricingConfig,
+    /// Quote configuration
+    pub quotes: QuoteConfig,
+    /// API configuration
+    pub api: ApiConfig,
+    /// HTTP server configuration
+    pub server: ServerConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,18 +102,55 @@ pub struct ExecutorConfig {
 }
 
 /// Configuration for a profit token
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProfitTokenConfig {
     /// Token address
     pub token: String,
     /// Price source
     pub price_source: Option<PriceSourceType>,
+    /// Sources tried in order after `price_source` when it has no fresh price
+    #[serde(default)]
+    pub fallback_sources: Vec<PriceSourceType>,
+    /// Maximum price age in seconds before falling back to the next source
+    pub max_staleness: Option<u64>,
     /// default price
     pub default_price: f64,
 }
 
+/// Price sources of a chain's profit tokens, a source is only registered when configured
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PriceSourcesConfig {
+    pub gecko_terminal_network: Option<String>, // GeckoTerminal network id, e.g. "eth"
+    #[serde(default)]
+    pub on_chain: bool,    // prices derived from the chain's indexed pools
+    #[serde(default)]
+    pub chainlink_feeds: HashMap<String, String>, // token address -> USD aggregator address
+    pub static_prices_path: Option<PathBuf>,    // TOML file of "<token address>" = <usd price>
+    pub max_staleness: Option<u64>,             // seconds, for tokens without their own limit
+}
+
+impl PriceSourcesConfig {
+    /// Sources this config registers
+    pub fn configured(&self) -> Vec<PriceSourceType> {
+        let mut sources = Vec::new();
+        if self.gecko_terminal_network.is_some() {
+            sources.push(PriceSourceType::GeckoTerminal);
+        }
+        if self.on_chain {
+            sources.push(PriceSourceType::OnChain);
+        }
+        if !self.chainlink_feeds.is_empty() {
+            sources.push(PriceSourceType::Chainlink);
+        }
+        if self.static_prices_path.is_some() {
+            sources.push(PriceSourceType::Static);
+        }
+        sources
+    }
+}
+
 /// Configuration for a pool
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PoolConfig {
     /// Pool address
     pub address: String,
@@ -88,8 +169,47 @@ impl PoolConfig {
     }
 }
 
+/// A pool as written in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolConfigRaw {
+    pub address: String,
+    pub pool_type: Option<toml::Value>, // "UniswapV3" or { ERC4626 = "VerioIP" }, default UniswapV2
+}
+
+impl PoolConfigRaw {
+    fn into_config(self) -> Result<PoolConfig> {
+        let pool_type = match &self.pool_type {
+            Some(pool_type) => parse_pool_type(pool_type).map_err(|e| anyhow!(e))?,
+            None => PoolType::default(),
+        };
+        Ok(PoolConfig {
+            address: self.address,
+            pool_type,
+        })
+    }
+}
+
+fn parse_pool_type(value: &toml::Value) -> Result<PoolType, String> {
+    PoolType::deserialize(value.clone()).map_err(|_| {
+        format!(
+            "unknown pool type {}, expected one of {}",
+            value,
+            PoolType::NAMES.join(", ")
+        )
+    })
+}
+
+/// Configuration for a swap router used to encode calldata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouterConfig {
+    /// Router address
+    pub address: String,
+    /// Router interface
+    pub router_type: RouterType,
+}
+
 /// Strategy-specific configuration
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct ChainConfigs {
     pub rpc_urls: Vec<String>,
     pub websocket_urls: Vec<String>,
@@ -98,11 +218,15 @@ pub struct ChainConfigs {
     pub max_blocks_per_batch: u64,
     pub wait_time_for_startup: u64,
     pub use_websocket: bool,
-    // pub wrap_native: String,
+    pub wrap_native: Option<String>,
     pub custom_multicall_address: Option<String>,
-    // pub min_profit_usd: f64,
-    // pub profit_tokens: Vec<ProfitTokenConfig>,
+    pub min_profit_usd: f64,
+    pub profit_tokens: Vec<ProfitTokenConfig>,
+    pub price_sources: PriceSourcesConfig,
     pub pools: Vec<PoolConfig>,
+    pub stablecoins: Vec<String>,
+    pub routers: Vec<RouterConfig>,
+    pub max_sync_lag: u64, // blocks behind the head before the chain is not ready
 }
 
 /// Database configuration
@@ -112,6 +236,158 @@ pub struct DatabaseConfig {
     pub load_snapshot_pool: Option<bool>,
 }
 
+/// Candle (OHLCV) configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleConfig {
+    /// Candle intervals in seconds
+    pub intervals: Vec<u64>,
+    /// Number of candles kept in memory per series
+    pub max_candles: usize,
+}
+
+impl Default for CandleConfig {
+    fn default() -> Self {
+        Self {
+            intervals: DEFAULT_CANDLE_INTERVALS.to_vec(),
+            max_candles: DEFAULT_MAX_CANDLES,
+        }
+    }
+}
+
+/// USD pricing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingConfig {
+    /// Minimum pool liquidity in USD for a pool to contribute to a price
+    pub min_liquidity_usd: f64,
+    /// Maximum number of pool hops from a stablecoin
+    pub max_hops: usize,
+    /// Interval between price recomputations in seconds
+    pub update_interval: u64,
+    /// Interval between gas price refreshes in seconds
+    pub gas_update_interval: u64,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            min_liquidity_usd: DEFAULT_MIN_LIQUIDITY_USD,
+            max_hops: DEFAULT_MAX_PRICE_HOPS,
+            update_interval: DEFAULT_PRICE_UPDATE_INTERVAL,
+            gas_update_interval: DEFAULT_GAS_UPDATE_INTERVAL,
+        }
+    }
+}
+
+/// Quote configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteConfig {
+    /// Number of route quotes kept in the quote cache, 0 disables caching
+    pub cache_size: usize,
+}
+
+/// API configuration
+#[derive(Debug, Clone)]
+pub struct ApiConfig {
+    /// Keys allowed to call the API, the API is open when empty
+    pub keys: Vec<ApiKeyConfig>,
+}
+
+/// An API key with its limits
+#[derive(Debug, Clone)]
+pub struct ApiKeyConfig {
+    /// Secret sent by clients in the `X-API-Key` header
+    pub key: String,
+    /// Name shown in logs and usage counters
+    pub name: String,
+    /// Whether the key can read every key's usage
+    pub admin: bool,
+    /// Requests allowed per minute, 0 disables rate limiting
+    pub requests_per_minute: u32,
+    /// Amounts allowed in one batch quote
+    pub max_batch_amounts: usize,
+    /// Pools allowed in a quoted route
+    pub max_hops: usize,
+}
+
+/// HTTP server configuration
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Address of the public quote API
+    pub bind_address: SocketAddr,
+    /// Address of the admin API
+    pub admin_bind_address: SocketAddr,
+    /// Origins allowed to call the API from a browser, `*` for any, CORS is off when empty
+    pub cors_origins: Vec<String>,
+    /// Seconds a request may take before it is answered with a timeout, 0 disables it
+    pub request_timeout: u64,
+    /// Maximum request body size in bytes
+    pub body_limit: usize,
+    /// Whether responses are gzip compressed for clients that accept it
+    pub compression: bool,
+    /// Serve both listeners over TLS when set
+    pub tls: Option<TlsConfig>,
+}
+
+/// TLS certificate and private key, PEM encoded
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+/// HTTP server configuration as written in the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerConfigRaw {
+    pub bind_address: Option<SocketAddr>, // public quote API, default 0.0.0.0:3001
+    pub admin_bind_address: Option<SocketAddr>, // admin API, default 127.0.0.1:3002
+    #[serde(default)]
+    pub cors_origins: Vec<String>, // origins allowed from browsers, "*" for any
+    pub request_timeout: Option<u64>,     // seconds, 0 disables it
+    pub body_limit: Option<usize>,        // maximum request body size in bytes
+    pub compression: Option<bool>,        // gzip responses, default true
+    pub tls_cert_path: Option<String>,    // PEM certificate chain, TLS needs both paths
+    pub tls_key_path: Option<String>,     // PEM private key
+}
+
+impl ServerConfigRaw {
+    fn into_config(self) -> Result<ServerConfig> {
+        let tls = match (self.tls_cert_path, self.tls_key_path) {
+            (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+                cert_path: cert_path.into(),
+                key_path: key_path.into(),
+            }),
+            (None, None) => None,
+            _ => {
+                return Err(anyhow!(
+                    "TLS needs both server.tls_cert_path and server.tls_key_path"
+                ))
+            }
+        };
+        Ok(ServerConfig {
+            bind_address: self.bind_address.unwrap_or(DEFAULT_BIND_ADDRESS),
+            admin_bind_address: self
+                .admin_bind_address
+                .unwrap_or(DEFAULT_ADMIN_BIND_ADDRESS),
+            cors_origins: self.cors_origins,
+            request_timeout: self.request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+            body_limit: self.body_limit.unwrap_or(DEFAULT_BODY_LIMIT),
+            compression: self.compression.unwrap_or(true),
+            tls,
+        })
+    }
+}
+
+/// An API key as written in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyConfigRaw {
+    pub key: String,
+    pub name: String,
+    pub admin: Option<bool>,
+    pub requests_per_minute: Option<u32>, // 0 disables rate limiting
+    pub max_batch_amounts: Option<usize>, // amounts allowed in one batch quote
+    pub max_hops: Option<usize>,          // pools allowed in a quoted route
+}
+
 /// Strategy-specific configuration
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ChainConfigRaws {
@@ -122,14 +398,23 @@ pub struct ChainConfigRaws {
     pub max_blocks_per_batch: u64,
     pub wait_time_for_startup: u64,
     pub use_websocket: bool,
-    // pub wrap_native: String,
+    pub wrap_native: Option<String>, // wrapped native token, used to price gas in other tokens
     pub custom_multicall_address: Option<String>,
-    // pub min_profit_usd: f64,
-    // pub profit_tokens: Vec<ProfitTokenConfig>,
+    #[serde(default)]
+    pub min_profit_usd: f64, // minimum profit in USD, converted to each profit token
+    #[serde(default)]
+    pub profit_tokens: Vec<ProfitTokenConfig>, // tokens priced by the configured price sources
+    #[serde(default)]
+    pub price_sources: PriceSourcesConfig,
     #[serde(default)]
     pub pool_addresses: Vec<String>,
     #[serde(default)]
-    pub pools_with_type: Vec<PoolConfig>,
+    pub pools_with_type: Vec<PoolConfigRaw>,
+    #[serde(default)]
+    pub stablecoins: Vec<String>, // stablecoins priced at 1 USD
+    #[serde(default)]
+    pub routers: Vec<RouterConfig>, // routers swap calldata can be encoded for
+    pub max_sync_lag: Option<u64>, // overrides the global max_sync_lag for this chain
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -150,19 +435,391 @@ pub struct ConfigFile {
     pub use_websocket: Option<bool>,
     // pub use_simple_nonce_management: Option<bool>,
     pub wait_time_for_startup: Option<u64>, // wait time for startup in milliseconds
+    pub candle_intervals: Option<Vec<u64>>, // candle intervals in seconds
+    pub max_candles: Option<usize>,         // candles kept in memory per series
+    pub min_liquidity_usd: Option<f64>,     // minimum pool liquidity used for pricing
+    pub max_price_hops: Option<usize>,      // maximum pool hops from a stablecoin
+    pub price_update_interval: Option<u64>, // price recomputation interval in seconds
+    pub gas_update_interval: Option<u64>,   // gas price refresh interval in seconds
+    pub quote_cache_size: Option<usize>,    // route quotes kept in the quote cache, 0 disables it
+    pub max_sync_lag: Option<u64>, // blocks a chain may fall behind its head and still be ready
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfigRaw>, // keys allowed to call the API, open when empty
+    #[serde(default)]
+    pub server: ServerConfigRaw, // HTTP listeners, CORS, limits and TLS
+}
+
+/// A config field that failed validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub field: String, // Path of the field, e.g. chains[1].rpc_urls[0]
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Collects the issues found while validating a config file
+#[derive(Default)]
+struct Validator {
+    issues: Vec<ConfigIssue>,
+}
+
+impl Validator {
+    fn issue(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.issues.push(ConfigIssue {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    fn address(&mut self, field: String, value: &str) -> Option<Address> {
+        match value.parse::<Address>() {
+            Ok(address) => Some(address),
+            Err(e) => {
+                self.issue(field, format!("invalid address \"{}\": {}", value, e));
+                None
+            }
+        }
+    }
+
+    /// Addresses of a list, reporting the ones listed twice. `member` names the address
+    /// field when the list holds tables.
+    fn addresses<'a>(
+        &mut self,
+        field: &str,
+        member: Option<&str>,
+        values: impl IntoIterator<Item = &'a str>,
+    ) {
+        let mut seen = HashSet::new();
+        for (index, value) in values.into_iter().enumerate() {
+            let item = match member {
+                Some(member) => format!("{}[{}].{}", field, index, member),
+                None => format!("{}[{}]", field, index),
+            };
+            if let Some(address) = self.address(item.clone(), value) {
+                if !seen.insert(address) {
+                    self.issue(item, format!("duplicate address {:?}", address));
+                }
+            }
+        }
+    }
+
+    fn urls(&mut self, field: &str, values: &[String], schemes: &[&str]) {
+        for (index, value) in values.iter().enumerate() {
+            let item = format!("{}[{}]", field, index);
+            match Url::parse(value) {
+                Ok(url) if schemes.contains(&url.scheme()) => {}
+                Ok(url) => self.issue(
+                    item,
+                    format!(
+                        "unsupported scheme {} in \"{}\", expected {}",
+                        url.scheme(),
+                        value,
+                        schemes.join(" or ")
+                    ),
+                ),
+                Err(e) => self.issue(item, format!("invalid URL \"{}\": {}", value, e)),
+            }
+        }
+    }
+
+    fn chain(&mut self, index: usize, chain: &ChainConfigRaws) {
+        let field = |name: &str| format!("chains[{}].{}", index, name);
+        if chain.rpc_urls.is_empty() {
+            self.issue(field("rpc_urls"), "at least one RPC URL is required");
+        }
+        self.urls(&field("rpc_urls"), &chain.rpc_urls, &["http", "https"]);
+        self.urls(
+            &field("websocket_urls"),
+            &chain.websocket_urls,
+            &["ws", "wss"],
+        );
+        if chain.use_websocket && chain.websocket_urls.is_empty() {
+            self.issue(
+                field("websocket_urls"),
+                "use_websocket is set but no websocket URL is configured",
+            );
+        }
+        if chain.max_blocks_per_batch == 0 {
+            self.issue(field("max_blocks_per_batch"), "must be at least 1");
+        }
+        if let Some(wrap_native) = &chain.wrap_native {
+            self.address(field("wrap_native"), wrap_native);
+        }
+        if let Some(multicall) = &chain.custom_multicall_address {
+            self.address(field("custom_multicall_address"), multicall);
+        }
+
+        // A pool may be in both lists, pools_with_type takes precedence
+        self.addresses(
+            &field("pool_addresses"),
+            None,
+            chain.pool_addresses.iter().map(String::as_str),
+        );
+        self.addresses(
+            &field("pools_with_type"),
+            Some("address"),
+            chain
+                .pools_with_type
+                .iter()
+                .map(|pool| pool.address.as_str()),
+        );
+        for (pool_index, pool) in chain.pools_with_type.iter().enumerate() {
+            if let Some(pool_type) = &pool.pool_type {
+                if let Err(e) = parse_pool_type(pool_type) {
+                    self.issue(
+                        format!("{}[{}].pool_type", field("pools_with_type"), pool_index),
+                        e,
+                    );
+                }
+            }
+        }
+
+        self.addresses(
+            &field("stablecoins"),
+            None,
+            chain.stablecoins.iter().map(String::as_str),
+        );
+        self.addresses(
+            &field("routers"),
+            Some("address"),
+            chain.routers.iter().map(|router| router.address.as_str()),
+        );
+        self.price_sources(index, chain);
+    }
+
+    /// Profit tokens may only name sources the chain configures, and a Chainlink source needs a
+    /// feed for the token
+    fn price_sources(&mut self, index: usize, chain: &ChainConfigRaws) {
+        let field = |name: &str| format!("chains[{}].{}", index, name);
+        let mut feeds = HashSet::new();
+        for (token, aggregator) in &chain.price_sources.chainlink_feeds {
+            let item = format!("{}.{}", field("price_sources.chainlink_feeds"), token);
+            if let Some(token) = self.address(item.clone(), token) {
+                feeds.insert(token);
+            }
+            self.address(item, aggregator);
+        }
+
+        let configured = chain.price_sources.configured();
+        for (token_index, token) in chain.profit_tokens.iter().enumerate() {
+            let item = format!("{}[{}]", field("profit_tokens"), token_index);
+            let address = self.address(format!("{}.token", item), &token.token);
+            let sources = token
+                .price_source
+                .iter()
+                .map(|source| (format!("{}.price_source", item), source))
+                .chain(
+                    token
+                        .fallback_sources
+                        .iter()
+                        .enumerate()
+                        .map(|(i, source)| (format!("{}.fallback_sources[{}]", item, i), source)),
+                );
+            for (source_field, source) in sources {
+                if !configured.contains(source) {
+                    self.issue(
+                        source_field,
+                        format!(
+                            "price source {:?} is not configured in price_sources",
+                            source
+                        ),
+                    );
+                } else if *source == PriceSourceType::Chainlink
+                    && address.is_some_and(|address| !feeds.contains(&address))
+                {
+                    self.issue(
+                        source_field,
+                        format!("no Chainlink feed is configured for {}", token.token),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Replaces every `${VAR}` in a config string with the variable's value, `$${` is a literal `${`
+fn interpolate(text: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = rest.strip_prefix("${") {
+            let end = reference
+                .find('}')
+                .ok_or_else(|| anyhow!("unterminated ${{ in {}", rest.lines().next().unwrap()))?;
+            let name = &reference[..end];
+            let value = vars
+                .get(name)
+                .ok_or_else(|| anyhow!("environment variable {} is not set", name))?;
+            result.push_str(value);
+            rest = &reference[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Interpolates every string value of a parsed config, `key` is the value's path
+fn interpolate_value(
+    value: &mut ::config::Value,
+    key: &str,
+    vars: &HashMap<String, String>,
+) -> Result<()> {
+    match &mut value.kind {
+        ::config::ValueKind::String(text) => {
+            *text = interpolate(text, vars).with_context(|| format!("in {}", key))?;
+        }
+        ::config::ValueKind::Table(table) => {
+            for (name, value) in table.iter_mut() {
+                interpolate_value(value, &format!("{}.{}", key, name), vars)?;
+            }
+        }
+        ::config::ValueKind::Array(values) => {
+            for (index, value) in values.iter_mut().enumerate() {
+                interpolate_value(value, &format!("{}[{}]", key, index), vars)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// A config file with its `${VAR}` references replaced
+#[derive(Debug, Clone)]
+struct InterpolatedFile(::config::Map<String, ::config::Value>);
+
+impl ::config::Source for InterpolatedFile {
+    fn clone_into_box(&self) -> Box<dyn ::config::Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<::config::Map<String, ::config::Value>, ::config::ConfigError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Config paths and values of the `EVM_INDEXER__` variables, sorted by path
+fn env_overrides(vars: &HashMap<String, String>) -> Vec<(String, ::config::Value)> {
+    let mut overrides = vars
+        .iter()
+        .filter_map(|(name, value)| {
+            let name = name.strip_prefix(ENV_OVERRIDE_PREFIX)?;
+            let mut path = String::new();
+            for segment in name.split("__").map(str::to_lowercase) {
+                if segment.parse::<usize>().is_ok() {
+                    path.push_str(&format!("[{}]", segment));
+                } else {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(&segment);
+                }
+            }
+            let key = path.rsplit('.').next().unwrap_or_default();
+            let value = if LIST_KEYS.contains(&key) {
+                value
+                    .split(',')
+                    .map(|item| item.trim().to_string())
+                    .filter(|item| !item.is_empty())
+                    .collect::<Vec<_>>()
+                    .into()
+            } else {
+                value.clone().into()
+            };
+            Some((path, value))
+        })
+        .collect::<Vec<_>>();
+    overrides.sort_by(|a, b| a.0.cmp(&b.0));
+    overrides
 }
 
 impl ConfigFile {
-    pub fn load() -> Result<Self> {
-        let strategy_path = PathBuf::from("configs/config.toml");
-        let config = fs::read_to_string(strategy_path)?;
-        Ok(toml::from_str(&config)?)
+    /// Reads the config files in order, each overriding the keys of the ones before it, then
+    /// applies the `EVM_INDEXER__` variables. `${VAR}` references are replaced in every file.
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        Self::load_with_vars(paths, &std::env::vars().collect())
+    }
+
+    fn load_with_vars(paths: &[PathBuf], vars: &HashMap<String, String>) -> Result<Self> {
+        let mut builder = ::config::Config::builder();
+        for path in paths {
+            builder = builder.add_source(Self::source(path, vars)?);
+        }
+        for (path, value) in env_overrides(vars) {
+            builder = builder
+                .set_override(&path, value)
+                .with_context(|| format!("Invalid config override for {}", path))?;
+        }
+        builder
+            .build()
+            .and_then(|config| config.try_deserialize())
+            .context("Invalid config")
+    }
+
+    fn source(path: &Path, vars: &HashMap<String, String>) -> Result<InterpolatedFile> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        // Parsed before interpolating, so values can hold any character and comments are left
+        // alone
+        let mut values = ::config::Config::builder()
+            .add_source(::config::File::from_str(&text, ::config::FileFormat::Toml))
+            .build()
+            .and_then(|config| ::config::Source::collect(&config))
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        for (key, value) in values.iter_mut() {
+            interpolate_value(value, key, vars)
+                .with_context(|| format!("Failed to interpolate config file {}", path.display()))?;
+        }
+        Ok(InterpolatedFile(values))
+    }
+
+    /// Checks every field without connecting to anything, returning all issues found
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let mut validator = Validator::default();
+        if self.chains.is_empty() {
+            validator.issue("chains", "at least one chain is required");
+        }
+        for (index, chain) in self.chains.iter().enumerate() {
+            validator.chain(index, chain);
+        }
+        if self.server.tls_cert_path.is_some() != self.server.tls_key_path.is_some() {
+            validator.issue(
+                "server",
+                "TLS needs both server.tls_cert_path and server.tls_key_path",
+            );
+        }
+        validator.issues
     }
 }
+
 impl AppConfig {
     /// Load configuration from a file
-    pub fn load() -> Result<Self> {
-        let config = ConfigFile::load()?;
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        Self::from_file(ConfigFile::load(paths)?)
+    }
+
+    /// Validates a parsed config file and fills in the defaults
+    pub fn from_file(config: ConfigFile) -> Result<Self> {
+        let issues = config.validate();
+        if !issues.is_empty() {
+            let issues = issues
+                .iter()
+                .map(|issue| format!("\n  {}", issue))
+                .collect::<String>();
+            return Err(ArbitrageError::ConfigError(format!("invalid config{}", issues)).into());
+        }
 
         let mut chain_configs = Vec::new();
 
@@ -171,7 +828,9 @@ impl AppConfig {
             let mut all_pools = Vec::new();
 
             // Add pools from new format (pools_with_type)
-            all_pools.extend(chain.pools_with_type.clone());
+            for pool in chain.pools_with_type {
+                all_pools.push(pool.into_config()?);
+            }
 
             // Add pools from old format (pool_addresses) - these will be converted to PoolConfig with default type
             let old_format_pools: Vec<PoolConfig> = chain
@@ -186,7 +845,7 @@ impl AppConfig {
             let mut unique_pools_map = std::collections::HashMap::new();
             all_pools.reverse(); // Reverse the order of the pools so pool in new format takes precedence
             for pool in all_pools {
-                unique_pools_map.insert(pool.address.clone(), pool);
+                unique_pools_map.insert(pool.address.to_lowercase(), pool);
             }
             let unique_pools: Vec<PoolConfig> = unique_pools_map.into_values().collect();
 
@@ -198,8 +857,18 @@ impl AppConfig {
                 max_blocks_per_batch: chain.max_blocks_per_batch,
                 wait_time_for_startup: chain.wait_time_for_startup,
                 use_websocket: chain.use_websocket,
+                wrap_native: chain.wrap_native,
                 custom_multicall_address: chain.custom_multicall_address,
+                min_profit_usd: chain.min_profit_usd,
+                profit_tokens: chain.profit_tokens,
+                price_sources: chain.price_sources,
                 pools: unique_pools,
+                stablecoins: chain.stablecoins,
+                routers: chain.routers,
+                max_sync_lag: chain
+                    .max_sync_lag
+                    .or(config.max_sync_lag)
+                    .unwrap_or(DEFAULT_MAX_SYNC_LAG),
             };
             chain_configs.push(chain_config);
         }
@@ -210,6 +879,298 @@ impl AppConfig {
                 load_snapshot_pool: config.load_snapshot_pool,
             },
             chain_configs,
+            candles: CandleConfig {
+                intervals: config
+                    .candle_intervals
+                    .unwrap_or_else(|| DEFAULT_CANDLE_INTERVALS.to_vec()),
+                max_candles: config.max_candles.unwrap_or(DEFAULT_MAX_CANDLES),
+            },
+            pricing: PricingConfig {
+                min_liquidity_usd: config
+                    .min_liquidity_usd
+                    .unwrap_or(DEFAULT_MIN_LIQUIDITY_USD),
+                max_hops: config.max_price_hops.unwrap_or(DEFAULT_MAX_PRICE_HOPS),
+                update_interval: config
+                    .price_update_interval
+                    .unwrap_or(DEFAULT_PRICE_UPDATE_INTERVAL),
+                gas_update_interval: config
+                    .gas_update_interval
+                    .unwrap_or(DEFAULT_GAS_UPDATE_INTERVAL),
+            },
+            quotes: QuoteConfig {
+                cache_size: config.quote_cache_size.unwrap_or(DEFAULT_QUOTE_CACHE_SIZE),
+            },
+            api: ApiConfig {
+                keys: config
+                    .api_keys
+                    .into_iter()
+                    .map(|key| ApiKeyConfig {
+                        key: key.key,
+                        name: key.name,
+                        admin: key.admin.unwrap_or(false),
+                        requests_per_minute: key
+                            .requests_per_minute
+                            .unwrap_or(DEFAULT_REQUESTS_PER_MINUTE),
+                        max_batch_amounts: key
+                            .max_batch_amounts
+                            .unwrap_or(DEFAULT_MAX_BATCH_AMOUNTS),
+                        max_hops: key.max_hops.unwrap_or(DEFAULT_MAX_HOPS),
+                    })
+                    .collect(),
+            },
+            server: config.server.into_config()?,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chain: &str) -> ConfigFile {
+        toml::from_str(&format!(
+            r#"
+            [[chains]]
+            websocket_urls = []
+            start_block = 0
+            max_blocks_per_batch = 10
+            wait_time_for_startup = 100
+            {}
+            "#,
+            chain
+        ))
+        .unwrap()
+    }
+
+    fn fields(config: &ConfigFile) -> Vec<String> {
+        config
+            .validate()
+            .into_iter()
+            .map(|issue| issue.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let config = parse(
+            r#"
+            rpc_urls = ["https://eth.llamarpc.com"]
+            use_websocket = false
+            pool_addresses = ["0x4e68Ccd3E89f51C3074ca5072bbAC773960dFa36"]
+            pools_with_type = [
+                { address = "0x4e68ccd3e89f51c3074ca5072bbac773960dfa36", pool_type = "UniswapV3" },
+                { address = "0xa98d625be12df46f7ecc060c1bffb505e80de6aa", pool_type = { ERC4626 = "VerioIP" } },
+            ]
+            "#,
+        );
+        assert!(config.validate().is_empty());
+
+        // Listed in both formats, the typed entry wins
+        let config = AppConfig::from_file(config).unwrap();
+        let pools = &config.chain_configs[0].pools;
+        assert_eq!(pools.len(), 2);
+        assert!(pools
+            .iter()
+            .any(|pool| pool.pool_type == PoolType::UniswapV3));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = parse(
+            r#"
+            rpc_urls = ["eth.llamarpc.com", "wss://eth.drpc.org"]
+            use_websocket = true
+            wrap_native = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756C"
+            pool_addresses = [
+                "0x4e68Ccd3E89f51C3074ca5072bbAC773960dFa36",
+                "0x4E68CCD3E89F51C3074CA5072BBAC773960DFA36",
+            ]
+            pools_with_type = [{ address = "0xa98d625be12df46f7ecc060c1bffb505e80de6aa", pool_type = "Curve" }]
+            "#,
+        );
+        assert_eq!(
+            fields(&config),
+            [
+                "chains[0].rpc_urls[0]",
+                "chains[0].rpc_urls[1]",
+                "chains[0].websocket_urls",
+                "chains[0].wrap_native",
+                "chains[0].pool_addresses[1]",
+                "chains[0].pools_with_type[0].pool_type",
+            ]
+        );
+
+        let error = AppConfig::from_file(config).unwrap_err().to_string();
+        assert!(error.contains("chains[0].pools_with_type[0].pool_type: unknown pool type"));
+    }
+
+    #[test]
+    fn test_price_sources_config() {
+        let weth = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+        let usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let config = parse(&format!(
+            r#"
+            rpc_urls = ["https://eth.llamarpc.com"]
+            use_websocket = false
+            profit_tokens = [
+                {{ token = "{weth}", price_source = "Chainlink", fallback_sources = ["OnChain"], default_price = 2000.0 }},
+                {{ token = "{usdc}", price_source = "Static", default_price = 1.0 }},
+            ]
+            [chains.price_sources]
+            on_chain = true
+            static_prices_path = "prices.toml"
+            chainlink_feeds = {{ "{weth}" = "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419" }}
+            "#
+        ));
+        assert!(config.validate().is_empty());
+        let config = AppConfig::from_file(config).unwrap();
+        assert_eq!(
+            config.chain_configs[0].price_sources.configured(),
+            [
+                PriceSourceType::OnChain,
+                PriceSourceType::Chainlink,
+                PriceSourceType::Static
+            ]
+        );
+
+        // Sources that are not configured, and a Chainlink source without a feed for the token
+        let config = parse(&format!(
+            r#"
+            rpc_urls = ["https://eth.llamarpc.com"]
+            use_websocket = false
+            profit_tokens = [
+                {{ token = "{weth}", price_source = "GeckoTerminal", fallback_sources = ["OnChain", "Static"], default_price = 2000.0 }},
+                {{ token = "{usdc}", price_source = "Chainlink", default_price = 1.0 }},
+            ]
+            [chains.price_sources]
+            on_chain = true
+            chainlink_feeds = {{ "{weth}" = "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419" }}
+            "#
+        ));
+        assert_eq!(
+            fields(&config),
+            [
+                "chains[0].profit_tokens[0].price_source",
+                "chains[0].profit_tokens[0].fallback_sources[1]",
+                "chains[0].profit_tokens[1].price_source",
+            ]
+        );
+        let error = AppConfig::from_file(config).unwrap_err().to_string();
+        assert!(error.contains("price source GeckoTerminal is not configured"));
+        assert!(error.contains(&format!("no Chainlink feed is configured for {}", usdc)));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let vars = HashMap::from([("ALCHEMY_KEY".to_string(), "secret".to_string())]);
+        assert_eq!(
+            interpolate("https://x/${ALCHEMY_KEY} $5, $${KEEP}", &vars).unwrap(),
+            "https://x/secret $5, ${KEEP}"
+        );
+        let error = interpolate("${MISSING}", &vars).unwrap_err();
+        assert!(error.to_string().contains("MISSING"));
+        assert!(interpolate("${ALCHEMY_KEY", &vars).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_parsed_values() {
+        let dir = std::env::temp_dir().join(format!("config-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(
+            &path,
+            r#"
+            # Set ${UNSET_IN_COMMENT} to move the database
+            db_path = "${SECRET}" # or ${ALSO_UNSET}
+            [[chains]]
+            rpc_urls = ["https://eth.example/${RPC_KEY}"]
+            websocket_urls = []
+            start_block = 0
+            max_blocks_per_batch = 10
+            wait_time_for_startup = 100
+            use_websocket = false
+            "#,
+        )
+        .unwrap();
+        let missing = dir.join("missing.toml");
+        fs::write(&missing, "db_path = \"${MISSING}\"\n").unwrap();
+
+        // Secrets are substituted verbatim, whatever TOML would make of their characters
+        let secret = "a\"b\\c\nd = 1";
+        let vars = HashMap::from([
+            ("SECRET".to_string(), secret.to_string()),
+            ("RPC_KEY".to_string(), "key".to_string()),
+        ]);
+        let config = ConfigFile::load_with_vars(std::slice::from_ref(&path), &vars);
+        let error = ConfigFile::load_with_vars(&[path, missing], &vars).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let config = config.unwrap();
+        assert_eq!(config.db_path.as_deref(), Some(secret));
+        assert_eq!(config.chains[0].rpc_urls, ["https://eth.example/key"]);
+        let error = format!("{:#}", error);
+        assert!(error.contains("in db_path"), "{}", error);
+        assert!(error.contains("MISSING"), "{}", error);
+    }
+
+    #[test]
+    fn test_layered_config() {
+        let dir = std::env::temp_dir().join(format!("config-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let base = dir.join("config.toml");
+        let production = dir.join("production.toml");
+        fs::write(
+            &base,
+            r#"
+            max_sync_lag = 5
+            quote_cache_size = 100
+            [[chains]]
+            rpc_urls = ["https://eth.example/${RPC_KEY}"]
+            websocket_urls = []
+            start_block = 0
+            max_blocks_per_batch = 10
+            wait_time_for_startup = 100
+            use_websocket = false
+            pools_with_type = [{ address = "0xa98d625be12df46f7ecc060c1bffb505e80de6aa", pool_type = { ERC4626 = "VerioIP" } }]
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            &production,
+            "max_sync_lag = 20\n[server]\ncompression = false\n",
+        )
+        .unwrap();
+
+        let vars = HashMap::from([
+            ("RPC_KEY".to_string(), "secret".to_string()),
+            (
+                "EVM_INDEXER__CHAINS__0__WEBSOCKET_URLS".to_string(),
+                "wss://a.example, wss://b.example".to_string(),
+            ),
+            ("EVM_INDEXER__QUOTE_CACHE_SIZE".to_string(), "0".to_string()),
+            ("UNRELATED".to_string(), "1".to_string()),
+        ]);
+        let config = ConfigFile::load_with_vars(&[base, production], &vars).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // The production file overrides the base, the environment overrides both
+        assert_eq!(config.max_sync_lag, Some(20));
+        assert_eq!(config.server.compression, Some(false));
+        assert_eq!(config.quote_cache_size, Some(0));
+        let chain = &config.chains[0];
+        assert_eq!(chain.rpc_urls, ["https://eth.example/secret"]);
+        assert_eq!(chain.websocket_urls, ["wss://a.example", "wss://b.example"]);
+        assert!(config.validate().is_empty());
+        let config = AppConfig::from_file(config).unwrap();
+        assert_eq!(
+            config.chain_configs[0].pools[0].pool_type,
+            PoolType::ERC4626(crate::models::pool::erc4626::ERC4626Pool::VerioIP)
+        );
+    }
+
+    #[test]
+    fn test_shipped_config() {
+        let config: ConfigFile = toml::from_str(include_str!("../../configs/config.toml")).unwrap();
+        assert!(config.validate().is_empty(), "{:?}", config.validate());
+    }
+}
@@ -1,3 +1,4 @@
+use alloy::primitives::Address;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,6 +20,24 @@ pub enum ArbitrageError {
 
     #[error("Provider error: {0}")]
     ProviderError(String),
+
+    #[error("Network {0} not found")]
+    NetworkNotFound(u64),
+
+    #[error("Pool {0:?} not found")]
+    PoolNotFound(Address),
+
+    #[error("Token {0:?} not found")]
+    TokenNotFound(Address),
+
+    #[error("Token {token:?} not in pool {pool:?}")]
+    TokenNotInPool { token: Address, pool: Address },
+
+    #[error("Insufficient liquidity: {0}")]
+    InsufficientLiquidity(String),
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
 }
 
 pub type Result<T> = std::result::Result<T, ArbitrageError>;
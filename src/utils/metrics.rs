@@ -1,9 +1,21 @@
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use alloy::primitives::{TxHash, U256};
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
 
+use crate::models::pool::base::PoolType;
+
+/// Prefix of every metric exposed at `/metrics`
+const METRICS_NAMESPACE: &str = "evm_indexer";
+
+/// Indexer and API metrics, exposed in the Prometheus text format by `render`
+#[derive(Debug)]
 pub struct Metrics {
     pub blocks_processed: AtomicU64,
     pub pools_updated: AtomicU64,
@@ -11,6 +23,16 @@ pub struct Metrics {
     pub simulation_time: AtomicU64,
     pub last_block_time: AtomicU64,
     pub opportunities: HashMap<(TxHash, u64), OpportunityMetrics>,
+    registry: Registry,
+    chain_head_block: IntGaugeVec,      // chain_id
+    last_processed_block: IntGaugeVec,  // chain_id
+    head_lag_blocks: IntGaugeVec,       // chain_id
+    events_applied: IntCounterVec,      // chain_id, pool_type
+    events_failed: IntCounterVec,       // chain_id, pool_type
+    event_queue_depth: IntGaugeVec,     // chain_id
+    rpc_request_duration: HistogramVec, // endpoint
+    rpc_errors: IntCounterVec,          // endpoint
+    api_request_duration: HistogramVec, // method, route, status
 }
 
 #[derive(Debug, Clone)]
@@ -25,8 +47,37 @@ pub struct OpportunityMetrics {
     pub steps: Vec<U256>,
 }
 
+/// Registers a collector and returns it
+fn register<C: Collector + Clone + 'static>(registry: &Registry, collector: C) -> C {
+    registry
+        .register(Box::new(collector.clone()))
+        .expect("metric names are unique");
+    collector
+}
+
 impl Default for Metrics {
     fn default() -> Self {
+        let registry = Registry::new_custom(Some(METRICS_NAMESPACE.to_string()), None)
+            .expect("metrics namespace is valid");
+        let gauge = |name: &str, help: &str, labels: &[&str]| {
+            register(
+                &registry,
+                IntGaugeVec::new(Opts::new(name, help), labels).unwrap(),
+            )
+        };
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            register(
+                &registry,
+                IntCounterVec::new(Opts::new(name, help), labels).unwrap(),
+            )
+        };
+        let histogram = |name: &str, help: &str, labels: &[&str]| {
+            register(
+                &registry,
+                HistogramVec::new(HistogramOpts::new(name, help), labels).unwrap(),
+            )
+        };
+
         Self {
             blocks_processed: AtomicU64::new(0),
             pools_updated: AtomicU64::new(0),
@@ -34,6 +85,52 @@ impl Default for Metrics {
             simulation_time: AtomicU64::new(0),
             last_block_time: AtomicU64::new(0),
             opportunities: HashMap::new(),
+            chain_head_block: gauge(
+                "chain_head_block",
+                "Latest block reported by the chain's RPC",
+                &["chain_id"],
+            ),
+            last_processed_block: gauge(
+                "last_processed_block",
+                "Latest block whose events were applied",
+                &["chain_id"],
+            ),
+            head_lag_blocks: gauge(
+                "head_lag_blocks",
+                "Blocks between the chain head and the last processed block",
+                &["chain_id"],
+            ),
+            events_applied: counter(
+                "events_applied_total",
+                "Pool events applied to pool states",
+                &["chain_id", "pool_type"],
+            ),
+            events_failed: counter(
+                "events_failed_total",
+                "Pool events that failed to apply",
+                &["chain_id", "pool_type"],
+            ),
+            event_queue_depth: gauge(
+                "event_queue_depth",
+                "Websocket events waiting to be applied",
+                &["chain_id"],
+            ),
+            rpc_request_duration: histogram(
+                "rpc_request_duration_seconds",
+                "Latency of JSON-RPC requests by endpoint host",
+                &["endpoint"],
+            ),
+            rpc_errors: counter(
+                "rpc_errors_total",
+                "JSON-RPC requests that failed or returned an error, by endpoint host",
+                &["endpoint"],
+            ),
+            api_request_duration: histogram(
+                "api_request_duration_seconds",
+                "Latency of API requests by route and status",
+                &["method", "route", "status"],
+            ),
+            registry,
         }
     }
 }
@@ -184,6 +281,127 @@ impl Metrics {
         );
     }
 
+    pub fn add_blocks_processed(&self, count: u64) {
+        self.blocks_processed.fetch_add(count, Ordering::Relaxed);
+        self.update_last_block_time();
+    }
+
+    pub fn add_pools_updated(&self, count: u64) {
+        self.pools_updated.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Records the chain head and the lag of the last processed block behind it
+    pub fn record_chain_head(&self, chain_id: u64, head: u64) {
+        let chain_id = chain_id.to_string();
+        let head = head as i64;
+        self.chain_head_block
+            .with_label_values(&[&chain_id])
+            .set(head);
+        let processed = self
+            .last_processed_block
+            .with_label_values(&[&chain_id])
+            .get();
+        self.head_lag_blocks
+            .with_label_values(&[&chain_id])
+            .set((head - processed).max(0));
+    }
+
+    /// Records a processed block, ignoring blocks older than the last one recorded
+    pub fn record_processed_block(&self, chain_id: u64, block: u64) {
+        let chain_id = chain_id.to_string();
+        let last_processed = self.last_processed_block.with_label_values(&[&chain_id]);
+        let block = block as i64;
+        if block <= last_processed.get() {
+            return;
+        }
+        last_processed.set(block);
+        let head = self.chain_head_block.with_label_values(&[&chain_id]).get();
+        self.head_lag_blocks
+            .with_label_values(&[&chain_id])
+            .set((head - block).max(0));
+    }
+
+    pub fn record_event(&self, chain_id: u64, pool_type: PoolType, applied: bool) {
+        let counter = if applied {
+            &self.events_applied
+        } else {
+            &self.events_failed
+        };
+        counter
+            .with_label_values(&[&chain_id.to_string(), pool_type.name()])
+            .inc();
+    }
+
+    pub fn set_event_queue_depth(&self, chain_id: u64, depth: usize) {
+        self.event_queue_depth
+            .with_label_values(&[&chain_id.to_string()])
+            .set(depth as i64);
+    }
+
+    pub fn record_rpc_request(&self, endpoint: &str, duration: Duration, success: bool) {
+        self.rpc_request_duration
+            .with_label_values(&[endpoint])
+            .observe(duration.as_secs_f64());
+        if !success {
+            self.rpc_errors.with_label_values(&[endpoint]).inc();
+        }
+    }
+
+    pub fn record_api_request(&self, method: &str, route: &str, status: u16, duration: Duration) {
+        self.api_request_duration
+            .with_label_values(&[method, route, &status.to_string()])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Every metric in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("metrics encode as text");
+        let mut output = String::from_utf8(buffer).expect("text metrics are UTF-8");
+
+        let totals = [
+            (
+                "blocks_processed_total",
+                "counter",
+                "Blocks processed by the pool updaters",
+                self.blocks_processed.load(Ordering::Relaxed),
+            ),
+            (
+                "pools_updated_total",
+                "counter",
+                "Pool state updates published",
+                self.pools_updated.load(Ordering::Relaxed),
+            ),
+            (
+                "opportunities_found_total",
+                "counter",
+                "Arbitrage opportunities found",
+                self.opportunities_found.load(Ordering::Relaxed),
+            ),
+            (
+                "simulation_time_microseconds_total",
+                "counter",
+                "Time spent simulating opportunities",
+                self.simulation_time.load(Ordering::Relaxed),
+            ),
+            (
+                "last_block_time_seconds",
+                "gauge",
+                "Unix time of the last processed block batch",
+                self.last_block_time.load(Ordering::Relaxed),
+            ),
+        ];
+        for (name, kind, help, value) in totals {
+            let name = format!("{}_{}", METRICS_NAMESPACE, name);
+            let _ = writeln!(output, "# HELP {} {}", name, help);
+            let _ = writeln!(output, "# TYPE {} {}", name, kind);
+            let _ = writeln!(output, "{} {}", name, value);
+        }
+        output
+    }
+
     pub fn get_metrics(&self) -> String {
         format!(
             "Blocks processed: {}\nPools updated: {}\nOpportunities found: {}\nAverage simulation time: {}ms\nLast block time: {}",
@@ -195,3 +413,40 @@ impl Metrics {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_text() {
+        let metrics = Metrics::new();
+        metrics.record_processed_block(1, 100);
+        metrics.record_chain_head(1, 105);
+        // Blocks older than the last one recorded are ignored
+        metrics.record_processed_block(1, 90);
+        metrics.record_processed_block(1, 102);
+        metrics.record_event(1, PoolType::UniswapV3, true);
+        metrics.record_event(1, PoolType::UniswapV3, true);
+        metrics.record_event(1, PoolType::UniswapV2, false);
+        metrics.record_rpc_request("eth.example.com", Duration::from_millis(20), false);
+        metrics.record_api_request("GET", "/networks/:network_id/pools", 200, Duration::ZERO);
+        metrics.add_blocks_processed(3);
+
+        let output = metrics.render();
+        for line in [
+            "evm_indexer_chain_head_block{chain_id=\"1\"} 105",
+            "evm_indexer_last_processed_block{chain_id=\"1\"} 102",
+            "evm_indexer_head_lag_blocks{chain_id=\"1\"} 3",
+            "evm_indexer_events_applied_total{chain_id=\"1\",pool_type=\"UniswapV3\"} 2",
+            "evm_indexer_events_failed_total{chain_id=\"1\",pool_type=\"UniswapV2\"} 1",
+            "evm_indexer_rpc_request_duration_seconds_count{endpoint=\"eth.example.com\"} 1",
+            "evm_indexer_rpc_errors_total{endpoint=\"eth.example.com\"} 1",
+            "evm_indexer_api_request_duration_seconds_count{method=\"GET\",route=\"/networks/:network_id/pools\",status=\"200\"} 1",
+            "# TYPE evm_indexer_blocks_processed_total counter",
+            "evm_indexer_blocks_processed_total 3",
+        ] {
+            assert!(output.lines().any(|l| l == line), "{} missing from\n{}", line, output);
+        }
+    }
+}
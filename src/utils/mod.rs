@@ -3,6 +3,7 @@ pub mod encode_packed;
 pub mod errors;
 // pub mod logger;
 pub mod metrics;
+pub mod telemetry;
 pub mod utils;
 // Re-export config types
 pub use config::{AppConfig, DatabaseConfig, ExecutorConfig};
@@ -0,0 +1,183 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Service name spans are exported under
+pub const DEFAULT_SERVICE_NAME: &str = "evm-indexer";
+
+/// Output format of the log lines written to stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json, // One JSON object per line, with the fields of the enclosing spans
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow!("Unknown log format {}, expected text or json", s)),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    pub log_level: String, // Default filter, RUST_LOG takes precedence
+    pub log_format: LogFormat,
+    pub otlp_endpoint: Option<String>, // Collector base URL, spans are posted to <endpoint>/v1/traces
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            log_level: "info".to_string(),
+            log_format: LogFormat::default(),
+            otlp_endpoint: None,
+            service_name: DEFAULT_SERVICE_NAME.to_string(),
+        }
+    }
+}
+
+/// Handle of the installed subscriber, keeps the OTLP exporter running until `shutdown`
+#[derive(Debug)]
+pub struct Telemetry {
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    /// Exports the spans still buffered and stops the exporter
+    pub fn shutdown(self) {
+        if let Some(tracer_provider) = self.tracer_provider {
+            if let Err(e) = tracer_provider.shutdown() {
+                eprintln!("Failed to flush traces: {}", e);
+            }
+        }
+    }
+}
+
+/// Installs the global subscriber. Records of the `log` macros are forwarded to it, so they
+/// carry the fields of the span they are emitted in.
+pub fn init(config: &TelemetryConfig) -> Result<Telemetry> {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&config.log_level))
+        .with_context(|| format!("Invalid log level {}", config.log_level))?;
+    let fmt_layer = match config.log_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    };
+    let tracer_provider = config
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| otlp_tracer_provider(endpoint, &config.service_name))
+        .transpose()?;
+    let otlp_layer = tracer_provider.as_ref().map(|tracer_provider| {
+        tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer(DEFAULT_SERVICE_NAME))
+    });
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(otlp_layer)
+        .with(filter)
+        .try_init()
+        .context("Failed to install the tracing subscriber")?;
+    Ok(Telemetry { tracer_provider })
+}
+
+/// Batches spans and posts them to an OTLP/HTTP collector
+pub fn otlp_tracer_provider(endpoint: &str, service_name: &str) -> Result<SdkTracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+        .with_context(|| format!("Failed to create the OTLP exporter for {}", endpoint))?;
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(service_name.to_string())
+                .build(),
+        )
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, extract::State, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+    use tracing::info_span;
+
+    #[test]
+    fn test_log_format() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("Text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_otlp_export() {
+        // Local collector keeping the body of every export request
+        let exports = Arc::new(Mutex::new(Vec::<Bytes>::new()));
+        let collector = Router::new()
+            .route(
+                "/v1/traces",
+                post(
+                    |State(exports): State<Arc<Mutex<Vec<Bytes>>>>, body: Bytes| async move {
+                        exports.lock().unwrap().push(body);
+                    },
+                ),
+            )
+            .with_state(exports.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, collector).await.unwrap() });
+
+        let tracer_provider =
+            otlp_tracer_provider(&format!("http://{}/", addr), "indexer-test").unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("test")));
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = info_span!("update_batch", chain_id = 1, from_block = 100).entered();
+        });
+        // The exporter sends with a blocking client
+        tokio::task::spawn_blocking(move || tracer_provider.force_flush())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let exports = exports.lock().unwrap();
+        assert_eq!(exports.len(), 1);
+        // Names are plain strings in the protobuf payload
+        let body = String::from_utf8_lossy(&exports[0]);
+        assert!(body.contains("update_batch"));
+        assert!(body.contains("indexer-test"));
+        assert!(body.contains("chain_id"));
+    }
+}